@@ -66,27 +66,6 @@ enum Verb {
     Apply,
 }
 
-fn resolve_api_resource(discovery: &Discovery, name: &str) -> Option<(ApiResource, ApiCapabilities)> {
-    // iterate through groups to find matching kind/plural names at recommended versions
-    // and then take the minimal match by group.name (equivalent to sorting groups by group.name).
-    // this is equivalent to kubectl's api group preference
-    discovery
-        .groups()
-        .flat_map(|group| {
-            group
-                .resources_by_stability()
-                .into_iter()
-                .map(move |res| (group, res))
-        })
-        .filter(|(_, (res, _))| {
-            // match on both resource name and kind name
-            // ideally we should allow shortname matches as well
-            name.eq_ignore_ascii_case(&res.kind) || name.eq_ignore_ascii_case(&res.plural)
-        })
-        .min_by_key(|(group, _res)| group.name())
-        .map(|(_, res)| res)
-}
-
 impl App {
     async fn get(&self, api: Api<DynamicObject>, lp: ListParams) -> Result<()> {
         let mut result: Vec<_> = if let Some(n) = &self.name {
@@ -196,7 +175,8 @@ async fn main() -> Result<()> {
     // Defer to methods for verbs
     if let Some(resource) = &app.resource {
         // Common discovery, parameters, and api configuration for a single resource
-        let (ar, caps) = resolve_api_resource(&discovery, resource)
+        let (ar, caps) = discovery
+            .resolve_alias(resource)
             .with_context(|| format!("resource {resource:?} not found in cluster"))?;
         let mut lp = ListParams::default();
         if let Some(label) = &app.selector {
@@ -1,5 +1,5 @@
 use anyhow::{anyhow, Result};
-use futures::{AsyncBufReadExt, TryStreamExt};
+use futures::TryStreamExt;
 use k8s_openapi::api::core::v1::Pod;
 use kube::{
     api::{Api, LogParams},
@@ -19,14 +19,8 @@ async fn main() -> Result<()> {
     info!("Fetching logs for {:?}", mypod);
 
     let pods: Api<Pod> = Api::default_namespaced(client);
-    let mut logs = pods
-        .log_stream(&mypod, &LogParams {
-            follow: true,
-            tail_lines: Some(1),
-            ..LogParams::default()
-        })
-        .await?
-        .lines();
+    let logs = pods.log_lines(&mypod, &LogParams::default().follow().tail_lines(1));
+    futures::pin_mut!(logs);
 
     while let Some(line) = logs.try_next().await? {
         info!("{}", line);
@@ -1,13 +1,49 @@
 use either::Either;
-use futures::Stream;
+use futures::{Stream, StreamExt, TryStreamExt};
 use serde::{de::DeserializeOwned, Serialize};
 use std::fmt::Debug;
 
-use crate::{api::Api, Error, Result};
+use crate::{
+    api::{Api, Restriction},
+    client::ListStream,
+    Error, Result,
+};
 use kube_core::{
-    metadata::PartialObjectMeta, object::ObjectList, params::*, response::Status, ErrorResponse, WatchEvent,
+    fields,
+    metadata::{ObjectMeta, PartialObjectMeta, PartialObjectMetaExt},
+    object::ObjectList,
+    params::*,
+    response::Status,
+    ApplyConflict, ErrorResponse, NamespaceResourceScope, Resource, WatchEvent,
 };
 
+/// How many namespaces [`Api::list_across`] requests at a time
+const LIST_ACROSS_CONCURRENCY: usize = 8;
+
+/// Maps a `NotFound` error of the apiserver to `None`
+///
+/// Other `404`s, like those of a path that is not served, are still errors.
+fn not_found_as_none<T>(result: Result<T>) -> Result<Option<T>> {
+    match result {
+        Ok(obj) => Ok(Some(obj)),
+        Err(Error::Api(ErrorResponse { reason, .. })) if reason == "NotFound" => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+/// The error of an `Exact` get whose list did not contain the object
+fn not_found_at(name: &str, gp: &GetParams) -> Error {
+    Error::Api(ErrorResponse::new(
+        "Failure",
+        format!(
+            "{name:?} not found at resourceVersion {}",
+            gp.resource_version.as_deref().unwrap_or_default()
+        ),
+        "NotFound",
+        404,
+    ))
+}
+
 /// PUSH/PUT/POST/GET abstractions
 impl<K> Api<K>
 where
@@ -77,14 +113,30 @@ where
     /// # }
     /// ```
     ///
+    /// The apiserver cannot get an object at an exact version, so a get with [`GetParams::exact`]
+    /// lists the object with a `metadata.name` field selector instead.
+    ///
     /// # Errors
     ///
     /// This function assumes that the object is expected to always exist, and returns [`Error`] if it does not.
     /// Consider using [`Api::get_opt`] if you need to handle missing objects.
     pub async fn get_with(&self, name: &str, gp: &GetParams) -> Result<K> {
+        if gp.version_match() == Some(&VersionMatch::Exact) {
+            let list = self.list(&gp.list_params(name)).await?;
+            return list
+                .items
+                .into_iter()
+                .next()
+                .ok_or_else(|| not_found_at(name, gp));
+        }
         let mut req = self.request.get(name, gp).map_err(Error::BuildRequest)?;
         req.extensions_mut().insert("get");
-        self.client.request::<K>(req).await
+        if self.restriction.selects_all() {
+            return self.client.request::<K>(req).await;
+        }
+        let object = self.client.request::<serde_json::Value>(req).await?;
+        self.check_restriction(name, &object, false)?;
+        serde_json::from_value(object).map_err(Error::SerdeError)
     }
 
     ///  [Get](`Api::get_metadata`) the metadata of an object using an explicit `resourceVersion`
@@ -106,16 +158,38 @@ where
     /// # }
     /// ```
     /// Note that the type may be converted to `ObjectMeta` through the usual
-    /// conversion traits.
+    /// conversion traits. Like [`Api::get_with`], a get with [`GetParams::exact`] lists the object.
     ///
     /// # Errors
     ///
     /// This function assumes that the object is expected to always exist, and returns [`Error`] if it does not.
     /// Consider using [`Api::get_metadata_opt`] if you need to handle missing objects.
     pub async fn get_metadata_with(&self, name: &str, gp: &GetParams) -> Result<PartialObjectMeta<K>> {
+        if gp.version_match() == Some(&VersionMatch::Exact) {
+            let list = self.list_metadata(&gp.list_params(name)).await?;
+            return list
+                .items
+                .into_iter()
+                .next()
+                .ok_or_else(|| not_found_at(name, gp));
+        }
         let mut req = self.request.get_metadata(name, gp).map_err(Error::BuildRequest)?;
         req.extensions_mut().insert("get_metadata");
-        self.client.request::<PartialObjectMeta<K>>(req).await
+        if self.restriction.selects_all() {
+            return self.client.request::<PartialObjectMeta<K>>(req).await;
+        }
+        let object = self.client.request::<serde_json::Value>(req).await?;
+        self.check_restriction(name, &object, true)?;
+        if self.restriction.checks_more_than_metadata() {
+            // The metadata cannot show the other fields, so ask the apiserver whether the view lists the object
+            let name_selector =
+                fields::Selector::from(fields::Expression::Equal("metadata.name".into(), name.into()));
+            let lp = ListParams::default().fields_from(&name_selector);
+            if self.list_metadata(&lp).await?.items.is_empty() {
+                return Err(Restriction::not_found(name));
+            }
+        }
+        serde_json::from_value(object).map_err(Error::SerdeError)
     }
 
     /// [Get](`Api::get`) a named resource if it exists, returns [`None`] if it doesn't exist
@@ -136,11 +210,7 @@ where
     /// # }
     /// ```
     pub async fn get_opt(&self, name: &str) -> Result<Option<K>> {
-        match self.get(name).await {
-            Ok(obj) => Ok(Some(obj)),
-            Err(Error::Api(ErrorResponse { reason, .. })) if &reason == "NotFound" => Ok(None),
-            Err(err) => Err(err),
-        }
+        not_found_as_none(self.get(name).await)
     }
 
     /// [Get Metadata](`Api::get_metadata`) for a named resource if it exists, returns [`None`] if it doesn't exit
@@ -148,7 +218,7 @@ where
     /// ```no_run
     /// # use kube::Api;
     /// use k8s_openapi::api::core::v1::Pod;
-
+    ///
     /// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
     /// # let client: kube::Client = todo!();
     /// let pods: Api<Pod> = Api::namespaced(client, "apps");
@@ -163,10 +233,39 @@ where
     ///
     /// Note that [`PartialObjectMeta`] embeds the raw `ObjectMeta`.
     pub async fn get_metadata_opt(&self, name: &str) -> Result<Option<PartialObjectMeta<K>>> {
-        match self.get_metadata(name).await {
-            Ok(meta) => Ok(Some(meta)),
-            Err(Error::Api(ErrorResponse { reason, .. })) if &reason == "NotFound" => Ok(None),
-            Err(err) => Err(err),
+        not_found_as_none(self.get_metadata(name).await)
+    }
+
+    /// [Get](`Api::get_with`) a named resource only if it changed since `resource_version`, returns [`None`] if it didn't
+    ///
+    /// The apiserver does not support `If-None-Match` for objects, so only the metadata is fetched first,
+    /// and the whole object is only fetched and deserialized when it has a newer `resourceVersion`.
+    /// Both are fetched with a `NotOlderThan` match of the versions, so unchanged objects are served cheaply
+    /// from the watch cache of the apiserver, and an older object is never returned as a change.
+    ///
+    /// ```no_run
+    /// use kube::{Api, ResourceExt};
+    /// use k8s_openapi::api::core::v1::ConfigMap;
+    ///
+    /// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client: kube::Client = todo!();
+    /// let cms: Api<ConfigMap> = Api::namespaced(client, "apps");
+    /// let mut cm = cms.get("settings").await?;
+    /// // later
+    /// if let Some(changed) = cms.get_if_changed("settings", &cm.resource_version().unwrap()).await? {
+    ///     cm = changed;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_if_changed(&self, name: &str, resource_version: &str) -> Result<Option<K>> {
+        let meta = self
+            .get_metadata_with(name, &GetParams::at(resource_version))
+            .await?;
+        match meta.metadata.resource_version {
+            Some(current) if current == resource_version => Ok(None),
+            Some(current) => self.get_with(name, &GetParams::at(&current)).await.map(Some),
+            None => self.get(name).await.map(Some),
         }
     }
 
@@ -188,12 +287,198 @@ where
     /// # Ok(())
     /// # }
     /// ```
+    ///
+    /// Lists with a [`ListParams::limit`] are chunked by the apiserver, and the token to list the next chunk with
+    /// is returned in the list, to build pagination like cursors of a UI on:
+    ///
+    /// ```no_run
+    /// use kube::api::{Api, ListParams};
+    /// use k8s_openapi::api::core::v1::Pod;
+    ///
+    /// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client: kube::Client = todo!();
+    /// let pods: Api<Pod> = Api::namespaced(client, "apps");
+    /// let lp = ListParams::default().limit(20);
+    /// let page = pods.list(&lp).await?;
+    /// if let Some(token) = page.continue_token() {
+    ///     println!("{:?} more pods after this page", page.remaining_item_count());
+    ///     let next = pods.list(&lp.next_page(token)).await?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
     pub async fn list(&self, lp: &ListParams) -> Result<ObjectList<K>> {
-        let mut req = self.request.list(lp).map_err(Error::BuildRequest)?;
+        let mut req = self
+            .request
+            .list(&self.restriction.list_params(lp))
+            .map_err(Error::BuildRequest)?;
         req.extensions_mut().insert("list");
         self.client.request::<ObjectList<K>>(req).await
     }
 
+    /// Get a list of resources as a stream, which deserializes the items as they are received
+    ///
+    /// This avoids buffering the whole response of large lists before deserializing it, unlike [`Api::list`].
+    /// The [`metadata`](crate::client::ListStream::metadata) of the list is available once the first item
+    /// has been received.
+    ///
+    /// When [`ListParams::limit`] is set, the following pages are requested with the `continue` token
+    /// of the previous page as the stream reaches them, see [`Api::list_pages`].
+    ///
+    /// ```no_run
+    /// use kube::api::{Api, ListParams, ResourceExt};
+    /// use k8s_openapi::api::core::v1::Pod;
+    /// use futures::TryStreamExt;
+    ///
+    /// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client: kube::Client = todo!();
+    /// let pods: Api<Pod> = Api::all(client);
+    /// let mut stream = pods.list_stream(&ListParams::default()).await?;
+    /// while let Some(p) = stream.try_next().await? {
+    ///     println!("Found Pod: {}", p.name_any());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn list_stream(&self, lp: &ListParams) -> Result<ListStream<K>>
+    where
+        K: 'static,
+    {
+        let body = self.list_page_body(lp).await?;
+        let (api, lp) = (self.clone(), lp.clone());
+        Ok(ListStream::new(body).follow_pages(Box::new(move |token| {
+            let (api, lp) = (api.clone(), lp.next_page(&token));
+            Box::pin(async move { api.list_page_body(&lp).await })
+        })))
+    }
+
+    /// Get a list of resources one page at a time, following the `continue` token of each page
+    ///
+    /// The size of the pages is set by [`ListParams::limit`], which keeps the responses within the size limits
+    /// of the apiserver. Without a limit, the whole list is returned as a single page.
+    ///
+    /// ```no_run
+    /// use kube::api::{Api, ListParams, ResourceExt};
+    /// use k8s_openapi::api::core::v1::Pod;
+    /// use futures::{pin_mut, TryStreamExt};
+    ///
+    /// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client: kube::Client = todo!();
+    /// let pods: Api<Pod> = Api::all(client);
+    /// let pages = pods.list_pages(&ListParams::default().limit(500));
+    /// pin_mut!(pages);
+    /// while let Some(page) = pages.try_next().await? {
+    ///     println!("Found {} Pods", page.items.len());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn list_pages(&self, lp: &ListParams) -> impl Stream<Item = Result<ObjectList<K>>> + Send + 'static
+    where
+        K: Send + 'static,
+    {
+        let api = self.clone();
+        futures::stream::try_unfold(Some(lp.clone()), move |lp| {
+            let api = api.clone();
+            async move {
+                let lp = match lp {
+                    Some(lp) => lp,
+                    None => return Ok(None),
+                };
+                let page = ListStream::<K>::new(api.list_page_body(&lp).await?)
+                    .try_collect_list()
+                    .await?;
+                let next = page.continue_token().map(|token| lp.next_page(token));
+                Ok(Some((page, next)))
+            }
+        })
+    }
+
+    /// List the resources of a namespaced kind in each of the `namespaces`, as a single list
+    ///
+    /// The namespaces are listed a few at a time, which only needs permission to list in those namespaces
+    /// rather than across the cluster. Each namespace is listed [page by page](Api::list_pages) when
+    /// [`ListParams::limit`] is set, so that every namespace is listed from a consistent snapshot of its own.
+    ///
+    /// The items are in the order of the namespaces. The lists of the namespaces have their own resource versions,
+    /// so the metadata of the merged list has no resource version or continue token.
+    ///
+    /// ```no_run
+    /// use kube::api::{Api, ListParams, ResourceExt};
+    /// use k8s_openapi::api::core::v1::Pod;
+    ///
+    /// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client: kube::Client = todo!();
+    /// let pods: Api<Pod> = Api::all(client);
+    /// let tenant = ["tenant-a", "tenant-b"];
+    /// for p in pods.list_across(&tenant, &ListParams::default().limit(500)).await? {
+    ///     println!("Found Pod: {}/{}", p.namespace().unwrap(), p.name_any());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn list_across(&self, namespaces: &[&str], lp: &ListParams) -> Result<ObjectList<K>>
+    where
+        K: Resource<Scope = NamespaceResourceScope> + Send + 'static,
+        K::DynamicType: Default,
+    {
+        let lists: Vec<Vec<K>> = futures::stream::iter(self.across(namespaces))
+            .map(|api| api.list_pages(lp).map_ok(|page| page.items).try_concat())
+            .buffered(LIST_ACROSS_CONCURRENCY)
+            .try_collect()
+            .await?;
+        Ok(ObjectList {
+            metadata: Default::default(),
+            items: lists.into_iter().flatten().collect(),
+        })
+    }
+
+    /// List the resources of a namespaced kind in each of the `namespaces`, as a stream of items
+    ///
+    /// This is the stream of [`Api::list_across`], which deserializes the items as they are received like
+    /// [`Api::list_stream`]. The next few namespaces are requested while the items of a namespace are received.
+    pub fn list_across_stream(
+        &self,
+        namespaces: &[&str],
+        lp: &ListParams,
+    ) -> impl Stream<Item = Result<K>> + Send + 'static
+    where
+        K: Resource<Scope = NamespaceResourceScope> + Send + 'static,
+        K::DynamicType: Default,
+    {
+        let lp = lp.clone();
+        futures::stream::iter(self.across(namespaces))
+            .map(move |api| {
+                let lp = lp.clone();
+                async move { api.list_stream(&lp).await }
+            })
+            .buffered(LIST_ACROSS_CONCURRENCY)
+            .try_flatten()
+    }
+
+    /// This api in each of the `namespaces`, in order and without duplicates
+    fn across(&self, namespaces: &[&str]) -> Vec<Self>
+    where
+        K: Resource<Scope = NamespaceResourceScope>,
+        K::DynamicType: Default,
+    {
+        let mut seen = std::collections::HashSet::new();
+        namespaces
+            .iter()
+            .filter(|ns| seen.insert(**ns))
+            .map(|ns| self.in_namespace(ns))
+            .collect()
+    }
+
+    async fn list_page_body(&self, lp: &ListParams) -> Result<hyper::Body> {
+        let mut req = self
+            .request
+            .list(&self.restriction.list_params(lp))
+            .map_err(Error::BuildRequest)?;
+        req.extensions_mut().insert("list");
+        self.client.request_list_body(req).await
+    }
+
     /// Get a list of resources that contains only their metadata as
     ///
     /// Similar to [list](`Api::list`), you use this to get everything, or a
@@ -216,7 +501,10 @@ where
     /// # }
     /// ```
     pub async fn list_metadata(&self, lp: &ListParams) -> Result<ObjectList<PartialObjectMeta<K>>> {
-        let mut req = self.request.list_metadata(lp).map_err(Error::BuildRequest)?;
+        let mut req = self
+            .request
+            .list_metadata(&self.restriction.list_params(lp))
+            .map_err(Error::BuildRequest)?;
         req.extensions_mut().insert("list_metadata");
         self.client.request::<ObjectList<PartialObjectMeta<K>>>(req).await
     }
@@ -257,15 +545,16 @@ where
     /// confirmation that the object being gone.
     ///
     /// 4XX and 5XX status types are returned as an [`Err(kube_client::Error::Api)`](crate::Error::Api).
+    /// Unmet [`Preconditions`] are returned as a `409 Conflict`, see [`ErrorResponse::is_conflict`].
     ///
     /// ```no_run
     /// use kube::api::{Api, DeleteParams};
     /// use k8s_openapi::apiextensions_apiserver::pkg::apis::apiextensions::v1 as apiexts;
     /// use apiexts::CustomResourceDefinition;
-
+    ///
     /// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
     /// # let client: kube::Client = todo!();
-
+    ///
     /// let crds: Api<CustomResourceDefinition> = Api::all(client);
     /// crds.delete("foos.clux.dev", &DeleteParams::default()).await?
     ///     .map_left(|o| println!("Deleting CRD: {:?}", o.status))
@@ -273,6 +562,25 @@ where
     /// # Ok(())
     /// # }
     /// ```
+    ///
+    /// Deleting only the object that was read before, and not one that was recreated with the same name since:
+    ///
+    /// ```no_run
+    /// use kube::api::{Api, DeleteParams, Preconditions, ResourceExt};
+    /// use k8s_openapi::api::core::v1::Pod;
+    /// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client: kube::Client = todo!();
+    /// let pods: Api<Pod> = Api::namespaced(client, "apps");
+    /// let pod = pods.get("blog").await?;
+    /// let dp = DeleteParams::default().preconditions(Preconditions::default().uid(&pod.uid().unwrap()));
+    /// match pods.delete("blog", &dp).await {
+    ///     Ok(_) => println!("Deleting pod"),
+    ///     Err(kube::Error::Api(err)) if err.is_conflict() => println!("Pod was replaced: {}", err.message),
+    ///     Err(err) => return Err(err.into()),
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
     pub async fn delete(&self, name: &str, dp: &DeleteParams) -> Result<Either<K, Status>> {
         let mut req = self.request.delete(name, dp).map_err(Error::BuildRequest)?;
         req.extensions_mut().insert("delete");
@@ -287,6 +595,9 @@ where
     ///
     /// 4XX and 5XX status types are returned as an [`Err(kube_client::Error::Api)`](crate::Error::Api).
     ///
+    /// The objects to delete are selected by the selectors, `limit`, `continue` token and resource version of the [`ListParams`],
+    /// and all of them are deleted with the same [`DeleteParams`], including its propagation policy, grace period and dry-run.
+    ///
     /// ```no_run
     /// use kube::api::{Api, DeleteParams, ListParams, ResourceExt};
     /// use k8s_openapi::api::core::v1::Pod;
@@ -294,7 +605,9 @@ where
     /// # let client: kube::Client = todo!();
     ///
     /// let pods: Api<Pod> = Api::namespaced(client, "apps");
-    /// match pods.delete_collection(&DeleteParams::default(), &ListParams::default()).await? {
+    /// let lp = ListParams::default().labels("app=blog").fields("status.phase=Succeeded");
+    /// let dp = DeleteParams::background().grace_period(0);
+    /// match pods.delete_collection(&dp, &lp).await? {
     ///     either::Left(list) => {
     ///         let names: Vec<_> = list.iter().map(ResourceExt::name_any).collect();
     ///         println!("Deleting collection of pods: {:?}", names);
@@ -313,7 +626,7 @@ where
     ) -> Result<Either<ObjectList<K>, Status>> {
         let mut req = self
             .request
-            .delete_collection(dp, lp)
+            .delete_collection(dp, &self.restriction.list_params(lp))
             .map_err(Error::BuildRequest)?;
         req.extensions_mut().insert("delete_collection");
         self.client.request_status::<ObjectList<K>>(req).await
@@ -362,6 +675,89 @@ where
         self.client.request::<K>(req).await
     }
 
+    /// Patch a named resource with a [JSON patch](https://datatracker.ietf.org/doc/html/rfc6902)
+    ///
+    /// This is [`Api::patch`] with a [`Patch::Json`], which is usually built with a [`PatchBuilder`](kube_core::jsonpatch::PatchBuilder).
+    ///
+    /// ```no_run
+    /// use kube::{api::{Api, PatchParams}, core::jsonpatch::{PatchBuilder, Pointer}};
+    /// use k8s_openapi::api::apps::v1::Deployment;
+    /// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client: kube::Client = todo!();
+    /// let deploys: Api<Deployment> = Api::namespaced(client, "apps");
+    /// let image = Pointer::root().field("spec").field("template").field("spec").field("containers").index(0).field("image");
+    /// let patch = PatchBuilder::new()
+    ///     .test(image.clone(), "blog:1.0")
+    ///     .replace(image, "blog:1.1")
+    ///     .build()?;
+    /// deploys.patch_json("blog", &PatchParams::default(), patch).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "jsonpatch")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "jsonpatch")))]
+    pub async fn patch_json(
+        &self,
+        name: &str,
+        pp: &PatchParams,
+        patch: kube_core::jsonpatch::Patch,
+    ) -> Result<K> {
+        self.patch(name, pp, &Patch::Json::<()>(patch)).await
+    }
+
+    /// Server-side apply `patch` to a named resource, and force it through the conflicts that `force_on` accepts
+    ///
+    /// The patch is applied without force first. When it conflicts with fields of other field managers,
+    /// `force_on` is called with the conflicts, and the patch is applied again with force if it returns `true`.
+    /// Otherwise the conflict error is returned, and the conflicts can be reported with [`ErrorResponse::apply_conflicts`].
+    ///
+    /// ```no_run
+    /// use kube::api::{Api, PatchParams};
+    /// use k8s_openapi::api::apps::v1::Deployment;
+    /// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client: kube::Client = todo!();
+    ///
+    /// let deploys: Api<Deployment> = Api::namespaced(client, "apps");
+    /// let patch = serde_json::json!({
+    ///     "apiVersion": "apps/v1",
+    ///     "kind": "Deployment",
+    ///     "spec": { "replicas": 3 }
+    /// });
+    /// // Take over fields that were set with kubectl, but not the fields of other controllers
+    /// let params = PatchParams::apply("myapp");
+    /// let deploy = deploys
+    ///     .apply_or_force("blog", &params, &patch, |conflicts| {
+    ///         conflicts.iter().all(|conflict| conflict.manager.starts_with("kubectl"))
+    ///     })
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn apply_or_force<P: Serialize + Debug>(
+        &self,
+        name: &str,
+        pp: &PatchParams,
+        patch: &P,
+        force_on: impl FnOnce(&[ApplyConflict]) -> bool,
+    ) -> Result<K> {
+        let mut pp = PatchParams {
+            force: false,
+            ..pp.clone()
+        };
+        let err = match self.patch(name, &pp, &Patch::Apply(patch)).await {
+            Err(Error::Api(err)) => err,
+            res => return res,
+        };
+        match err.apply_conflicts() {
+            Some(conflicts) if force_on(&conflicts) => {
+                tracing::debug!(?conflicts, "forcing server-side apply through conflicts");
+            }
+            _ => return Err(Error::Api(err)),
+        }
+        pp.force = true;
+        self.patch(name, &pp, &Patch::Apply(patch)).await
+    }
+
     /// Patch a metadata subset of a resource's properties from [`PartialObjectMeta`]
     ///
     /// Takes a [`Patch`] along with [`PatchParams`] for the call.
@@ -412,6 +808,46 @@ where
         self.client.request::<PartialObjectMeta<K>>(req).await
     }
 
+    /// Patch only the metadata of a named resource to `metadata`, and receive its [`PartialObjectMeta`]
+    ///
+    /// Unlike [`Api::patch_metadata`], nothing but the `apiVersion`, `kind` and `metadata` is sent, so controllers that
+    /// only manage labels, annotations, finalizers or owner references never send or deserialize the whole object.
+    /// This is sent with server-side apply when `pp` has a field manager, as with [`PatchParams::apply`],
+    /// and as a JSON merge patch otherwise.
+    ///
+    /// ```no_run
+    /// use kube::api::{Api, ObjectMeta, PatchParams};
+    /// use k8s_openapi::api::core::v1::Pod;
+    ///
+    /// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client: kube::Client = todo!();
+    /// let pods: Api<Pod> = Api::namespaced(client, "apps");
+    /// let metadata = ObjectMeta {
+    ///     annotations: Some([("example.com/owner".to_string(), "team-a".to_string())].into()),
+    ///     ..ObjectMeta::default()
+    /// };
+    /// let meta = pods.patch_metadata_from("blog", &PatchParams::apply("myapp"), &metadata).await?;
+    /// println!("Annotated {:?}", meta.metadata.name);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn patch_metadata_from(
+        &self,
+        name: &str,
+        pp: &PatchParams,
+        metadata: &ObjectMeta,
+    ) -> Result<PartialObjectMeta<K>>
+    where
+        K: Resource<DynamicType = ()>,
+    {
+        let partial = metadata.clone().into_request_partial::<K>();
+        if pp.field_manager.is_some() {
+            self.patch_metadata(name, pp, &Patch::Apply(partial)).await
+        } else {
+            self.patch_metadata(name, pp, &Patch::Merge(partial)).await
+        }
+    }
+
     /// Replace a resource entirely with a new one
     ///
     /// This is used just like [`Api::create`], but with one additional instruction:
@@ -480,6 +916,8 @@ where
     /// Note that a `watch` call can terminate for many reasons (even before the specified
     /// [`WatchParams::timeout`] is triggered), and will have to be re-issued
     /// with the last seen resource version when or if it closes.
+    /// When [`WatchParams::bookmarks`] are enabled, the apiserver sends [`WatchEvent::Bookmark`] events with the
+    /// resource version to resume from, even when none of the watched objects changed.
     ///
     /// Consider using a managed [`watcher`] to deal with automatic re-watches and error cases.
     ///
@@ -494,13 +932,14 @@ where
     /// let lp = WatchParams::default()
     ///     .fields("metadata.name=my_job")
     ///     .timeout(20); // upper bound of how long we watch for
-    /// let mut stream = jobs.watch(&lp, "0").await?.boxed();
+    /// let mut version = "0".to_string();
+    /// let mut stream = jobs.watch(&lp, &version).await?.boxed();
     /// while let Some(status) = stream.try_next().await? {
     ///     match status {
     ///         WatchEvent::Added(s) => println!("Added {}", s.name_any()),
     ///         WatchEvent::Modified(s) => println!("Modified: {}", s.name_any()),
     ///         WatchEvent::Deleted(s) => println!("Deleted {}", s.name_any()),
-    ///         WatchEvent::Bookmark(s) => {},
+    ///         WatchEvent::Bookmark(b) => version = b.resource_version().to_string(),
     ///         WatchEvent::Error(s) => println!("{}", s),
     ///     }
     /// }
@@ -508,13 +947,17 @@ where
     /// # }
     /// ```
     /// [`WatchParams::timeout`]: super::WatchParams::timeout
+    /// [`WatchParams::bookmarks`]: super::WatchParams::bookmarks
     /// [`watcher`]: https://docs.rs/kube_runtime/*/kube_runtime/watcher/fn.watcher.html
     pub async fn watch(
         &self,
         wp: &WatchParams,
         version: &str,
     ) -> Result<impl Stream<Item = Result<WatchEvent<K>>>> {
-        let mut req = self.request.watch(wp, version).map_err(Error::BuildRequest)?;
+        let mut req = self
+            .request
+            .watch(&self.restriction.watch_params(wp), version)
+            .map_err(Error::BuildRequest)?;
         req.extensions_mut().insert("watch");
         self.client.request_events::<K>(req).await
     }
@@ -565,7 +1008,7 @@ where
     ) -> Result<impl Stream<Item = Result<WatchEvent<PartialObjectMeta<K>>>>> {
         let mut req = self
             .request
-            .watch_metadata(wp, version)
+            .watch_metadata(&self.restriction.watch_params(wp), version)
             .map_err(Error::BuildRequest)?;
         req.extensions_mut().insert("watch_metadata");
         self.client.request_events::<PartialObjectMeta<K>>(req).await
@@ -56,6 +56,56 @@ impl<K: Resource + Clone + DeserializeOwned + Debug> Api<K> {
             None => Entry::Vacant(VacantEntry { api: self, name }),
         })
     }
+
+    /// Gets an object's [`Entry`], lets `f` create or modify it, and commits it, retrying on write conflicts
+    ///
+    /// When another client creates or modifies the object between the get and the commit, the apiserver rejects
+    /// the commit with a `409 Conflict`. The latest object is then fetched again and passed to `f`, up to
+    /// 5 attempts in total. The committed object is returned.
+    ///
+    /// ```rust,no_run
+    /// # use std::collections::BTreeMap;
+    /// # use k8s_openapi::api::core::v1::ConfigMap;
+    /// # async fn wrapper() -> Result <(), Box<dyn std::error::Error>> {
+    /// let kube = kube::Client::try_default().await?;
+    /// let cms = kube::Api::<ConfigMap>::namespaced(kube, "default");
+    /// let pp = kube::api::PostParams::default();
+    /// let cm = cms
+    ///     .commit_entry("entry-example", &pp, |entry| {
+    ///         entry.or_insert(ConfigMap::default).and_modify(|cm| {
+    ///             cm.data
+    ///                 .get_or_insert_with(BTreeMap::default)
+    ///                 .insert("key".to_string(), "value".to_string());
+    ///         })
+    ///     })
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn commit_entry<F>(&self, name: &str, pp: &PostParams, mut f: F) -> Result<K, CommitError>
+    where
+        K: Serialize,
+        F: for<'e> FnMut(Entry<'e, K>) -> OccupiedEntry<'e, K>,
+    {
+        const ATTEMPTS: u32 = 5;
+        let mut attempt = 1;
+        loop {
+            let entry = self.entry(name).await.map_err(CommitError::Save)?;
+            let mut entry = f(entry);
+            match entry.commit(pp).await {
+                Ok(()) => return Ok(entry.into_object()),
+                Err(CommitError::Save(Error::Api(err))) if err.code == 409 && attempt < ATTEMPTS => {
+                    tracing::debug!(
+                        attempt,
+                        "object was written concurrently, retrying: {}",
+                        err.message
+                    );
+                    attempt += 1;
+                }
+                Err(err) => return Err(err),
+            }
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -177,7 +227,7 @@ impl<'a, K> OccupiedEntry<'a, K> {
     /// client modified the object in the meantime).
     ///
     /// Any retries should be coarse-grained enough to also include the call to [`Api::entry`], so that the latest
-    /// state can be fetched.
+    /// state can be fetched, as [`Api::commit_entry`] does.
     #[tracing::instrument(skip(self))]
     pub async fn commit(&mut self, pp: &PostParams) -> Result<(), CommitError>
     where
@@ -12,12 +12,18 @@ mod subresource;
 #[cfg(feature = "ws")]
 #[cfg_attr(docsrs, doc(cfg(feature = "ws")))]
 pub use subresource::{Attach, AttachParams, Execute, Portforward};
-pub use subresource::{Evict, EvictParams, Log, LogParams, ScaleSpec, ScaleStatus};
+pub use subresource::{
+    Bind, Ephemeral, Evict, EvictParams, EvictionBlocked, EvictionOutcome, HasSubresource, Log, LogParams,
+    Proxy, Scale, ScaleSpec, ScaleStatus, Subresource,
+};
 
 mod util;
 
 pub mod entry;
 
+mod restricted;
+pub use restricted::Restriction;
+
 // Re-exports from kube-core
 #[cfg(feature = "admission")]
 #[cfg_attr(docsrs, doc(cfg(feature = "admission")))]
@@ -52,6 +58,8 @@ pub struct Api<K> {
     /// The client to use (from this library)
     pub(crate) client: Client,
     namespace: Option<String>,
+    /// The selectors of a view created by [`Api::restricted`]
+    restriction: Restriction,
     /// Note: Using `iter::Empty` over `PhantomData`, because we never actually keep any
     /// `K` objects, so `Empty` better models our constraints (in particular, `Empty<K>`
     /// is `Send`, even if `K` may not be).
@@ -71,6 +79,7 @@ impl<K: Resource> Api<K> {
             client,
             request: Request::new(url),
             namespace: None,
+            restriction: Restriction::default(),
             _phantom: std::iter::empty(),
         }
     }
@@ -88,6 +97,7 @@ impl<K: Resource> Api<K> {
             client,
             request: Request::new(url),
             namespace: Some(ns.to_string()),
+            restriction: Restriction::default(),
             _phantom: std::iter::empty(),
         }
     }
@@ -177,6 +187,7 @@ where
             client,
             request: Request::new(url),
             namespace: Some(ns.to_string()),
+            restriction: Restriction::default(),
             _phantom: std::iter::empty(),
         }
     }
@@ -209,6 +220,17 @@ where
         let ns = client.default_namespace().to_string();
         Self::namespaced(client, &ns)
     }
+
+    /// This api in the namespace `ns`, keeping the restriction of the view
+    pub(crate) fn in_namespace(&self, ns: &str) -> Self
+    where
+        K: Resource<Scope = NamespaceResourceScope>,
+    {
+        Self {
+            restriction: self.restriction.clone(),
+            ..Self::namespaced(self.client.clone(), ns)
+        }
+    }
 }
 
 impl<K> From<Api<K>> for Client {
@@ -224,12 +246,14 @@ impl<K> Debug for Api<K> {
             request,
             client: _,
             namespace,
+            restriction,
             _phantom,
         } = self;
         f.debug_struct("Api")
             .field("request", &request)
             .field("client", &"...")
             .field("namespace", &namespace)
+            .field("restriction", &restriction)
             .finish()
     }
 }
@@ -254,4 +278,842 @@ mod test {
         let _: Api<corev1::PersistentVolume> = Api::all(client.clone());
         let _: Api<corev1::ConfigMap> = Api::namespaced(client, "default");
     }
+
+    /// Responds to two list requests with two pages of `ConfigMap`s
+    async fn serve_pages(handle: mock::Handle<Request<Body>, Response<Body>>) {
+        futures::pin_mut!(handle);
+        let (request, send) = handle.next_request().await.expect("service not called");
+        assert_eq!(
+            request.uri().to_string(),
+            "/api/v1/namespaces/default/configmaps?&limit=2&resourceVersion=0&resourceVersionMatch=NotOlderThan"
+        );
+        let page = serde_json::json!({
+            "metadata": {"resourceVersion": "10", "continue": "abc"},
+            "items": [{"metadata": {"name": "a"}}, {"metadata": {"name": "b"}}],
+        });
+        send.send_response(Response::new(Body::from(page.to_string())));
+
+        let (request, send) = handle.next_request().await.expect("service not called");
+        assert_eq!(
+            request.uri().to_string(),
+            "/api/v1/namespaces/default/configmaps?&limit=2&continue=abc"
+        );
+        let page = serde_json::json!({
+            "metadata": {"resourceVersion": "10"},
+            "items": [{"metadata": {"name": "c"}}],
+        });
+        send.send_response(Response::new(Body::from(page.to_string())));
+    }
+
+    fn paged_params() -> crate::api::ListParams {
+        crate::api::ListParams::default()
+            .limit(2)
+            .at("0")
+            .matching(crate::api::VersionMatch::NotOlderThan)
+    }
+
+    #[tokio::test]
+    async fn list_stream_follows_continue_tokens() {
+        use futures::TryStreamExt;
+        let (mock_service, handle) = mock::pair::<Request<Body>, Response<Body>>();
+        let spawned = tokio::spawn(serve_pages(handle));
+        let api: Api<corev1::ConfigMap> = Api::namespaced(Client::new(mock_service, "default"), "default");
+
+        let names = api
+            .list_stream(&paged_params())
+            .await
+            .unwrap()
+            .map_ok(|cm| cm.metadata.name.unwrap())
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        assert_eq!(names, ["a", "b", "c"]);
+        spawned.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn list_pages_follows_continue_tokens() {
+        use futures::TryStreamExt;
+        let (mock_service, handle) = mock::pair::<Request<Body>, Response<Body>>();
+        let spawned = tokio::spawn(serve_pages(handle));
+        let api: Api<corev1::ConfigMap> = Api::namespaced(Client::new(mock_service, "default"), "default");
+
+        let pages = api
+            .list_pages(&paged_params())
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        let sizes = pages.iter().map(|page| page.items.len()).collect::<Vec<_>>();
+        assert_eq!(sizes, [2, 1]);
+        assert_eq!(pages[1].metadata.continue_, None);
+        spawned.await.unwrap();
+    }
+
+    fn apply_conflict(manager: &str) -> Response<Body> {
+        let status = serde_json::json!({
+            "kind": "Status",
+            "status": "Failure",
+            "message": format!("Apply failed with 1 conflict: conflict with \"{manager}\": .data.key"),
+            "reason": "Conflict",
+            "details": {
+                "causes": [{
+                    "reason": "FieldManagerConflict",
+                    "message": format!("conflict with \"{manager}\""),
+                    "field": ".data.key"
+                }]
+            },
+            "code": 409
+        });
+        Response::builder()
+            .status(409)
+            .body(Body::from(status.to_string()))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn apply_or_force_forces_accepted_conflicts() {
+        let (mock_service, handle) = mock::pair::<Request<Body>, Response<Body>>();
+        let spawned = tokio::spawn(async move {
+            futures::pin_mut!(handle);
+            let (request, send) = handle.next_request().await.expect("service not called");
+            assert_eq!(request.uri().query(), Some("&fieldManager=app"));
+            send.send_response(apply_conflict("kubectl-edit"));
+
+            let (request, send) = handle.next_request().await.expect("service not called");
+            assert_eq!(request.uri().query(), Some("&force=true&fieldManager=app"));
+            let cm = serde_json::json!({"metadata": {"name": "cm"}});
+            send.send_response(Response::new(Body::from(cm.to_string())));
+
+            let (_, send) = handle.next_request().await.expect("service not called");
+            send.send_response(apply_conflict("other-controller"));
+        });
+        let api: Api<corev1::ConfigMap> = Api::namespaced(Client::new(mock_service, "default"), "default");
+        let patch = serde_json::json!({"data": {"key": "value"}});
+        let from_kubectl = |conflicts: &[crate::error::ApplyConflict]| {
+            conflicts.iter().all(|c| c.manager.starts_with("kubectl"))
+        };
+
+        let pp = crate::api::PatchParams::apply("app");
+        api.apply_or_force("cm", &pp, &patch, from_kubectl).await.unwrap();
+        let err = api
+            .apply_or_force("cm", &pp, &patch, from_kubectl)
+            .await
+            .unwrap_err();
+        let conflicts = match err {
+            crate::Error::Api(err) => err.apply_conflicts().unwrap(),
+            err => panic!("expected a conflict, got {err:?}"),
+        };
+        assert_eq!(conflicts[0].manager, "other-controller");
+        spawned.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn delete_reports_precondition_failures() {
+        use crate::api::{DeleteParams, Preconditions};
+        let (mock_service, handle) = mock::pair::<Request<Body>, Response<Body>>();
+        let spawned = tokio::spawn(async move {
+            futures::pin_mut!(handle);
+            let (request, send) = handle.next_request().await.expect("service not called");
+            let body = hyper::body::to_bytes(request.into_body()).await.unwrap();
+            let dp: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            assert_eq!(dp, serde_json::json!({"preconditions": {"uid": "1f0a"}}));
+            let status = serde_json::json!({
+                "kind": "Status",
+                "status": "Failure",
+                "message": "Operation cannot be fulfilled on configmaps \"cm\": Precondition failed: UID in precondition: 1f0a, UID in object meta: 9c2b",
+                "reason": "Conflict",
+                "code": 409
+            });
+            send.send_response(
+                Response::builder()
+                    .status(409)
+                    .body(Body::from(status.to_string()))
+                    .unwrap(),
+            );
+        });
+        let api: Api<corev1::ConfigMap> = Api::namespaced(Client::new(mock_service, "default"), "default");
+
+        let dp = DeleteParams::default().preconditions(Preconditions::default().uid("1f0a"));
+        match api.delete("cm", &dp).await.unwrap_err() {
+            crate::Error::Api(err) => assert!(err.is_conflict()),
+            err => panic!("expected a conflict, got {err:?}"),
+        }
+        spawned.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn evict_reports_disruption_budgets() {
+        use crate::api::{EvictParams, EvictionOutcome};
+        let (mock_service, handle) = mock::pair::<Request<Body>, Response<Body>>();
+        let spawned = tokio::spawn(async move {
+            futures::pin_mut!(handle);
+            let (request, send) = handle.next_request().await.expect("service not called");
+            assert_eq!(
+                request.uri().path(),
+                "/api/v1/namespaces/default/pods/web-0/eviction"
+            );
+            let status = serde_json::json!({
+                "kind": "Status",
+                "status": "Failure",
+                "message": "Cannot evict pod as it would violate the pod's disruption budget.",
+                "reason": "TooManyRequests",
+                "details": {
+                    "causes": [{
+                        "reason": "DisruptionBudget",
+                        "message": "The disruption budget web needs 2 healthy pods and has 2 currently"
+                    }]
+                },
+                "code": 429
+            });
+            send.send_response(
+                Response::builder()
+                    .status(429)
+                    .body(Body::from(status.to_string()))
+                    .unwrap(),
+            );
+
+            let (_, send) = handle.next_request().await.expect("service not called");
+            let status = serde_json::json!({"kind": "Status", "status": "Success", "code": 201});
+            send.send_response(Response::new(Body::from(status.to_string())));
+        });
+        let pods: Api<corev1::Pod> = Api::namespaced(Client::new(mock_service, "default"), "default");
+
+        let blocked = match pods.evict("web-0", &EvictParams::default()).await.unwrap() {
+            EvictionOutcome::Blocked(blocked) => blocked,
+            outcome => panic!("expected the eviction to be blocked, got {outcome:?}"),
+        };
+        assert_eq!(blocked.disruption_budget.as_deref(), Some("web"));
+        assert!(pods
+            .evict("web-0", &EvictParams::default())
+            .await
+            .unwrap()
+            .is_evicted());
+        spawned.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn log_lines_resumes_followed_logs() {
+        use crate::api::LogParams;
+        use futures::TryStreamExt;
+        let (mock_service, handle) = mock::pair::<Request<Body>, Response<Body>>();
+        let spawned = tokio::spawn(async move {
+            futures::pin_mut!(handle);
+            let (request, send) = handle.next_request().await.expect("service not called");
+            let query = request.uri().query().unwrap().to_owned();
+            assert!(
+                query.contains("tailLines=2") && query.contains("timestamps=true"),
+                "{query}"
+            );
+            let logs = "2023-01-01T00:00:01.5Z a\n2023-01-01T00:00:02.1Z b\n2023-01-01T00:00:02.1Z c\n";
+            send.send_response(Response::new(Body::from(logs)));
+
+            // Resumed from before the last line, after the log file was rotated
+            let (request, send) = handle.next_request().await.expect("service not called");
+            let query = request.uri().query().unwrap().to_owned();
+            assert!(
+                query.contains("sinceSeconds=") && !query.contains("tailLines"),
+                "{query}"
+            );
+            let logs = "2023-01-01T00:00:01.5Z a\n2023-01-01T00:00:02.1Z b\n2023-01-01T00:00:02.1Z c\n2023-01-01T00:00:02.7Z d\n";
+            send.send_response(Response::new(Body::from(logs)));
+
+            // The container has terminated
+            let (_, send) = handle.next_request().await.expect("service not called");
+            send.send_response(Response::new(Body::empty()));
+        });
+        let pods: Api<corev1::Pod> = Api::namespaced(Client::new(mock_service, "default"), "default");
+
+        let lp = LogParams::default().follow().tail_lines(2);
+        let lines: Vec<String> = pods.log_lines("web-0", &lp).try_collect().await.unwrap();
+        assert_eq!(lines, ["a", "b", "c", "d"]);
+        spawned.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn log_lines_resumes_followed_logs_without_lines() {
+        use crate::api::LogParams;
+        use futures::TryStreamExt;
+        let (mock_service, handle) = mock::pair::<Request<Body>, Response<Body>>();
+        let spawned = tokio::spawn(async move {
+            futures::pin_mut!(handle);
+            // Dropped before the container logged anything
+            let (_, send) = handle.next_request().await.expect("service not called");
+            send.send_response(Response::new(Body::empty()));
+
+            let (request, send) = handle.next_request().await.expect("service not called");
+            assert!(request.uri().query().unwrap().contains("follow=true"));
+            send.send_response(Response::new(Body::from("2023-01-01T00:00:01Z a\n")));
+
+            // The container has terminated
+            let (_, send) = handle.next_request().await.expect("service not called");
+            send.send_response(Response::new(Body::from("2023-01-01T00:00:01Z a\n")));
+        });
+        let pods: Api<corev1::Pod> = Api::namespaced(Client::new(mock_service, "default"), "default");
+
+        let lp = LogParams::default().follow();
+        let lines: Vec<String> = pods.log_lines("web-0", &lp).try_collect().await.unwrap();
+        assert_eq!(lines, ["a"]);
+        spawned.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn patch_status_from_sends_the_status() {
+        use crate::api::{Patch, PatchParams};
+        let (mock_service, handle) = mock::pair::<Request<Body>, Response<Body>>();
+        let spawned = tokio::spawn(async move {
+            futures::pin_mut!(handle);
+            for content_type in ["application/apply-patch+yaml", "application/merge-patch+json"] {
+                let (request, send) = handle.next_request().await.expect("service not called");
+                assert_eq!(
+                    request.uri().path(),
+                    "/apis/batch/v1/namespaces/default/jobs/baz/status"
+                );
+                assert_eq!(request.headers()[http::header::CONTENT_TYPE], content_type);
+                let body = hyper::body::to_bytes(request.into_body()).await.unwrap();
+                let patch: serde_json::Value = serde_json::from_slice(&body).unwrap();
+                assert_eq!(
+                    patch,
+                    serde_json::json!({"apiVersion": "batch/v1", "kind": "Job", "status": {"succeeded": 2}})
+                );
+                send.send_response(Response::new(Body::from(body)));
+            }
+            let (request, send) = handle.next_request().await.expect("service not called");
+            let body = hyper::body::to_bytes(request.into_body()).await.unwrap();
+            let patch: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            assert_eq!(
+                patch,
+                serde_json::json!({"apiVersion": "batch/v1", "kind": "Job"})
+            );
+            let job =
+                serde_json::json!({"apiVersion": "batch/v1", "kind": "Job", "metadata": {"name": "baz"}});
+            send.send_response(Response::new(Body::from(job.to_string())));
+        });
+        let jobs: Api<k8s_openapi::api::batch::v1::Job> =
+            Api::namespaced(Client::new(mock_service, "default"), "default");
+        let mut job = k8s_openapi::api::batch::v1::Job::default();
+        job.metadata.name = Some("baz".into());
+        job.spec = Some(Default::default());
+        job.status = Some(k8s_openapi::api::batch::v1::JobStatus {
+            succeeded: Some(2),
+            ..Default::default()
+        });
+
+        let patched = jobs
+            .patch_status_from(&job, &PatchParams::apply("app"), Patch::Apply)
+            .await
+            .unwrap();
+        assert_eq!(patched.status.unwrap().succeeded, Some(2));
+        let patched = jobs
+            .patch_status_from(&job, &PatchParams::default(), Patch::Merge)
+            .await
+            .unwrap();
+        assert_eq!(patched.status.unwrap().succeeded, Some(2));
+
+        // Objects without status do not clear it
+        job.status = None;
+        jobs.patch_status_from(&job, &PatchParams::default(), Patch::Merge)
+            .await
+            .unwrap();
+        job.metadata.name = None;
+        assert!(jobs
+            .patch_status_from(&job, &PatchParams::default(), Patch::Merge)
+            .await
+            .is_err());
+        spawned.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn bind_names_the_binding_after_the_pod() {
+        let (mock_service, handle) = mock::pair::<Request<Body>, Response<Body>>();
+        let spawned = tokio::spawn(async move {
+            futures::pin_mut!(handle);
+            let (request, send) = handle.next_request().await.expect("service not called");
+            assert_eq!(request.method(), http::Method::POST);
+            assert_eq!(
+                request.uri().path(),
+                "/api/v1/namespaces/default/pods/web-0/binding"
+            );
+            let body = hyper::body::to_bytes(request.into_body()).await.unwrap();
+            let binding: corev1::Binding = serde_json::from_slice(&body).unwrap();
+            assert_eq!(binding.metadata.name.as_deref(), Some("web-0"));
+            assert_eq!(binding.target.name.as_deref(), Some("node-1"));
+            let status = serde_json::json!({"kind": "Status", "status": "Success", "code": 201});
+            send.send_response(Response::new(Body::from(status.to_string())));
+        });
+        let pods: Api<corev1::Pod> = Api::namespaced(Client::new(mock_service, "default"), "default");
+
+        let binding = corev1::Binding {
+            target: corev1::ObjectReference {
+                kind: Some("Node".into()),
+                name: Some("node-1".into()),
+                ..corev1::ObjectReference::default()
+            },
+            ..corev1::Binding::default()
+        };
+        let status = pods.bind("web-0", &Default::default(), &binding).await.unwrap();
+        assert_eq!(status.code, 201);
+        spawned.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn typed_subresources_use_their_name_and_object() {
+        use crate::api::{HasSubresource, Patch, PatchParams, Subresource};
+        #[derive(Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+        struct Usage {
+            cpu: String,
+        }
+        struct UsageSubresource;
+        impl Subresource for UsageSubresource {
+            type Object = Usage;
+
+            const NAME: &'static str = "usage";
+        }
+        impl HasSubresource<UsageSubresource> for corev1::ConfigMap {}
+
+        let (mock_service, handle) = mock::pair::<Request<Body>, Response<Body>>();
+        let spawned = tokio::spawn(async move {
+            futures::pin_mut!(handle);
+            for method in [http::Method::GET, http::Method::PATCH, http::Method::PUT] {
+                let (request, send) = handle.next_request().await.expect("service not called");
+                assert_eq!(request.method(), method);
+                assert_eq!(
+                    request.uri().path(),
+                    "/api/v1/namespaces/default/configmaps/cm/usage"
+                );
+                let usage = serde_json::json!({"cpu": "2"});
+                send.send_response(Response::new(Body::from(usage.to_string())));
+            }
+        });
+        let api: Api<corev1::ConfigMap> = Api::namespaced(Client::new(mock_service, "default"), "default");
+
+        let usage = api.get_typed_subresource::<UsageSubresource>("cm").await.unwrap();
+        assert_eq!(usage.cpu, "2");
+        let patch = Patch::Merge(serde_json::json!({"cpu": "2"}));
+        api.patch_typed_subresource::<UsageSubresource, _>("cm", &PatchParams::default(), &patch)
+            .await
+            .unwrap();
+        api.replace_typed_subresource::<UsageSubresource>("cm", &Default::default(), &usage)
+            .await
+            .unwrap();
+        spawned.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn approve_adds_an_approved_condition() {
+        use k8s_openapi::api::certificates::v1::CertificateSigningRequest;
+        let (mock_service, handle) = mock::pair::<Request<Body>, Response<Body>>();
+        let spawned = tokio::spawn(async move {
+            futures::pin_mut!(handle);
+            let (request, send) = handle.next_request().await.expect("service not called");
+            assert_eq!(request.method(), http::Method::GET);
+            let csr = serde_json::json!({
+                "metadata": {"name": "webhook", "resourceVersion": "3"},
+                "spec": {"request": "", "signerName": "example.com/serving"}
+            });
+            send.send_response(Response::new(Body::from(csr.to_string())));
+
+            let (request, send) = handle.next_request().await.expect("service not called");
+            assert_eq!(request.method(), http::Method::PUT);
+            assert_eq!(
+                request.uri().path(),
+                "/apis/certificates.k8s.io/v1/certificatesigningrequests/webhook/approval"
+            );
+            let body = hyper::body::to_bytes(request.into_body()).await.unwrap();
+            let csr: CertificateSigningRequest = serde_json::from_slice(&body).unwrap();
+            let conditions = csr.status.as_ref().unwrap().conditions.as_ref().unwrap();
+            assert_eq!(conditions[0].type_, "Approved");
+            assert_eq!(conditions[0].status, "True");
+            assert_eq!(conditions[0].reason.as_deref(), Some("Bootstrap"));
+            send.send_response(Response::new(Body::from(body)));
+        });
+        let csrs: Api<CertificateSigningRequest> = Api::all(Client::new(mock_service, "default"));
+
+        let csr = csrs
+            .approve("webhook", "Bootstrap", "approved by test")
+            .await
+            .unwrap();
+        assert_eq!(csr.metadata.resource_version.as_deref(), Some("3"));
+        spawned.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn get_opt_maps_not_found_to_none() {
+        let (mock_service, handle) = mock::pair::<Request<Body>, Response<Body>>();
+        let spawned = tokio::spawn(async move {
+            futures::pin_mut!(handle);
+            let status = serde_json::json!({
+                "kind": "Status",
+                "status": "Failure",
+                "message": "pods \"web-0\" not found",
+                "reason": "NotFound",
+                "code": 404
+            });
+            for (code, body) in [
+                (404, status.to_string()),
+                (404, "404 page not found".into()),
+                (403, "forbidden".into()),
+            ] {
+                let (_, send) = handle.next_request().await.expect("service not called");
+                send.send_response(Response::builder().status(code).body(Body::from(body)).unwrap());
+            }
+        });
+        let pods: Api<corev1::Pod> = Api::namespaced(Client::new(mock_service, "default"), "default");
+
+        assert!(pods.get_opt("web-0").await.unwrap().is_none());
+        // A 404 without a NotFound Status is not a missing object, but a path that is not served
+        assert!(pods.get_metadata_opt("web-0").await.is_err());
+        assert!(pods.get_opt("web-0").await.is_err());
+        spawned.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn get_with_version_semantics() {
+        use crate::api::GetParams;
+        let (mock_service, handle) = mock::pair::<Request<Body>, Response<Body>>();
+        let spawned = tokio::spawn(async move {
+            futures::pin_mut!(handle);
+            let cm = |rv: &str| serde_json::json!({"metadata": {"name": "cm", "resourceVersion": rv}});
+            let list = serde_json::json!({"metadata": {"resourceVersion": "5"}, "items": [cm("5")]});
+            for (query, body) in [
+                (
+                    "fieldSelector=metadata.name%3Dcm&resourceVersion=5&resourceVersionMatch=Exact",
+                    list,
+                ),
+                ("resourceVersion=7", cm("7")),
+                ("resourceVersion=7", cm("8")),
+                ("resourceVersion=8", cm("8")),
+            ] {
+                let (request, send) = handle.next_request().await.expect("service not called");
+                assert_eq!(request.uri().query(), Some(&*format!("&{query}")));
+                send.send_response(Response::new(Body::from(body.to_string())));
+            }
+        });
+        let api: Api<corev1::ConfigMap> = Api::namespaced(Client::new(mock_service, "default"), "default");
+
+        let cm = api.get_with("cm", &GetParams::exact("5")).await.unwrap();
+        assert_eq!(cm.metadata.resource_version.as_deref(), Some("5"));
+        // Unchanged objects are only fetched as metadata
+        assert!(api.get_if_changed("cm", "7").await.unwrap().is_none());
+        let cm = api.get_if_changed("cm", "7").await.unwrap().unwrap();
+        assert_eq!(cm.metadata.resource_version.as_deref(), Some("8"));
+        spawned.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn metadata_requests_accept_partial_objects() {
+        use crate::api::{ListParams, WatchEvent, WatchParams};
+        use futures::TryStreamExt;
+        let (mock_service, handle) = mock::pair::<Request<Body>, Response<Body>>();
+        let spawned = tokio::spawn(async move {
+            futures::pin_mut!(handle);
+            let meta = serde_json::json!({
+                "apiVersion": "meta.k8s.io/v1",
+                "kind": "PartialObjectMetadata",
+                "metadata": {"name": "web-0", "labels": {"app": "web"}, "resourceVersion": "7"}
+            });
+            let list = serde_json::json!({
+                "apiVersion": "meta.k8s.io/v1",
+                "kind": "PartialObjectMetadataList",
+                "metadata": {"resourceVersion": "8"},
+                "items": [meta]
+            });
+            let event = serde_json::json!({"type": "ADDED", "object": meta});
+            for (accept, body) in [
+                ("as=PartialObjectMetadata;", meta.to_string()),
+                ("as=PartialObjectMetadataList;", list.to_string()),
+                ("as=PartialObjectMetadata;", event.to_string()),
+            ] {
+                let (request, send) = handle.next_request().await.expect("service not called");
+                let header = request.headers()[http::header::ACCEPT]
+                    .to_str()
+                    .unwrap()
+                    .to_owned();
+                assert!(header.contains(accept), "{header}");
+                send.send_response(Response::new(Body::from(body)));
+            }
+        });
+        let pods: Api<corev1::Pod> = Api::namespaced(Client::new(mock_service, "default"), "default");
+
+        let meta = pods.get_metadata("web-0").await.unwrap();
+        assert_eq!(meta.metadata.resource_version.as_deref(), Some("7"));
+        let list = pods.list_metadata(&ListParams::default()).await.unwrap();
+        assert_eq!(list.items[0].metadata.labels.as_ref().unwrap()["app"], "web");
+        let events: Vec<_> = pods
+            .watch_metadata(&WatchParams::default(), "8")
+            .await
+            .unwrap()
+            .try_collect()
+            .await
+            .unwrap();
+        assert!(
+            matches!(&events[..], [WatchEvent::Added(meta)] if meta.metadata.name.as_deref() == Some("web-0"))
+        );
+        spawned.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn patch_metadata_from_sends_only_metadata() {
+        use crate::api::{ObjectMeta, PatchParams};
+        let (mock_service, handle) = mock::pair::<Request<Body>, Response<Body>>();
+        let spawned = tokio::spawn(async move {
+            futures::pin_mut!(handle);
+            for (content_type, query) in [
+                ("application/apply-patch+yaml", "&fieldManager=app"),
+                ("application/merge-patch+json", ""),
+            ] {
+                let (request, send) = handle.next_request().await.expect("service not called");
+                assert_eq!(request.headers()["content-type"], content_type);
+                assert_eq!(request.uri().query(), Some(query));
+                let body = hyper::body::to_bytes(request.into_body()).await.unwrap();
+                let patch: serde_json::Value = serde_json::from_slice(&body).unwrap();
+                assert_eq!(
+                    patch,
+                    serde_json::json!({
+                        "apiVersion": "v1",
+                        "kind": "Pod",
+                        "metadata": {"labels": {"app": "web"}}
+                    })
+                );
+                let meta = serde_json::json!({
+                    "apiVersion": "meta.k8s.io/v1",
+                    "kind": "PartialObjectMetadata",
+                    "metadata": {"name": "web-0", "labels": {"app": "web"}}
+                });
+                send.send_response(Response::new(Body::from(meta.to_string())));
+            }
+        });
+        let pods: Api<corev1::Pod> = Api::namespaced(Client::new(mock_service, "default"), "default");
+        let metadata = ObjectMeta {
+            labels: Some([("app".to_string(), "web".to_string())].into()),
+            ..ObjectMeta::default()
+        };
+
+        let meta = pods
+            .patch_metadata_from("web-0", &PatchParams::apply("app"), &metadata)
+            .await
+            .unwrap();
+        assert_eq!(meta.metadata.name.as_deref(), Some("web-0"));
+        pods.patch_metadata_from("web-0", &PatchParams::default(), &metadata)
+            .await
+            .unwrap();
+        spawned.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn scale_replicas_patches_the_scale() {
+        use crate::api::PatchParams;
+        let (mock_service, handle) = mock::pair::<Request<Body>, Response<Body>>();
+        let spawned = tokio::spawn(async move {
+            futures::pin_mut!(handle);
+            let (request, send) = handle.next_request().await.expect("service not called");
+            assert_eq!(request.method(), http::Method::PATCH);
+            assert_eq!(
+                request.uri().path(),
+                "/apis/apps/v1/namespaces/default/deployments/blog/scale"
+            );
+            assert_eq!(request.headers()["content-type"], "application/merge-patch+json");
+            let body = hyper::body::to_bytes(request.into_body()).await.unwrap();
+            let patch: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            assert_eq!(patch, serde_json::json!({"spec": {"replicas": 3}}));
+            let scale = serde_json::json!({
+                "apiVersion": "autoscaling/v1",
+                "kind": "Scale",
+                "metadata": {"name": "blog"},
+                "spec": {"replicas": 3}
+            });
+            send.send_response(Response::new(Body::from(scale.to_string())));
+        });
+        let deploys: Api<k8s_openapi::api::apps::v1::Deployment> =
+            Api::namespaced(Client::new(mock_service, "default"), "default");
+
+        let scale = deploys
+            .scale_replicas("blog", &PatchParams::default(), 3)
+            .await
+            .unwrap();
+        assert_eq!(scale.spec.unwrap().replicas, Some(3));
+        spawned.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn commit_entry_retries_conflicts() {
+        use http::Method;
+        let (mock_service, handle) = mock::pair::<Request<Body>, Response<Body>>();
+        let spawned = tokio::spawn(async move {
+            futures::pin_mut!(handle);
+            let status = |code: u16, reason: &str| {
+                let status = serde_json::json!({"kind": "Status", "status": "Failure", "reason": reason, "code": code});
+                Response::builder()
+                    .status(code)
+                    .body(Body::from(status.to_string()))
+                    .unwrap()
+            };
+            let cm = |rv: &str| {
+                let cm = serde_json::json!({"metadata": {"name": "cm", "namespace": "default", "resourceVersion": rv}});
+                Response::new(Body::from(cm.to_string()))
+            };
+            // Created by another client in the meantime, and then modified by another client
+            let responses = [
+                (Method::GET, status(404, "NotFound")),
+                (Method::POST, status(409, "AlreadyExists")),
+                (Method::GET, cm("1")),
+                (Method::PUT, status(409, "Conflict")),
+                (Method::GET, cm("2")),
+                (Method::PUT, cm("3")),
+            ];
+            for (method, response) in responses {
+                let (request, send) = handle.next_request().await.expect("service not called");
+                assert_eq!(request.method(), method);
+                send.send_response(response);
+            }
+        });
+        let cms: Api<corev1::ConfigMap> = Api::namespaced(Client::new(mock_service, "default"), "default");
+
+        let mut calls = 0;
+        let cm = cms
+            .commit_entry("cm", &Default::default(), |entry| {
+                calls += 1;
+                entry.or_insert(Default::default).and_modify(|cm| {
+                    cm.data = Some([("key".to_owned(), "value".to_owned())].into());
+                })
+            })
+            .await
+            .unwrap();
+        assert_eq!(calls, 3);
+        assert_eq!(cm.metadata.resource_version.as_deref(), Some("3"));
+        spawned.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn restricted_views_filter_requests() {
+        use crate::api::{ListParams, Restriction};
+        let (mock_service, handle) = mock::pair::<Request<Body>, Response<Body>>();
+        let spawned = tokio::spawn(async move {
+            futures::pin_mut!(handle);
+            let pod = |name: &str, node: &str| {
+                serde_json::json!({
+                    "apiVersion": "v1",
+                    "kind": "Pod",
+                    "metadata": {"name": name, "labels": {"app": "web"}},
+                    "spec": {"nodeName": node, "containers": []}
+                })
+            };
+            let meta = serde_json::json!({
+                "apiVersion": "meta.k8s.io/v1",
+                "kind": "PartialObjectMetadata",
+                "metadata": {"name": "web-1", "labels": {"app": "web"}}
+            });
+            let list = |items: Vec<serde_json::Value>| serde_json::json!({"apiVersion": "v1", "kind": "List", "metadata": {}, "items": items});
+            for (path, query, body) in [
+                (
+                    "/api/v1/namespaces/default/pods",
+                    Some("&fieldSelector=spec.nodeName%3Dnode-1&labelSelector=app%3Dweb%2Ctier%3Dfrontend"),
+                    list(vec![]),
+                ),
+                ("/api/v1/namespaces/default/pods/web-0", None, pod("web-0", "node-2")),
+                ("/api/v1/namespaces/default/pods/web-1", None, pod("web-1", "node-1")),
+                ("/api/v1/namespaces/default/pods/web-1", None, meta.clone()),
+                (
+                    "/api/v1/namespaces/default/pods",
+                    Some("&fieldSelector=spec.nodeName%3Dnode-1%2Cmetadata.name%3Dweb-1&labelSelector=app%3Dweb"),
+                    list(vec![meta]),
+                ),
+            ] {
+                let (request, send) = handle.next_request().await.expect("service not called");
+                assert_eq!(request.uri().path(), path);
+                assert_eq!(request.uri().query(), query);
+                send.send_response(Response::builder().body(Body::from(body.to_string())).unwrap());
+            }
+        });
+        let restriction = Restriction::default()
+            .fields("spec.nodeName=node-1".parse().unwrap())
+            .labels("app=web".parse().unwrap());
+        let pods: Api<corev1::Pod> =
+            Api::namespaced(Client::new(mock_service, "default"), "default").restricted(restriction);
+
+        let lp = ListParams::default().labels("tier=frontend");
+        assert!(pods.list(&lp).await.unwrap().items.is_empty());
+        // Objects outside of the view are not found
+        assert!(pods.get_opt("web-0").await.unwrap().is_none());
+        assert!(pods.get_opt("web-1").await.unwrap().is_some());
+        // The node of the metadata is checked with a list
+        assert!(pods.get_metadata_opt("web-1").await.unwrap().is_some());
+        spawned.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn proxy_http_returns_the_response() {
+        let (mock_service, handle) = mock::pair::<Request<Body>, Response<Body>>();
+        let spawned = tokio::spawn(async move {
+            futures::pin_mut!(handle);
+            let (request, send) = handle.next_request().await.expect("service not called");
+            assert_eq!(request.method(), http::Method::POST);
+            assert_eq!(
+                request.uri().to_string(),
+                "/api/v1/namespaces/default/pods/web-0:9090/proxy/admin/reload?force=true"
+            );
+            assert_eq!(hyper::body::to_bytes(request.into_body()).await.unwrap(), "{}");
+            send.send_response(
+                Response::builder()
+                    .status(503)
+                    .body(Body::from("reloading"))
+                    .unwrap(),
+            );
+        });
+        let pods: Api<corev1::Pod> = Api::namespaced(Client::new(mock_service, "default"), "default");
+
+        let request = Request::post("/admin/reload?force=true")
+            .body(b"{}".to_vec())
+            .unwrap();
+        // The status of the proxied endpoint is not an error of the apiserver
+        let response = pods.proxy_http("web-0:9090", request).await.unwrap();
+        assert_eq!(response.status(), 503);
+        assert_eq!(response.body(), b"reloading");
+        spawned.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn list_across_merges_namespaces() {
+        use crate::api::{ListParams, ResourceExt};
+        use futures::TryStreamExt;
+        let (mock_service, handle) = mock::pair::<Request<Body>, Response<Body>>();
+        let spawned = tokio::spawn(async move {
+            futures::pin_mut!(handle);
+            let list = |names: &[&str], continue_: &str| {
+                let items: Vec<_> = names
+                    .iter()
+                    .map(|name| serde_json::json!({"metadata": {"name": name}}))
+                    .collect();
+                serde_json::json!({"metadata": {"continue": continue_}, "items": items}).to_string()
+            };
+            for _ in 0..5 {
+                let (request, send) = handle.next_request().await.expect("service not called");
+                let body = match request.uri().to_string().as_str() {
+                    "/api/v1/namespaces/a/configmaps?&limit=1" => list(&["a-0"], "next"),
+                    "/api/v1/namespaces/a/configmaps?&limit=1&continue=next" => list(&["a-1"], ""),
+                    "/api/v1/namespaces/b/configmaps?&limit=1" => list(&["b-0"], ""),
+                    "/api/v1/namespaces/a/configmaps?" => list(&["a-0", "a-1"], ""),
+                    "/api/v1/namespaces/b/configmaps?" => list(&["b-0"], ""),
+                    uri => panic!("unexpected request {uri}"),
+                };
+                send.send_response(Response::builder().body(Body::from(body)).unwrap());
+            }
+        });
+        // Also from a namespaced api, and with a duplicate namespace
+        let cms: Api<corev1::ConfigMap> = Api::namespaced(Client::new(mock_service, "default"), "other");
+
+        let list = cms
+            .list_across(&["a", "b", "a"], &ListParams::default().limit(1))
+            .await
+            .unwrap();
+        let names: Vec<_> = list.iter().map(ResourceExt::name_any).collect();
+        assert_eq!(names, ["a-0", "a-1", "b-0"]);
+        let items: Vec<_> = cms
+            .list_across_stream(&["a", "b"], &ListParams::default())
+            .try_collect()
+            .await
+            .unwrap();
+        let names: Vec<_> = items.iter().map(ResourceExt::name_any).collect();
+        assert_eq!(names, ["a-0", "a-1", "b-0"]);
+        spawned.await.unwrap();
+    }
 }
@@ -82,6 +82,7 @@ enum Message {
 /// Provides `AsyncRead + AsyncWrite` for each port and **does not** bind to local ports.  Error
 /// channel for each port is only written by the server when there's an exception and
 /// the port cannot be used (didn't initialize or can't be used anymore).
+#[cfg_attr(docsrs, doc(cfg(feature = "ws")))]
 pub struct Portforwarder {
     ports: HashMap<u16, DuplexStream>,
     errors: HashMap<u16, ErrorReceiver>,
@@ -144,6 +145,7 @@ impl Portforwarder {
     }
 
     /// Waits for port forwarding task to complete.
+    #[allow(clippy::result_large_err)]
     pub async fn join(self) -> Result<(), Error> {
         let Self {
             mut ports,
@@ -354,3 +356,74 @@ where
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tokio::io::AsyncReadExt;
+    use tokio_tungstenite::tungstenite::protocol::Role;
+
+    // Sends the initial frames of the data and error channels of each port, like the kubelet
+    async fn init_channels<S>(server: &mut WebSocketStream<S>, ports: &[u16])
+    where
+        S: AsyncRead + AsyncWrite + Unpin,
+    {
+        for (i, port) in ports.iter().enumerate() {
+            for ch in [2 * i as u8, 2 * i as u8 + 1] {
+                let [lo, hi] = port.to_le_bytes();
+                server.send(ws::Message::binary(vec![ch, lo, hi])).await.unwrap();
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn forwards_each_port_on_its_own_channels() {
+        let (client, server) = tokio::io::duplex(1024);
+        let client = WebSocketStream::from_raw_socket(client, Role::Client, None).await;
+        let mut server = WebSocketStream::from_raw_socket(server, Role::Server, None).await;
+        let mut pf = Portforwarder::new(client, &[80, 443]);
+        init_channels(&mut server, &[80, 443]).await;
+
+        server
+            .send(ws::Message::binary(b"\x02hello".to_vec()))
+            .await
+            .unwrap();
+        let mut https = pf.take_stream(443).unwrap();
+        let mut buf = [0; 5];
+        https.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"hello");
+
+        let mut http = pf.take_stream(80).unwrap();
+        http.write_all(b"ping").await.unwrap();
+        assert_eq!(
+            server.next().await.unwrap().unwrap(),
+            ws::Message::binary(b"\x00ping".to_vec())
+        );
+        assert!(pf.take_stream(80).is_none());
+
+        server
+            .send(ws::Message::binary(b"\x01refused".to_vec()))
+            .await
+            .unwrap();
+        assert_eq!(pf.take_error(80).unwrap().await.as_deref(), Some("refused"));
+        pf.abort();
+    }
+
+    #[tokio::test]
+    async fn rejects_mismatching_port() {
+        let (client, server) = tokio::io::duplex(1024);
+        let client = WebSocketStream::from_raw_socket(client, Role::Client, None).await;
+        let mut server = WebSocketStream::from_raw_socket(server, Role::Server, None).await;
+        let pf = Portforwarder::new(client, &[80]);
+        init_channels(&mut server, &[8080]).await;
+
+        assert!(matches!(
+            pf.join().await,
+            Err(Error::InvalidPortMapping {
+                actual: 8080,
+                expected: 80
+            })
+        ));
+    }
+}
@@ -226,6 +226,7 @@ impl AttachedProcess {
     }
 
     /// Waits for the remote command task to complete.
+    #[allow(clippy::result_large_err)]
     pub async fn join(self) -> Result<(), Error> {
         self.task.await.unwrap_or_else(|e| Err(Error::Spawn(e)))
     }
@@ -237,6 +238,28 @@ impl AttachedProcess {
         self.status_rx.take().map(|recv| recv.map(|res| res.ok()))
     }
 
+    /// Take a future that resolves with the exit code of the remote command.
+    ///
+    /// The exit code is read from the status object, so this and [`AttachedProcess::take_status`] are mutually exclusive.
+    /// Resolves with `None` if the command did not report how it exited, for example because it was aborted.
+    ///
+    /// ```no_run
+    /// # use kube_client::api::AttachedProcess;
+    /// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let mut attached: AttachedProcess = todo!();
+    /// let exit_code = attached.take_exit_code().unwrap();
+    /// attached.join().await?;
+    /// assert_eq!(exit_code.await, Some(0));
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// Returns `None` if called more than once, or after [`AttachedProcess::take_status`].
+    pub fn take_exit_code(&mut self) -> Option<impl Future<Output = Option<i32>>> {
+        self.take_status()
+            .map(|status| status.map(|status| status.as_ref().and_then(exit_code)))
+    }
+
     /// Async writer to change the terminal size
     /// ```no_run
     /// # use kube_client::api::{AttachedProcess, TerminalSize};
@@ -258,6 +281,27 @@ impl AttachedProcess {
     }
 }
 
+/// The exit code that the `status` of a remote command reports
+///
+/// Successful commands report no code, and failed commands report it as the `ExitCode` cause of a `NonZeroExitCode`.
+fn exit_code(status: &Status) -> Option<i32> {
+    match (status.status.as_deref(), status.reason.as_deref()) {
+        (Some("Success"), _) => Some(0),
+        (Some("Failure"), Some("NonZeroExitCode")) => status
+            .details
+            .as_ref()?
+            .causes
+            .as_ref()?
+            .iter()
+            .find(|cause| cause.reason.as_deref() == Some("ExitCode"))?
+            .message
+            .as_ref()?
+            .parse()
+            .ok(),
+        _ => None,
+    }
+}
+
 // theses values come from here: https://github.com/kubernetes/kubernetes/blob/master/pkg/kubelet/cri/streaming/remotecommand/websocket.go#L34
 const STDIN_CHANNEL: u8 = 0;
 const STDOUT_CHANNEL: u8 = 1;
@@ -393,3 +437,78 @@ async fn filter_message(wsm: Result<ws::Message, ws::Error>) -> Option<Result<Me
         Err(err) => Some(Err(err)),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tokio::io::AsyncReadExt;
+    use tokio_tungstenite::tungstenite::protocol::Role;
+
+    #[tokio::test]
+    async fn attached_tty() {
+        let (client, server) = tokio::io::duplex(1024);
+        let client = WebSocketStream::from_raw_socket(client, Role::Client, None).await;
+        let mut server = WebSocketStream::from_raw_socket(server, Role::Server, None).await;
+        let mut attached = AttachedProcess::new(client, &AttachParams::interactive_tty());
+        assert!(attached.stderr().is_none());
+        let mut stdin = attached.stdin().unwrap();
+        let mut stdout = attached.stdout().unwrap();
+
+        server
+            .send(ws::Message::binary(b"\x01$ ".to_vec()))
+            .await
+            .unwrap();
+        let mut prompt = [0; 2];
+        stdout.read_exact(&mut prompt).await.unwrap();
+        assert_eq!(&prompt, b"$ ");
+
+        attached
+            .terminal_size()
+            .unwrap()
+            .send(TerminalSize {
+                width: 80,
+                height: 24,
+            })
+            .await
+            .unwrap();
+        assert_eq!(
+            server.next().await.unwrap().unwrap(),
+            ws::Message::binary(b"\x04{\"width\":80,\"height\":24}".to_vec())
+        );
+
+        stdin.write_all(b"exit\n").await.unwrap();
+        assert_eq!(
+            server.next().await.unwrap().unwrap(),
+            ws::Message::binary(b"\x00exit\n".to_vec())
+        );
+
+        let status = attached.take_status().unwrap();
+        server
+            .send(ws::Message::binary(
+                b"\x03{\"metadata\":{},\"status\":\"Success\"}".to_vec(),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(status.await.unwrap().status.as_deref(), Some("Success"));
+        attached.join().await.unwrap();
+    }
+
+    #[test]
+    fn exit_code_from_status() {
+        let success = serde_json::from_str(r#"{"metadata":{},"status":"Success"}"#).unwrap();
+        assert_eq!(exit_code(&success), Some(0));
+
+        let failure = serde_json::from_str(
+            r#"{"metadata":{},"status":"Failure","message":"command terminated with non-zero exit code: error executing command [sh -c exit 3], exit code 3","reason":"NonZeroExitCode","details":{"causes":[{"reason":"ExitCode","message":"3"}]}}"#,
+        )
+        .unwrap();
+        assert_eq!(exit_code(&failure), Some(3));
+
+        let error = serde_json::from_str(
+            r#"{"metadata":{},"status":"Failure","message":"container not found","reason":"InternalError"}"#,
+        )
+        .unwrap();
+        assert_eq!(exit_code(&error), None);
+    }
+}
@@ -0,0 +1,207 @@
+use std::collections::BTreeMap;
+
+use kube_core::{
+    fields, labels,
+    params::{ListParams, WatchParams},
+    ErrorResponse,
+};
+use serde_json::Value;
+
+use crate::{Api, Error, Result};
+
+/// The selectors of a restricted view of a kind, see [`Api::restricted`]
+///
+/// ```
+/// use kube::api::Restriction;
+/// # fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+/// let restriction = Restriction::default()
+///     .fields("spec.nodeName=node-1".parse()?)
+///     .labels("app=web".parse()?);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Restriction {
+    /// The objects must match this field selector
+    pub fields: fields::Selector,
+    /// The labels of the objects must match this label selector
+    pub labels: labels::Selector,
+}
+
+impl Restriction {
+    /// Only objects whose fields match the `selector`
+    #[must_use]
+    pub fn fields(mut self, selector: fields::Selector) -> Self {
+        self.fields = self.fields & selector;
+        self
+    }
+
+    /// Only objects whose labels match the `selector`
+    #[must_use]
+    pub fn labels(mut self, selector: labels::Selector) -> Self {
+        self.labels = selector
+            .expressions()
+            .iter()
+            .cloned()
+            .fold(self.labels, labels::Selector::with);
+        self
+    }
+
+    /// Whether the restriction admits every object
+    pub fn selects_all(&self) -> bool {
+        self.fields.selects_all() && self.labels.selects_all()
+    }
+
+    /// Whether the restriction admits the serialized `object`
+    ///
+    /// When the object only has its metadata, only the fields of the metadata are checked.
+    pub(crate) fn admits(&self, object: &Value, metadata_only: bool) -> bool {
+        let labels: BTreeMap<String, String> = object
+            .pointer("/metadata/labels")
+            .and_then(|labels| serde_json::from_value(labels.clone()).ok())
+            .unwrap_or_default();
+        self.labels.matches(&labels)
+            && self.fields.expressions().iter().all(|expression| {
+                (metadata_only && !expression.field().starts_with("metadata.")) || expression.matches(object)
+            })
+    }
+
+    /// Whether checking a metadata-only object leaves fields outside of the metadata unchecked
+    pub(crate) fn checks_more_than_metadata(&self) -> bool {
+        self.fields
+            .expressions()
+            .iter()
+            .any(|expression| !expression.field().starts_with("metadata."))
+    }
+
+    pub(crate) fn list_params(&self, lp: &ListParams) -> ListParams {
+        ListParams {
+            field_selector: join(&self.fields, lp.field_selector.as_deref()),
+            label_selector: join(&self.labels, lp.label_selector.as_deref()),
+            ..lp.clone()
+        }
+    }
+
+    pub(crate) fn watch_params(&self, wp: &WatchParams) -> WatchParams {
+        WatchParams {
+            field_selector: join(&self.fields, wp.field_selector.as_deref()),
+            label_selector: join(&self.labels, wp.label_selector.as_deref()),
+            ..wp.clone()
+        }
+    }
+
+    /// The error of a get for an object outside of the view, which is not found as far as the view is concerned
+    pub(crate) fn not_found(name: &str) -> Error {
+        Error::Api(ErrorResponse::new(
+            "Failure",
+            format!("{name:?} is not in the restricted view"),
+            "NotFound",
+            404,
+        ))
+    }
+}
+
+/// Both selectors, since a selector matches when all of its comma separated terms match
+fn join(restriction: &impl ToString, requested: Option<&str>) -> Option<String> {
+    let restriction = restriction.to_string();
+    match requested.filter(|requested| !requested.is_empty()) {
+        _ if restriction.is_empty() => requested.map(str::to_string),
+        Some(requested) => Some(format!("{restriction},{requested}")),
+        None => Some(restriction),
+    }
+}
+
+impl<K> Api<K> {
+    /// A view of this [`Api`] that only sees the objects that match the `restriction`
+    ///
+    /// The restriction is added to the selectors of every list, watch and collection delete, and gets
+    /// return a `404 Not Found` error for objects outside of the view, so the view can be handed to components
+    /// that should only see a part of a kind, such as the pods on the node that they run on.
+    /// Writes to named objects are not restricted. Restricting a restricted view narrows it further.
+    ///
+    /// The apiserver only supports a few field selectors per kind, and rejects lists and watches
+    /// with other field selectors.
+    ///
+    /// ```no_run
+    /// use kube::{api::{Api, ListParams, Restriction}, core::fields::field};
+    /// use k8s_openapi::api::core::v1::Pod;
+    /// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client: kube::Client = todo!();
+    /// let node_name = std::env::var("NODE_NAME")?;
+    /// let node_selector = field("spec.nodeName").eq(node_name).into();
+    /// let pods: Api<Pod> = Api::all(client).restricted(Restriction::default().fields(node_selector));
+    /// // Only lists the pods on the node
+    /// let local_pods = pods.list(&ListParams::default()).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn restricted(&self, restriction: Restriction) -> Self {
+        Self {
+            request: self.request.clone(),
+            client: self.client.clone(),
+            namespace: self.namespace.clone(),
+            restriction: self
+                .restriction
+                .clone()
+                .fields(restriction.fields)
+                .labels(restriction.labels),
+            _phantom: std::iter::empty(),
+        }
+    }
+
+    /// The restriction of this view, which admits every object unless it was created by [`Api::restricted`]
+    pub fn restriction(&self) -> &Restriction {
+        &self.restriction
+    }
+
+    pub(crate) fn check_restriction(&self, name: &str, object: &Value, metadata_only: bool) -> Result<()> {
+        if self.restriction.admits(object, metadata_only) {
+            Ok(())
+        } else {
+            Err(Restriction::not_found(name))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn joins_selectors() {
+        let restriction = Restriction::default()
+            .fields("spec.nodeName=node-1".parse().unwrap())
+            .labels("app=web".parse().unwrap());
+        let lp = restriction.list_params(&ListParams::default().labels("tier=frontend").limit(10));
+        assert_eq!(lp.field_selector.as_deref(), Some("spec.nodeName=node-1"));
+        assert_eq!(lp.label_selector.as_deref(), Some("app=web,tier=frontend"));
+        assert_eq!(lp.limit, Some(10));
+        let wp = Restriction::default().watch_params(&WatchParams::default().fields("metadata.name=a"));
+        assert_eq!(wp.field_selector.as_deref(), Some("metadata.name=a"));
+        assert_eq!(wp.label_selector, None);
+    }
+
+    #[test]
+    fn admits_objects() {
+        let restriction = Restriction::default()
+            .fields("spec.nodeName=node-1,metadata.namespace=apps".parse().unwrap())
+            .labels("app=web".parse().unwrap());
+        let pod = json!({
+            "metadata": {"name": "web", "namespace": "apps", "labels": {"app": "web"}},
+            "spec": {"nodeName": "node-1"},
+        });
+        assert!(restriction.admits(&pod, false));
+        let other_node = json!({
+            "metadata": {"name": "web", "namespace": "apps", "labels": {"app": "web"}},
+            "spec": {"nodeName": "node-2"},
+        });
+        assert!(!restriction.admits(&other_node, false));
+        let metadata = json!({"metadata": {"name": "web", "namespace": "apps", "labels": {"app": "web"}}});
+        assert!(restriction.admits(&metadata, true));
+        assert!(restriction.checks_more_than_metadata());
+        let other_labels = json!({"metadata": {"name": "web", "namespace": "apps", "labels": {"app": "db"}}});
+        assert!(!restriction.admits(&other_labels, true));
+    }
+}
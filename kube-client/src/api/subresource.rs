@@ -1,30 +1,49 @@
-use futures::AsyncBufRead;
+use chrono::{DateTime, Utc};
+use futures::{stream::BoxStream, AsyncBufRead, AsyncBufReadExt, Stream, StreamExt};
 use serde::de::DeserializeOwned;
-use std::fmt::Debug;
+use serde_json::Value;
+use std::{fmt::Debug, time::Duration};
 
 use crate::{
     api::{Api, Patch, PatchParams, PostParams},
+    error::ErrorResponse,
     Error, Result,
 };
 
-use kube_core::response::Status;
 pub use kube_core::subresource::{EvictParams, LogParams};
+use kube_core::{response::Status, Resource};
 
 #[cfg(feature = "ws")]
 #[cfg_attr(docsrs, doc(cfg(feature = "ws")))]
 pub use kube_core::subresource::AttachParams;
 
 pub use k8s_openapi::api::autoscaling::v1::{Scale, ScaleSpec, ScaleStatus};
+use k8s_openapi::api::core::v1::Binding;
 
 #[cfg(feature = "ws")] use crate::api::portforward::Portforwarder;
 #[cfg(feature = "ws")] use crate::api::remote_command::AttachedProcess;
 
 /// Methods for [scale subresource](https://kubernetes.io/docs/tasks/access-kubernetes-api/custom-resources/custom-resource-definitions/#scale-subresource).
+///
+/// These work for the built-in resources that can be scaled, such as `Deployment`, `StatefulSet` and `ReplicaSet`,
+/// and for custom resources that enable the scale subresource in their `CustomResourceDefinition`.
 impl<K> Api<K>
 where
     K: Clone + DeserializeOwned,
 {
     /// Fetch the scale subresource
+    ///
+    /// ```no_run
+    /// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+    /// # use k8s_openapi::api::apps::v1::Deployment;
+    /// # use kube::{Api, Client};
+    /// # let client: Client = todo!();
+    /// let deploys: Api<Deployment> = Api::default_namespaced(client);
+    /// let scale = deploys.get_scale("blog").await?;
+    /// let replicas = scale.status.map_or(0, |status| status.replicas);
+    /// # Ok(())
+    /// # }
+    /// ```
     pub async fn get_scale(&self, name: &str) -> Result<Scale> {
         let mut req = self
             .request
@@ -35,6 +54,18 @@ where
     }
 
     /// Update the scale subresource
+    ///
+    /// ```no_run
+    /// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+    /// # use k8s_openapi::api::apps::v1::StatefulSet;
+    /// # use kube::{api::{Patch, PatchParams}, Api, Client};
+    /// # let client: Client = todo!();
+    /// let sts: Api<StatefulSet> = Api::default_namespaced(client);
+    /// let patch = serde_json::json!({ "spec": { "replicas": 3 } });
+    /// sts.patch_scale("web", &PatchParams::default(), &Patch::Merge(&patch)).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
     pub async fn patch_scale<P: serde::Serialize + Debug>(
         &self,
         name: &str,
@@ -50,7 +81,24 @@ where
     }
 
     /// Replace the scale subresource
-    pub async fn replace_scale(&self, name: &str, pp: &PostParams, data: Vec<u8>) -> Result<Scale> {
+    ///
+    /// The `data` is usually a [`Scale`] from [`Api::get_scale`] with a new `spec`, so that its `resourceVersion`
+    /// guards against concurrent updates.
+    ///
+    /// ```no_run
+    /// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+    /// # use k8s_openapi::api::apps::v1::Deployment;
+    /// # use kube::{api::{PostParams, ScaleSpec}, Api, Client};
+    /// # let client: Client = todo!();
+    /// let deploys: Api<Deployment> = Api::default_namespaced(client);
+    /// let mut scale = deploys.get_scale("blog").await?;
+    /// scale.spec = Some(ScaleSpec { replicas: Some(5) });
+    /// deploys.replace_scale("blog", &PostParams::default(), &scale).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn replace_scale(&self, name: &str, pp: &PostParams, data: &Scale) -> Result<Scale> {
+        let data = serde_json::to_vec(data).map_err(Error::SerdeError)?;
         let mut req = self
             .request
             .replace_subresource("scale", name, pp, data)
@@ -58,6 +106,26 @@ where
         req.extensions_mut().insert("replace_scale");
         self.client.request::<Scale>(req).await
     }
+
+    /// Set the desired replicas through the scale subresource
+    ///
+    /// This merge patches the `spec.replicas` of the [`Scale`], which the apiserver writes to the replica path of
+    /// the resource, such as the `scale_spec_path` of a custom resource implementing `HasScale`.
+    ///
+    /// ```no_run
+    /// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+    /// # use k8s_openapi::api::apps::v1::Deployment;
+    /// # use kube::{api::PatchParams, Api, Client};
+    /// # let client: Client = todo!();
+    /// let deploys: Api<Deployment> = Api::default_namespaced(client);
+    /// deploys.scale_replicas("blog", &PatchParams::default(), 3).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn scale_replicas(&self, name: &str, pp: &PatchParams, replicas: i32) -> Result<Scale> {
+        let patch = serde_json::json!({ "spec": { "replicas": replicas } });
+        self.patch_scale(name, pp, &Patch::Merge(&patch)).await
+    }
 }
 
 /// Arbitrary subresources
@@ -127,6 +195,121 @@ where
     }
 }
 
+// ----------------------------------------------------------------------------
+// Custom subresources
+// ----------------------------------------------------------------------------
+
+/// A subresource beyond `status` and `scale`, such as those of aggregated APIs
+///
+/// Implement this on a marker type to name the subresource and the type of its objects,
+/// and declare that a kind has it with [`HasSubresource`].
+///
+/// ```
+/// use kube::api::Subresource;
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Clone, Debug, Deserialize, Serialize)]
+/// pub struct Usage {
+///     pub cpu: String,
+/// }
+///
+/// /// The `usage` subresource of a `Workspace`
+/// pub struct UsageSubresource;
+///
+/// impl Subresource for UsageSubresource {
+///     const NAME: &'static str = "usage";
+///     type Object = Usage;
+/// }
+/// ```
+pub trait Subresource {
+    /// The name of the subresource in the url path, like `"usage"` in `/workspaces/dev/usage`
+    const NAME: &'static str;
+
+    /// The type of the objects of the subresource
+    type Object: DeserializeOwned + serde::Serialize;
+}
+
+/// Marker trait for kinds that have the custom subresource `S`
+///
+/// This enables the typed subresource methods of [`Api`], like [`Api::get_typed_subresource`].
+pub trait HasSubresource<S: Subresource> {}
+
+impl<K> Api<K>
+where
+    K: DeserializeOwned,
+{
+    /// Get the custom subresource `S` of a named object
+    ///
+    /// ```no_run
+    /// # use kube::api::{Api, HasSubresource, Subresource};
+    /// # #[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+    /// # struct Usage { cpu: String }
+    /// # struct UsageSubresource;
+    /// # impl Subresource for UsageSubresource { const NAME: &'static str = "usage"; type Object = Usage; }
+    /// # type Workspace = k8s_openapi::api::core::v1::ConfigMap;
+    /// # impl HasSubresource<UsageSubresource> for Workspace {}
+    /// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client: kube::Client = todo!();
+    /// let workspaces: Api<Workspace> = Api::namespaced(client, "apps");
+    /// let usage = workspaces.get_typed_subresource::<UsageSubresource>("dev").await?;
+    /// println!("dev uses {} cpu", usage.cpu);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_typed_subresource<S>(&self, name: &str) -> Result<S::Object>
+    where
+        S: Subresource,
+        K: HasSubresource<S>,
+    {
+        let mut req = self
+            .request
+            .get_subresource(S::NAME, name)
+            .map_err(Error::BuildRequest)?;
+        req.extensions_mut().insert("get_subresource");
+        self.client.request::<S::Object>(req).await
+    }
+
+    /// Patch the custom subresource `S` of a named object
+    pub async fn patch_typed_subresource<S, P>(
+        &self,
+        name: &str,
+        pp: &PatchParams,
+        patch: &Patch<P>,
+    ) -> Result<S::Object>
+    where
+        S: Subresource,
+        K: HasSubresource<S>,
+        P: serde::Serialize + Debug,
+    {
+        let mut req = self
+            .request
+            .patch_subresource(S::NAME, name, pp, patch)
+            .map_err(Error::BuildRequest)?;
+        req.extensions_mut().insert("patch_subresource");
+        self.client.request::<S::Object>(req).await
+    }
+
+    /// Replace the custom subresource `S` of a named object with `data`
+    pub async fn replace_typed_subresource<S>(
+        &self,
+        name: &str,
+        pp: &PostParams,
+        data: &S::Object,
+    ) -> Result<S::Object>
+    where
+        S: Subresource,
+        K: HasSubresource<S>,
+    {
+        let data = serde_json::to_vec(data).map_err(Error::SerdeError)?;
+        let mut req = self
+            .request
+            .replace_subresource(S::NAME, name, pp, data)
+            .map_err(Error::BuildRequest)?;
+        req.extensions_mut().insert("replace_subresource");
+        self.client.request::<S::Object>(req).await
+    }
+}
+
 // ----------------------------------------------------------------------------
 
 // TODO: Replace examples with owned custom resources. Bad practice to write to owned objects
@@ -212,6 +395,85 @@ where
         req.extensions_mut().insert("replace_status");
         self.client.request::<K>(req).await
     }
+
+    /// Patch the status object to the `.status` of `obj`
+    ///
+    /// Only the `apiVersion`, `kind` and `status` of `obj` are sent, to the object with the name of `obj`,
+    /// in the kind of patch made by `patch`, like [`Patch::Apply`] or [`Patch::Merge`].
+    /// The `status` is left out when `obj` has none, so that it is not cleared.
+    ///
+    /// NB: Requires that the resource has a status subresource.
+    ///
+    /// ```no_run
+    /// use kube::{api::{Api, Patch, PatchParams}, Client};
+    /// use k8s_openapi::api::batch::v1::{Job, JobStatus};
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = Client::try_default().await?;
+    ///     let jobs: Api<Job> = Api::namespaced(client, "apps");
+    ///     let mut j = jobs.get("baz").await?;
+    ///     j.status = Some(JobStatus { succeeded: Some(2), ..JobStatus::default() });
+    ///     let o = jobs.patch_status_from(&j, &PatchParams::apply("myapp"), Patch::Apply).await?;
+    ///     assert_eq!(o.status.unwrap().succeeded, Some(2));
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn patch_status_from(
+        &self,
+        obj: &K,
+        pp: &PatchParams,
+        patch: impl FnOnce(Value) -> Patch<Value>,
+    ) -> Result<K>
+    where
+        K: Resource + serde::Serialize,
+    {
+        let name = status_name(obj)?;
+        let mut data = serde_json::to_value(obj).map_err(Error::SerdeError)?;
+        let mut fields = serde_json::Map::new();
+        for field in ["apiVersion", "kind", "status"] {
+            match data.get_mut(field).map(Value::take) {
+                None | Some(Value::Null) => {}
+                Some(value) => {
+                    fields.insert(field.to_owned(), value);
+                }
+            }
+        }
+        self.patch_status(name, pp, &patch(Value::Object(fields))).await
+    }
+
+    /// Replace the status object with the `.status` of `obj`
+    ///
+    /// The whole `obj` is sent to the object with its name, and `.spec` is ignored like in [`Api::replace_status`].
+    /// This fails with a `409 Conflict` when the `resourceVersion` of `obj` is outdated.
+    ///
+    /// ```no_run
+    /// use kube::{api::{Api, PostParams}, Client};
+    /// use k8s_openapi::api::batch::v1::{Job, JobStatus};
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = Client::try_default().await?;
+    ///     let jobs: Api<Job> = Api::namespaced(client, "apps");
+    ///     let mut o = jobs.get_status("baz").await?;
+    ///     o.status = Some(JobStatus::default());
+    ///     let o = jobs.replace_status_from(&o, &PostParams::default()).await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn replace_status_from(&self, obj: &K, pp: &PostParams) -> Result<K>
+    where
+        K: Resource + serde::Serialize,
+    {
+        let data = serde_json::to_vec(obj).map_err(Error::SerdeError)?;
+        self.replace_status(status_name(obj)?, pp, data).await
+    }
+}
+
+fn status_name<K: Resource>(obj: &K) -> Result<&str> {
+    obj.meta().name.as_deref().ok_or_else(|| {
+        Error::BuildRequest(kube_core::request::Error::Validation(
+            "the object needs a name to update its status".into(),
+        ))
+    })
 }
 
 // ----------------------------------------------------------------------------
@@ -277,6 +539,152 @@ where
         req.extensions_mut().insert("log_stream");
         self.client.request_stream(req).await
     }
+
+    /// Stream the lines of the logs
+    ///
+    /// When following the logs, the stream reconnects whenever the connection ends or fails, which happens when
+    /// the log file of the container is rotated, or when the connection is dropped.
+    /// It resumes shortly before the timestamp of the last line, and skips the lines that were already returned.
+    /// The stream ends once a reconnection does not receive any new lines, because the container has terminated.
+    /// A [`LogParams::limit_bytes`] applies to each connection.
+    ///
+    /// ```no_run
+    /// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+    /// # use k8s_openapi::api::core::v1::Pod;
+    /// # use kube::{api::{Api, LogParams}, Client};
+    /// # let client: Client = todo!();
+    /// use futures::TryStreamExt;
+    ///
+    /// let pods: Api<Pod> = Api::default_namespaced(client);
+    /// let lines = pods.log_lines("my-pod", &LogParams::default().follow().tail_lines(10));
+    /// futures::pin_mut!(lines);
+    /// while let Some(line) = lines.try_next().await? {
+    ///     println!("{line}");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn log_lines(&self, name: &str, lp: &LogParams) -> impl Stream<Item = Result<String>> + Send + 'static
+    where
+        K: Clone + Send + 'static,
+    {
+        let state = LogLines {
+            api: self.clone(),
+            name: name.to_owned(),
+            lp: lp.clone(),
+            lines: None,
+            last: None,
+            skip: 0,
+            received: false,
+            connections: 0,
+        };
+        futures::stream::try_unfold(state, |mut state| async move {
+            match state.next_line().await? {
+                Some(line) => Ok(Some((line, state))),
+                None => Ok(None),
+            }
+        })
+    }
+}
+
+/// The state of [`Api::log_lines`]
+struct LogLines<K> {
+    api: Api<K>,
+    name: String,
+    lp: LogParams,
+    lines: Option<BoxStream<'static, std::io::Result<String>>>,
+    /// The timestamp of the last returned line, and how many lines were returned with that timestamp
+    last: Option<(DateTime<Utc>, usize)>,
+    /// The number of lines with the `last` timestamp that still need to be skipped after reconnecting
+    skip: usize,
+    /// Whether new lines were received on the current connection
+    received: bool,
+    /// The number of connections that were made
+    connections: usize,
+}
+
+impl<K: DeserializeOwned + Log> LogLines<K> {
+    async fn connect(&mut self) -> Result<BoxStream<'static, std::io::Result<String>>> {
+        // Timestamps are needed to resume, and are removed again unless they were asked for
+        let mut lp = LogParams {
+            timestamps: true,
+            ..self.lp.clone()
+        };
+        if let Some((time, count)) = self.last {
+            // The logs are resumed from up to a second before the last line, so the lines since then are sent again
+            lp = LogParams {
+                tail_lines: None,
+                ..lp.since_time(time)
+            };
+            self.skip = count;
+        }
+        self.received = false;
+        self.connections += 1;
+        let mut req = self
+            .api
+            .request
+            .logs(&self.name, &lp)
+            .map_err(Error::BuildRequest)?;
+        req.extensions_mut().insert("log_stream");
+        let reader = self.api.client.request_stream(req).await?;
+        Ok(reader.lines().boxed())
+    }
+
+    async fn next_line(&mut self) -> Result<Option<String>> {
+        loop {
+            if self.lines.is_none() {
+                self.lines = Some(self.connect().await?);
+            }
+            let lines = self.lines.as_mut().expect("connected above");
+            let line = match lines.next().await {
+                Some(Ok(line)) => line,
+                end => {
+                    self.lines = None;
+                    // A first connection without lines may have been dropped before the container logged anything,
+                    // while a reconnection without new lines means that the container has terminated
+                    if self.lp.follow && (self.received || self.connections == 1) {
+                        tracing::debug!(pod = %self.name, "log stream ended, resuming it");
+                        continue;
+                    }
+                    return match end {
+                        Some(Err(err)) => Err(Error::ReadEvents(err)),
+                        _ => Ok(None),
+                    };
+                }
+            };
+
+            let (time, text) = match line.split_once(' ').and_then(|(time, text)| {
+                DateTime::parse_from_rfc3339(time)
+                    .ok()
+                    .map(|time| (time.with_timezone(&Utc), text))
+            }) {
+                Some(parsed) => parsed,
+                // Not a log line, such as an error message of the kubelet
+                None => return Ok(Some(line)),
+            };
+            match self.last {
+                Some((last, _)) if time < last => continue,
+                Some((last, ref mut count)) if time == last => {
+                    if self.skip > 0 {
+                        self.skip -= 1;
+                        continue;
+                    }
+                    *count += 1;
+                }
+                _ => {
+                    self.last = Some((time, 1));
+                    self.skip = 0;
+                }
+            }
+            self.received = true;
+            let line = if self.lp.timestamps {
+                line.clone()
+            } else {
+                text.to_owned()
+            };
+            return Ok(Some(line));
+        }
+    }
 }
 
 // ----------------------------------------------------------------------------
@@ -298,18 +706,334 @@ pub trait Evict {}
 
 impl Evict for k8s_openapi::api::core::v1::Pod {}
 
+/// The outcome of an [`Api::evict`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EvictionOutcome {
+    /// The eviction was accepted, and the pod is being deleted
+    Evicted(Status),
+    /// The eviction was refused, because it would violate a `PodDisruptionBudget`
+    ///
+    /// The budget may allow the eviction later, so tools like `kubectl drain` retry these evictions until they succeed.
+    Blocked(EvictionBlocked),
+}
+
+impl EvictionOutcome {
+    /// Whether the eviction was accepted
+    pub fn is_evicted(&self) -> bool {
+        matches!(self, Self::Evicted(_))
+    }
+}
+
+/// An eviction that was refused by a `PodDisruptionBudget`, see [`EvictionOutcome::Blocked`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EvictionBlocked {
+    /// The message of the refusal, like `Cannot evict pod as it would violate the pod's disruption budget.`
+    pub message: String,
+    /// The name of the `PodDisruptionBudget` that refused the eviction, if reported
+    pub disruption_budget: Option<String>,
+    /// Why the budget refused the eviction, like `The disruption budget web needs 2 healthy pods and has 2 currently`
+    pub cause: Option<String>,
+    /// How long to wait before retrying, if the apiserver asked for a delay
+    pub retry_after: Option<Duration>,
+}
+
+impl EvictionBlocked {
+    /// Decodes the `429 Too Many Requests` error that the apiserver returns for refused evictions
+    fn from_error(err: &ErrorResponse) -> Option<Self> {
+        if err.code != 429 {
+            return None;
+        }
+        let details = err.details()?;
+        // Requests that are throttled have no causes, while evictions that are refused by a budget do
+        let cause = details
+            .causes
+            .iter()
+            .find(|cause| cause.reason == "DisruptionBudget")?;
+        let disruption_budget = cause
+            .message
+            .strip_prefix("The disruption budget ")
+            .and_then(|rest| rest.split_once(' '))
+            .map(|(name, _)| name.to_owned());
+        let retry_after = match details.retry_after_seconds {
+            0 => err.priority_and_fairness().and_then(|apf| apf.retry_after),
+            seconds => Some(Duration::from_secs(seconds.into())),
+        };
+        Some(Self {
+            message: err.message.clone(),
+            disruption_budget,
+            cause: Some(cause.message.clone()).filter(|message| !message.is_empty()),
+            retry_after,
+        })
+    }
+}
+
 impl<K> Api<K>
 where
     K: DeserializeOwned + Evict,
 {
-    /// Create an eviction
-    pub async fn evict(&self, name: &str, ep: &EvictParams) -> Result<Status> {
+    /// Create a `policy/v1` eviction of a pod, which deletes it unless a `PodDisruptionBudget` refuses it
+    ///
+    /// The [`EvictParams::delete_options`] are used for the deletion, for example to set a grace period.
+    /// Evictions that are refused by a budget are returned as [`EvictionOutcome::Blocked`], other failures as errors.
+    ///
+    /// ```no_run
+    /// use kube::api::{Api, EvictParams, EvictionOutcome};
+    /// use k8s_openapi::api::core::v1::Pod;
+    /// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client: kube::Client = todo!();
+    /// let pods: Api<Pod> = Api::namespaced(client, "apps");
+    /// loop {
+    ///     match pods.evict("blog", &EvictParams::default()).await? {
+    ///         EvictionOutcome::Evicted(_) => break,
+    ///         EvictionOutcome::Blocked(blocked) => {
+    ///             println!("waiting for {:?}: {}", blocked.disruption_budget, blocked.message);
+    ///             tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+    ///         }
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn evict(&self, name: &str, ep: &EvictParams) -> Result<EvictionOutcome> {
         let mut req = self.request.evict(name, ep).map_err(Error::BuildRequest)?;
         req.extensions_mut().insert("evict");
+        match self.client.request::<Status>(req).await {
+            Ok(status) => Ok(EvictionOutcome::Evicted(status)),
+            Err(Error::Api(err)) => match EvictionBlocked::from_error(&err) {
+                Some(blocked) => Ok(EvictionOutcome::Blocked(blocked)),
+                None => Err(Error::Api(err)),
+            },
+            Err(err) => Err(err),
+        }
+    }
+}
+
+// ----------------------------------------------------------------------------
+// Ephemeral containers subresource
+// ----------------------------------------------------------------------------
+
+#[test]
+fn ephemeral_containers_path() {
+    use crate::api::{Request, Resource};
+    use k8s_openapi::api::core::v1 as corev1;
+    let url = corev1::Pod::url_path(&(), Some("ns"));
+    let req = Request::new(url)
+        .get_subresource("ephemeralcontainers", "foo")
+        .unwrap();
+    assert_eq!(req.uri(), "/api/v1/namespaces/ns/pods/foo/ephemeralcontainers");
+}
+
+/// Marker trait for objects that have ephemeral containers
+pub trait Ephemeral {}
+
+impl Ephemeral for k8s_openapi::api::core::v1::Pod {}
+
+/// Methods for the [ephemeral containers](https://kubernetes.io/docs/concepts/workloads/pods/ephemeral-containers/) of pods
+///
+/// Ephemeral containers can only be added through this subresource, not by updating the pod itself,
+/// and cannot be changed or removed once they have been added. They are meant for debugging, like `kubectl debug`.
+impl<K> Api<K>
+where
+    K: Clone + DeserializeOwned + Ephemeral,
+{
+    /// Get the pod through its ephemeral containers subresource
+    pub async fn get_ephemeral_containers(&self, name: &str) -> Result<K> {
+        let mut req = self
+            .request
+            .get_subresource("ephemeralcontainers", name)
+            .map_err(Error::BuildRequest)?;
+        req.extensions_mut().insert("get_ephemeral_containers");
+        self.client.request::<K>(req).await
+    }
+
+    /// Add ephemeral containers to a pod by patching its `spec.ephemeralContainers`
+    ///
+    /// ```no_run
+    /// use kube::api::{Api, Patch, PatchParams};
+    /// use k8s_openapi::api::core::v1::Pod;
+    /// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client: kube::Client = todo!();
+    /// let pods: Api<Pod> = Api::namespaced(client, "apps");
+    /// let patch = serde_json::json!({
+    ///     "apiVersion": "v1",
+    ///     "kind": "Pod",
+    ///     "spec": {
+    ///         "ephemeralContainers": [{
+    ///             "name": "debugger",
+    ///             "image": "busybox",
+    ///             "command": ["sh"],
+    ///             "stdin": true,
+    ///             "tty": true,
+    ///             "targetContainerName": "app"
+    ///         }]
+    ///     }
+    /// });
+    /// let pod = pods
+    ///     .patch_ephemeral_containers("blog", &PatchParams::apply("myapp"), &Patch::Apply(&patch))
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn patch_ephemeral_containers<P: serde::Serialize + Debug>(
+        &self,
+        name: &str,
+        pp: &PatchParams,
+        patch: &Patch<P>,
+    ) -> Result<K> {
+        let mut req = self
+            .request
+            .patch_subresource("ephemeralcontainers", name, pp, patch)
+            .map_err(Error::BuildRequest)?;
+        req.extensions_mut().insert("patch_ephemeral_containers");
+        self.client.request::<K>(req).await
+    }
+
+    /// Replace the ephemeral containers of a pod with those of `data`
+    ///
+    /// The `data` must be the whole pod, with a `resourceVersion` to guard against concurrent changes.
+    pub async fn replace_ephemeral_containers(&self, name: &str, pp: &PostParams, data: &K) -> Result<K>
+    where
+        K: serde::Serialize,
+    {
+        let data = serde_json::to_vec(data).map_err(Error::SerdeError)?;
+        let mut req = self
+            .request
+            .replace_subresource("ephemeralcontainers", name, pp, data)
+            .map_err(Error::BuildRequest)?;
+        req.extensions_mut().insert("replace_ephemeral_containers");
+        self.client.request::<K>(req).await
+    }
+}
+
+// ----------------------------------------------------------------------------
+// Binding subresource
+// ----------------------------------------------------------------------------
+
+#[test]
+fn binding_path() {
+    use crate::api::{Request, Resource};
+    use k8s_openapi::api::core::v1 as corev1;
+    let url = corev1::Pod::url_path(&(), Some("ns"));
+    let pp = PostParams::default();
+    let req = Request::new(url)
+        .create_subresource("binding", "foo", &pp, vec![])
+        .unwrap();
+    assert_eq!(req.uri(), "/api/v1/namespaces/ns/pods/foo/binding?");
+    assert_eq!(req.method(), "POST");
+}
+
+/// Marker trait for objects that can be bound to a node
+pub trait Bind {}
+
+impl Bind for k8s_openapi::api::core::v1::Pod {}
+
+/// Methods for the binding subresource of pods, which assigns them to a node
+impl<K> Api<K>
+where
+    K: Clone + DeserializeOwned + Bind,
+{
+    /// Bind a pod to the node that is the `target` of the `binding`, like the scheduler does
+    ///
+    /// The name of the `binding` defaults to the name of the pod. The pod must not be bound to a node already.
+    ///
+    /// ```no_run
+    /// use kube::api::{Api, PostParams};
+    /// use k8s_openapi::api::core::v1::{Binding, ObjectReference, Pod};
+    /// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client: kube::Client = todo!();
+    /// let pods: Api<Pod> = Api::namespaced(client, "apps");
+    /// let binding = Binding {
+    ///     target: ObjectReference {
+    ///         api_version: Some("v1".into()),
+    ///         kind: Some("Node".into()),
+    ///         name: Some("node-1".into()),
+    ///         ..ObjectReference::default()
+    ///     },
+    ///     ..Binding::default()
+    /// };
+    /// pods.bind("blog", &PostParams::default(), &binding).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn bind(&self, name: &str, pp: &PostParams, binding: &Binding) -> Result<Status> {
+        let data = if binding.metadata.name.is_some() {
+            serde_json::to_vec(binding)
+        } else {
+            let mut binding = binding.clone();
+            binding.metadata.name = Some(name.to_owned());
+            serde_json::to_vec(&binding)
+        }
+        .map_err(Error::SerdeError)?;
+        let mut req = self
+            .request
+            .create_subresource("binding", name, pp, data)
+            .map_err(Error::BuildRequest)?;
+        req.extensions_mut().insert("bind");
         self.client.request::<Status>(req).await
     }
 }
 
+// ----------------------------------------------------------------------------
+// Proxy subresource
+// ----------------------------------------------------------------------------
+
+#[test]
+fn proxy_path() {
+    use crate::api::{Request, Resource};
+    use k8s_openapi::api::core::v1 as corev1;
+    let url = corev1::Service::url_path(&(), Some("ns"));
+    let request = http::Request::get("/healthz").body(vec![]).unwrap();
+    let req = Request::new(url).proxy("foo:http", request).unwrap();
+    assert_eq!(req.uri(), "/api/v1/namespaces/ns/services/foo:http/proxy/healthz");
+}
+
+/// Marker trait for objects that the apiserver can proxy requests to
+pub trait Proxy {}
+
+impl Proxy for k8s_openapi::api::core::v1::Pod {}
+impl Proxy for k8s_openapi::api::core::v1::Service {}
+impl Proxy for k8s_openapi::api::core::v1::Node {}
+
+/// Methods for the proxy subresource of pods, services and nodes
+impl<K> Api<K>
+where
+    K: Proxy,
+{
+    /// Send an HTTP `request` to an object through the proxy of the apiserver, and return its response
+    ///
+    /// The request is sent to the path and query of its uri, which saves a direct connection to the pod,
+    /// the endpoints of the service, or the kubelet of the node. The `name` can pick a port and scheme,
+    /// like `web:8080` or `https:web:8443`, where services use the names of their ports.
+    ///
+    /// The response is returned whatever its status is, since it is usually the response of the object.
+    /// Errors of the apiserver itself, like a missing pod, are also returned as responses.
+    ///
+    /// ```no_run
+    /// use kube::api::Api;
+    /// use k8s_openapi::api::core::v1::Pod;
+    /// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client: kube::Client = todo!();
+    /// let pods: Api<Pod> = Api::namespaced(client, "apps");
+    /// let request = http::Request::post("/admin/reload").body(vec![])?;
+    /// let response = pods.proxy_http("blog:9090", request).await?;
+    /// println!("{}: {}", response.status(), String::from_utf8_lossy(response.body()));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn proxy_http(
+        &self,
+        name: &str,
+        request: http::Request<Vec<u8>>,
+    ) -> Result<http::Response<Vec<u8>>> {
+        let mut req = self.request.proxy(name, request).map_err(Error::BuildRequest)?;
+        req.extensions_mut().insert("proxy");
+        let res = self.client.send(req.map(hyper::Body::from)).await?;
+        let (parts, body) = res.into_parts();
+        let body = hyper::body::to_bytes(body).await.map_err(Error::HyperError)?;
+        Ok(http::Response::from_parts(parts, body.to_vec()))
+    }
+}
+
 // ----------------------------------------------------------------------------
 // Attach subresource
 // ----------------------------------------------------------------------------
@@ -347,6 +1071,26 @@ where
     K: Clone + DeserializeOwned + Attach,
 {
     /// Attach to pod
+    ///
+    /// Attaches to the main process of a container, which must have been started with `stdin` and `tty`
+    /// for an interactive session:
+    ///
+    /// ```no_run
+    /// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+    /// use futures::SinkExt;
+    /// use k8s_openapi::api::core::v1::Pod;
+    /// use kube::api::{Api, AttachParams, TerminalSize};
+    /// use tokio::io::AsyncWriteExt;
+    /// # let client: kube::Client = todo!();
+    /// let pods: Api<Pod> = Api::default_namespaced(client);
+    /// let mut attached = pods.attach("debug", &AttachParams::interactive_tty()).await?;
+    /// let mut resize = attached.terminal_size().unwrap();
+    /// resize.send(TerminalSize { width: 120, height: 40 }).await?;
+    /// attached.stdin().unwrap().write_all(b"exit\n").await?;
+    /// attached.join().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
     pub async fn attach(&self, name: &str, ap: &AttachParams) -> Result<AttachedProcess> {
         let mut req = self.request.attach(name, ap).map_err(Error::BuildRequest)?;
         req.extensions_mut().insert("attach");
@@ -393,14 +1137,9 @@ where
     K: Clone + DeserializeOwned + Execute,
 {
     /// Execute a command in a pod
-    pub async fn exec<I: Debug, T>(
-        &self,
-        name: &str,
-        command: I,
-        ap: &AttachParams,
-    ) -> Result<AttachedProcess>
+    pub async fn exec<I, T>(&self, name: &str, command: I, ap: &AttachParams) -> Result<AttachedProcess>
     where
-        I: IntoIterator<Item = T>,
+        I: IntoIterator<Item = T> + Debug,
         T: Into<String>,
     {
         let mut req = self
@@ -431,22 +1170,29 @@ fn portforward_path() {
 
 /// Marker trait for objects that has portforward
 #[cfg(feature = "ws")]
+#[cfg_attr(docsrs, doc(cfg(feature = "ws")))]
 pub trait Portforward {}
 
 #[cfg(feature = "ws")]
+#[cfg_attr(docsrs, doc(cfg(feature = "ws")))]
 impl Portforward for k8s_openapi::api::core::v1::Pod {}
 
 #[cfg(feature = "ws")]
+#[cfg_attr(docsrs, doc(cfg(feature = "ws")))]
 impl<K> Api<K>
 where
     K: Clone + DeserializeOwned + Portforward,
 {
     /// Forward ports of a pod
+    ///
+    /// Each port gets its own stream from [`Portforwarder::take_stream`], which is tunneled over a single WebSocket.
+    /// No local ports are bound, so the streams can be used directly, or copied to accepted connections like `kubectl port-forward`.
     pub async fn portforward(&self, name: &str, ports: &[u16]) -> Result<Portforwarder> {
-        let req = self
+        let mut req = self
             .request
             .portforward(name, ports)
             .map_err(Error::BuildRequest)?;
+        req.extensions_mut().insert("portforward");
         let stream = self.client.connect(req).await?;
         Ok(Portforwarder::new(stream, ports))
     }
@@ -1,8 +1,18 @@
 use crate::{api::Api, Error, Result};
-use k8s_openapi::api::certificates::v1::CertificateSigningRequest;
-use kube_core::params::{Patch, PatchParams};
+use chrono::Utc;
+use k8s_openapi::{
+    api::certificates::v1::{CertificateSigningRequest, CertificateSigningRequestCondition},
+    apimachinery::pkg::apis::meta::v1::Time,
+};
+use kube_core::params::{Patch, PatchParams, PostParams};
 
 
+/// Methods for the [approval](https://kubernetes.io/docs/reference/access-authn-authz/certificate-signing-requests/#approval-rejection)
+/// of certificate signing requests
+///
+/// A `CertificateSigningRequest` is created with [`Api::create`], approved or denied through its `approval`
+/// subresource, and then signed by the signer that it names, which sets its `status.certificate`.
+/// Waiting for the certificate is done with `kube::runtime::wait::conditions::is_certificate_issued`.
 impl Api<CertificateSigningRequest> {
     /// Partially update approval of the specified CertificateSigningRequest.
     pub async fn patch_approval<P: serde::Serialize>(
@@ -23,4 +33,81 @@ impl Api<CertificateSigningRequest> {
     pub async fn get_approval(&self, name: &str) -> Result<CertificateSigningRequest> {
         self.get_subresource("approval", name).await
     }
+
+    /// Replace the approval conditions of the specified CertificateSigningRequest.
+    ///
+    /// This fails with a `409 Conflict` when the `resourceVersion` of `csr` is outdated.
+    pub async fn replace_approval(
+        &self,
+        name: &str,
+        pp: &PostParams,
+        csr: &CertificateSigningRequest,
+    ) -> Result<CertificateSigningRequest> {
+        let data = serde_json::to_vec(csr).map_err(Error::SerdeError)?;
+        self.replace_subresource("approval", name, pp, data).await
+    }
+
+    /// Approve a CertificateSigningRequest, like `kubectl certificate approve`
+    ///
+    /// The `reason` is a short CamelCase reason like `AutoApproved`, and the `message` explains it to humans.
+    /// Requests that are already approved are returned unchanged, while approving a denied request fails,
+    /// as the decision cannot be changed once it was made.
+    ///
+    /// ```no_run
+    /// use kube::api::{Api, PostParams};
+    /// use k8s_openapi::api::certificates::v1::CertificateSigningRequest;
+    /// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client: kube::Client = todo!();
+    /// # let csr: CertificateSigningRequest = todo!();
+    /// let csrs: Api<CertificateSigningRequest> = Api::all(client);
+    /// csrs.create(&PostParams::default(), &csr).await?;
+    /// csrs.approve("webhook-serving", "WebhookBootstrap", "Serving certificate of the webhook").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn approve(
+        &self,
+        name: &str,
+        reason: &str,
+        message: &str,
+    ) -> Result<CertificateSigningRequest> {
+        self.decide_approval(name, "Approved", reason, message).await
+    }
+
+    /// Deny a CertificateSigningRequest, like `kubectl certificate deny`
+    ///
+    /// Requests that are already denied are returned unchanged, while denying an approved request fails.
+    pub async fn deny(&self, name: &str, reason: &str, message: &str) -> Result<CertificateSigningRequest> {
+        self.decide_approval(name, "Denied", reason, message).await
+    }
+
+    async fn decide_approval(
+        &self,
+        name: &str,
+        decision: &str,
+        reason: &str,
+        message: &str,
+    ) -> Result<CertificateSigningRequest> {
+        let mut csr = self.get_approval(name).await?;
+        let conditions = csr
+            .status
+            .get_or_insert_with(Default::default)
+            .conditions
+            .get_or_insert_with(Vec::new);
+        if conditions
+            .iter()
+            .any(|c| c.type_ == decision && c.status == "True")
+        {
+            return Ok(csr);
+        }
+        conditions.push(CertificateSigningRequestCondition {
+            type_: decision.into(),
+            status: "True".into(),
+            reason: Some(reason.into()),
+            message: Some(message.into()),
+            last_update_time: Some(Time(Utc::now())),
+            ..CertificateSigningRequestCondition::default()
+        });
+        self.replace_approval(name, &PostParams::default(), &csr).await
+    }
 }
@@ -0,0 +1,541 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use form_urlencoded::Serializer;
+use http::{
+    header::{HeaderValue, ACCEPT, CONTENT_TYPE},
+    Method, Request, StatusCode, Version,
+};
+use hyper::{body, client::HttpConnector, Client};
+use secrecy::{ExposeSecret, SecretString};
+use serde::{Deserialize, Deserializer};
+use tokio::sync::Mutex;
+
+/// Possible errors when handling Azure AD authentication.
+pub mod errors {
+    use http::StatusCode;
+    use thiserror::Error;
+
+    /// Possible errors when dealing with Azure AD.
+    #[derive(Error, Debug)]
+    pub enum Error {
+        /// Missing field in the configuration.
+        #[error("missing field {0}")]
+        MissingField(&'static str),
+        /// Unknown cloud environment in the configuration.
+        #[error("unknown Azure environment {0}")]
+        UnknownEnvironment(String),
+        /// Failed to create an HTTPS client.
+        #[cfg(feature = "openssl-tls")]
+        #[cfg_attr(docsrs, doc(cfg(feature = "openssl-tls")))]
+        #[error("failed to create OpenSSL HTTPS connector: {0}")]
+        CreateOpensslHttpsConnector(
+            #[source]
+            #[from]
+            openssl::error::ErrorStack,
+        ),
+        /// [`hyper::Error`] occurred while requesting a token.
+        #[error("hyper error: {0}")]
+        HyperError(
+            #[source]
+            #[from]
+            hyper::Error,
+        ),
+        /// [`http::Error`] occurred while requesting a token.
+        #[error("http error: {0}")]
+        HttpError(
+            #[source]
+            #[from]
+            http::Error,
+        ),
+        /// Received an invalid status code from Azure AD.
+        #[error("request failed with status code {0}: {1}")]
+        RequestFailed(StatusCode, String),
+        /// Failed to parse the response from Azure AD.
+        #[error("invalid response received from Azure AD: {0}")]
+        InvalidResponse(#[source] serde_json::Error),
+        /// The user did not complete the device code login in time.
+        #[error("device code login expired before it was completed")]
+        DeviceCodeExpired,
+    }
+}
+
+#[cfg(not(any(feature = "rustls-tls", feature = "openssl-tls")))]
+compile_error!("At least one of rustls-tls or openssl-tls feature must be enabled to use azure feature");
+// Current TLS feature precedence when more than one are set:
+// 1. openssl-tls
+// 2. rustls-tls
+#[cfg(feature = "openssl-tls")]
+type HttpsConnector = hyper_openssl::HttpsConnector<HttpConnector>;
+#[cfg(all(not(feature = "openssl-tls"), feature = "rustls-tls"))]
+type HttpsConnector = hyper_rustls::HttpsConnector<HttpConnector>;
+
+/// How tokens are obtained from Azure AD.
+#[derive(Debug, Clone)]
+enum Flow {
+    /// Interactive login on another device, followed by refresh token grants.
+    DeviceCode { tenant_id: String, client_id: String },
+    /// Service principal login with a client secret.
+    ClientCredentials {
+        tenant_id: String,
+        client_id: String,
+        client_secret: SecretString,
+    },
+    /// The managed identity of the Azure VM (or AKS node) that the client runs on.
+    ManagedIdentity { client_id: Option<String> },
+}
+
+/// Token source for Azure AD integrated clusters, such as AKS with AAD enabled.
+///
+/// This is shared by all requests, and only locked briefly to access the tokens, so that requests can keep
+/// using the current access token while a new one is requested (which may take minutes for a device code login).
+#[derive(Debug)]
+pub struct Azure {
+    flow: Flow,
+    /// Azure AD endpoint, depending on the cloud environment.
+    authority_host: String,
+    /// The application ID of the apiserver, which is the audience of requested tokens.
+    apiserver_id: String,
+    tokens: Mutex<Tokens>,
+    /// Held while requesting a new access token, so that only one request is made at a time.
+    refreshing: Mutex<()>,
+    https_client: Client<HttpsConnector, String>,
+}
+
+#[derive(Debug)]
+struct Tokens {
+    access_token: Option<(SecretString, DateTime<Utc>)>,
+    refresh_token: Option<SecretString>,
+}
+
+/// Token response from Azure AD and the instance metadata service.
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    refresh_token: Option<String>,
+    #[serde(deserialize_with = "deserialize_seconds")]
+    expires_in: i64,
+}
+
+/// Device authorization response from Azure AD.
+#[derive(Deserialize)]
+struct DeviceCodeResponse {
+    device_code: String,
+    message: String,
+    #[serde(deserialize_with = "deserialize_seconds")]
+    expires_in: i64,
+    #[serde(default = "default_interval", deserialize_with = "deserialize_seconds")]
+    interval: i64,
+}
+
+fn default_interval() -> i64 {
+    5
+}
+
+/// Error response from Azure AD, used to tell pending device code logins apart from failures.
+#[derive(Deserialize)]
+struct ErrorResponse {
+    error: String,
+}
+
+/// The instance metadata service returns durations as strings, while Azure AD uses numbers.
+fn deserialize_seconds<'de, D: Deserializer<'de>>(deserializer: D) -> Result<i64, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Seconds {
+        Number(i64),
+        String(String),
+    }
+
+    match Seconds::deserialize(deserializer)? {
+        Seconds::Number(seconds) => Ok(seconds),
+        Seconds::String(seconds) => seconds.parse().map_err(serde::de::Error::custom),
+    }
+}
+
+impl Azure {
+    /// Config keys for a cached token, as written by client-go's legacy azure provider.
+    const CONFIG_ACCESS_TOKEN: &str = "access-token";
+    /// Config key for the application ID of the apiserver.
+    const CONFIG_APISERVER_ID: &str = "apiserver-id";
+    /// Config key for the client ID.
+    const CONFIG_CLIENT_ID: &str = "client-id";
+    /// Config key for the client secret, which selects the client credentials flow.
+    const CONFIG_CLIENT_SECRET: &str = "client-secret";
+    /// Config key for the cloud environment.
+    const CONFIG_ENVIRONMENT: &str = "environment";
+    const CONFIG_EXPIRES_ON: &str = "expires-on";
+    /// Config key which selects the managed identity flow when set to `true`.
+    const CONFIG_MANAGED_IDENTITY: &str = "use-managed-identity";
+    const CONFIG_REFRESH_TOKEN: &str = "refresh-token";
+    /// Config key for the tenant ID.
+    const CONFIG_TENANT_ID: &str = "tenant-id";
+    /// The well-known application ID of the AKS AAD server.
+    const DEFAULT_APISERVER_ID: &str = "6dae42f8-4368-4678-94ff-3960e28e3630";
+    /// How many seconds before token expiration we want to refresh it.
+    const EXPIRY_DELTA_SECONDS: i64 = 60;
+    /// The instance metadata service endpoint for managed identity tokens.
+    const IMDS_TOKEN_ENDPOINT: &str = "http://169.254.169.254/metadata/identity/oauth2/token";
+
+    /// Create an instance of this struct from the auth provider config.
+    pub fn from_config(config: &HashMap<String, String>) -> Result<Self, errors::Error> {
+        let get_field = |name: &'static str| {
+            config
+                .get(name)
+                .filter(|value| !value.is_empty())
+                .cloned()
+                .ok_or(errors::Error::MissingField(name))
+        };
+
+        let flow = if config.get(Self::CONFIG_MANAGED_IDENTITY).map(String::as_str) == Some("true") {
+            Flow::ManagedIdentity {
+                client_id: get_field(Self::CONFIG_CLIENT_ID).ok(),
+            }
+        } else if let Ok(client_secret) = get_field(Self::CONFIG_CLIENT_SECRET) {
+            Flow::ClientCredentials {
+                tenant_id: get_field(Self::CONFIG_TENANT_ID)?,
+                client_id: get_field(Self::CONFIG_CLIENT_ID)?,
+                client_secret: client_secret.into(),
+            }
+        } else {
+            Flow::DeviceCode {
+                tenant_id: get_field(Self::CONFIG_TENANT_ID)?,
+                client_id: get_field(Self::CONFIG_CLIENT_ID)?,
+            }
+        };
+
+        let authority_host = match config.get(Self::CONFIG_ENVIRONMENT).map(String::as_str) {
+            None | Some("" | "AzurePublicCloud") => "https://login.microsoftonline.com",
+            Some("AzureChinaCloud") => "https://login.chinacloudapi.cn",
+            Some("AzureUSGovernmentCloud") => "https://login.microsoftonline.us",
+            Some(other) => return Err(errors::Error::UnknownEnvironment(other.to_owned())),
+        }
+        .to_owned();
+        let apiserver_id =
+            get_field(Self::CONFIG_APISERVER_ID).unwrap_or_else(|_| Self::DEFAULT_APISERVER_ID.to_owned());
+
+        let access_token = get_field(Self::CONFIG_ACCESS_TOKEN).ok().and_then(|token| {
+            let expires_on = get_field(Self::CONFIG_EXPIRES_ON).ok()?.parse::<i64>().ok()?;
+            let expires_on = Utc.timestamp_opt(expires_on, 0).single()?;
+            Some((token.into(), expires_on))
+        });
+        let refresh_token = get_field(Self::CONFIG_REFRESH_TOKEN).ok().map(SecretString::from);
+
+        // Managed identity tokens are served over plain HTTP by the instance metadata service
+        #[cfg(feature = "openssl-tls")]
+        let https = hyper_openssl::HttpsConnector::new()?;
+        #[cfg(all(not(feature = "openssl-tls"), feature = "rustls-tls"))]
+        let https = hyper_rustls::HttpsConnectorBuilder::new()
+            .with_native_roots()
+            .https_or_http()
+            .enable_http1()
+            .build();
+
+        Ok(Self {
+            flow,
+            authority_host,
+            apiserver_id,
+            tokens: Mutex::new(Tokens {
+                access_token,
+                refresh_token,
+            }),
+            refreshing: Mutex::new(()),
+            https_client: hyper::Client::builder().build(https),
+        })
+    }
+
+    /// The current access token, if it is valid for at least `delta_seconds`.
+    async fn valid_token(&self, delta_seconds: i64) -> Option<String> {
+        let tokens = self.tokens.lock().await;
+        let (token, expires_on) = tokens.access_token.as_ref()?;
+        (Utc::now() + Duration::seconds(delta_seconds) < *expires_on).then(|| token.expose_secret().clone())
+    }
+
+    /// Retrieve the access token, requesting a new one from Azure AD if it is about to expire.
+    pub async fn token(&self) -> Result<String, errors::Error> {
+        if let Some(token) = self.valid_token(Self::EXPIRY_DELTA_SECONDS).await {
+            return Ok(token);
+        }
+        let _refreshing = match self.refreshing.try_lock() {
+            Ok(guard) => guard,
+            Err(_) => {
+                // Another request is already getting a new token, keep using the current one until it expires
+                if let Some(token) = self.valid_token(0).await {
+                    return Ok(token);
+                }
+                self.refreshing.lock().await
+            }
+        };
+        // The token may have been replaced while waiting for the other request
+        if let Some(token) = self.valid_token(Self::EXPIRY_DELTA_SECONDS).await {
+            return Ok(token);
+        }
+
+        let refresh_token = self.tokens.lock().await.refresh_token.clone();
+        let response = match (&self.flow, refresh_token) {
+            (Flow::DeviceCode { .. }, Some(refresh_token)) => {
+                let request = self.refresh_token_request(&refresh_token)?;
+                match self.request_token(request).await {
+                    Ok(response) => response,
+                    // The refresh token was revoked or has expired, so the user needs to log in again
+                    Err(errors::Error::RequestFailed(StatusCode::BAD_REQUEST, _)) => {
+                        self.device_code_login().await?
+                    }
+                    Err(err) => return Err(err),
+                }
+            }
+            (Flow::DeviceCode { .. }, None) => self.device_code_login().await?,
+            (Flow::ClientCredentials { .. } | Flow::ManagedIdentity { .. }, _) => {
+                let request = self.token_request()?;
+                self.request_token(request).await?
+            }
+        };
+
+        let mut tokens = self.tokens.lock().await;
+        if let Some(refresh_token) = response.refresh_token {
+            tokens.refresh_token = Some(refresh_token.into());
+        }
+        let expires_on = Utc::now() + Duration::seconds(response.expires_in);
+        tokens.access_token = Some((response.access_token.clone().into(), expires_on));
+        Ok(response.access_token)
+    }
+
+    /// The scope of requested tokens, which grants access to the apiserver.
+    fn scope(&self) -> String {
+        format!("{}/.default", self.apiserver_id)
+    }
+
+    fn endpoint(&self, tenant_id: &str, path: &str) -> String {
+        format!("{}/{tenant_id}/oauth2/v2.0/{path}", self.authority_host)
+    }
+
+    fn form_request(endpoint: &str, params: &[(&str, &str)]) -> Result<Request<String>, errors::Error> {
+        let body = Serializer::new(String::new()).extend_pairs(params).finish();
+        Request::builder()
+            .uri(endpoint)
+            .method(Method::POST)
+            .header(
+                CONTENT_TYPE,
+                HeaderValue::from_static("application/x-www-form-urlencoded"),
+            )
+            .version(Version::HTTP_11)
+            .body(body)
+            .map_err(Into::into)
+    }
+
+    /// Prepare a token request for the non-interactive flows.
+    fn token_request(&self) -> Result<Request<String>, errors::Error> {
+        match &self.flow {
+            Flow::ClientCredentials {
+                tenant_id,
+                client_id,
+                client_secret,
+            } => {
+                let scope = self.scope();
+                let form = [
+                    ("grant_type", "client_credentials"),
+                    ("client_id", client_id),
+                    ("client_secret", client_secret.expose_secret()),
+                    ("scope", &scope),
+                ];
+                Self::form_request(&self.endpoint(tenant_id, "token"), &form)
+            }
+            Flow::ManagedIdentity { client_id } => {
+                let mut query = Serializer::new(String::new());
+                query.extend_pairs([("api-version", "2018-02-01"), ("resource", &self.apiserver_id)]);
+                if let Some(client_id) = client_id {
+                    query.append_pair("client_id", client_id);
+                }
+                Request::builder()
+                    .uri(format!("{}?{}", Self::IMDS_TOKEN_ENDPOINT, query.finish()))
+                    .method(Method::GET)
+                    .header("Metadata", HeaderValue::from_static("true"))
+                    .header(ACCEPT, HeaderValue::from_static("application/json"))
+                    .body(String::new())
+                    .map_err(Into::into)
+            }
+            Flow::DeviceCode { .. } => unreachable!("device code tokens are requested interactively"),
+        }
+    }
+
+    /// Prepare a request to exchange the refresh token from a device code login for a new access token.
+    fn refresh_token_request(&self, refresh_token: &SecretString) -> Result<Request<String>, errors::Error> {
+        let (tenant_id, client_id) = match &self.flow {
+            Flow::DeviceCode { tenant_id, client_id } => (tenant_id, client_id),
+            _ => unreachable!("only device code logins return refresh tokens"),
+        };
+        let scope = format!("{} offline_access", self.scope());
+        let form = [
+            ("grant_type", "refresh_token"),
+            ("client_id", client_id),
+            ("refresh_token", refresh_token.expose_secret()),
+            ("scope", &scope),
+        ];
+        Self::form_request(&self.endpoint(tenant_id, "token"), &form)
+    }
+
+    /// Run the device code flow, which asks the user to log in on another device and waits until they did.
+    async fn device_code_login(&self) -> Result<TokenResponse, errors::Error> {
+        let (tenant_id, client_id) = match &self.flow {
+            Flow::DeviceCode { tenant_id, client_id } => (tenant_id, client_id),
+            _ => unreachable!("device code login requires the device code flow"),
+        };
+        let scope = format!("{} offline_access", self.scope());
+        let form = [("client_id", client_id.as_str()), ("scope", &scope)];
+        let request = Self::form_request(&self.endpoint(tenant_id, "devicecode"), &form)?;
+        let (status, body) = self.send(request).await?;
+        if !status.is_success() {
+            return Err(errors::Error::RequestFailed(status, body));
+        }
+        let device_code =
+            serde_json::from_str::<DeviceCodeResponse>(&body).map_err(errors::Error::InvalidResponse)?;
+        // The message tells the user which code to enter where. Print it to stderr like `kubelogin` does,
+        // since the user has to act on it even when no tracing subscriber is installed.
+        eprintln!("{}", device_code.message);
+
+        let deadline = Utc::now() + Duration::seconds(device_code.expires_in);
+        let mut interval = device_code.interval;
+        while Utc::now() < deadline {
+            tokio::time::sleep(std::time::Duration::from_secs(interval.unsigned_abs())).await;
+            let form = [
+                ("grant_type", "urn:ietf:params:oauth:grant-type:device_code"),
+                ("client_id", client_id),
+                ("device_code", &device_code.device_code),
+            ];
+            let request = Self::form_request(&self.endpoint(tenant_id, "token"), &form)?;
+            let (status, body) = self.send(request).await?;
+            if status.is_success() {
+                return serde_json::from_str(&body).map_err(errors::Error::InvalidResponse);
+            }
+            match serde_json::from_str::<ErrorResponse>(&body).map(|res| res.error) {
+                Ok(error) if error == "authorization_pending" => {}
+                Ok(error) if error == "slow_down" => interval += 5,
+                _ => return Err(errors::Error::RequestFailed(status, body)),
+            }
+        }
+        Err(errors::Error::DeviceCodeExpired)
+    }
+
+    async fn request_token(&self, request: Request<String>) -> Result<TokenResponse, errors::Error> {
+        let (status, body) = self.send(request).await?;
+        if !status.is_success() {
+            return Err(errors::Error::RequestFailed(status, body));
+        }
+        serde_json::from_str(&body).map_err(errors::Error::InvalidResponse)
+    }
+
+    async fn send(&self, request: Request<String>) -> Result<(StatusCode, String), errors::Error> {
+        let response = self.https_client.request(request).await?;
+        let status = response.status();
+        let body = body::to_bytes(response.into_body()).await?;
+        Ok((status, String::from_utf8_lossy(&body).into_owned()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| ((*k).to_owned(), (*v).to_owned()))
+            .collect()
+    }
+
+    #[test]
+    fn flow_from_config() {
+        let azure = Azure::from_config(&config(&[("tenant-id", "tenant"), ("client-id", "client")])).unwrap();
+        assert!(matches!(
+            azure.flow,
+            Flow::DeviceCode { tenant_id, client_id } if tenant_id == "tenant" && client_id == "client"
+        ));
+        assert_eq!(azure.apiserver_id, Azure::DEFAULT_APISERVER_ID);
+
+        let azure = Azure::from_config(&config(&[
+            ("tenant-id", "tenant"),
+            ("client-id", "client"),
+            ("client-secret", "secret"),
+            ("apiserver-id", "apiserver"),
+        ]))
+        .unwrap();
+        assert!(matches!(azure.flow, Flow::ClientCredentials { .. }));
+        assert_eq!(azure.scope(), "apiserver/.default");
+
+        let azure = Azure::from_config(&config(&[("use-managed-identity", "true")])).unwrap();
+        assert!(matches!(azure.flow, Flow::ManagedIdentity { client_id: None }));
+
+        assert!(matches!(
+            Azure::from_config(&config(&[("client-id", "client")])),
+            Err(errors::Error::MissingField("tenant-id"))
+        ));
+        assert!(matches!(
+            Azure::from_config(&config(&[
+                ("tenant-id", "tenant"),
+                ("client-id", "client"),
+                ("environment", "AzureMoonCloud")
+            ])),
+            Err(errors::Error::UnknownEnvironment(_))
+        ));
+    }
+
+    #[test]
+    fn token_requests() {
+        let azure = Azure::from_config(&config(&[
+            ("tenant-id", "tenant"),
+            ("client-id", "client"),
+            ("client-secret", "secret"),
+            ("environment", "AzureChinaCloud"),
+        ]))
+        .unwrap();
+        let request = azure.token_request().unwrap();
+        assert_eq!(
+            request.uri(),
+            "https://login.chinacloudapi.cn/tenant/oauth2/v2.0/token"
+        );
+        assert_eq!(
+            request.body(),
+            "grant_type=client_credentials&client_id=client&client_secret=secret&scope=6dae42f8-4368-4678-94ff-3960e28e3630%2F.default"
+        );
+
+        let azure = Azure::from_config(&config(&[
+            ("use-managed-identity", "true"),
+            ("client-id", "identity"),
+        ]))
+        .unwrap();
+        let request = azure.token_request().unwrap();
+        assert_eq!(request.method(), Method::GET);
+        assert_eq!(
+            request.uri(),
+            "http://169.254.169.254/metadata/identity/oauth2/token?api-version=2018-02-01&resource=6dae42f8-4368-4678-94ff-3960e28e3630&client_id=identity"
+        );
+        assert_eq!(request.headers()["Metadata"], "true");
+    }
+
+    #[tokio::test]
+    async fn cached_token() {
+        let expires_on = (Utc::now() + Duration::hours(1)).timestamp().to_string();
+        let azure = Azure::from_config(&config(&[
+            ("tenant-id", "tenant"),
+            ("client-id", "client"),
+            ("access-token", "cached"),
+            ("expires-on", &expires_on),
+        ]))
+        .unwrap();
+        assert_eq!(azure.token().await.unwrap(), "cached");
+    }
+
+    #[test]
+    fn token_response_durations() {
+        let aad: TokenResponse =
+            serde_json::from_str(r#"{"access_token": "a", "refresh_token": "r", "expires_in": 3599}"#)
+                .unwrap();
+        assert_eq!(aad.expires_in, 3599);
+        let imds: TokenResponse = serde_json::from_str(
+            r#"{"access_token": "a", "expires_in": "86399", "expires_on": "1700000000"}"#,
+        )
+        .unwrap();
+        assert_eq!(imds.expires_in, 86399);
+        assert!(imds.refresh_token.is_none());
+    }
+}
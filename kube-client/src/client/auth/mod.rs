@@ -18,8 +18,10 @@ use thiserror::Error;
 use tokio::sync::{Mutex, RwLock};
 use tower::{filter::AsyncPredicate, BoxError};
 
-use crate::config::{AuthInfo, AuthProviderConfig, ExecConfig, ExecInteractiveMode};
+use crate::config::{AuthInfo, AuthProviderConfig, ExecAuthCluster, ExecConfig, ExecInteractiveMode};
 
+#[cfg(feature = "azure")] mod azure;
+#[cfg(feature = "azure")] pub use azure::errors as azure_errors;
 #[cfg(feature = "oauth")] mod oauth;
 #[cfg(feature = "oauth")] pub use oauth::Error as OAuthError;
 #[cfg(feature = "oidc")] mod oidc;
@@ -99,6 +101,12 @@ pub enum Error {
     #[cfg_attr(docsrs, doc(cfg(feature = "oidc")))]
     #[error("failed OIDC: {0}")]
     Oidc(#[source] oidc_errors::Error),
+
+    /// Azure AD error
+    #[cfg(feature = "azure")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "azure")))]
+    #[error("failed Azure AD: {0}")]
+    Azure(#[source] azure_errors::Error),
 }
 
 #[derive(Debug, Clone)]
@@ -163,6 +171,7 @@ impl TokenFile {
 // - token-file refreshed at least once per minute
 // - gcp: command based token source (exec)
 // - gcp: application credential based token source (requires `oauth` feature)
+// - azure: device code, client credential, and managed identity token sources (requires `azure` feature)
 //
 // Note that the visibility must be `pub` for `impl Layer for AuthLayer`, but this is not exported from the crate.
 // It's not accessible from outside and not shown on docs.
@@ -174,6 +183,8 @@ pub enum RefreshableToken {
     GcpOauth(Arc<Mutex<oauth::Gcp>>),
     #[cfg(feature = "oidc")]
     Oidc(Arc<Mutex<oidc::Oidc>>),
+    #[cfg(feature = "azure")]
+    Azure(Arc<azure::Azure>),
 }
 
 // For use with `AsyncFilterLayer` to add `Authorization` header with a refreshed token.
@@ -224,6 +235,8 @@ impl RefreshableToken {
                         Auth::RefreshableToken(RefreshableToken::GcpOauth(_)) => unreachable!(),
                         #[cfg(feature = "oidc")]
                         Auth::RefreshableToken(RefreshableToken::Oidc(_)) => unreachable!(),
+                        #[cfg(feature = "azure")]
+                        Auth::RefreshableToken(RefreshableToken::Azure(_)) => unreachable!(),
                     }
                 }
 
@@ -254,6 +267,12 @@ impl RefreshableToken {
                 let token = oidc.lock().await.id_token().await.map_err(Error::Oidc)?;
                 bearer_header(&token)
             }
+
+            #[cfg(feature = "azure")]
+            RefreshableToken::Azure(azure) => {
+                let token = azure.token().await.map_err(Error::Azure)?;
+                bearer_header(&token)
+            }
         }
     }
 }
@@ -271,8 +290,15 @@ impl TryFrom<&AuthInfo> for Auth {
     /// exec plugins as well as specified in
     /// https://kubernetes.io/docs/reference/access-authn-authz/authentication/#client-go-credential-plugins
     fn try_from(auth_info: &AuthInfo) -> Result<Self, Self::Error> {
+        Self::new(auth_info, false)
+    }
+}
+
+impl Auth {
+    /// Like [`Auth::try_from`], optionally writing tokens refreshed by the `oidc` provider back to the kubeconfig
+    pub(crate) fn new(auth_info: &AuthInfo, persist_refreshed_tokens: bool) -> Result<Self, Error> {
         if let Some(provider) = &auth_info.auth_provider {
-            match token_from_provider(provider)? {
+            match token_from_provider(provider, persist_refreshed_tokens)? {
                 #[cfg(feature = "oidc")]
                 ProviderToken::Oidc(oidc) => {
                     return Ok(Self::RefreshableToken(RefreshableToken::Oidc(Arc::new(
@@ -306,6 +332,11 @@ impl TryFrom<&AuthInfo> for Auth {
                         Mutex::new(gcp),
                     ))));
                 }
+
+                #[cfg(feature = "azure")]
+                ProviderToken::Azure(azure) => {
+                    return Ok(Self::RefreshableToken(RefreshableToken::Azure(Arc::new(azure))));
+                }
             }
         }
 
@@ -352,6 +383,7 @@ impl TryFrom<&AuthInfo> for Auth {
 }
 
 // We need to differentiate providers because the keys/formats to store token expiration differs.
+#[allow(clippy::large_enum_variant)]
 enum ProviderToken {
     #[cfg(feature = "oidc")]
     Oidc(oidc::Oidc),
@@ -361,17 +393,18 @@ enum ProviderToken {
     GcpCommand(String, Option<DateTime<Utc>>),
     #[cfg(feature = "oauth")]
     GcpOauth(oauth::Gcp),
-    // "access-token", "expires-on" (timestamp)
-    // Azure(String, Option<DateTime<Utc>>),
+    #[cfg(feature = "azure")]
+    Azure(azure::Azure),
 }
 
-fn token_from_provider(provider: &AuthProviderConfig) -> Result<ProviderToken, Error> {
+fn token_from_provider(
+    provider: &AuthProviderConfig,
+    persist_refreshed_tokens: bool,
+) -> Result<ProviderToken, Error> {
     match provider.name.as_ref() {
-        "oidc" => token_from_oidc_provider(provider),
+        "oidc" => token_from_oidc_provider(provider, persist_refreshed_tokens),
         "gcp" => token_from_gcp_provider(provider),
-        "azure" => Err(Error::AuthExec(
-            "The azure auth plugin is not supported; use https://github.com/Azure/kubelogin instead".into(),
-        )),
+        "azure" => token_from_azure_provider(provider),
         _ => Err(Error::AuthExec(format!(
             "Authentication with provider {:} not supported",
             provider.name
@@ -380,14 +413,17 @@ fn token_from_provider(provider: &AuthProviderConfig) -> Result<ProviderToken, E
 }
 
 #[cfg(feature = "oidc")]
-fn token_from_oidc_provider(provider: &AuthProviderConfig) -> Result<ProviderToken, Error> {
+fn token_from_oidc_provider(
+    provider: &AuthProviderConfig,
+    persist_refreshed_tokens: bool,
+) -> Result<ProviderToken, Error> {
     oidc::Oidc::from_config(&provider.config)
         .map_err(Error::Oidc)
-        .map(ProviderToken::Oidc)
+        .map(|oidc| ProviderToken::Oidc(oidc.persist_refreshed_tokens(persist_refreshed_tokens)))
 }
 
 #[cfg(not(feature = "oidc"))]
-fn token_from_oidc_provider(provider: &AuthProviderConfig) -> Result<ProviderToken, Error> {
+fn token_from_oidc_provider(provider: &AuthProviderConfig, _persist: bool) -> Result<ProviderToken, Error> {
     match provider.config.get("id-token") {
         Some(id_token) => Ok(ProviderToken::Oidc(id_token.clone())),
         None => Err(Error::AuthExec(
@@ -396,6 +432,21 @@ fn token_from_oidc_provider(provider: &AuthProviderConfig) -> Result<ProviderTok
     }
 }
 
+#[cfg(feature = "azure")]
+fn token_from_azure_provider(provider: &AuthProviderConfig) -> Result<ProviderToken, Error> {
+    azure::Azure::from_config(&provider.config)
+        .map_err(Error::Azure)
+        .map(ProviderToken::Azure)
+}
+
+#[cfg(not(feature = "azure"))]
+fn token_from_azure_provider(_provider: &AuthProviderConfig) -> Result<ProviderToken, Error> {
+    Err(Error::AuthExec(
+        "Enable azure feature to use the azure auth provider, or use https://github.com/Azure/kubelogin instead"
+            .into(),
+    ))
+}
+
 fn token_from_gcp_provider(provider: &AuthProviderConfig) -> Result<ProviderToken, Error> {
     if let Some(id_token) = provider.config.get("id-token") {
         return Ok(ProviderToken::GcpCommand(id_token.clone(), None));
@@ -512,6 +563,9 @@ pub struct ExecCredential {
 pub struct ExecCredentialSpec {
     #[serde(skip_serializing_if = "Option::is_none")]
     interactive: Option<bool>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cluster: Option<ExecAuthCluster>,
 }
 
 /// ExecCredentialStatus holds credentials for the transport to use.
@@ -558,6 +612,7 @@ fn auth_exec(auth: &ExecConfig) -> Result<ExecCredential, Error> {
         kind: None,
         spec: Some(ExecCredentialSpec {
             interactive: Some(interactive),
+            cluster: auth.cluster.clone().filter(|_| auth.provide_cluster_info),
         }),
         status: None,
     })
@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, path::Path};
 
 use base64::{
     alphabet,
@@ -17,6 +17,7 @@ use hyper::{body, client::HttpConnector, http::Uri, Client, Request};
 use secrecy::{ExposeSecret, SecretString};
 use serde::{Deserialize, Deserializer};
 use serde_json::Number;
+use tokio::io::AsyncWriteExt;
 
 /// Possible errors when handling OIDC authentication.
 pub mod errors {
@@ -148,6 +149,8 @@ const BASE64_ENGINE: FastPortable = FastPortable::from(
 pub struct Oidc {
     id_token: SecretString,
     refresher: Result<Refresher, errors::RefreshInitError>,
+    /// Whether refreshed tokens are written back to the kubeconfig.
+    persist_refreshed_tokens: bool,
 }
 
 impl Oidc {
@@ -182,7 +185,21 @@ impl Oidc {
             return Ok(self.id_token.expose_secret().clone());
         }
 
-        let id_token = self.refresher.as_mut().map_err(|e| e.clone())?.id_token().await?;
+        let refresher = self.refresher.as_mut().map_err(|e| e.clone())?;
+        let old_refresh_token = refresher.refresh_token.clone();
+        let id_token = refresher.id_token().await?;
+
+        if self.persist_refreshed_tokens {
+            let old = Tokens {
+                id_token: self.id_token.expose_secret(),
+                refresh_token: old_refresh_token.expose_secret(),
+            };
+            let new = Tokens {
+                id_token: &id_token,
+                refresh_token: refresher.refresh_token.expose_secret(),
+            };
+            persist_tokens(&crate::config::Kubeconfig::paths(), &old, &new).await;
+        }
 
         self.id_token = id_token.clone().into();
 
@@ -198,8 +215,83 @@ impl Oidc {
             .into();
         let refresher = Refresher::from_config(config);
 
-        Ok(Self { id_token, refresher })
+        Ok(Self {
+            id_token,
+            refresher,
+            persist_refreshed_tokens: false,
+        })
+    }
+
+    /// Write refreshed tokens back to the kubeconfig file that contains the current ID token.
+    pub fn persist_refreshed_tokens(mut self, enabled: bool) -> Self {
+        self.persist_refreshed_tokens = enabled;
+        self
+    }
+}
+
+/// The tokens of an `oidc` auth provider
+struct Tokens<'a> {
+    id_token: &'a str,
+    refresh_token: &'a str,
+}
+
+/// Replace the `old` tokens with the `new` ones in the first of the kubeconfig `paths` that uses them
+async fn persist_tokens(paths: &[impl AsRef<Path>], old: &Tokens<'_>, new: &Tokens<'_>) {
+    for path in paths {
+        let path = path.as_ref();
+        match replace_tokens(path, old, new).await {
+            Ok(true) => return,
+            Ok(false) => {}
+            Err(err) => {
+                tracing::warn!(?path, error = %err, "failed to persist refreshed OIDC tokens to kubeconfig");
+                return;
+            }
+        }
+    }
+    tracing::warn!("failed to persist refreshed OIDC tokens: no kubeconfig contains the current id-token");
+}
+
+/// Replace the `old` tokens with the `new` ones in the kubeconfig at `path`, if an `oidc` auth provider uses them
+///
+/// The tokens are replaced in the text of the file, so that comments and formatting are kept.
+/// The file is replaced atomically, keeping its permissions.
+async fn replace_tokens(path: &Path, old: &Tokens<'_>, new: &Tokens<'_>) -> std::io::Result<bool> {
+    let data = match tokio::fs::read_to_string(path).await {
+        Ok(data) => data,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(false),
+        Err(err) => return Err(err),
+    };
+    if !uses_id_token(&data, old.id_token) {
+        return Ok(false);
     }
+    let mut data = data.replace(old.id_token, new.id_token);
+    if !old.refresh_token.is_empty() {
+        data = data.replace(old.refresh_token, new.refresh_token);
+    }
+
+    let tmp = path.with_extension("tmp");
+    let permissions = tokio::fs::metadata(path).await?.permissions();
+    let mut file = tokio::fs::File::create(&tmp).await?;
+    file.set_permissions(permissions).await?;
+    file.write_all(data.as_bytes()).await?;
+    file.sync_all().await?;
+    drop(file);
+    tokio::fs::rename(&tmp, path).await?;
+    Ok(true)
+}
+
+/// Whether the kubeconfig `data` has an `oidc` auth provider using `id_token`
+fn uses_id_token(data: &str, id_token: &str) -> bool {
+    crate::config::Kubeconfig::from_yaml(data).map_or(false, |config| {
+        config
+            .auth_infos
+            .iter()
+            .filter_map(|user| user.auth_info.as_ref()?.auth_provider.as_ref())
+            .any(|provider| {
+                provider.name == "oidc"
+                    && provider.config.get(Oidc::CONFIG_ID_TOKEN).map(String::as_str) == Some(id_token)
+            })
+    })
 }
 
 /// Claims extracted from the ID token. Only expiration time here is important.
@@ -447,6 +539,7 @@ mod tests {
             refresher: Err(errors::RefreshInitError::MissingField(
                 Refresher::CONFIG_REFRESH_TOKEN,
             )),
+            persist_refreshed_tokens: false,
         };
 
         // Proper JWT expiring at 2123-06-28T15:18:12.629Z
@@ -535,4 +628,85 @@ mod tests {
         assert_eq!(refresher.client_secret.expose_secret(), "some_client_secret");
         assert_eq!(refresher.auth_style, None);
     }
+
+    #[tokio::test]
+    async fn persist_refreshed_tokens() {
+        let dir = tempfile::tempdir().unwrap();
+        let other = dir.path().join("other");
+        std::fs::write(
+            &other,
+            r#"
+users:
+- name: other
+  user:
+    auth-provider:
+      name: oidc
+      config:
+        id-token: other_id_token
+"#,
+        )
+        .unwrap();
+        let mine = dir.path().join("mine");
+        std::fs::write(
+            &mine,
+            r#"
+# my cluster
+users:
+- name: me
+  user:
+    auth-provider:
+      name: oidc
+      config:
+        client-id: some_client_id
+        id-token: old_id_token # expires hourly
+        refresh-token: old_refresh_token
+"#,
+        )
+        .unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&mine, std::fs::Permissions::from_mode(0o600)).unwrap();
+        }
+
+        let old = Tokens {
+            id_token: "old_id_token",
+            refresh_token: "old_refresh_token",
+        };
+        let new = Tokens {
+            id_token: "new_id_token",
+            refresh_token: "new_refresh_token",
+        };
+        persist_tokens(
+            &[dir.path().join("missing"), other.clone(), mine.clone()],
+            &old,
+            &new,
+        )
+        .await;
+
+        // Only the file using the tokens is changed, and it keeps its comments
+        let expected = r#"
+# my cluster
+users:
+- name: me
+  user:
+    auth-provider:
+      name: oidc
+      config:
+        client-id: some_client_id
+        id-token: new_id_token # expires hourly
+        refresh-token: new_refresh_token
+"#;
+        assert_eq!(std::fs::read_to_string(&mine).unwrap(), expected);
+        assert!(std::fs::read_to_string(&other)
+            .unwrap()
+            .contains("id-token: other_id_token"));
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&mine).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        assert!(!replace_tokens(&mine, &old, &new).await.unwrap());
+    }
 }
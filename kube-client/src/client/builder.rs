@@ -1,14 +1,27 @@
 use bytes::Bytes;
-use http::{Request, Response};
-use hyper::{self, client::HttpConnector};
+use http::{uri::Scheme, Request, Response, Uri};
+use hyper::{
+    self,
+    client::{connect::Connection, HttpConnector},
+};
 use hyper_timeout::TimeoutConnector;
-pub use kube_core::response::Status;
-use tower::{util::BoxService, BoxError, Layer, Service, ServiceBuilder};
+#[allow(unused_imports)] pub use kube_core::response::Status;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tower::{
+    layer::util::Identity,
+    util::{BoxCloneService, BoxService},
+    BoxError, Layer, Service, ServiceBuilder, ServiceExt,
+};
 use tower_http::{
     classify::ServerErrorsFailureClass, map_response_body::MapResponseBodyLayer, trace::TraceLayer,
 };
 
-use crate::{client::ConfigExt, Client, Config, Error, Result};
+#[cfg(feature = "gzip")] use crate::client::middleware::RequestCompressionLayer;
+use crate::{
+    client::{middleware::TraceBodiesLayer, ConfigExt, ProxyConnector},
+    config::TlsBackend,
+    Client, Config, Error, Result,
+};
 
 /// HTTP body of a dynamic backing type.
 ///
@@ -66,106 +79,348 @@ impl TryFrom<Config> for ClientBuilder<BoxService<Request<hyper::Body>, Response
 
     /// Builds a default [`ClientBuilder`] stack from a given configuration
     fn try_from(config: Config) -> Result<Self> {
-        use std::time::Duration;
+        Self::try_from_with_inner_layer(config, Identity::new())
+    }
+}
+
+#[cfg(feature = "openssl-tls")]
+const DEFAULT_TLS_BACKEND: Option<TlsBackend> = Some(TlsBackend::OpenSsl);
+#[cfg(all(not(feature = "openssl-tls"), feature = "rustls-tls"))]
+const DEFAULT_TLS_BACKEND: Option<TlsBackend> = Some(TlsBackend::Rustls);
+#[cfg(not(any(feature = "openssl-tls", feature = "rustls-tls")))]
+const DEFAULT_TLS_BACKEND: Option<TlsBackend> = None;
 
-        use http::header::HeaderMap;
-        use tracing::Span;
+/// The connection to the apiserver that is wrapped by [`ClientBuilder::try_from_with_inner_layer`]
+pub type InnerService = BoxCloneService<Request<hyper::Body>, Response<hyper::Body>, BoxError>;
 
-        let default_ns = config.default_namespace.clone();
+impl ClientBuilder<BoxService<Request<hyper::Body>, Response<Box<DynBody>>, BoxError>> {
+    /// Builds the default [`ClientBuilder`] stack from a given configuration, with a custom `layer` around the connection
+    ///
+    /// Unlike [`ClientBuilder::with_layer`], which wraps the whole default stack, the `layer` is placed between the
+    /// default middleware and the connection to the apiserver. The requests it sees have been authenticated and
+    /// have absolute URIs, and the responses it sees have not been decompressed yet.
+    ///
+    /// ```rust
+    /// # async fn doc() -> Result<(), Box<dyn std::error::Error>> {
+    /// # use http::{HeaderValue, Request};
+    /// # use kube::{client::ClientBuilder, Config};
+    /// # use tower::util::MapRequestLayer;
+    /// let config = Config::infer().await?;
+    /// let layer = MapRequestLayer::new(|mut req: Request<hyper::Body>| {
+    ///     req.headers_mut().insert("x-request-source", HeaderValue::from_static("operator"));
+    ///     req
+    /// });
+    /// let client = ClientBuilder::try_from_with_inner_layer(config, layer)?.build();
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn try_from_with_inner_layer<L>(config: Config, layer: L) -> Result<Self>
+    where
+        L: Layer<InnerService>,
+        L::Service: Service<Request<hyper::Body>, Response = Response<hyper::Body>> + Clone + Send + 'static,
+        <L::Service as Service<Request<hyper::Body>>>::Future: Send + 'static,
+        <L::Service as Service<Request<hyper::Body>>>::Error: Into<BoxError>,
+    {
+        if let Some(path) = config.unix_socket.clone() {
+            #[cfg(unix)]
+            return Self::try_from_connector_with_layer(config, super::UnixConnector::new(path), layer);
+            #[cfg(not(unix))]
+            return Err(Error::Service(
+                format!("cannot connect to {path:?}: unix sockets are not supported on this platform").into(),
+            ));
+        }
 
-        let client: hyper::Client<_, hyper::Body> = {
-            let mut connector = HttpConnector::new();
-            connector.enforce_http(false);
+        let mut connector = HttpConnector::new();
+        connector.enforce_http(false);
+        connector.set_keepalive(config.tcp_keepalive);
+        let connector = ProxyConnector::new(connector, config.proxy_url.clone());
+        if config.cluster_url.scheme() == Some(&Scheme::HTTP) {
+            // No TLS stack is needed, and `try_from_service` checks that plain HTTP is allowed
+            return Self::try_from_connector_with_layer(config, connector, layer);
+        }
 
-            // Current TLS feature precedence when more than one are set:
-            // 1. openssl-tls
-            // 2. rustls-tls
-            // Create a custom client to use something else.
-            // If TLS features are not enabled, http connector will be used.
+        // Current TLS feature precedence when more than one are set, unless `Config::tls_backend` is set:
+        // 1. openssl-tls
+        // 2. rustls-tls
+        // Create a custom client to use something else.
+        // If TLS features are not enabled, http connector will be used.
+        match config.tls_backend.or(DEFAULT_TLS_BACKEND) {
             #[cfg(feature = "openssl-tls")]
-            let connector = config.openssl_https_connector_with_connector(connector)?;
-            #[cfg(all(not(feature = "openssl-tls"), feature = "rustls-tls"))]
-            let connector = config.rustls_https_connector_with_connector(connector)?;
-
-            let mut connector = TimeoutConnector::new(connector);
-
-            // Set the timeouts for the client
-            connector.set_connect_timeout(config.connect_timeout);
-            connector.set_read_timeout(config.read_timeout);
-            connector.set_write_timeout(config.write_timeout);
-
-            hyper::Client::builder().build(connector)
-        };
-
-        let stack = ServiceBuilder::new().layer(config.base_uri_layer()).into_inner();
-        #[cfg(feature = "gzip")]
-        let stack = ServiceBuilder::new()
-            .layer(stack)
-            .layer(tower_http::decompression::DecompressionLayer::new())
-            .into_inner();
-
-        let service = ServiceBuilder::new()
-            .layer(stack)
-            .option_layer(config.auth_layer()?)
-            .layer(config.extra_headers_layer()?)
-            .layer(
-                // Attribute names follow [Semantic Conventions].
-                // [Semantic Conventions]: https://github.com/open-telemetry/opentelemetry-specification/blob/main/specification/trace/semantic_conventions/http.md
-                TraceLayer::new_for_http()
-                    .make_span_with(|req: &Request<hyper::Body>| {
-                        tracing::debug_span!(
-                            "HTTP",
-                             http.method = %req.method(),
-                             http.url = %req.uri(),
-                             http.status_code = tracing::field::Empty,
-                             otel.name = req.extensions().get::<&'static str>().unwrap_or(&"HTTP"),
-                             otel.kind = "client",
-                             otel.status_code = tracing::field::Empty,
-                        )
-                    })
-                    .on_request(|_req: &Request<hyper::Body>, _span: &Span| {
-                        tracing::debug!("requesting");
-                    })
-                    .on_response(|res: &Response<hyper::Body>, _latency: Duration, span: &Span| {
-                        let status = res.status();
-                        span.record("http.status_code", status.as_u16());
-                        if status.is_client_error() || status.is_server_error() {
-                            span.record("otel.status_code", "ERROR");
-                        }
-                    })
-                    // Explicitly disable `on_body_chunk`. The default does nothing.
-                    .on_body_chunk(())
-                    .on_eos(|_: Option<&HeaderMap>, _duration: Duration, _span: &Span| {
-                        tracing::debug!("stream closed");
-                    })
-                    .on_failure(|ec: ServerErrorsFailureClass, _latency: Duration, span: &Span| {
-                        // Called when
-                        // - Calling the inner service errored
-                        // - Polling `Body` errored
-                        // - the response was classified as failure (5xx)
-                        // - End of stream was classified as failure
+            Some(TlsBackend::OpenSsl) => Self::try_from_tls(
+                config,
+                move |config: &Config| {
+                    super::config_ext::openssl_https_connector_wrapping(config, connector.clone())
+                },
+                layer,
+            ),
+            #[cfg(feature = "rustls-tls")]
+            Some(TlsBackend::Rustls) => Self::try_from_tls(
+                config,
+                move |config: &Config| {
+                    super::config_ext::rustls_https_connector_wrapping(config, connector.clone())
+                },
+                layer,
+            ),
+            #[cfg(not(any(feature = "openssl-tls", feature = "rustls-tls")))]
+            Some(backend) => match backend {},
+            None => Self::try_from_connector_with_layer(config, connector, layer),
+        }
+    }
+
+    /// Builds the default [`ClientBuilder`] stack from a given configuration, on top of a custom `connector`
+    ///
+    /// This is useful to bring a preconfigured TLS connector. The connector is responsible for TLS and
+    /// for tunneling through [`Config::proxy_url`], while the timeouts and the rest of the configuration
+    /// are applied as usual.
+    ///
+    /// ```rust
+    /// # async fn doc() -> Result<(), Box<dyn std::error::Error>> {
+    /// # use hyper::client::HttpConnector;
+    /// # use kube::{client::ClientBuilder, Config};
+    /// let config = Config::infer().await?;
+    /// let mut http = HttpConnector::new();
+    /// http.enforce_http(false);
+    /// // Wrap `http` in a TLS connector, such as a `hyper_openssl::HttpsConnector` with a custom `SslConnector`
+    /// let client = ClientBuilder::try_from_connector(config, http)?.build();
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn try_from_connector<C>(config: Config, connector: C) -> Result<Self>
+    where
+        C: Service<Uri> + Clone + Send + Sync + 'static,
+        C::Response: AsyncRead + AsyncWrite + Connection + Send + Unpin,
+        C::Future: Send + 'static,
+        C::Error: Into<BoxError>,
+    {
+        Self::try_from_connector_with_layer(config, connector, Identity::new())
+    }
+
+    /// Builds the default middleware stack from a given configuration, on top of a custom `service` that sends the requests
+    ///
+    /// Unlike [`ClientBuilder::new`], authentication, extra headers, tracing, and the other middleware configured by
+    /// `config` are still applied. The `service` receives requests with absolute URIs, and is responsible
+    /// for everything below HTTP, such as connecting, TLS, proxies, and connection timeouts.
+    /// This is useful for transports that do not fit a hyper connector, or to test against a mocked apiserver.
+    pub fn try_from_service<S>(config: Config, service: S) -> Result<Self>
+    where
+        S: Service<Request<hyper::Body>, Response = Response<hyper::Body>> + Clone + Send + 'static,
+        S::Future: Send + 'static,
+        S::Error: Into<BoxError>,
+    {
+        let insecure = config.cluster_url.scheme() == Some(&Scheme::HTTP) && config.unix_socket.is_none();
+        if insecure && !config.allow_insecure_http {
+            return Err(Error::InsecureHttp(config.cluster_url));
+        }
+        make_service(&config, service).map(|service| Self::new(service, config.default_namespace))
+    }
+
+    fn try_from_connector_with_layer<C, L>(config: Config, connector: C, layer: L) -> Result<Self>
+    where
+        C: Service<Uri> + Clone + Send + Sync + 'static,
+        C::Response: AsyncRead + AsyncWrite + Connection + Send + Unpin,
+        C::Future: Send + 'static,
+        C::Error: Into<BoxError>,
+        L: Layer<InnerService>,
+        L::Service: Service<Request<hyper::Body>, Response = Response<hyper::Body>> + Clone + Send + 'static,
+        <L::Service as Service<Request<hyper::Body>>>::Future: Send + 'static,
+        <L::Service as Service<Request<hyper::Body>>>::Error: Into<BoxError>,
+    {
+        let mut builder = hyper::Client::builder();
+        builder
+            .pool_idle_timeout(config.pool_idle_timeout)
+            .pool_max_idle_per_host(config.pool_max_idle_per_host.unwrap_or(usize::MAX))
+            .http2_keep_alive_interval(config.http2_keep_alive_interval)
+            .http2_keep_alive_while_idle(config.http2_keep_alive_interval.is_some());
+        if let Some(timeout) = config.http2_keep_alive_timeout {
+            builder.http2_keep_alive_timeout(timeout);
+        }
+        let client = builder.build(timeout_connector(&config, connector));
+        let client = BoxCloneService::new(client.map_err(BoxError::from));
+        Self::try_from_service(config, layer.layer(client))
+    }
+
+    /// Builds the default stack on top of the TLS connector made by `build`, which is rebuilt when
+    /// the certificate files of the `config` change
+    #[cfg(any(feature = "rustls-tls", feature = "openssl-tls"))]
+    fn try_from_tls<C, L>(
+        config: Config,
+        build: impl Fn(&Config) -> Result<C> + Send + Sync + 'static,
+        layer: L,
+    ) -> Result<Self>
+    where
+        C: Service<Uri> + Clone + Send + Sync + 'static,
+        C::Response: AsyncRead + AsyncWrite + Connection + Send + Unpin,
+        C::Future: Send + 'static,
+        C::Error: Into<BoxError>,
+        L: Layer<InnerService>,
+        L::Service: Service<Request<hyper::Body>, Response = Response<hyper::Body>> + Clone + Send + 'static,
+        <L::Service as Service<Request<hyper::Body>>>::Future: Send + 'static,
+        <L::Service as Service<Request<hyper::Body>>>::Error: Into<BoxError>,
+    {
+        let connector = build(&config)?;
+        let cert_files = super::reload::cert_files(&config);
+        if cert_files.is_empty() {
+            return Self::try_from_connector_with_layer(config, connector, layer);
+        }
+        let connector = super::reload::CertReloader::new(connector, config.clone(), cert_files, build);
+        Self::try_from_connector_with_layer(config, connector, layer)
+    }
+}
+
+fn timeout_connector<C>(config: &Config, connector: C) -> TimeoutConnector<C>
+where
+    C: Service<Uri> + Send + Clone + 'static,
+    C::Response: AsyncRead + AsyncWrite + Connection + Send + Unpin,
+    C::Future: Send + 'static,
+    C::Error: Into<BoxError>,
+{
+    let mut connector = TimeoutConnector::new(connector);
+
+    // Set the timeouts for the client
+    connector.set_connect_timeout(config.connect_timeout);
+    connector.set_read_timeout(config.read_timeout);
+    connector.set_write_timeout(config.write_timeout);
+    connector
+}
+
+/// Wraps the `client` in the default middleware stack
+fn make_service<S>(
+    config: &Config,
+    client: S,
+) -> Result<BoxService<Request<hyper::Body>, Response<Box<DynBody>>, BoxError>>
+where
+    S: Service<Request<hyper::Body>, Response = Response<hyper::Body>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    S::Error: Into<BoxError>,
+{
+    use std::time::Duration;
+
+    use http::header::HeaderMap;
+    use tracing::Span;
+
+    let client = client.map_err(<S::Error as Into<BoxError>>::into);
+    #[cfg(feature = "gzip")]
+    let client = ServiceBuilder::new()
+        .option_layer(config.request_compression.map(RequestCompressionLayer::new))
+        .service(client);
+
+    let stack = ServiceBuilder::new()
+        .option_layer(config.rate_limit_layer())
+        .layer(config.request_timeout_layer())
+        .layer(config.base_uri_layer())
+        .into_inner();
+    #[cfg(feature = "gzip")]
+    let stack = ServiceBuilder::new()
+        .layer(stack)
+        .layer(tower_http::decompression::DecompressionLayer::new())
+        .into_inner();
+
+    let service = ServiceBuilder::new()
+        .layer(stack)
+        .option_layer(config.auth_layer()?)
+        .layer(config.extra_headers_layer()?)
+        .layer(
+            // Attribute names follow [Semantic Conventions].
+            // [Semantic Conventions]: https://github.com/open-telemetry/opentelemetry-specification/blob/main/specification/trace/semantic_conventions/http.md
+            TraceLayer::new_for_http()
+                .make_span_with(|req: &Request<hyper::Body>| {
+                    tracing::debug_span!(
+                        "HTTP",
+                         http.method = %req.method(),
+                         http.url = %req.uri(),
+                         http.status_code = tracing::field::Empty,
+                         http.response.header.audit_id = tracing::field::Empty,
+                         otel.name = req.extensions().get::<&'static str>().unwrap_or(&"HTTP"),
+                         otel.kind = "client",
+                         otel.status_code = tracing::field::Empty,
+                    )
+                })
+                .on_request(|_req: &Request<hyper::Body>, _span: &Span| {
+                    tracing::debug!("requesting");
+                })
+                .on_response(|res: &Response<hyper::Body>, latency: Duration, span: &Span| {
+                    let status = res.status();
+                    span.record("http.status_code", status.as_u16());
+                    // The apiserver's audit id correlates the request with audit logs and apiserver traces
+                    if let Some(audit_id) = res.headers().get("audit-id").and_then(|id| id.to_str().ok()) {
+                        span.record("http.response.header.audit_id", audit_id);
+                    }
+                    tracing::debug!(?latency, "received response");
+                    if status.is_client_error() || status.is_server_error() {
                         span.record("otel.status_code", "ERROR");
-                        match ec {
-                            ServerErrorsFailureClass::StatusCode(status) => {
-                                span.record("http.status_code", status.as_u16());
-                                tracing::error!("failed with status {}", status)
-                            }
-                            ServerErrorsFailureClass::Error(err) => {
-                                tracing::error!("failed with error {}", err)
-                            }
-                        }
-                    }),
-            )
-            .service(client);
-
-        Ok(Self::new(
-            BoxService::new(
-                MapResponseBodyLayer::new(|body| {
-                    Box::new(http_body::Body::map_err(body, BoxError::from)) as Box<DynBody>
+                    }
                 })
-                .layer(service),
-            ),
-            default_ns,
-        ))
+                // Explicitly disable `on_body_chunk`. The default does nothing.
+                .on_body_chunk(())
+                .on_eos(|_: Option<&HeaderMap>, _duration: Duration, _span: &Span| {
+                    tracing::debug!("stream closed");
+                })
+                .on_failure(|ec: ServerErrorsFailureClass, _latency: Duration, span: &Span| {
+                    // Called when
+                    // - Calling the inner service errored
+                    // - Polling `Body` errored
+                    // - the response was classified as failure (5xx)
+                    // - End of stream was classified as failure
+                    span.record("otel.status_code", "ERROR");
+                    match ec {
+                        ServerErrorsFailureClass::StatusCode(status) => {
+                            span.record("http.status_code", status.as_u16());
+                            tracing::error!("failed with status {}", status)
+                        }
+                        ServerErrorsFailureClass::Error(err) => {
+                            tracing::error!("failed with error {}", err)
+                        }
+                    }
+                }),
+        )
+        .option_layer(config.trace_bodies.then(TraceBodiesLayer::default))
+        .service(client);
+
+    Ok(BoxService::new(
+        MapResponseBodyLayer::new(|body| {
+            Box::new(http_body::Body::map_err(body, BoxError::from)) as Box<DynBody>
+        })
+        .layer(service),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use futures::pin_mut;
+    use hyper::Body;
+    use tower_test::mock;
+
+    #[tokio::test]
+    async fn custom_service_gets_default_middleware() {
+        let mut config = Config::new("https://kubernetes.default.svc".parse().unwrap());
+        config.auth_info.token = Some("hunter2".to_owned().into());
+        let (service, handle) = mock::pair::<Request<Body>, Response<Body>>();
+        let client = ClientBuilder::try_from_service(config, service).unwrap().build();
+        let spawned = tokio::spawn(async move {
+            pin_mut!(handle);
+            let (req, send) = handle.next_request().await.expect("service not called");
+            assert_eq!(req.uri(), "https://kubernetes.default.svc/version");
+            assert_eq!(req.headers()[http::header::AUTHORIZATION], "Bearer hunter2");
+            send.send_response(Response::new(Body::from("{}")));
+        });
+
+        let res = client
+            .request_text(Request::get("/version").body(vec![]).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(res, "{}");
+        spawned.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn plain_http_requires_opt_in() {
+        let config = Config::new("http://127.0.0.1:8001".parse().unwrap());
+        assert!(matches!(
+            ClientBuilder::try_from(config.clone()),
+            Err(Error::InsecureHttp(_))
+        ));
+        ClientBuilder::try_from(config.allow_insecure_http(true)).unwrap();
     }
 }
@@ -7,7 +7,10 @@ use tower::{filter::AsyncFilterLayer, util::Either};
 #[cfg(any(feature = "rustls-tls", feature = "openssl-tls"))] use super::tls;
 use super::{
     auth::Auth,
-    middleware::{AddAuthorizationLayer, AuthLayer, BaseUriLayer, ExtraHeadersLayer},
+    middleware::{
+        AddAuthorizationLayer, AuthLayer, BaseUriLayer, ExtraHeadersLayer, RateLimitLayer,
+        RequestTimeoutLayer,
+    },
 };
 use crate::{Config, Error, Result};
 
@@ -26,6 +29,16 @@ pub trait ConfigExt: private::Sealed {
     /// Layer to add non-authn HTTP headers depending on the config.
     fn extra_headers_layer(&self) -> Result<ExtraHeadersLayer>;
 
+    /// Layer to fail requests that take longer than [`Config::request_timeout`](crate::Config::request_timeout).
+    ///
+    /// No timeout is applied if it is not set.
+    fn request_timeout_layer(&self) -> RequestTimeoutLayer;
+
+    /// Optional layer to limit the rate of requests to [`Config::qps`](crate::Config::qps) and [`Config::burst`](crate::Config::burst).
+    ///
+    /// Every call creates a new limit, which is shared by the services that the layer wraps.
+    fn rate_limit_layer(&self) -> Option<RateLimitLayer>;
+
     /// Create [`hyper_rustls::HttpsConnector`] based on config.
     ///
     /// # Example
@@ -156,19 +169,34 @@ impl ConfigExt for Config {
     }
 
     fn auth_layer(&self) -> Result<Option<AuthLayer>> {
-        Ok(match Auth::try_from(&self.auth_info).map_err(Error::Auth)? {
-            Auth::None => None,
-            Auth::Basic(user, pass) => Some(AuthLayer(Either::A(
-                AddAuthorizationLayer::basic(&user, pass.expose_secret()).as_sensitive(true),
-            ))),
-            Auth::Bearer(token) => Some(AuthLayer(Either::A(
-                AddAuthorizationLayer::bearer(token.expose_secret()).as_sensitive(true),
-            ))),
-            Auth::RefreshableToken(refreshable) => {
-                Some(AuthLayer(Either::B(AsyncFilterLayer::new(refreshable))))
-            }
-            Auth::Certificate(_client_certificate_data, _client_key_data) => None,
-        })
+        Ok(
+            match Auth::new(&self.auth_info, self.persist_refreshed_tokens).map_err(Error::Auth)? {
+                Auth::None => None,
+                Auth::Basic(user, pass) => Some(AuthLayer(Either::A(
+                    AddAuthorizationLayer::basic(&user, pass.expose_secret()).as_sensitive(true),
+                ))),
+                Auth::Bearer(token) => Some(AuthLayer(Either::A(
+                    AddAuthorizationLayer::bearer(token.expose_secret()).as_sensitive(true),
+                ))),
+                Auth::RefreshableToken(refreshable) => {
+                    Some(AuthLayer(Either::B(AsyncFilterLayer::new(refreshable))))
+                }
+                Auth::Certificate(_client_certificate_data, _client_key_data) => None,
+            },
+        )
+    }
+
+    fn rate_limit_layer(&self) -> Option<RateLimitLayer> {
+        let qps = self.qps.filter(|qps| *qps > 0.0)?;
+        // Allow at least one request at a time, even when less than one is allowed per second
+        let burst = self.burst.unwrap_or_else(|| qps.ceil() as u32);
+        Some(RateLimitLayer::new(qps, burst))
+    }
+
+    fn request_timeout_layer(&self) -> RequestTimeoutLayer {
+        RequestTimeoutLayer {
+            timeout: self.request_timeout,
+        }
     }
 
     fn extra_headers_layer(&self) -> Result<ExtraHeadersLayer> {
@@ -191,6 +219,37 @@ impl ConfigExt for Config {
                 ));
             }
         }
+        if let Some(impersonate_uid) = &self.auth_info.impersonate_uid {
+            headers.push((
+                HeaderName::from_static("impersonate-uid"),
+                HeaderValue::from_str(impersonate_uid)
+                    .map_err(http::Error::from)
+                    .map_err(Error::HttpError)?,
+            ));
+        }
+        if let Some(impersonate_user_extra) = &self.auth_info.impersonate_user_extra {
+            for (key, values) in impersonate_user_extra {
+                let name = HeaderName::try_from(format!("impersonate-extra-{}", escape_header_key(key)))
+                    .map_err(http::Error::from)
+                    .map_err(Error::HttpError)?;
+                for value in values {
+                    headers.push((
+                        name.clone(),
+                        HeaderValue::from_str(value)
+                            .map_err(http::Error::from)
+                            .map_err(Error::HttpError)?,
+                    ));
+                }
+            }
+        }
+        if let Some(user_agent) = &self.user_agent {
+            headers.push((
+                http::header::USER_AGENT,
+                HeaderValue::from_str(user_agent)
+                    .map_err(http::Error::from)
+                    .map_err(Error::HttpError)?,
+            ));
+        }
         Ok(ExtraHeadersLayer {
             headers: Arc::new(headers),
         })
@@ -203,6 +262,7 @@ impl ConfigExt for Config {
             identity.as_deref(),
             self.root_cert.as_deref(),
             self.accept_invalid_certs,
+            self.tls_cipher_suites.as_deref(),
         )
         .map_err(Error::RustlsTls)
     }
@@ -219,22 +279,19 @@ impl ConfigExt for Config {
         &self,
         connector: hyper::client::HttpConnector,
     ) -> Result<hyper_rustls::HttpsConnector<hyper::client::HttpConnector>> {
-        let rustls_config = self.rustls_client_config()?;
-        let mut builder = hyper_rustls::HttpsConnectorBuilder::new()
-            .with_tls_config(rustls_config)
-            .https_or_http();
-        if let Some(tsn) = self.tls_server_name.as_ref() {
-            builder = builder.with_server_name(tsn.clone());
-        }
-        Ok(builder.enable_http1().wrap_connector(connector))
+        rustls_https_connector_wrapping(self, connector)
     }
 
     #[cfg(feature = "openssl-tls")]
     fn openssl_ssl_connector_builder(&self) -> Result<openssl::ssl::SslConnectorBuilder> {
         let identity = self.exec_identity_pem().or_else(|| self.identity_pem());
         // TODO: pass self.tls_server_name for openssl
-        tls::openssl_tls::ssl_connector_builder(identity.as_ref(), self.root_cert.as_ref())
-            .map_err(|e| Error::OpensslTls(tls::openssl_tls::Error::CreateSslConnector(e)))
+        tls::openssl_tls::ssl_connector_builder(
+            identity.as_ref(),
+            self.root_cert.as_ref(),
+            self.tls_cipher_suites.as_ref(),
+        )
+        .map_err(|e| Error::OpensslTls(tls::openssl_tls::Error::CreateSslConnector(e)))
     }
 
     #[cfg(feature = "openssl-tls")]
@@ -262,6 +319,52 @@ impl ConfigExt for Config {
     }
 }
 
+/// Wraps any `connector`, like the proxy connector of the default client, with rustls
+#[cfg(feature = "rustls-tls")]
+pub(crate) fn rustls_https_connector_wrapping<H>(
+    config: &Config,
+    connector: H,
+) -> Result<hyper_rustls::HttpsConnector<H>> {
+    let rustls_config = config.rustls_client_config()?;
+    let mut builder = hyper_rustls::HttpsConnectorBuilder::new()
+        .with_tls_config(rustls_config)
+        .https_or_http();
+    if let Some(tsn) = config.tls_server_name.as_ref() {
+        builder = builder.with_server_name(tsn.clone());
+    }
+    Ok(builder.enable_http1().wrap_connector(connector))
+}
+
+/// Wraps any `connector`, like the proxy connector of the default client, with openssl
+#[cfg(feature = "openssl-tls")]
+pub(crate) fn openssl_https_connector_wrapping<H>(
+    config: &Config,
+    connector: H,
+) -> Result<hyper_openssl::HttpsConnector<H>> {
+    let mut https = hyper_openssl::HttpsLayer::with_connector(config.openssl_ssl_connector_builder()?)
+        .map_err(|e| Error::OpensslTls(tls::openssl_tls::Error::CreateHttpsConnector(e)))?;
+    if config.accept_invalid_certs {
+        https.set_callback(|ssl, _uri| {
+            ssl.set_verify(openssl::ssl::SslVerifyMode::NONE);
+            Ok(())
+        });
+    }
+    Ok(tower::Layer::layer(&https, connector))
+}
+
+/// Percent-encode the bytes of an `Impersonate-Extra-` key that are not allowed in header names, like client-go
+fn escape_header_key(key: &str) -> String {
+    key.bytes()
+        .map(|b| {
+            if b.is_ascii_alphanumeric() || b"!#$&'*+-.^_`|~".contains(&b) {
+                char::from(b.to_ascii_lowercase()).to_string()
+            } else {
+                format!("%{b:02X}")
+            }
+        })
+        .collect()
+}
+
 impl Config {
     // This is necessary to retrieve an identity when an exec plugin
     // returns a client certificate and key instead of a token.
@@ -282,3 +385,52 @@ impl Config {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn impersonation_headers() {
+        let config = Config::new("https://kubernetes".parse().unwrap())
+            .impersonate("jane")
+            .impersonate_groups(["developers", "system:authenticated"])
+            .impersonate_uid("1234")
+            .impersonate_extra("acme.com/Project", ["kube"]);
+        let layer = config.extra_headers_layer().unwrap();
+        let headers = layer
+            .headers
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.to_str().unwrap()))
+            .collect::<Vec<_>>();
+        assert_eq!(headers, [
+            ("impersonate-user", "jane"),
+            ("impersonate-group", "developers"),
+            ("impersonate-group", "system:authenticated"),
+            ("impersonate-uid", "1234"),
+            ("impersonate-extra-acme.com%2fproject", "kube"),
+        ]);
+    }
+
+    #[test]
+    fn fluent_config() {
+        let config = Config::new("https://kubernetes".parse().unwrap())
+            .default_namespace("apps")
+            .token("hunter2")
+            .user_agent("my-operator/1.0")
+            .read_timeout(None)
+            .request_timeout(std::time::Duration::from_secs(30));
+        assert_eq!(config.default_namespace, "apps");
+        assert_eq!(config.read_timeout, None);
+        assert_eq!(config.request_timeout, Some(std::time::Duration::from_secs(30)));
+        assert!(config.auth_layer().unwrap().is_some());
+        let layer = config.extra_headers_layer().unwrap();
+        assert_eq!(layer.headers[..], [(
+            http::header::USER_AGENT,
+            HeaderValue::from_static("my-operator/1.0")
+        )]);
+
+        assert!(config.rate_limit_layer().is_none());
+        assert!(config.rate_limit(0.5, 1).rate_limit_layer().is_some());
+    }
+}
@@ -0,0 +1,373 @@
+//! Incremental parsing of list responses
+use std::{
+    collections::VecDeque,
+    marker::PhantomData,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use futures::{future::BoxFuture, Stream, TryStreamExt};
+use hyper::Body;
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::ListMeta;
+use kube_core::ObjectList;
+use serde::de::{DeserializeOwned, Error as _};
+
+use crate::{Error, Result};
+
+/// Requests the page of a list with the given continue token
+pub(crate) type NextPage = Box<dyn FnMut(String) -> BoxFuture<'static, Result<Body>> + Send>;
+
+/// Stream of the items of a list response, which are deserialized as they are received
+///
+/// Unlike a buffered list, the response body is never held in memory as a whole, only the item
+/// that is currently being received. Returned by [`Api::list_stream`](crate::Api::list_stream),
+/// which also follows the `continue` tokens of paginated lists, and by
+/// [`Client::request_list_stream`](crate::Client::request_list_stream), which does not.
+pub struct ListStream<T> {
+    body: Body,
+    parser: ListParser,
+    metadata: Option<ListMeta>,
+    next_page: Option<NextPage>,
+    pending_page: Option<BoxFuture<'static, Result<Body>>>,
+    done: bool,
+    _item: PhantomData<fn() -> T>,
+}
+
+impl<T> ListStream<T> {
+    pub(crate) fn new(body: Body) -> Self {
+        Self {
+            body,
+            parser: ListParser::default(),
+            metadata: None,
+            next_page: None,
+            pending_page: None,
+            done: false,
+            _item: PhantomData,
+        }
+    }
+
+    /// Continue with the next page by calling `next_page` when a page has a continue token
+    pub(crate) fn follow_pages(mut self, next_page: NextPage) -> Self {
+        self.next_page = Some(next_page);
+        self
+    }
+
+    /// The metadata of the list, such as its `resourceVersion` and `continue` token
+    ///
+    /// The apiserver sends the metadata before the items, so this is available once the first item
+    /// has been received, or once the stream has ended for an empty list. When pages are followed,
+    /// this is the metadata of the page that is being received.
+    pub fn metadata(&self) -> Option<&ListMeta> {
+        self.metadata.as_ref()
+    }
+
+    /// Collect all items along with the metadata, like [`Api::list`](crate::Api::list) would return them
+    ///
+    /// When pages are followed, the items of all pages are collected along with the metadata of the last page.
+    pub async fn try_collect_list(mut self) -> Result<ObjectList<T>>
+    where
+        T: Clone + DeserializeOwned,
+    {
+        let mut items = Vec::new();
+        while let Some(item) = self.try_next().await? {
+            items.push(item);
+        }
+        Ok(ObjectList {
+            metadata: self.metadata.unwrap_or_default(),
+            items,
+        })
+    }
+
+    fn feed(&mut self, chunk: &[u8]) -> Result<()> {
+        self.parser.feed(chunk).map_err(Error::SerdeError)?;
+        if let Some(metadata) = self.parser.metadata.take() {
+            self.metadata = Some(serde_json::from_slice(&metadata).map_err(Error::SerdeError)?);
+        }
+        Ok(())
+    }
+}
+
+impl<T: DeserializeOwned> Stream for ListStream<T> {
+    type Item = Result<T>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            if let Some(item) = this.parser.items.pop_front() {
+                return Poll::Ready(Some(serde_json::from_slice(&item).map_err(Error::SerdeError)));
+            }
+            if this.done {
+                return Poll::Ready(None);
+            }
+            if let Some(pending_page) = &mut this.pending_page {
+                match futures::ready!(pending_page.as_mut().poll(cx)) {
+                    Ok(body) => {
+                        this.body = body;
+                        this.parser = ListParser::default();
+                        this.pending_page = None;
+                    }
+                    Err(err) => {
+                        this.done = true;
+                        this.pending_page = None;
+                        return Poll::Ready(Some(Err(err)));
+                    }
+                }
+            }
+            match futures::ready!(Pin::new(&mut this.body).poll_next(cx)) {
+                Some(Ok(chunk)) => {
+                    if let Err(err) = this.feed(&chunk) {
+                        this.done = true;
+                        return Poll::Ready(Some(Err(err)));
+                    }
+                }
+                Some(Err(err)) => {
+                    this.done = true;
+                    return Poll::Ready(Some(Err(Error::HyperError(err))));
+                }
+                None => {
+                    if !this.parser.finished {
+                        this.done = true;
+                        let err =
+                            serde_json::Error::custom("list response ended before the list was complete");
+                        return Poll::Ready(Some(Err(Error::SerdeError(err))));
+                    }
+                    // Take the token, so that a next page without metadata does not request itself again
+                    let token = match (&this.next_page, &mut this.metadata) {
+                        (Some(_), Some(metadata)) => {
+                            metadata.continue_.take().filter(|token| !token.is_empty())
+                        }
+                        _ => None,
+                    };
+                    match (&mut this.next_page, token) {
+                        (Some(next_page), Some(token)) => {
+                            this.pending_page = Some(next_page(token));
+                        }
+                        _ => this.done = true,
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The top-level field of the list that is being parsed
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+enum Field {
+    #[default]
+    Other,
+    Items,
+    Metadata,
+}
+
+/// Splits a JSON list object into the raw JSON of its items and metadata, without parsing the items
+///
+/// Only the structure of the JSON is tracked: nesting, strings, and the keys of the top-level object.
+/// Parsing the items and metadata is left to serde, which also reports any invalid JSON within them.
+#[derive(Debug, Default)]
+struct ListParser {
+    /// Bytes that have not been consumed yet, from the start of the current key or value
+    buf: Vec<u8>,
+    /// The next byte of `buf` to scan
+    pos: usize,
+    depth: usize,
+    in_string: bool,
+    escaped: bool,
+    /// Whether the next string in the top-level object is a key
+    expect_key: bool,
+    /// Start of the key, item, or metadata that is being received
+    start: Option<usize>,
+    key: Vec<u8>,
+    field: Field,
+    finished: bool,
+
+    items: VecDeque<Vec<u8>>,
+    metadata: Option<Vec<u8>>,
+}
+
+impl ListParser {
+    fn feed(&mut self, chunk: &[u8]) -> serde_json::Result<()> {
+        self.buf.extend_from_slice(chunk);
+        while self.pos < self.buf.len() {
+            let i = self.pos;
+            let byte = self.buf[i];
+            self.pos += 1;
+            if self.finished {
+                if !byte.is_ascii_whitespace() {
+                    return Err(serde_json::Error::custom("trailing characters after the list"));
+                }
+                continue;
+            }
+            if self.in_string {
+                if self.escaped {
+                    self.escaped = false;
+                } else if byte == b'\\' {
+                    self.escaped = true;
+                } else if byte == b'"' {
+                    self.in_string = false;
+                    if self.depth == 1 && self.expect_key {
+                        let start = self.start.take().unwrap_or(i);
+                        self.key = self.buf[start + 1..i].to_vec();
+                    }
+                }
+                continue;
+            }
+            match byte {
+                b'"' => {
+                    self.in_string = true;
+                    if self.depth == 1 && self.expect_key {
+                        self.start = Some(i);
+                    } else {
+                        self.expect_item_or_value()?;
+                    }
+                }
+                b'{' | b'[' => {
+                    if self.depth == 0 {
+                        if byte != b'{' {
+                            return Err(serde_json::Error::custom("expected the list to be an object"));
+                        }
+                        self.expect_key = true;
+                    } else if self.start.is_none()
+                        && ((self.depth == 1 && self.field == Field::Metadata)
+                            || (self.depth == 2 && self.field == Field::Items))
+                    {
+                        self.start = Some(i);
+                    } else {
+                        self.expect_item_or_value()?;
+                    }
+                    self.depth += 1;
+                }
+                b'}' | b']' => {
+                    self.depth = self
+                        .depth
+                        .checked_sub(1)
+                        .ok_or_else(|| serde_json::Error::custom("unbalanced list"))?;
+                    match (self.depth, self.field) {
+                        (0, _) => self.finished = true,
+                        (1, Field::Metadata) | (2, Field::Items) => {
+                            if let Some(start) = self.start.take() {
+                                let value = self.buf[start..=i].to_vec();
+                                if self.field == Field::Items {
+                                    self.items.push_back(value);
+                                } else {
+                                    self.metadata = Some(value);
+                                }
+                            }
+                        }
+                        (1, Field::Items) => self.field = Field::Other,
+                        _ => {}
+                    }
+                }
+                b':' if self.depth == 1 => {
+                    self.expect_key = false;
+                    self.field = match self.key.as_slice() {
+                        b"items" => Field::Items,
+                        b"metadata" => Field::Metadata,
+                        _ => Field::Other,
+                    };
+                }
+                b',' if self.depth == 1 => {
+                    self.expect_key = true;
+                    self.field = Field::Other;
+                }
+                _ if byte.is_ascii_whitespace() || byte == b',' => {}
+                _ => self.expect_item_or_value()?,
+            }
+        }
+
+        // Drop everything that has been consumed
+        let consumed = self.start.unwrap_or(self.pos);
+        self.buf.drain(..consumed);
+        self.pos -= consumed;
+        if let Some(start) = &mut self.start {
+            *start -= consumed;
+        }
+        Ok(())
+    }
+
+    /// Checks a value that starts outside of an item or the metadata
+    fn expect_item_or_value(&self) -> serde_json::Result<()> {
+        if self.depth == 2 && self.field == Field::Items && self.start.is_none() {
+            return Err(serde_json::Error::custom("expected list items to be objects"));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use futures::StreamExt;
+    use k8s_openapi::api::core::v1::ConfigMap;
+    use serde_json::json;
+
+    const LIST: &str = r#"{"kind":"ConfigMapList","apiVersion":"v1","metadata":{"resourceVersion":"123","continue":"abc"},"items":[{"metadata":{"name":"a","annotations":{"brackets":"}]\"{["}},"data":{"key":"value"}},{"metadata":{"name":"b"}}]}"#;
+
+    fn chunked(data: &'static str, size: usize) -> Body {
+        let chunks = data
+            .as_bytes()
+            .chunks(size)
+            .map(|chunk| Ok::<_, std::io::Error>(chunk.to_vec()))
+            .collect::<Vec<_>>();
+        Body::wrap_stream(futures::stream::iter(chunks))
+    }
+
+    #[tokio::test]
+    async fn streams_items() {
+        // Items are split across chunks at every possible position
+        for size in 1..LIST.len() {
+            let list = ListStream::<ConfigMap>::new(chunked(LIST, size))
+                .try_collect_list()
+                .await
+                .unwrap();
+            assert_eq!(list.metadata.resource_version.as_deref(), Some("123"));
+            assert_eq!(list.metadata.continue_.as_deref(), Some("abc"));
+            let names = list
+                .items
+                .iter()
+                .map(|cm| cm.metadata.name.as_deref().unwrap())
+                .collect::<Vec<_>>();
+            assert_eq!(names, ["a", "b"]);
+            assert_eq!(
+                list.items[0].metadata.annotations.as_ref().unwrap()["brackets"],
+                "}]\"{["
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn metadata_is_available_with_the_first_item() {
+        let mut stream = ListStream::<ConfigMap>::new(chunked(LIST, 7));
+        assert!(stream.metadata().is_none());
+        stream.next().await.unwrap().unwrap();
+        assert_eq!(
+            stream.metadata().unwrap().resource_version.as_deref(),
+            Some("123")
+        );
+    }
+
+    #[tokio::test]
+    async fn empty_and_null_items() {
+        for list in [
+            json!({"metadata": {}, "items": []}),
+            json!({"items": null, "metadata": {"resourceVersion": "1"}}),
+        ] {
+            let body = Body::from(list.to_string());
+            let list = ListStream::<ConfigMap>::new(body)
+                .try_collect_list()
+                .await
+                .unwrap();
+            assert!(list.items.is_empty());
+        }
+    }
+
+    #[tokio::test]
+    async fn truncated_list_fails() {
+        let truncated = &LIST[..LIST.len() - 2];
+        let mut stream = ListStream::<ConfigMap>::new(Body::from(truncated));
+        assert!(stream.next().await.unwrap().is_ok());
+        assert!(stream.next().await.unwrap().is_ok());
+        assert!(matches!(stream.next().await, Some(Err(Error::SerdeError(_)))));
+        assert!(stream.next().await.is_none());
+    }
+}
@@ -0,0 +1,248 @@
+//! Make every mutating request a server-side dry run.
+use futures::future::BoxFuture;
+use http::{request::Request, uri::PathAndQuery, Method, Uri};
+use thiserror::Error;
+use tower::{BoxError, Layer, Service};
+
+#[derive(Clone, Copy, Debug, Default)]
+/// Layer that makes every mutating request a server-side dry run
+///
+/// All `POST`, `PUT`, `PATCH`, and `DELETE` requests have `dryRun=All` added to their query,
+/// so that the apiserver validates them without persisting anything. This makes it possible to run a whole
+/// operator in an audit mode where it cannot change the cluster.
+///
+/// Requests that would have an effect even as a dry run are rejected with a [`DryRunError`] instead of being sent:
+/// `exec`, `attach`, and `portforward` requests, mutating requests to `proxy` subresources and to paths outside
+/// of the resource APIs, and requests that set a `dryRun` other than `All`.
+///
+/// ```no_run
+/// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+/// use kube::{client::{middleware::DryRunLayer, ClientBuilder}, Client, Config};
+///
+/// let config = Config::infer().await?;
+/// let client: Client = ClientBuilder::try_from(config)?.with_layer(&DryRunLayer).build();
+/// # Ok(())
+/// # }
+/// ```
+pub struct DryRunLayer;
+
+impl<S> Layer<S> for DryRunLayer {
+    type Service = DryRun<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        DryRun { inner }
+    }
+}
+
+#[derive(Clone)]
+/// Service that makes every mutating request a server-side dry run, see [`DryRunLayer`]
+pub struct DryRun<S> {
+    inner: S,
+}
+
+/// Error returned by [`DryRun`] for a request that cannot be made a dry run
+#[derive(Debug, Error)]
+#[error("{method} {path} cannot be a dry run")]
+pub struct DryRunError {
+    /// The method of the rejected request
+    pub method: Method,
+    /// The path of the rejected request
+    pub path: String,
+}
+
+impl<S, ReqBody> Service<Request<ReqBody>> for DryRun<S>
+where
+    S: Service<Request<ReqBody>>,
+    S::Future: Send + 'static,
+    S::Error: Into<BoxError>,
+{
+    type Error = BoxError;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+    type Response = S::Response;
+
+    fn poll_ready(&mut self, cx: &mut std::task::Context<'_>) -> std::task::Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, req: Request<ReqBody>) -> Self::Future {
+        let (mut parts, body) = req.into_parts();
+        let mutating = matches!(
+            parts.method,
+            Method::POST | Method::PUT | Method::PATCH | Method::DELETE
+        );
+        if !can_dry_run(&parts.uri, mutating) {
+            let err = DryRunError {
+                method: parts.method,
+                path: parts.uri.path().to_owned(),
+            };
+            return Box::pin(async move { Err(err.into()) });
+        }
+        if mutating {
+            parts.uri = set_dry_run(parts.uri);
+        }
+        let res = self.inner.call(Request::from_parts(parts, body));
+        Box::pin(async move { res.await.map_err(Into::into) })
+    }
+}
+
+/// Whether a request either has no effect, or only has an effect that the apiserver can dry run
+fn can_dry_run(uri: &Uri, mutating: bool) -> bool {
+    let conflicting = uri.query().map_or(false, |q| {
+        q.split('&')
+            .any(|p| p.starts_with("dryRun=") && p != "dryRun=All")
+    });
+    if conflicting {
+        return false;
+    }
+
+    let segments = uri
+        .path()
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>();
+    // The segments after `/api/{version}` or `/apis/{group}/{version}`
+    let rest = match segments.first() {
+        Some(&"api") => segments.get(2..),
+        Some(&"apis") => segments.get(3..),
+        _ => None,
+    };
+    let rest = match rest {
+        Some(rest) => rest,
+        // Non-resource paths, like `/version`, cannot be dry run
+        None => return !mutating,
+    };
+    let subresource = match rest {
+        ["namespaces", _, _, _, subresource, ..] => Some(*subresource),
+        [_, _, subresource, ..] => Some(*subresource),
+        _ => None,
+    };
+    match subresource {
+        // These run commands or open connections in containers, whatever their method
+        Some("exec" | "attach" | "portforward") => false,
+        // Proxied requests are passed on to the proxied server, which knows nothing about dry runs
+        Some("proxy") => !mutating,
+        _ => true,
+    }
+}
+
+fn set_dry_run(uri: Uri) -> Uri {
+    let path_and_query = match uri.path_and_query() {
+        Some(pq)
+            if pq
+                .query()
+                .map_or(false, |q| q.split('&').any(|p| p.starts_with("dryRun="))) =>
+        {
+            return uri;
+        }
+        Some(pq) => match pq.query() {
+            Some(q) => format!("{}?{q}&dryRun=All", pq.path()),
+            None => format!("{}?dryRun=All", pq.path()),
+        },
+        None => String::from("/?dryRun=All"),
+    };
+    let mut parts = uri.into_parts();
+    parts.path_and_query = Some(
+        path_and_query
+            .parse::<PathAndQuery>()
+            .expect("adding a query parameter keeps the path valid"),
+    );
+    Uri::from_parts(parts).expect("adding a query parameter keeps the uri valid")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use futures::pin_mut;
+    use http::{Request, Response};
+    use hyper::Body;
+    use tokio_test::assert_ready_ok;
+    use tower_test::mock;
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn dry_run_only_mutating_requests() {
+        let (mut service, handle) = mock::spawn_layer::<Request<Body>, Response<Body>, _>(DryRunLayer);
+        let spawned = tokio::spawn(async move {
+            pin_mut!(handle);
+            for expected in [
+                "/api/v1/namespaces/default/pods?fieldManager=kube&dryRun=All",
+                "/api/v1/namespaces/default/pods/foo?dryRun=All",
+                "/api/v1/namespaces/default/pods/foo?dryRun=All",
+                "/api/v1/namespaces/default/pods",
+            ] {
+                let (request, send) = handle.next_request().await.expect("service not called");
+                assert_eq!(request.uri().to_string(), expected);
+                send.send_response(Response::builder().body(Body::empty()).unwrap());
+            }
+        });
+
+        for (method, uri) in [
+            (Method::POST, "/api/v1/namespaces/default/pods?fieldManager=kube"),
+            (Method::DELETE, "/api/v1/namespaces/default/pods/foo"),
+            (Method::PATCH, "/api/v1/namespaces/default/pods/foo?dryRun=All"),
+            (Method::GET, "/api/v1/namespaces/default/pods"),
+        ] {
+            let req = Request::builder()
+                .method(method)
+                .uri(uri)
+                .body(Body::empty())
+                .unwrap();
+            assert_ready_ok!(service.poll_ready());
+            service.call(req).await.unwrap();
+        }
+        spawned.await.unwrap();
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn rejects_requests_that_cannot_be_dry_run() {
+        let (mut service, _handle) = mock::spawn_layer::<Request<Body>, Response<Body>, _>(DryRunLayer);
+        for (method, uri) in [
+            (Method::GET, "/api/v1/namespaces/default/pods/foo/exec?command=ls"),
+            (Method::POST, "/api/v1/namespaces/default/pods/foo/attach"),
+            (
+                Method::GET,
+                "/api/v1/namespaces/default/pods/foo/portforward?ports=80",
+            ),
+            (
+                Method::POST,
+                "/api/v1/namespaces/default/services/foo/proxy/reset",
+            ),
+            (Method::DELETE, "/api/v1/nodes/foo/proxy/logs"),
+            (Method::POST, "/custom/endpoint"),
+            (
+                Method::PATCH,
+                "/apis/apps/v1/namespaces/default/deployments/foo?dryRun=",
+            ),
+        ] {
+            let req = Request::builder()
+                .method(method)
+                .uri(uri)
+                .body(Body::empty())
+                .unwrap();
+            assert_ready_ok!(service.poll_ready());
+            let err = service.call(req).await.unwrap_err();
+            assert!(err.is::<DryRunError>(), "{uri} was not rejected");
+        }
+    }
+
+    #[test]
+    fn allows_reads_and_dry_runnable_subresources() {
+        for (method, uri) in [
+            (
+                Method::GET,
+                "/api/v1/namespaces/default/services/foo/proxy/metrics",
+            ),
+            (Method::GET, "/version"),
+            (Method::POST, "/api/v1/namespaces/default/pods/foo/eviction"),
+            (Method::PUT, "/api/v1/namespaces/foo/finalize"),
+            (
+                Method::PATCH,
+                "/apis/apps/v1/namespaces/default/deployments/foo/scale",
+            ),
+        ] {
+            let uri = uri.parse().unwrap();
+            let mutating = method != Method::GET;
+            assert!(can_dry_run(&uri, mutating), "{uri} was rejected");
+        }
+    }
+}
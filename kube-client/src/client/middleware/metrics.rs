@@ -0,0 +1,471 @@
+//! Record request metrics, like the rest-client metrics of client-go.
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use futures::future::BoxFuture;
+use http::{Request, Response, StatusCode};
+use kube_core::response::PriorityAndFairness;
+use tower::{BoxError, Layer, Service};
+
+/// Labels that identify the kind of request that a metric is recorded for
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RequestLabels {
+    /// The name of the [`Api`](crate::Api) method, like `list` or `patch_status`, or the lowercase HTTP method for other requests
+    pub verb: String,
+    /// The API group of the requested resource, empty for the core group
+    pub group: String,
+    /// The plural name of the requested resource, like `pods`, empty for non-resource requests like `/version`
+    pub resource: String,
+}
+
+impl RequestLabels {
+    pub(crate) fn from_request<B>(req: &Request<B>) -> Self {
+        let verb = match req.extensions().get::<&'static str>() {
+            Some(name) => (*name).to_owned(),
+            None => req.method().as_str().to_lowercase(),
+        };
+        let segments = req
+            .uri()
+            .path()
+            .split('/')
+            .filter(|s| !s.is_empty())
+            .collect::<Vec<_>>();
+        // `/api/{version}/...` for the core group, `/apis/{group}/{version}/...` for the others
+        let (group, rest) = match segments.iter().position(|s| *s == "api" || *s == "apis") {
+            Some(i) if segments[i] == "api" => ("", segments.get(i + 2..)),
+            Some(i) => (
+                segments.get(i + 1).copied().unwrap_or_default(),
+                segments.get(i + 3..),
+            ),
+            None => ("", None),
+        };
+        let resource = match rest.unwrap_or_default() {
+            ["namespaces", _, resource, ..] => resource,
+            [resource, ..] => resource,
+            [] => "",
+        };
+        Self {
+            verb,
+            group: group.to_owned(),
+            resource: resource.to_owned(),
+        }
+    }
+}
+
+/// Receives the metrics that are recorded by [`Metrics`]
+///
+/// Implementations are expected to be cheap, as they are called for every request.
+pub trait MetricsRecorder: Send + Sync + 'static {
+    /// A request was sent
+    fn request_started(&self, labels: &RequestLabels);
+
+    /// A request that was [started](MetricsRecorder::request_started) received a response after `latency`
+    ///
+    /// `status` is `None` when no response was received, because the request failed or was cancelled.
+    /// The latency of streaming requests, like watches, is the time until the response headers were received.
+    fn request_finished(&self, labels: &RequestLabels, status: Option<StatusCode>, latency: Duration);
+
+    /// A request that was [started](MetricsRecorder::request_started) received a response with the
+    /// API Priority and Fairness metadata `apf`, before it [finished](MetricsRecorder::request_finished)
+    ///
+    /// This can be used to tell whether requests are throttled by the client or rejected by the apiserver.
+    /// Does nothing by default.
+    fn priority_and_fairness(&self, _labels: &RequestLabels, _apf: &PriorityAndFairness) {}
+}
+
+/// Layer that applies [`Metrics`], which records the count, latency, and number of in-flight requests to a [`MetricsRecorder`].
+///
+/// Add it on top of the default [`ClientBuilder`](crate::client::ClientBuilder) stack to record all requests of a [`Client`](crate::Client):
+///
+/// ```no_run
+/// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+/// use kube::{client::{middleware::{MetricsLayer, MetricsRecorder, RequestLabels}, ClientBuilder}, Client, Config};
+/// use std::time::Duration;
+///
+/// struct LogRecorder;
+/// impl MetricsRecorder for LogRecorder {
+///     fn request_started(&self, _labels: &RequestLabels) {}
+///
+///     fn request_finished(&self, labels: &RequestLabels, status: Option<http::StatusCode>, latency: Duration) {
+///         println!("{} {}: {status:?} after {latency:?}", labels.verb, labels.resource);
+///     }
+/// }
+///
+/// let config = Config::infer().await?;
+/// let client: Client = ClientBuilder::try_from(config)?
+///     .with_layer(&MetricsLayer::new(LogRecorder))
+///     .build();
+/// # Ok(())
+/// # }
+/// ```
+pub struct MetricsLayer<R> {
+    recorder: Arc<R>,
+}
+
+impl<R> MetricsLayer<R> {
+    /// Record the metrics of all requests to `recorder`
+    pub fn new(recorder: R) -> Self {
+        Self {
+            recorder: Arc::new(recorder),
+        }
+    }
+}
+
+impl<R> Clone for MetricsLayer<R> {
+    fn clone(&self) -> Self {
+        Self {
+            recorder: self.recorder.clone(),
+        }
+    }
+}
+
+impl<S, R> Layer<S> for MetricsLayer<R> {
+    type Service = Metrics<S, R>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Metrics {
+            recorder: self.recorder.clone(),
+            inner,
+        }
+    }
+}
+
+/// Service that records request metrics, see [`MetricsLayer`].
+pub struct Metrics<S, R> {
+    recorder: Arc<R>,
+    inner: S,
+}
+
+impl<S: Clone, R> Clone for Metrics<S, R> {
+    fn clone(&self) -> Self {
+        Self {
+            recorder: self.recorder.clone(),
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+/// Reports a request as finished when dropped, so that cancelled requests are not counted as in-flight forever
+struct InFlight<R: MetricsRecorder> {
+    recorder: Arc<R>,
+    labels: RequestLabels,
+    started: Instant,
+    status: Option<StatusCode>,
+}
+
+impl<R: MetricsRecorder> Drop for InFlight<R> {
+    fn drop(&mut self) {
+        self.recorder
+            .request_finished(&self.labels, self.status, self.started.elapsed());
+    }
+}
+
+impl<S, R, ReqBody, ResBody> Service<Request<ReqBody>> for Metrics<S, R>
+where
+    S: Service<Request<ReqBody>, Response = Response<ResBody>>,
+    S::Future: Send + 'static,
+    S::Error: Into<BoxError>,
+    R: MetricsRecorder,
+{
+    type Error = BoxError;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+    type Response = Response<ResBody>;
+
+    fn poll_ready(&mut self, cx: &mut std::task::Context<'_>) -> std::task::Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, req: Request<ReqBody>) -> Self::Future {
+        let labels = RequestLabels::from_request(&req);
+        self.recorder.request_started(&labels);
+        let in_flight = InFlight {
+            recorder: self.recorder.clone(),
+            labels,
+            started: Instant::now(),
+            status: None,
+        };
+        let res = self.inner.call(req);
+        Box::pin(async move {
+            // Move the whole guard into the future, rather than only the captured field
+            let mut in_flight = in_flight;
+            let res = res.await.map_err(Into::into)?;
+            in_flight.status = Some(res.status());
+            in_flight
+                .recorder
+                .priority_and_fairness(&in_flight.labels, &super::priority_and_fairness(&res));
+            Ok(res)
+        })
+    }
+}
+
+#[cfg(feature = "prometheus")]
+pub use self::prometheus_recorder::PrometheusRecorder;
+
+#[cfg(feature = "prometheus")]
+mod prometheus_recorder {
+    use std::time::Duration;
+
+    use http::StatusCode;
+    use kube_core::response::PriorityAndFairness;
+    use prometheus::{HistogramOpts, HistogramVec, IntCounterVec, IntGaugeVec, Opts, Registry};
+
+    use super::{MetricsRecorder, RequestLabels};
+
+    /// [`MetricsRecorder`] that exports the metrics to a Prometheus [`Registry`]
+    ///
+    /// Registers the following metrics, labeled by `verb`, `group`, and `resource`:
+    ///
+    /// - `kube_client_requests_total`: counter of finished requests, also labeled by the response `code` (or `<error>`)
+    /// - `kube_client_request_duration_seconds`: histogram of request latencies
+    /// - `kube_client_requests_in_flight`: gauge of requests that are waiting for a response
+    /// - `kube_client_rate_limiter_duration_seconds`: histogram of the time that requests waited for the client rate limit
+    /// - `kube_client_retry_after_seconds`: histogram of the `Retry-After` delays of requests that the apiserver rejected
+    #[cfg_attr(docsrs, doc(cfg(feature = "prometheus")))]
+    #[derive(Clone)]
+    pub struct PrometheusRecorder {
+        requests: IntCounterVec,
+        duration: HistogramVec,
+        in_flight: IntGaugeVec,
+        rate_limiter: HistogramVec,
+        retry_after: HistogramVec,
+    }
+
+    impl PrometheusRecorder {
+        /// Create the metrics and register them to `registry`
+        pub fn new(registry: &Registry) -> Result<Self, prometheus::Error> {
+            const LABELS: [&str; 3] = ["verb", "group", "resource"];
+            let requests = IntCounterVec::new(
+                Opts::new(
+                    "kube_client_requests_total",
+                    "Number of finished requests to the apiserver",
+                ),
+                &["verb", "group", "resource", "code"],
+            )?;
+            let duration = HistogramVec::new(
+                HistogramOpts::new(
+                    "kube_client_request_duration_seconds",
+                    "Latency of requests to the apiserver",
+                ),
+                &LABELS,
+            )?;
+            let in_flight = IntGaugeVec::new(
+                Opts::new(
+                    "kube_client_requests_in_flight",
+                    "Number of requests to the apiserver that are waiting for a response",
+                ),
+                &LABELS,
+            )?;
+            let rate_limiter = HistogramVec::new(
+                HistogramOpts::new(
+                    "kube_client_rate_limiter_duration_seconds",
+                    "Time that requests to the apiserver waited for the client rate limit",
+                ),
+                &LABELS,
+            )?;
+            let retry_after = HistogramVec::new(
+                HistogramOpts::new(
+                    "kube_client_retry_after_seconds",
+                    "Retry-After delays of requests that were rejected by the apiserver",
+                ),
+                &LABELS,
+            )?;
+            registry.register(Box::new(requests.clone()))?;
+            registry.register(Box::new(duration.clone()))?;
+            registry.register(Box::new(in_flight.clone()))?;
+            registry.register(Box::new(rate_limiter.clone()))?;
+            registry.register(Box::new(retry_after.clone()))?;
+            Ok(Self {
+                requests,
+                duration,
+                in_flight,
+                rate_limiter,
+                retry_after,
+            })
+        }
+    }
+
+    impl MetricsRecorder for PrometheusRecorder {
+        fn request_started(&self, labels: &RequestLabels) {
+            self.in_flight
+                .with_label_values(&[&labels.verb, &labels.group, &labels.resource])
+                .inc();
+        }
+
+        fn request_finished(&self, labels: &RequestLabels, status: Option<StatusCode>, latency: Duration) {
+            let values = [labels.verb.as_str(), &labels.group, &labels.resource];
+            self.in_flight.with_label_values(&values).dec();
+            self.duration
+                .with_label_values(&values)
+                .observe(latency.as_secs_f64());
+            let code = status.map_or_else(|| "<error>".to_owned(), |status| status.as_u16().to_string());
+            self.requests
+                .with_label_values(&[&labels.verb, &labels.group, &labels.resource, &code])
+                .inc();
+        }
+
+        fn priority_and_fairness(&self, labels: &RequestLabels, apf: &PriorityAndFairness) {
+            let values = [labels.verb.as_str(), &labels.group, &labels.resource];
+            if let Some(wait) = apf.rate_limit_wait {
+                self.rate_limiter
+                    .with_label_values(&values)
+                    .observe(wait.as_secs_f64());
+            }
+            if let Some(retry_after) = apf.retry_after {
+                self.retry_after
+                    .with_label_values(&values)
+                    .observe(retry_after.as_secs_f64());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::Mutex;
+
+    use futures::pin_mut;
+    use hyper::Body;
+    use tower_test::mock;
+
+    #[derive(Default)]
+    struct TestRecorder {
+        events: Mutex<Vec<(String, Option<StatusCode>)>>,
+    }
+
+    impl MetricsRecorder for TestRecorder {
+        fn request_started(&self, labels: &RequestLabels) {
+            self.events
+                .lock()
+                .unwrap()
+                .push((format!("start {}", labels.verb), None));
+        }
+
+        fn request_finished(&self, labels: &RequestLabels, status: Option<StatusCode>, _latency: Duration) {
+            self.events
+                .lock()
+                .unwrap()
+                .push((format!("finish {}", labels.verb), status));
+        }
+
+        fn priority_and_fairness(&self, labels: &RequestLabels, apf: &PriorityAndFairness) {
+            let level = apf.priority_level_uid.as_deref().unwrap_or_default();
+            self.events
+                .lock()
+                .unwrap()
+                .push((format!("apf {} {level}", labels.verb), None));
+        }
+    }
+
+    fn labels(uri: &str) -> (String, String, String) {
+        let mut req = Request::get(uri).body(()).unwrap();
+        req.extensions_mut().insert("list");
+        let labels = RequestLabels::from_request(&req);
+        (labels.verb, labels.group, labels.resource)
+    }
+
+    #[test]
+    fn labels_from_path() {
+        let owned =
+            |(verb, group, resource): (&str, &str, &str)| (verb.into(), group.into(), resource.into());
+        assert_eq!(
+            labels("/api/v1/namespaces/ns/pods?limit=1"),
+            owned(("list", "", "pods"))
+        );
+        assert_eq!(labels("/api/v1/namespaces/ns"), owned(("list", "", "namespaces")));
+        assert_eq!(
+            labels("/apis/apps/v1/namespaces/ns/deployments/name/scale"),
+            owned(("list", "apps", "deployments"))
+        );
+        assert_eq!(
+            labels("/apis/kube.rs/v1/documents"),
+            owned(("list", "kube.rs", "documents"))
+        );
+        assert_eq!(labels("/version"), owned(("list", "", "")));
+        let req = Request::post("/api/v1/namespaces/ns/pods").body(()).unwrap();
+        assert_eq!(RequestLabels::from_request(&req).verb, "post");
+    }
+
+    #[tokio::test]
+    async fn records_requests() {
+        let (service, handle) = mock::pair::<Request<Body>, Response<Body>>();
+        let recorder = Arc::new(TestRecorder::default());
+        let mut service = Metrics {
+            recorder: recorder.clone(),
+            inner: service,
+        };
+        let spawned = tokio::spawn(async move {
+            pin_mut!(handle);
+            let (_, send) = handle.next_request().await.expect("service not called");
+            send.send_response(
+                Response::builder()
+                    .status(404)
+                    .header("X-Kubernetes-PF-PriorityLevel-UID", "level")
+                    .body(Body::empty())
+                    .unwrap(),
+            );
+            let (_, send) = handle.next_request().await.expect("service not called");
+            send.send_error("connection reset");
+        });
+
+        for _ in 0..2 {
+            let mut req = Request::get("/api/v1/namespaces/ns/pods/name")
+                .body(Body::empty())
+                .unwrap();
+            req.extensions_mut().insert("get");
+            futures::future::poll_fn(|cx| service.poll_ready(cx))
+                .await
+                .unwrap();
+            let _ = service.call(req).await;
+        }
+        spawned.await.unwrap();
+        let expected = [
+            ("start get".to_owned(), None),
+            ("apf get level".to_owned(), None),
+            ("finish get".to_owned(), Some(StatusCode::NOT_FOUND)),
+            ("start get".to_owned(), None),
+            ("finish get".to_owned(), None),
+        ];
+        assert_eq!(*recorder.events.lock().unwrap(), expected);
+    }
+
+    #[cfg(feature = "prometheus")]
+    #[test]
+    fn prometheus_recorder() {
+        let registry = prometheus::Registry::new();
+        let recorder = PrometheusRecorder::new(&registry).unwrap();
+        let labels = RequestLabels {
+            verb: "list".into(),
+            group: "apps".into(),
+            resource: "deployments".into(),
+        };
+        recorder.request_started(&labels);
+        recorder.request_finished(&labels, Some(StatusCode::OK), Duration::from_millis(10));
+        let apf = PriorityAndFairness {
+            rate_limit_wait: Some(Duration::from_millis(100)),
+            ..PriorityAndFairness::default()
+        };
+        recorder.priority_and_fairness(&labels, &apf);
+
+        let families = registry.gather();
+        let family = |name: &str| families.iter().find(|f| f.get_name() == name).unwrap();
+        let requests = &family("kube_client_requests_total").get_metric()[0];
+        assert_eq!(requests.get_counter().get_value(), 1.0);
+        assert!(requests
+            .get_label()
+            .iter()
+            .any(|label| label.get_name() == "code" && label.get_value() == "200"));
+        let in_flight = &family("kube_client_requests_in_flight").get_metric()[0];
+        assert_eq!(in_flight.get_gauge().get_value(), 0.0);
+        let duration = &family("kube_client_request_duration_seconds").get_metric()[0];
+        assert_eq!(duration.get_histogram().get_sample_count(), 1);
+        let rate_limiter = &family("kube_client_rate_limiter_duration_seconds").get_metric()[0];
+        assert_eq!(rate_limiter.get_histogram().get_sample_sum(), 0.1);
+        assert!(!families
+            .iter()
+            .any(|f| f.get_name() == "kube_client_retry_after_seconds"));
+    }
+}
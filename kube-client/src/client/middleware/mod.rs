@@ -1,12 +1,75 @@
 //! Middleware types returned from `ConfigExt` methods.
+use http::{request::Parts, Extensions, Request, Response};
+use hyper::Body;
+use kube_core::response::PriorityAndFairness;
 use tower::{filter::AsyncFilterLayer, util::Either, Layer};
 pub(crate) use tower_http::auth::AddAuthorizationLayer;
 
 mod base_uri;
+mod dry_run;
 mod extra_headers;
+mod metrics;
+mod rate_limit;
+#[cfg(feature = "gzip")] mod request_compression;
+mod request_timeout;
+mod retry;
+mod trace_bodies;
 
 pub use base_uri::{BaseUri, BaseUriLayer};
+pub use dry_run::{DryRun, DryRunError, DryRunLayer};
 pub use extra_headers::{ExtraHeaders, ExtraHeadersLayer};
+#[cfg(feature = "prometheus")] pub use metrics::PrometheusRecorder;
+pub use metrics::{Metrics, MetricsLayer, MetricsRecorder, RequestLabels};
+pub use rate_limit::{RateLimit, RateLimitLayer};
+#[cfg(feature = "gzip")]
+pub use request_compression::{RequestCompression, RequestCompressionLayer};
+pub use request_timeout::{
+    RequestTimeout, RequestTimeoutLayer, ResponseFuture as RequestTimeoutFuture, TimeoutBody,
+};
+pub use retry::{Retry, RetryAttempts, RetryError, RetryLayer};
+pub use trace_bodies::{TraceBodies, TraceBodiesLayer};
+
+/// The [`PriorityAndFairness`] extension of a response, where middleware record how long the request waited
+pub(crate) fn waits_mut<B>(res: &mut Response<B>) -> &mut PriorityAndFairness {
+    if res.extensions().get::<PriorityAndFairness>().is_none() {
+        res.extensions_mut().insert(PriorityAndFairness::default());
+    }
+    res.extensions_mut()
+        .get_mut::<PriorityAndFairness>()
+        .expect("extension was inserted")
+}
+
+/// The [`PriorityAndFairness`] metadata of a response, along with the waits recorded by middleware
+pub(crate) fn priority_and_fairness<B>(res: &Response<B>) -> PriorityAndFairness {
+    let waits = res.extensions().get::<PriorityAndFairness>();
+    PriorityAndFairness {
+        rate_limit_wait: waits.and_then(|waits| waits.rate_limit_wait),
+        retry_wait: waits.and_then(|waits| waits.retry_wait),
+        ..PriorityAndFairness::from_headers(res.status(), res.headers())
+    }
+}
+
+/// Rebuilds a request from `parts`, for middleware that send a request more than once
+///
+/// `http::Extensions` cannot be cloned, so only the extensions that kube sets on requests are copied,
+/// which is the name of the request set by [`Api`](crate::Api) methods.
+pub(crate) fn request_from_parts(parts: &Parts, body: Body) -> Request<Body> {
+    let mut req = Request::new(body);
+    *req.method_mut() = parts.method.clone();
+    *req.uri_mut() = parts.uri.clone();
+    *req.version_mut() = parts.version;
+    *req.headers_mut() = parts.headers.clone();
+    *req.extensions_mut() = clone_extensions(&parts.extensions);
+    req
+}
+
+fn clone_extensions(extensions: &Extensions) -> Extensions {
+    let mut cloned = Extensions::new();
+    if let Some(name) = extensions.get::<&'static str>() {
+        cloned.insert(*name);
+    }
+    cloned
+}
 
 use super::auth::RefreshableToken;
 /// Layer to set up `Authorization` header depending on the config.
@@ -0,0 +1,137 @@
+//! Limit the rate of requests with a token bucket.
+use std::sync::{Arc, Mutex};
+
+use futures::future::BoxFuture;
+use http::{Request, Response};
+use tokio::time::{Duration, Instant};
+use tower::{BoxError, Layer, Service};
+
+/// Layer that applies [`RateLimit`], which limits the rate of requests, like the `QPS` and `Burst` of client-go.
+///
+/// Up to `burst` requests are sent right away, after which requests are delayed so that no more than `qps`
+/// requests are sent per second on average. All services created by the same layer share their limit.
+/// The time that a request waited is recorded as the [`PriorityAndFairness::rate_limit_wait`](kube_core::PriorityAndFairness::rate_limit_wait)
+/// of its response.
+/// This is added to the default stack when [`Config::rate_limit`](crate::Config::rate_limit) is set.
+#[derive(Debug, Clone)]
+pub struct RateLimitLayer {
+    qps: f64,
+    burst: u32,
+    bucket: Arc<Mutex<Bucket>>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    /// Negative when requests are waiting for tokens
+    tokens: f64,
+    updated: Instant,
+}
+
+impl RateLimitLayer {
+    /// Allow `qps` requests per second on average, and bursts of up to `burst` requests
+    ///
+    /// # Panics
+    ///
+    /// Panics if `qps` is not positive.
+    pub fn new(qps: f64, burst: u32) -> Self {
+        assert!(qps > 0.0, "qps must be positive");
+        Self {
+            qps,
+            burst,
+            bucket: Arc::new(Mutex::new(Bucket {
+                tokens: f64::from(burst),
+                updated: Instant::now(),
+            })),
+        }
+    }
+
+    /// Takes a token, and returns how long to wait until it is available
+    fn acquire(&self) -> Duration {
+        let mut bucket = self
+            .bucket
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        let now = Instant::now();
+        let refilled = bucket.tokens + now.duration_since(bucket.updated).as_secs_f64() * self.qps;
+        bucket.tokens = refilled.min(f64::from(self.burst.max(1))) - 1.0;
+        bucket.updated = now;
+        if bucket.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-bucket.tokens / self.qps)
+        }
+    }
+}
+
+impl<S> Layer<S> for RateLimitLayer {
+    type Service = RateLimit<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RateLimit {
+            limit: self.clone(),
+            inner,
+        }
+    }
+}
+
+/// Service that limits the rate of requests, see [`RateLimitLayer`].
+#[derive(Debug, Clone)]
+pub struct RateLimit<S> {
+    limit: RateLimitLayer,
+    inner: S,
+}
+
+impl<S, ReqBody, ResBody> Service<Request<ReqBody>> for RateLimit<S>
+where
+    S: Service<Request<ReqBody>, Response = Response<ResBody>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    S::Error: Into<BoxError>,
+    ReqBody: Send + 'static,
+{
+    type Error = BoxError;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+    type Response = Response<ResBody>;
+
+    fn poll_ready(&mut self, cx: &mut std::task::Context<'_>) -> std::task::Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, req: Request<ReqBody>) -> Self::Future {
+        // Use the service that was driven to readiness, and leave a fresh clone for the next call
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let wait = self.limit.acquire();
+        Box::pin(async move {
+            if wait.is_zero() {
+                return inner.call(req).await.map_err(Into::into);
+            }
+            tracing::debug!(?wait, "waiting for the client rate limit");
+            tokio::time::sleep(wait).await;
+            let mut res = inner.call(req).await.map_err(Into::into)?;
+            super::waits_mut(&mut res).rate_limit_wait = Some(wait);
+            Ok(res)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn delays_requests_after_burst() {
+        let limit = RateLimitLayer::new(2.0, 3);
+        for _ in 0..3 {
+            assert_eq!(limit.acquire(), Duration::ZERO);
+        }
+        assert_eq!(limit.acquire(), Duration::from_millis(500));
+        assert_eq!(limit.acquire(), Duration::from_millis(1000));
+
+        // The bucket refills over time, but not beyond the burst
+        tokio::time::advance(Duration::from_secs(60)).await;
+        for _ in 0..3 {
+            assert_eq!(limit.acquire(), Duration::ZERO);
+        }
+        assert_eq!(limit.acquire(), Duration::from_millis(500));
+    }
+}
@@ -0,0 +1,229 @@
+//! Compress large request bodies with gzip.
+use std::{
+    io::Write,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+use flate2::{write::GzEncoder, Compression};
+use futures::future::BoxFuture;
+use http::{header, HeaderValue, Request, Response, StatusCode};
+use http_body::Body as _;
+use hyper::Body;
+use tower::{BoxError, Layer, Service, ServiceExt};
+
+use super::request_from_parts;
+
+/// Layer that applies [`RequestCompression`], which compresses request bodies with gzip.
+///
+/// Only bodies of known size of at least `min_size` bytes are compressed, and only when they do not have a
+/// `Content-Encoding` already. This is added to the default stack when [`Config::request_compression`](crate::Config::request_compression) is set.
+///
+/// A server that does not accept compressed requests responds with `415 Unsupported Media Type`. The request
+/// is then sent again uncompressed, with only the extensions that kube sets on requests, and requests through
+/// this layer are no longer compressed. Other errors, like a `400 Bad Request` for an invalid object, are
+/// returned as they are.
+#[derive(Debug, Clone)]
+pub struct RequestCompressionLayer {
+    min_size: usize,
+    supported: Arc<AtomicBool>,
+}
+
+impl RequestCompressionLayer {
+    /// Compress request bodies that are at least `min_size` bytes
+    pub fn new(min_size: usize) -> Self {
+        Self {
+            min_size,
+            supported: Arc::new(AtomicBool::new(true)),
+        }
+    }
+}
+
+impl<S> Layer<S> for RequestCompressionLayer {
+    type Service = RequestCompression<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RequestCompression {
+            min_size: self.min_size,
+            supported: self.supported.clone(),
+            inner,
+        }
+    }
+}
+
+/// Service that compresses request bodies, see [`RequestCompressionLayer`].
+#[derive(Debug, Clone)]
+pub struct RequestCompression<S> {
+    min_size: usize,
+    supported: Arc<AtomicBool>,
+    inner: S,
+}
+
+impl<S> RequestCompression<S> {
+    fn should_compress(&self, req: &Request<Body>) -> bool {
+        self.supported.load(Ordering::Relaxed)
+            && !req.headers().contains_key(header::CONTENT_ENCODING)
+            && req
+                .body()
+                .size_hint()
+                .exact()
+                .map_or(false, |size| size >= self.min_size as u64)
+    }
+}
+
+impl<S, ResBody> Service<Request<Body>> for RequestCompression<S>
+where
+    S: Service<Request<Body>, Response = Response<ResBody>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    S::Error: Into<BoxError>,
+    ResBody: Send + 'static,
+{
+    type Error = BoxError;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+    type Response = Response<ResBody>;
+
+    fn poll_ready(&mut self, cx: &mut std::task::Context<'_>) -> std::task::Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        // Use the service that was driven to readiness, and leave a fresh clone for the next call
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        if !self.should_compress(&req) {
+            let res = inner.call(req);
+            return Box::pin(async move { res.await.map_err(Into::into) });
+        }
+
+        let supported = self.supported.clone();
+        Box::pin(async move {
+            let (mut parts, body) = req.into_parts();
+            let body = hyper::body::to_bytes(body).await?;
+            let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+            encoder.write_all(&body)?;
+            let compressed = encoder.finish()?;
+
+            parts.headers.remove(header::CONTENT_LENGTH);
+            let uncompressed = request_from_parts(&parts, Body::from(body));
+
+            parts
+                .headers
+                .insert(header::CONTENT_ENCODING, HeaderValue::from_static("gzip"));
+            let res = inner
+                .call(Request::from_parts(parts, Body::from(compressed)))
+                .await
+                .map_err(Into::into)?;
+            if res.status() != StatusCode::UNSUPPORTED_MEDIA_TYPE {
+                return Ok(res);
+            }
+            tracing::debug!("compressed request was rejected, sending requests uncompressed");
+            supported.store(false, Ordering::Relaxed);
+            inner.oneshot(uncompressed).await.map_err(Into::into)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::io::Read;
+
+    use flate2::read::GzDecoder;
+    use futures::pin_mut;
+    use tower_test::mock;
+
+    fn decompress(body: &[u8]) -> Vec<u8> {
+        let mut decompressed = Vec::new();
+        GzDecoder::new(body).read_to_end(&mut decompressed).unwrap();
+        decompressed
+    }
+
+    #[tokio::test]
+    async fn compresses_large_bodies() {
+        let (service, handle) = mock::pair::<Request<Body>, Response<Body>>();
+        let mut service = RequestCompressionLayer::new(10).layer(service);
+        let spawned = tokio::spawn(async move {
+            pin_mut!(handle);
+            let (req, send) = handle.next_request().await.expect("service not called");
+            assert_eq!(req.headers()[header::CONTENT_ENCODING], "gzip");
+            let body = hyper::body::to_bytes(req.into_body()).await.unwrap();
+            assert_eq!(decompress(&body), b"a large enough body");
+            send.send_response(Response::new(Body::empty()));
+
+            let (req, send) = handle.next_request().await.expect("service not called");
+            assert!(!req.headers().contains_key(header::CONTENT_ENCODING));
+            send.send_response(Response::new(Body::empty()));
+        });
+
+        service.ready().await.unwrap();
+        service
+            .call(Request::new(Body::from("a large enough body")))
+            .await
+            .unwrap();
+        service.ready().await.unwrap();
+        service.call(Request::new(Body::from("small"))).await.unwrap();
+        spawned.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn falls_back_when_unsupported() {
+        let (service, handle) = mock::pair::<Request<Body>, Response<Body>>();
+        let mut service = RequestCompressionLayer::new(0).layer(service);
+        let spawned = tokio::spawn(async move {
+            pin_mut!(handle);
+            let (req, send) = handle.next_request().await.expect("service not called");
+            assert_eq!(req.headers()[header::CONTENT_ENCODING], "gzip");
+            let unsupported = StatusCode::UNSUPPORTED_MEDIA_TYPE;
+            send.send_response(
+                Response::builder()
+                    .status(unsupported)
+                    .body(Body::empty())
+                    .unwrap(),
+            );
+
+            // The rejected request is sent again, and later requests are not compressed
+            for _ in 0..2 {
+                let (req, send) = handle.next_request().await.expect("service not called");
+                assert!(!req.headers().contains_key(header::CONTENT_ENCODING));
+                assert_eq!(req.extensions().get::<&'static str>(), Some(&"create"));
+                assert_eq!(hyper::body::to_bytes(req.into_body()).await.unwrap(), "body");
+                send.send_response(Response::new(Body::empty()));
+            }
+        });
+
+        for _ in 0..2 {
+            service.ready().await.unwrap();
+            let mut req = Request::new(Body::from("body"));
+            req.extensions_mut().insert("create");
+            let res = service.call(req).await.unwrap();
+            assert_eq!(res.status(), StatusCode::OK);
+        }
+        spawned.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn keeps_compressing_after_bad_requests() {
+        let (service, handle) = mock::pair::<Request<Body>, Response<Body>>();
+        let mut service = RequestCompressionLayer::new(0).layer(service);
+        let spawned = tokio::spawn(async move {
+            pin_mut!(handle);
+            // A validation error is not sent again, and later requests are still compressed
+            for status in [StatusCode::BAD_REQUEST, StatusCode::OK] {
+                let (req, send) = handle.next_request().await.expect("service not called");
+                assert_eq!(req.headers()[header::CONTENT_ENCODING], "gzip");
+                send.send_response(Response::builder().status(status).body(Body::empty()).unwrap());
+            }
+        });
+
+        let statuses = [StatusCode::BAD_REQUEST, StatusCode::OK];
+        for status in statuses {
+            service.ready().await.unwrap();
+            let res = service.call(Request::new(Body::from("body"))).await.unwrap();
+            assert_eq!(res.status(), status);
+        }
+        spawned.await.unwrap();
+    }
+}
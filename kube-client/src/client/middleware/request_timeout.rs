@@ -0,0 +1,238 @@
+//! Limit the total time that a request may take, including its response body.
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use http::{HeaderMap, Request, Response};
+use http_body::Body;
+use pin_project::pin_project;
+use tokio::time::Sleep;
+use tower::{timeout::error::Elapsed, BoxError, Layer, Service};
+
+/// Layer that applies [`RequestTimeout`], which fails requests that do not complete within a timeout.
+///
+/// The timeout covers the whole request, until the response body has been read. Watches and followed logs
+/// are exempt, because they are expected to stream for a long time. Their duration is bounded by the server
+/// (see [`WatchParams::timeout`](crate::api::WatchParams::timeout)) and by [`Config::read_timeout`](crate::Config::read_timeout) instead.
+#[derive(Debug, Clone)]
+pub struct RequestTimeoutLayer {
+    pub(crate) timeout: Option<Duration>,
+}
+
+impl RequestTimeoutLayer {
+    /// Fail requests that take longer than `timeout`
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            timeout: Some(timeout),
+        }
+    }
+}
+
+impl<S> Layer<S> for RequestTimeoutLayer {
+    type Service = RequestTimeout<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RequestTimeout {
+            timeout: self.timeout,
+            inner,
+        }
+    }
+}
+
+/// Service that limits the duration of requests, see [`RequestTimeoutLayer`].
+#[derive(Debug, Clone)]
+pub struct RequestTimeout<S> {
+    timeout: Option<Duration>,
+    inner: S,
+}
+
+/// Whether the response to `uri` is expected to stream indefinitely
+fn is_streaming(uri: &http::Uri) -> bool {
+    uri.query().map_or(false, |query| {
+        query
+            .split('&')
+            .any(|pair| pair == "watch=true" || pair == "watch=1" || pair == "follow=true")
+    })
+}
+
+impl<S, ReqBody, ResBody> Service<Request<ReqBody>> for RequestTimeout<S>
+where
+    S: Service<Request<ReqBody>, Response = Response<ResBody>>,
+    S::Error: Into<BoxError>,
+{
+    type Error = BoxError;
+    type Future = ResponseFuture<S::Future>;
+    type Response = Response<TimeoutBody<ResBody>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, req: Request<ReqBody>) -> Self::Future {
+        let sleep = match self.timeout {
+            Some(timeout) if !is_streaming(req.uri()) => Some(Box::pin(tokio::time::sleep(timeout))),
+            _ => None,
+        };
+        ResponseFuture {
+            inner: self.inner.call(req),
+            sleep,
+        }
+    }
+}
+
+fn poll_elapsed(sleep: &mut Option<Pin<Box<Sleep>>>, cx: &mut Context<'_>) -> bool {
+    sleep
+        .as_mut()
+        .map_or(false, |sleep| sleep.as_mut().poll(cx).is_ready())
+}
+
+/// Future returned by [`RequestTimeout`]
+#[pin_project]
+#[derive(Debug)]
+pub struct ResponseFuture<F> {
+    #[pin]
+    inner: F,
+    sleep: Option<Pin<Box<Sleep>>>,
+}
+
+impl<F, B, E> Future for ResponseFuture<F>
+where
+    F: Future<Output = Result<Response<B>, E>>,
+    E: Into<BoxError>,
+{
+    type Output = Result<Response<TimeoutBody<B>>, BoxError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        if let Poll::Ready(res) = this.inner.poll(cx) {
+            let sleep = this.sleep.take();
+            return Poll::Ready(
+                res.map(|res| res.map(|inner| TimeoutBody { inner, sleep }))
+                    .map_err(Into::into),
+            );
+        }
+        if poll_elapsed(this.sleep, cx) {
+            return Poll::Ready(Err(Elapsed::new().into()));
+        }
+        Poll::Pending
+    }
+}
+
+/// Response body that fails once the timeout of its request has elapsed
+#[pin_project]
+#[derive(Debug)]
+pub struct TimeoutBody<B> {
+    #[pin]
+    inner: B,
+    sleep: Option<Pin<Box<Sleep>>>,
+}
+
+impl<B> Body for TimeoutBody<B>
+where
+    B: Body,
+    B::Error: Into<BoxError>,
+{
+    type Data = B::Data;
+    type Error = BoxError;
+
+    fn poll_data(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Self::Data, Self::Error>>> {
+        let this = self.project();
+        if let Poll::Ready(data) = this.inner.poll_data(cx) {
+            return Poll::Ready(data.map(|data| data.map_err(Into::into)));
+        }
+        if poll_elapsed(this.sleep, cx) {
+            return Poll::Ready(Some(Err(Elapsed::new().into())));
+        }
+        Poll::Pending
+    }
+
+    fn poll_trailers(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<Option<HeaderMap>, Self::Error>> {
+        let this = self.project();
+        if let Poll::Ready(trailers) = this.inner.poll_trailers(cx) {
+            return Poll::Ready(trailers.map_err(Into::into));
+        }
+        if poll_elapsed(this.sleep, cx) {
+            return Poll::Ready(Err(Elapsed::new().into()));
+        }
+        Poll::Pending
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> http_body::SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use futures::pin_mut;
+    use hyper::Body;
+    use tower_test::mock;
+
+    #[tokio::test(start_paused = true)]
+    async fn times_out_slow_response_body() {
+        let (service, handle) = mock::pair::<Request<Body>, Response<Body>>();
+        let mut service = RequestTimeoutLayer::new(Duration::from_secs(10)).layer(service);
+        let spawned = tokio::spawn(async move {
+            pin_mut!(handle);
+            let (_, send) = handle.next_request().await.expect("service not called");
+            let (mut sender, body) = Body::channel();
+            send.send_response(Response::new(body));
+            sender.send_data("partial".into()).await.unwrap();
+            tokio::time::sleep(Duration::from_secs(60)).await;
+            drop(sender);
+        });
+
+        futures::future::poll_fn(|cx| service.poll_ready(cx))
+            .await
+            .unwrap();
+        let res = service
+            .call(Request::get("/api/v1/pods").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let err = hyper::body::to_bytes(res.into_body()).await.unwrap_err();
+        assert!(err.is::<Elapsed>());
+        spawned.abort();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn times_out_slow_response() {
+        let (service, handle) = mock::pair::<Request<Body>, Response<Body>>();
+        let mut service = RequestTimeoutLayer::new(Duration::from_secs(10)).layer(service);
+        let _handle = handle;
+
+        futures::future::poll_fn(|cx| service.poll_ready(cx))
+            .await
+            .unwrap();
+        let err = service
+            .call(Request::get("/api/v1/pods").body(Body::empty()).unwrap())
+            .await
+            .unwrap_err();
+        assert!(err.is::<Elapsed>());
+    }
+
+    #[test]
+    fn watches_are_streaming() {
+        assert!(is_streaming(
+            &"/api/v1/pods?&watch=true&timeoutSeconds=290".parse().unwrap()
+        ));
+        assert!(is_streaming(
+            &"/api/v1/namespaces/ns/pods/p/log?&follow=true".parse().unwrap()
+        ));
+        assert!(!is_streaming(&"/api/v1/pods?&limit=500".parse().unwrap()));
+    }
+}
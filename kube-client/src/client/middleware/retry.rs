@@ -0,0 +1,304 @@
+//! Retry idempotent requests that were rejected by an overloaded apiserver.
+use std::time::Duration;
+
+use futures::future::BoxFuture;
+use http::{header::RETRY_AFTER, Method, Request, Response, StatusCode};
+use hyper::Body;
+use thiserror::Error;
+use tower::{BoxError, Layer, Service};
+
+/// Layer that applies [`Retry`], which retries idempotent requests on `429`, `502`, `503`, and `504` responses.
+///
+/// Retries are delayed by the `Retry-After` header when the apiserver sets one (as API Priority and Fairness does),
+/// and by an exponential backoff otherwise. Only `GET`, `HEAD`, and `OPTIONS` requests are retried by default,
+/// see [`RetryLayer::retry_idempotent_writes`] to also retry `PUT` and `DELETE` requests.
+/// The total delay is recorded as the [`PriorityAndFairness::retry_wait`](kube_core::PriorityAndFairness::retry_wait)
+/// of the final response.
+///
+/// The wrapped service must be [`Clone`], so the default [`ClientBuilder`](crate::client::ClientBuilder)
+/// stack needs to be [buffered](tower::buffer::BufferLayer) first:
+///
+/// ```no_run
+/// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+/// use kube::{client::{middleware::RetryLayer, ClientBuilder}, Client, Config};
+/// use tower::buffer::BufferLayer;
+///
+/// let config = Config::infer().await?;
+/// let client: Client = ClientBuilder::try_from(config)?
+///     .with_layer(&BufferLayer::new(1024))
+///     .with_layer(&RetryLayer::default().max_retries(5))
+///     .build();
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct RetryLayer {
+    max_retries: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    idempotent_writes: bool,
+}
+
+impl Default for RetryLayer {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            idempotent_writes: false,
+        }
+    }
+}
+
+impl RetryLayer {
+    /// Maximum number of times that a request is retried (default: 3)
+    #[must_use]
+    pub fn max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Delay before the first retry, doubled for every subsequent retry (default: 500ms)
+    #[must_use]
+    pub fn initial_backoff(mut self, initial_backoff: Duration) -> Self {
+        self.initial_backoff = initial_backoff;
+        self
+    }
+
+    /// Upper bound for the delay between retries, including delays requested by `Retry-After` (default: 30s)
+    #[must_use]
+    pub fn max_backoff(mut self, max_backoff: Duration) -> Self {
+        self.max_backoff = max_backoff;
+        self
+    }
+
+    /// Also retry `PUT` and `DELETE` requests (default: false)
+    ///
+    /// These are idempotent, but a retried request may be applied after a concurrent change by another client.
+    #[must_use]
+    pub fn retry_idempotent_writes(mut self, enabled: bool) -> Self {
+        self.idempotent_writes = enabled;
+        self
+    }
+
+    fn retries(&self, method: &Method) -> bool {
+        match *method {
+            Method::GET | Method::HEAD | Method::OPTIONS => true,
+            Method::PUT | Method::DELETE => self.idempotent_writes,
+            _ => false,
+        }
+    }
+
+    fn backoff(&self, retry: u32, retry_after: Option<Duration>) -> Duration {
+        retry_after
+            .unwrap_or_else(|| self.initial_backoff.saturating_mul(2u32.saturating_pow(retry)))
+            .min(self.max_backoff)
+    }
+}
+
+impl<S> Layer<S> for RetryLayer {
+    type Service = Retry<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Retry {
+            policy: self.clone(),
+            inner,
+        }
+    }
+}
+
+/// Service that retries idempotent requests, see [`RetryLayer`].
+#[derive(Debug, Clone)]
+pub struct Retry<S> {
+    policy: RetryLayer,
+    inner: S,
+}
+
+/// The number of attempts that were made for a request, set as an extension on responses from [`Retry`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryAttempts(pub u32);
+
+/// Error returned by [`Retry`] when a request fails after it has already been retried
+#[derive(Debug, Error)]
+#[error("request failed after {attempts} attempts")]
+pub struct RetryError {
+    /// The number of attempts that were made, including the failed one
+    pub attempts: u32,
+    /// The error returned by the final attempt
+    #[source]
+    pub source: BoxError,
+}
+
+impl<S, B> Service<Request<Body>> for Retry<S>
+where
+    S: Service<Request<Body>, Response = Response<B>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    S::Error: Into<BoxError>,
+    B: Send + 'static,
+{
+    type Error = BoxError;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+    type Response = Response<B>;
+
+    fn poll_ready(&mut self, cx: &mut std::task::Context<'_>) -> std::task::Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        // Use the service that was driven to readiness, and leave a fresh clone for the next call
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let policy = self.policy.clone();
+        Box::pin(async move {
+            if !policy.retries(req.method()) {
+                return inner.call(req).await.map_err(Into::into);
+            }
+
+            let (parts, body) = req.into_parts();
+            let body = hyper::body::to_bytes(body).await?;
+            let mut attempts = 1;
+            let mut waited = Duration::ZERO;
+            loop {
+                let req = super::request_from_parts(&parts, Body::from(body.clone()));
+                let mut res = match inner.call(req).await {
+                    Ok(res) => res,
+                    Err(err) if attempts == 1 => return Err(err.into()),
+                    Err(err) => {
+                        return Err(RetryError {
+                            attempts,
+                            source: err.into(),
+                        }
+                        .into())
+                    }
+                };
+                let retryable = matches!(
+                    res.status(),
+                    StatusCode::TOO_MANY_REQUESTS
+                        | StatusCode::BAD_GATEWAY
+                        | StatusCode::SERVICE_UNAVAILABLE
+                        | StatusCode::GATEWAY_TIMEOUT
+                );
+                if !retryable || attempts > policy.max_retries {
+                    res.extensions_mut().insert(RetryAttempts(attempts));
+                    if !waited.is_zero() {
+                        super::waits_mut(&mut res).retry_wait = Some(waited);
+                    }
+                    return Ok(res);
+                }
+
+                let retry_after = res
+                    .headers()
+                    .get(RETRY_AFTER)
+                    .and_then(|value| value.to_str().ok())
+                    .and_then(|value| value.trim().parse().ok())
+                    .map(Duration::from_secs);
+                let delay = policy.backoff(attempts - 1, retry_after);
+                tracing::debug!(status = %res.status(), attempts, ?delay, "retrying request");
+                drop(res);
+                tokio::time::sleep(delay).await;
+                waited += delay;
+                attempts += 1;
+                futures::future::poll_fn(|cx| inner.poll_ready(cx))
+                    .await
+                    .map_err(|err| RetryError {
+                        attempts,
+                        source: err.into(),
+                    })?;
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use futures::pin_mut;
+    use tower_test::mock;
+
+    fn response(status: StatusCode, retry_after: Option<&str>) -> Response<Body> {
+        let mut res = Response::builder().status(status);
+        if let Some(retry_after) = retry_after {
+            res = res.header(RETRY_AFTER, retry_after);
+        }
+        res.body(Body::empty()).unwrap()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn retries_idempotent_requests() {
+        let (service, handle) = mock::pair::<Request<Body>, Response<Body>>();
+        let mut service = RetryLayer::default().retry_idempotent_writes(true).layer(service);
+        let spawned = tokio::spawn(async move {
+            pin_mut!(handle);
+            let started = tokio::time::Instant::now();
+            for res in [
+                response(StatusCode::TOO_MANY_REQUESTS, Some("2")),
+                response(StatusCode::SERVICE_UNAVAILABLE, None),
+                response(StatusCode::OK, None),
+            ] {
+                let (request, send) = handle.next_request().await.expect("service not called");
+                assert_eq!(request.extensions().get::<&'static str>(), Some(&"replace"));
+                assert_eq!(hyper::body::to_bytes(request.into_body()).await.unwrap(), "body");
+                send.send_response(res);
+            }
+            // 2s from Retry-After, then 1s of backoff for the 2nd retry
+            assert_eq!(started.elapsed(), Duration::from_secs(3));
+        });
+
+        let mut req = Request::put("/").body(Body::from("body")).unwrap();
+        req.extensions_mut().insert("replace");
+        futures::future::poll_fn(|cx| service.poll_ready(cx))
+            .await
+            .unwrap();
+        let res = service.call(req).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.extensions().get::<RetryAttempts>(), Some(&RetryAttempts(3)));
+        let apf = res.extensions().get::<kube_core::PriorityAndFairness>().unwrap();
+        assert_eq!(apf.retry_wait, Some(Duration::from_secs(3)));
+        spawned.await.unwrap();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn gives_up_after_max_retries() {
+        let (service, handle) = mock::pair::<Request<Body>, Response<Body>>();
+        let mut service = RetryLayer::default().max_retries(1).layer(service);
+        let spawned = tokio::spawn(async move {
+            pin_mut!(handle);
+            for _ in 0..2 {
+                let (_, send) = handle.next_request().await.expect("service not called");
+                send.send_response(response(StatusCode::GATEWAY_TIMEOUT, None));
+            }
+        });
+
+        let req = Request::get("/").body(Body::empty()).unwrap();
+        futures::future::poll_fn(|cx| service.poll_ready(cx))
+            .await
+            .unwrap();
+        let res = service.call(req).await.unwrap();
+        assert_eq!(res.status(), StatusCode::GATEWAY_TIMEOUT);
+        assert_eq!(res.extensions().get::<RetryAttempts>(), Some(&RetryAttempts(2)));
+        spawned.await.unwrap();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn does_not_retry_writes_by_default() {
+        let (service, handle) = mock::pair::<Request<Body>, Response<Body>>();
+        let mut service = RetryLayer::default().layer(service);
+        let spawned = tokio::spawn(async move {
+            pin_mut!(handle);
+            for _ in 0..3 {
+                let (_, send) = handle.next_request().await.expect("service not called");
+                send.send_response(response(StatusCode::TOO_MANY_REQUESTS, None));
+            }
+        });
+
+        for req in [Request::post("/"), Request::put("/"), Request::delete("/")] {
+            futures::future::poll_fn(|cx| service.poll_ready(cx))
+                .await
+                .unwrap();
+            let res = service.call(req.body(Body::empty()).unwrap()).await.unwrap();
+            assert_eq!(res.status(), StatusCode::TOO_MANY_REQUESTS);
+        }
+        spawned.await.unwrap();
+    }
+}
@@ -0,0 +1,233 @@
+//! Log request and response bodies, with credentials and `Secret` data redacted.
+use futures::future::BoxFuture;
+use http::{header, HeaderMap, Request, Response};
+use hyper::Body;
+use serde_json::Value;
+use tower::{BoxError, Layer, Service};
+
+use super::RequestLabels;
+
+const REDACTED: &str = "<redacted>";
+
+/// Layer that applies [`TraceBodies`], which logs the headers and bodies of requests and responses at `TRACE` level.
+///
+/// This is added to the default stack when [`Config::trace_bodies`](crate::Config::trace_bodies) is set.
+/// The values of the `Authorization`, `Proxy-Authorization`, `Cookie`, and `Set-Cookie` headers are redacted,
+/// as well as the `data` and `stringData` of `Secret` objects and the tokens of `TokenRequest`s and `TokenReview`s.
+///
+/// Bodies are buffered in full before they are logged, except for the responses of watches, followed logs,
+/// and protocol upgrades, which are streamed without being logged.
+#[derive(Debug, Clone, Default)]
+pub struct TraceBodiesLayer {}
+
+impl<S> Layer<S> for TraceBodiesLayer {
+    type Service = TraceBodies<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        TraceBodies { inner }
+    }
+}
+
+/// Service that logs request and response bodies, see [`TraceBodiesLayer`].
+#[derive(Debug, Clone)]
+pub struct TraceBodies<S> {
+    inner: S,
+}
+
+impl<S> Service<Request<Body>> for TraceBodies<S>
+where
+    S: Service<Request<Body>, Response = Response<Body>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    S::Error: Into<BoxError>,
+{
+    type Error = BoxError;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+    type Response = Response<Body>;
+
+    fn poll_ready(&mut self, cx: &mut std::task::Context<'_>) -> std::task::Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        // Use the service that was driven to readiness, and leave a fresh clone for the next call
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        Box::pin(async move {
+            if !tracing::enabled!(tracing::Level::TRACE) {
+                return inner.call(req).await.map_err(Into::into);
+            }
+
+            let is_secret = RequestLabels::from_request(&req).resource == "secrets";
+            let streaming = req.uri().query().map_or(false, |query| {
+                query
+                    .split('&')
+                    .any(|pair| pair == "watch=true" || pair == "follow=true")
+            });
+            let (parts, body) = req.into_parts();
+            let body = hyper::body::to_bytes(body).await?;
+            tracing::trace!(
+                http.method = %parts.method,
+                http.url = %parts.uri,
+                headers = ?redact_headers(&parts.headers),
+                body = %redact_body(&body, is_secret),
+                "request"
+            );
+            let res = inner
+                .call(Request::from_parts(parts, Body::from(body)))
+                .await
+                .map_err(Into::into)?;
+
+            if streaming || res.status() == http::StatusCode::SWITCHING_PROTOCOLS {
+                tracing::trace!(
+                    http.status_code = res.status().as_u16(),
+                    headers = ?redact_headers(res.headers()),
+                    "response with a streaming body"
+                );
+                return Ok(res);
+            }
+            let (parts, body) = res.into_parts();
+            let body = hyper::body::to_bytes(body).await?;
+            tracing::trace!(
+                http.status_code = parts.status.as_u16(),
+                headers = ?redact_headers(&parts.headers),
+                body = %redact_body(&body, is_secret),
+                "response"
+            );
+            Ok(Response::from_parts(parts, Body::from(body)))
+        })
+    }
+}
+
+fn redact_headers(headers: &HeaderMap) -> Vec<(String, String)> {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            let value = match *name {
+                header::AUTHORIZATION | header::PROXY_AUTHORIZATION | header::COOKIE | header::SET_COOKIE => {
+                    REDACTED.to_owned()
+                }
+                _ => String::from_utf8_lossy(value.as_bytes()).into_owned(),
+            };
+            (name.as_str().to_owned(), value)
+        })
+        .collect()
+}
+
+/// Renders a body for logging, with the data of `Secret`s and tokens redacted
+///
+/// Bodies of requests to the `secrets` resource are redacted even when they are not a full object, like patches.
+fn redact_body(body: &[u8], is_secret: bool) -> String {
+    match serde_json::from_slice::<Value>(body) {
+        Ok(mut value @ Value::Object(_)) => {
+            redact_value(&mut value, is_secret);
+            value.to_string()
+        }
+        // Such as JSON patches, which can contain secret data anywhere
+        Ok(_) | Err(_) if is_secret => format!("<{} bytes redacted>", body.len()),
+        Ok(value) => value.to_string(),
+        Err(_) => String::from_utf8_lossy(body).into_owned(),
+    }
+}
+
+fn redact_value(value: &mut Value, is_secret: bool) {
+    let object = match value {
+        Value::Object(object) => object,
+        _ => return,
+    };
+    if is_secret || object.get("kind").and_then(Value::as_str) == Some("Secret") {
+        for field in ["data", "stringData"] {
+            if let Some(Value::Object(data)) = object.get_mut(field) {
+                for value in data.values_mut() {
+                    *value = Value::String(REDACTED.to_owned());
+                }
+            }
+        }
+    }
+    // The tokens of `serviceaccounts/token` responses and of `TokenReview`s
+    let token = match object.get("kind").and_then(Value::as_str) {
+        Some("TokenRequest") => Some("status"),
+        Some("TokenReview") => Some("spec"),
+        _ => None,
+    };
+    if let Some(Value::Object(field)) = token.and_then(|field| object.get_mut(field)) {
+        if let Some(token) = field.get_mut("token") {
+            *token = Value::String(REDACTED.to_owned());
+        }
+    }
+    if let Some(Value::Array(items)) = object.get_mut("items") {
+        for item in items {
+            redact_value(item, is_secret);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use serde_json::json;
+
+    fn redacted(value: Value, is_secret: bool) -> Value {
+        serde_json::from_str(&redact_body(value.to_string().as_bytes(), is_secret)).unwrap()
+    }
+
+    #[test]
+    fn redacts_secret_data() {
+        let secret =
+            json!({"kind": "Secret", "metadata": {"name": "a"}, "data": {"password": "aHVudGVyMg=="}});
+        assert_eq!(
+            redacted(secret, false),
+            json!({"kind": "Secret", "metadata": {"name": "a"}, "data": {"password": "<redacted>"}})
+        );
+
+        let list =
+            json!({"kind": "SecretList", "items": [{"kind": "Secret", "stringData": {"token": "abc"}}]});
+        assert_eq!(
+            redacted(list, false),
+            json!({"kind": "SecretList", "items": [{"kind": "Secret", "stringData": {"token": "<redacted>"}}]})
+        );
+
+        // Patches do not have a kind, but are sent to the secrets resource
+        let patch = json!({"data": {"password": "aHVudGVyMg=="}});
+        assert_eq!(redacted(patch, true), json!({"data": {"password": "<redacted>"}}));
+        assert_eq!(redact_body(br#"[{"op":"add"}]"#, true), "<14 bytes redacted>");
+
+        let config_map = json!({"kind": "ConfigMap", "data": {"key": "value"}});
+        assert_eq!(redacted(config_map.clone(), false), config_map);
+    }
+
+    #[test]
+    fn redacts_tokens() {
+        let token_request = json!({
+            "kind": "TokenRequest",
+            "spec": {"audiences": ["api"]},
+            "status": {"token": "eyJhbGciOi", "expirationTimestamp": "2023-01-01T00:00:00Z"}
+        });
+        assert_eq!(
+            redacted(token_request, false),
+            json!({
+                "kind": "TokenRequest",
+                "spec": {"audiences": ["api"]},
+                "status": {"token": "<redacted>", "expirationTimestamp": "2023-01-01T00:00:00Z"}
+            })
+        );
+
+        let token_review = json!({"kind": "TokenReview", "spec": {"token": "eyJhbGciOi"}});
+        assert_eq!(
+            redacted(token_review, false),
+            json!({"kind": "TokenReview", "spec": {"token": "<redacted>"}})
+        );
+    }
+
+    #[test]
+    fn redacts_credentials() {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::AUTHORIZATION, "Bearer token".parse().unwrap());
+        headers.insert(header::ACCEPT, "application/json".parse().unwrap());
+        let expected = [
+            ("authorization".to_owned(), REDACTED.to_owned()),
+            ("accept".to_owned(), "application/json".to_owned()),
+        ];
+        assert_eq!(redact_headers(&headers), expected);
+    }
+}
@@ -7,6 +7,12 @@
 //!
 //! The [`Client`] can also be used with [`Discovery`](crate::Discovery) to dynamically
 //! retrieve the resources served by the kubernetes API.
+//!
+//! Requests and responses are always encoded as JSON. The protobuf wire format
+//! (`application/vnd.kubernetes.protobuf`) is not negotiated, since the `k8s_openapi` types
+//! only implement (de)serialization through `serde`.
+use std::sync::Arc;
+
 use either::{Either, Left, Right};
 use futures::{self, AsyncBufRead, StreamExt, TryStream, TryStreamExt};
 use http::{self, Request, Response, StatusCode};
@@ -25,6 +31,7 @@ use tower::{buffer::Buffer, util::BoxService, BoxError, Layer, Service, ServiceE
 use tower_http::map_response_body::MapResponseBodyLayer;
 
 use crate::{api::WatchEvent, error::ErrorResponse, Config, Error, Result};
+use kube_core::response::{PriorityAndFairness, ResponseMetadata};
 
 mod auth;
 mod body;
@@ -32,15 +39,24 @@ mod builder;
 // Add `into_stream()` to `http::Body`
 use body::BodyStreamExt;
 mod config_ext;
+mod list_stream;
 pub use auth::Error as AuthError;
 pub use config_ext::ConfigExt;
+pub use list_stream::ListStream;
 pub mod middleware;
+mod proxy;
+pub use proxy::ProxyConnector;
+#[cfg(unix)] mod unix;
+#[cfg(unix)] pub use unix::{UnixConnection, UnixConnector};
+#[cfg(any(feature = "rustls-tls", feature = "openssl-tls"))] mod reload;
 #[cfg(any(feature = "rustls-tls", feature = "openssl-tls"))] mod tls;
 
 #[cfg(feature = "openssl-tls")]
 pub use tls::openssl_tls::Error as OpensslTlsError;
 #[cfg(feature = "rustls-tls")] pub use tls::rustls_tls::Error as RustlsTlsError;
 #[cfg(feature = "ws")] mod upgrade;
+mod warnings;
+pub use warnings::{IgnoreWarnings, LogWarnings, WarningHandler};
 
 #[cfg(feature = "oauth")]
 #[cfg_attr(docsrs, doc(cfg(feature = "oauth")))]
@@ -50,9 +66,16 @@ pub use auth::OAuthError;
 #[cfg_attr(docsrs, doc(cfg(feature = "oidc")))]
 pub use auth::oidc_errors;
 
+#[cfg(feature = "azure")]
+#[cfg_attr(docsrs, doc(cfg(feature = "azure")))]
+pub use auth::azure_errors;
+
 #[cfg(feature = "ws")] pub use upgrade::UpgradeConnectionError;
 
-pub use builder::{ClientBuilder, DynBody};
+pub use builder::{ClientBuilder, DynBody, InnerService};
+
+/// The [`ClientBuilder`] of the default stack, as returned by [`Client::builder`]
+pub type DefaultClientBuilder = ClientBuilder<BoxService<Request<Body>, Response<Box<DynBody>>, BoxError>>;
 
 /// Client for connecting with a Kubernetes cluster.
 ///
@@ -67,6 +90,7 @@ pub struct Client {
     // - `BoxService` for dynamic response future type
     inner: Buffer<BoxService<Request<Body>, Response<Body>, BoxError>, Request<Body>>,
     default_ns: String,
+    warning_handler: Arc<dyn WarningHandler>,
 }
 
 impl Client {
@@ -113,9 +137,52 @@ impl Client {
         Self {
             inner: Buffer::new(BoxService::new(service), 1024),
             default_ns: default_namespace.into(),
+            warning_handler: Arc::new(LogWarnings),
         }
     }
 
+    /// Handle the warnings of all responses with `handler`, instead of logging them
+    ///
+    /// The apiserver warns about the use of deprecated APIs, and admission webhooks may warn about requests,
+    /// which `kubectl` shows to its users. Warnings are logged at `WARN` level by default, see [`LogWarnings`].
+    ///
+    /// ```rust
+    /// # async fn doc() -> Result<(), Box<dyn std::error::Error>> {
+    /// use kube::{core::response::{ResponseMetadata, Warning}, Client};
+    ///
+    /// let client = Client::try_default().await?.with_warning_handler(|warning: &Warning, _: &ResponseMetadata| {
+    ///     eprintln!("Warning: {}", warning.text);
+    /// });
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn with_warning_handler(mut self, handler: impl WarningHandler) -> Self {
+        self.warning_handler = Arc::new(handler);
+        self
+    }
+
+    /// Start a [`ClientBuilder`] with the default stack for `config`, to add custom middleware
+    ///
+    /// ```rust
+    /// # fn doc() -> Result<(), Box<dyn std::error::Error>> {
+    /// use std::time::Duration;
+    /// use kube::{client::middleware::DryRunLayer, Client, Config};
+    ///
+    /// let config = Config::new("https://kubernetes.example.com".parse()?)
+    ///     .token("my-token")
+    ///     .default_namespace("apps")
+    ///     .user_agent("my-operator/1.0")
+    ///     .request_timeout(Duration::from_secs(30))
+    ///     .rate_limit(5.0, 10);
+    /// let client = Client::builder(config)?.with_layer(&DryRunLayer).build();
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn builder(config: Config) -> Result<DefaultClientBuilder> {
+        ClientBuilder::try_from(config)
+    }
+
     /// Create and initialize a [`Client`] using the inferred configuration.
     ///
     /// Will use [`Config::infer`] which attempts to load the local kubeconfig first,
@@ -141,9 +208,13 @@ impl Client {
     /// Perform a raw HTTP request against the API and return the raw response back.
     /// This method can be used to get raw access to the API which may be used to, for example,
     /// create a proxy server or application-level gateway between localhost and the API server.
+    ///
+    /// The response has [`PriorityAndFairness`](kube_core::PriorityAndFairness) and
+    /// [`ResponseMetadata`](kube_core::ResponseMetadata) extensions with the metadata of the apiserver.
+    /// Its warnings have been passed to the [`WarningHandler`] of the client.
     pub async fn send(&self, request: Request<Body>) -> Result<Response<Body>> {
         let mut svc = self.inner.clone();
-        let res = svc
+        let mut res = svc
             .ready()
             .await
             .map_err(Error::Service)?
@@ -156,8 +227,15 @@ impl Client {
                     // Error requesting
                     .or_else(|err| err.downcast::<hyper::Error>().map(|err| Error::HyperError(*err)))
                     // Error from another middleware
-                    .unwrap_or_else(|err| Error::Service(err))
+                    .unwrap_or_else(Error::Service)
             })?;
+        let apf = middleware::priority_and_fairness(&res);
+        let metadata = ResponseMetadata::from_headers(res.headers());
+        for warning in &metadata.warnings {
+            self.warning_handler.handle_warning(warning, &metadata);
+        }
+        res.extensions_mut().insert(apf);
+        res.extensions_mut().insert(metadata);
         Ok(res)
     }
 
@@ -225,14 +303,15 @@ impl Client {
     /// Perform a raw HTTP request against the API and get back the response
     /// as a string
     pub async fn request_text(&self, request: Request<Vec<u8>>) -> Result<String> {
-        let res = self.send(request.map(Body::from)).await?;
+        let mut res = self.send(request.map(Body::from)).await?;
         let status = res.status();
+        let apf = res.extensions_mut().remove::<PriorityAndFairness>();
         // trace!("Status = {:?} for {}", status, res.url());
         let body_bytes = hyper::body::to_bytes(res.into_body())
             .await
             .map_err(Error::HyperError)?;
         let text = String::from_utf8(body_bytes.to_vec()).map_err(Error::FromUtf8)?;
-        handle_api_errors(&text, status)?;
+        handle_api_errors(&text, status, apf)?;
 
         Ok(text)
     }
@@ -251,6 +330,32 @@ impl Client {
         Ok(body.into_async_read())
     }
 
+    /// Perform a raw list request against the API and stream the items of the response as they are received
+    ///
+    /// Error responses are buffered and returned as errors, like with [`Client::request`].
+    pub async fn request_list_stream<T>(&self, request: Request<Vec<u8>>) -> Result<ListStream<T>>
+    where
+        T: DeserializeOwned,
+    {
+        self.request_list_body(request).await.map(ListStream::new)
+    }
+
+    /// Perform a raw request, and get back the body of a successful response
+    pub(crate) async fn request_list_body(&self, request: Request<Vec<u8>>) -> Result<Body> {
+        let mut res = self.send(request.map(Body::from)).await?;
+        let status = res.status();
+        if !status.is_client_error() && !status.is_server_error() {
+            return Ok(res.into_body());
+        }
+        let apf = res.extensions_mut().remove::<PriorityAndFairness>();
+        let body_bytes = hyper::body::to_bytes(res.into_body())
+            .await
+            .map_err(Error::HyperError)?;
+        let text = String::from_utf8(body_bytes.to_vec()).map_err(Error::FromUtf8)?;
+        handle_api_errors(&text, status, apf)?;
+        Ok(Body::from(text))
+    }
+
     /// Perform a raw HTTP request against the API and get back either an object
     /// deserialized as JSON or a [`Status`] Object.
     pub async fn request_status<T>(&self, request: Request<Vec<u8>>) -> Result<Either<T, Status>>
@@ -427,6 +532,89 @@ impl Client {
     }
 }
 
+/// Requests to paths that are not served by [`Api`](crate::Api).
+///
+/// These cover the non-resource endpoints of the apiserver, such as `/version`, `/readyz`, or `/metrics`,
+/// and the subpaths of aggregated APIs that do not behave like resources.
+/// The `path` is relative to the cluster url, and can contain a query string.
+impl Client {
+    /// Get a path and deserialize the response as JSON to some known type.
+    ///
+    /// ### Example usage:
+    /// ```rust
+    /// # async fn scope(client: kube::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// let usage: serde_json::Value = client.get_path("/apis/metrics.k8s.io/v1beta1/nodes").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_path<T>(&self, path: &str) -> Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        self.request(get_path_request(path)?).await
+    }
+
+    /// Get a path and get back the response as a string.
+    pub async fn get_path_text(&self, path: &str) -> Result<String> {
+        self.request_text(get_path_request(path)?).await
+    }
+
+    /// Get a path and stream the response body, see [`Client::request_stream`].
+    pub async fn get_path_stream(&self, path: &str) -> Result<impl AsyncBufRead> {
+        self.request_stream(get_path_request(path)?).await
+    }
+
+    /// Checks whether the apiserver is live.
+    ///
+    /// Fails with an [`Error::Api`] that includes the failed checks when it is not.
+    ///
+    /// With `verbose`, the response also lists the checks that passed.
+    /// See [Kubernetes API health endpoints](https://kubernetes.io/docs/reference/using-api/health-checks/).
+    pub async fn livez(&self, verbose: bool) -> Result<String> {
+        self.get_path_text(if verbose { "/livez?verbose" } else { "/livez" })
+            .await
+    }
+
+    /// Checks whether the apiserver is ready to serve requests.
+    ///
+    /// Fails with an [`Error::Api`] that includes the failed checks when it is not.
+    ///
+    /// With `verbose`, the response also lists the checks that passed.
+    /// See [Kubernetes API health endpoints](https://kubernetes.io/docs/reference/using-api/health-checks/).
+    pub async fn readyz(&self, verbose: bool) -> Result<String> {
+        self.get_path_text(if verbose { "/readyz?verbose" } else { "/readyz" })
+            .await
+    }
+
+    /// Checks the health of the apiserver.
+    ///
+    /// This endpoint is deprecated by Kubernetes, prefer [`Client::livez`] and [`Client::readyz`].
+    pub async fn healthz(&self, verbose: bool) -> Result<String> {
+        self.get_path_text(if verbose { "/healthz?verbose" } else { "/healthz" })
+            .await
+    }
+
+    /// Returns the metrics of the apiserver in the Prometheus text format.
+    pub async fn apiserver_metrics(&self) -> Result<String> {
+        self.get_path_text("/metrics").await
+    }
+
+    /// Returns a log file of the apiserver's node, or the listing of a log directory.
+    ///
+    /// The `path` is relative to `/var/log` on the node, use `""` to list the directory.
+    pub async fn node_logs(&self, path: &str) -> Result<String> {
+        self.get_path_text(&format!("/logs/{}", path.trim_start_matches('/')))
+            .await
+    }
+}
+
+fn get_path_request(path: &str) -> Result<Request<Vec<u8>>> {
+    Request::builder()
+        .uri(path)
+        .body(vec![])
+        .map_err(Error::HttpError)
+}
+
 /// Kubernetes returned error handling
 ///
 /// Either kube returned an explicit ApiError struct,
@@ -434,21 +622,27 @@ impl Client {
 ///
 /// In either case, present an ApiError upstream.
 /// The latter is probably a bug if encountered.
-fn handle_api_errors(text: &str, s: StatusCode) -> Result<()> {
+fn handle_api_errors(text: &str, s: StatusCode, apf: Option<PriorityAndFairness>) -> Result<()> {
     if s.is_client_error() || s.is_server_error() {
         // Print better debug when things do fail
         // trace!("Parsing error: {}", text);
-        if let Ok(errdata) = serde_json::from_str::<ErrorResponse>(text) {
+        if let Ok(mut errdata) = serde_json::from_str::<ErrorResponse>(text) {
+            if let Some(apf) = apf {
+                errdata = errdata.with_priority_and_fairness(apf);
+            }
             tracing::debug!("Unsuccessful: {:?}", errdata);
             Err(Error::Api(errdata))
         } else {
             tracing::warn!("Unsuccessful data error parse: {}", text);
-            let ae = ErrorResponse {
-                status: s.to_string(),
-                code: s.as_u16(),
-                message: format!("{text:?}"),
-                reason: "Failed to parse error data".into(),
-            };
+            let mut ae = ErrorResponse::new(
+                s.to_string(),
+                format!("{text:?}"),
+                "Failed to parse error data",
+                s.as_u16(),
+            );
+            if let Some(apf) = apf {
+                ae = ae.with_priority_and_fairness(apf);
+            }
             tracing::debug!("Unsuccessful: {:?} (reconstruct)", ae);
             Err(Error::Api(ae))
         }
@@ -516,4 +710,118 @@ mod tests {
         assert_eq!(pod.metadata.annotations.unwrap().get("kube-rs").unwrap(), "test");
         spawned.await.unwrap();
     }
+
+    #[tokio::test]
+    async fn test_non_resource_paths() {
+        let (mock_service, handle) = mock::pair::<Request<Body>, Response<Body>>();
+        let spawned = tokio::spawn(async move {
+            pin_mut!(handle);
+            let (request, send) = handle.next_request().await.expect("service not called");
+            assert_eq!(request.uri().to_string(), "/readyz?verbose");
+            send.send_response(Response::new(Body::from("[+]ping ok\nreadyz check passed")));
+
+            let (request, send) = handle.next_request().await.expect("service not called");
+            assert_eq!(request.uri().to_string(), "/livez");
+            send.send_response(
+                Response::builder()
+                    .status(http::StatusCode::INTERNAL_SERVER_ERROR)
+                    .body(Body::from("[-]etcd failed: reason withheld\nlivez check failed"))
+                    .unwrap(),
+            );
+
+            let (request, send) = handle.next_request().await.expect("service not called");
+            assert_eq!(request.uri().to_string(), "/logs/kube-apiserver.log");
+            send.send_response(Response::new(Body::from("log line")));
+        });
+
+        let client = Client::new(mock_service, "default");
+        assert!(client
+            .readyz(true)
+            .await
+            .unwrap()
+            .ends_with("readyz check passed"));
+        let err = client.livez(false).await.unwrap_err();
+        assert!(matches!(err, crate::Error::Api(e) if e.code == 500 && e.message.contains("etcd failed")));
+        assert_eq!(client.node_logs("/kube-apiserver.log").await.unwrap(), "log line");
+        spawned.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_priority_and_fairness_errors() {
+        let (mock_service, handle) = mock::pair::<Request<Body>, Response<Body>>();
+        let spawned = tokio::spawn(async move {
+            pin_mut!(handle);
+            let (_, send) = handle.next_request().await.expect("service not called");
+            let status = serde_json::json!({
+                "kind": "Status",
+                "status": "Failure",
+                "message": "Too many requests, please try again later.",
+                "reason": "TooManyRequests",
+                "code": 429,
+            });
+            send.send_response(
+                Response::builder()
+                    .status(429)
+                    .header("Retry-After", "1")
+                    .header("X-Kubernetes-PF-FlowSchema-UID", "flow")
+                    .header("X-Kubernetes-PF-PriorityLevel-UID", "level")
+                    .body(Body::from(status.to_string()))
+                    .unwrap(),
+            );
+        });
+
+        let pods: Api<Pod> = Api::default_namespaced(Client::new(mock_service, "default"));
+        let err = match pods.get("test").await {
+            Err(crate::Error::Api(err)) => err,
+            res => panic!("expected an api error, got {res:?}"),
+        };
+        assert_eq!(err.code, 429);
+        let apf = err.priority_and_fairness().unwrap();
+        assert_eq!(apf.flow_schema_uid.as_deref(), Some("flow"));
+        assert_eq!(apf.priority_level_uid.as_deref(), Some("level"));
+        assert_eq!(apf.retry_after, Some(std::time::Duration::from_secs(1)));
+        spawned.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_warning_handler() {
+        use kube_core::response::{ResponseMetadata, Warning};
+        use std::sync::{Arc, Mutex};
+
+        let (mock_service, handle) = mock::pair::<Request<Body>, Response<Body>>();
+        let spawned = tokio::spawn(async move {
+            pin_mut!(handle);
+            let (_, send) = handle.next_request().await.expect("service not called");
+            send.send_response(
+                Response::builder()
+                    .header("Audit-Id", "2b5c3f8a")
+                    .header(
+                        "Warning",
+                        r#"299 - "policy/v1beta1 PodSecurityPolicy is deprecated""#,
+                    )
+                    .body(Body::from("{}"))
+                    .unwrap(),
+            );
+        });
+
+        let warnings = Arc::new(Mutex::new(Vec::new()));
+        let handled = warnings.clone();
+        let client = Client::new(mock_service, "default").with_warning_handler(
+            move |warning: &Warning, metadata: &ResponseMetadata| {
+                let audit_id = metadata.audit_id.clone().unwrap();
+                handled.lock().unwrap().push((warning.text.clone(), audit_id));
+            },
+        );
+        let res = client
+            .send(Request::get("/version").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let metadata = res.extensions().get::<ResponseMetadata>().unwrap();
+        assert_eq!(metadata.warnings.len(), 1);
+        assert_eq!(*warnings.lock().unwrap(), [(
+            "policy/v1beta1 PodSecurityPolicy is deprecated".to_owned(),
+            "2b5c3f8a".to_owned()
+        )]);
+        spawned.await.unwrap();
+    }
 }
@@ -0,0 +1,291 @@
+//! Pick up rotated certificates without rebuilding the client
+use std::{
+    future::Future,
+    path::PathBuf,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+
+use http::Uri;
+use tower::{BoxError, Service, ServiceExt};
+
+use crate::{Config, Result};
+
+/// How often the certificate files are checked for changes
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+type BuildConnector<C> = dyn Fn(&Config) -> Result<C> + Send + Sync;
+
+/// Connector that rebuilds the TLS connector when the certificate files of the [`Config`] change
+///
+/// These are the [`Config::root_cert_file`], and the client certificate and key files of the
+/// [`AuthInfo`](crate::config::AuthInfo) when they are not given inline.
+/// The files are re-read at most once per [`CHECK_INTERVAL`], and only new connections use the new certificates.
+/// If the new certificates cannot be loaded, the previous connector keeps being used.
+pub(crate) struct CertReloader<C> {
+    state: Arc<Mutex<State<C>>>,
+    build: Arc<BuildConnector<C>>,
+}
+
+struct State<C> {
+    connector: C,
+    config: Config,
+    files: Vec<(PathBuf, Vec<u8>)>,
+    next_check: Instant,
+}
+
+/// The certificate files that `config` reads when a connector is built from it
+pub(crate) fn cert_files(config: &Config) -> Vec<PathBuf> {
+    let auth_info = &config.auth_info;
+    let client_certificate = auth_info
+        .client_certificate
+        .as_ref()
+        .filter(|_| auth_info.client_certificate_data.is_none());
+    let client_key = auth_info
+        .client_key
+        .as_ref()
+        .filter(|_| auth_info.client_key_data.is_none());
+    config
+        .root_cert_file
+        .iter()
+        .chain(client_certificate.map(PathBuf::from).as_ref())
+        .chain(client_key.map(PathBuf::from).as_ref())
+        .cloned()
+        .collect()
+}
+
+impl<C> CertReloader<C> {
+    /// Use `connector`, which was built from `config` by `build`, until the certificates at `paths` change
+    pub(crate) fn new(
+        connector: C,
+        config: Config,
+        paths: Vec<PathBuf>,
+        build: impl Fn(&Config) -> Result<C> + Send + Sync + 'static,
+    ) -> Self {
+        let files = paths
+            .into_iter()
+            .map(|path| {
+                let contents = std::fs::read(&path).unwrap_or_default();
+                (path, contents)
+            })
+            .collect();
+        Self {
+            state: Arc::new(Mutex::new(State {
+                connector,
+                config,
+                files,
+                next_check: Instant::now() + CHECK_INTERVAL,
+            })),
+            build: Arc::new(build),
+        }
+    }
+}
+
+impl<C> Clone for CertReloader<C> {
+    fn clone(&self) -> Self {
+        Self {
+            state: self.state.clone(),
+            build: self.build.clone(),
+        }
+    }
+}
+
+/// Reads the certificate files, returning their new contents if any of them changed
+async fn read_changed(mut files: Vec<(PathBuf, Vec<u8>)>) -> Option<Vec<(PathBuf, Vec<u8>)>> {
+    let mut changed = false;
+    for (path, contents) in &mut files {
+        match tokio::fs::read(&*path).await {
+            Ok(new_contents) if new_contents != *contents => {
+                *contents = new_contents;
+                changed = true;
+            }
+            Ok(_) => {}
+            Err(err) => {
+                tracing::warn!(
+                    ?path,
+                    error = &err as &dyn std::error::Error,
+                    "failed to read certificates"
+                );
+            }
+        }
+    }
+    changed.then(|| files)
+}
+
+/// Builds a connector from `config` with the new contents of the certificate `files`
+fn rebuild<C>(
+    mut config: Config,
+    files: &[(PathBuf, Vec<u8>)],
+    build: &BuildConnector<C>,
+) -> Option<(C, Config)> {
+    if let Some(path) = &config.root_cert_file {
+        let contents = files
+            .iter()
+            .find(|(file, _)| file == path)
+            .map(|(_, contents)| contents);
+        match crate::config::certs(contents.map_or(&[][..], Vec::as_slice)) {
+            Ok(certs) => config.root_cert = Some(certs),
+            Err(err) => {
+                tracing::warn!(
+                    ?path,
+                    error = &err as &dyn std::error::Error,
+                    "failed to parse root certificates"
+                );
+                return None;
+            }
+        }
+    }
+    // Client certificates are read from their files by `build`. A certificate and key that are rotated
+    // one after the other can fail to build until both are replaced, which is retried on the next change.
+    match build(&config) {
+        Ok(connector) => {
+            tracing::debug!("reloaded certificates");
+            Some((connector, config))
+        }
+        Err(err) => {
+            tracing::warn!(
+                error = &err as &dyn std::error::Error,
+                "failed to use reloaded certificates"
+            );
+            None
+        }
+    }
+}
+
+impl<C> Service<Uri> for CertReloader<C>
+where
+    C: Service<Uri> + Clone + Send + 'static,
+    C::Future: Send + 'static,
+    C::Error: Into<BoxError>,
+{
+    type Error = BoxError;
+    type Future = Pin<Box<dyn Future<Output = std::result::Result<Self::Response, Self::Error>> + Send>>;
+    type Response = C::Response;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<std::result::Result<(), Self::Error>> {
+        // The connector is only picked in `call`, after a possible reload
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, dst: Uri) -> Self::Future {
+        // The lock is only held to pick the connector and claim a due check, the files are read by the
+        // connection that claimed it, while the others keep using the current connector
+        let (connector, check) = {
+            let mut state = self
+                .state
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner);
+            let now = Instant::now();
+            let check = (now >= state.next_check).then(|| {
+                state.next_check = now + CHECK_INTERVAL;
+                (state.files.clone(), state.config.clone())
+            });
+            (state.connector.clone(), check)
+        };
+        let state = self.state.clone();
+        let build = self.build.clone();
+        Box::pin(async move {
+            let mut connector = connector;
+            if let Some((files, config)) = check {
+                if let Some(files) = read_changed(files).await {
+                    let rebuilt = rebuild(config, &files, build.as_ref());
+                    let mut state = state.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+                    // Remember the contents even if they are invalid, so that a broken file is only reported once
+                    state.files = files;
+                    if let Some((new_connector, config)) = rebuilt {
+                        state.connector = new_connector.clone();
+                        state.config = config;
+                        connector = new_connector;
+                    }
+                }
+            }
+            connector.oneshot(dst).await.map_err(Into::into)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CERT: &str = "-----BEGIN CERTIFICATE-----\naGVsbG8K\n-----END CERTIFICATE-----\n";
+
+    /// A connector that responds with the number of root certificates and the client certificate it was built with
+    fn connector(config: &Config) -> Result<tower::util::BoxCloneService<Uri, String, crate::Error>> {
+        let root_certs = config.root_cert.as_ref().map_or(0, Vec::len);
+        let client_cert = config.auth_info.load_client_certificate().unwrap_or_default();
+        let client_cert = String::from_utf8(client_cert).unwrap().trim_end().to_owned();
+        if client_cert == "broken" {
+            return Err(crate::Error::Service("broken client certificate".into()));
+        }
+        let response = format!("{root_certs} {client_cert}");
+        Ok(tower::util::BoxCloneService::new(tower::service_fn(move |_| {
+            futures::future::ready(Ok(response.clone()))
+        })))
+    }
+
+    fn force_check<C>(reloader: &CertReloader<C>) {
+        reloader.state.lock().unwrap().next_check = Instant::now();
+    }
+
+    #[tokio::test]
+    async fn reloads_changed_root_certificates() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(file.path(), CERT).unwrap();
+        let mut config = Config::new(Uri::from_static("https://kubernetes"));
+        config.root_cert = Some(crate::config::certs(CERT.as_bytes()).unwrap());
+        config.root_cert_file = Some(file.path().into());
+        let paths = cert_files(&config);
+        let mut reloader = CertReloader::new(connector(&config).unwrap(), config, paths, connector);
+        let uri = Uri::from_static("https://kubernetes");
+
+        assert_eq!(reloader.call(uri.clone()).await.unwrap(), "1 ");
+        // Changes are only noticed once the check interval has passed
+        std::fs::write(file.path(), CERT.repeat(2)).unwrap();
+        assert_eq!(reloader.call(uri.clone()).await.unwrap(), "1 ");
+        force_check(&reloader);
+        assert_eq!(reloader.call(uri.clone()).await.unwrap(), "2 ");
+
+        // Invalid certificates keep the previous connector
+        std::fs::write(
+            file.path(),
+            "-----BEGIN CERTIFICATE-----\n!!!\n-----END CERTIFICATE-----\n",
+        )
+        .unwrap();
+        force_check(&reloader);
+        assert_eq!(reloader.call(uri.clone()).await.unwrap(), "2 ");
+    }
+
+    #[tokio::test]
+    async fn reloads_changed_client_certificates() {
+        let cert = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(cert.path(), "first").unwrap();
+        let mut config = Config::new(Uri::from_static("https://kubernetes"));
+        config.auth_info.client_certificate = Some(cert.path().to_str().unwrap().to_owned());
+        let paths = cert_files(&config);
+        assert_eq!(paths, [cert.path()]);
+        let mut reloader = CertReloader::new(connector(&config).unwrap(), config, paths, connector);
+        let uri = Uri::from_static("https://kubernetes");
+
+        assert_eq!(reloader.call(uri.clone()).await.unwrap(), "0 first");
+        std::fs::write(cert.path(), "second").unwrap();
+        force_check(&reloader);
+        assert_eq!(reloader.call(uri.clone()).await.unwrap(), "0 second");
+
+        // A connector that fails to build keeps the previous one
+        std::fs::write(cert.path(), "broken").unwrap();
+        force_check(&reloader);
+        assert_eq!(reloader.call(uri.clone()).await.unwrap(), "0 second");
+    }
+
+    #[test]
+    fn inline_certificates_are_not_watched() {
+        let mut config = Config::new(Uri::from_static("https://kubernetes"));
+        config.auth_info.client_certificate = Some("/tls.crt".into());
+        config.auth_info.client_certificate_data = Some("aGVsbG8K".into());
+        config.auth_info.client_key = Some("/tls.key".into());
+        assert_eq!(cert_files(&config), [PathBuf::from("/tls.key")]);
+    }
+}
@@ -35,6 +35,14 @@ pub mod rustls_tls {
         /// Failed to add a root certificate
         #[error("failed to add a root certificate: {0}")]
         AddRootCertificate(#[source] Box<dyn std::error::Error + Send + Sync>),
+
+        /// Cipher suite is not known to rustls
+        #[error("unknown cipher suite: {0}")]
+        UnknownCipherSuite(String),
+
+        /// Cipher suites cannot be used with any supported TLS version
+        #[error("invalid cipher suites: {0}")]
+        InvalidCipherSuites(#[source] rustls::Error),
     }
 
     /// Create `rustls::ClientConfig`.
@@ -42,13 +50,25 @@ pub mod rustls_tls {
         identity_pem: Option<&[u8]>,
         root_certs: Option<&[Vec<u8>]>,
         accept_invalid: bool,
+        cipher_suites: Option<&[String]>,
     ) -> Result<ClientConfig, Error> {
+        let versions_builder = match cipher_suites {
+            Some(names) => ClientConfig::builder()
+                .with_cipher_suites(
+                    &names
+                        .iter()
+                        .map(|name| cipher_suite(name))
+                        .collect::<Result<Vec<_>, _>>()?,
+                )
+                .with_safe_default_kx_groups()
+                .with_safe_default_protocol_versions()
+                .map_err(Error::InvalidCipherSuites)?,
+            None => ClientConfig::builder().with_safe_defaults(),
+        };
         let config_builder = if let Some(certs) = root_certs {
-            ClientConfig::builder()
-                .with_safe_defaults()
-                .with_root_certificates(root_store(certs)?)
+            versions_builder.with_root_certificates(root_store(certs)?)
         } else {
-            ClientConfig::builder().with_safe_defaults().with_native_roots()
+            versions_builder.with_native_roots()
         };
 
         let mut client_config = if let Some((chain, pkey)) = identity_pem.map(client_auth).transpose()? {
@@ -67,6 +87,20 @@ pub mod rustls_tls {
         Ok(client_config)
     }
 
+    /// Look up a cipher suite by its IANA name
+    ///
+    /// rustls prefixes TLS 1.3 suites with `TLS13_` rather than `TLS_`, so both are accepted.
+    fn cipher_suite(name: &str) -> Result<rustls::SupportedCipherSuite, Error> {
+        rustls::ALL_CIPHER_SUITES
+            .iter()
+            .find(|suite| {
+                let rustls_name = format!("{:?}", suite.suite());
+                rustls_name == name || rustls_name.replacen("TLS13_", "TLS_", 1) == name
+            })
+            .copied()
+            .ok_or_else(|| Error::UnknownCipherSuite(name.to_owned()))
+    }
+
     fn root_store(root_certs: &[Vec<u8>]) -> Result<rustls::RootCertStore, Error> {
         let mut root_store = rustls::RootCertStore::empty();
         for der in root_certs {
@@ -142,7 +176,7 @@ pub mod rustls_tls {
 pub mod openssl_tls {
     use openssl::{
         pkey::PKey,
-        ssl::{SslConnector, SslConnectorBuilder, SslMethod},
+        ssl::{cipher_name, SslConnector, SslConnectorBuilder, SslMethod, SslVersion},
         x509::X509,
     };
     use thiserror::Error;
@@ -197,12 +231,21 @@ pub mod openssl_tls {
         /// Failed to add a root certificate
         #[error("failed to add a root certificate: {0}")]
         AddRootCertificate(#[source] openssl::error::ErrorStack),
+
+        /// Cipher suite is not known to OpenSSL
+        #[error("unknown cipher suite: {0}")]
+        UnknownCipherSuite(String),
+
+        /// Failed to restrict the cipher suites
+        #[error("failed to set cipher suites: {0}")]
+        SetCipherSuites(#[source] openssl::error::ErrorStack),
     }
 
     /// Create `openssl::ssl::SslConnectorBuilder` required for `hyper_openssl::HttpsConnector`.
     pub fn ssl_connector_builder(
         identity_pem: Option<&Vec<u8>>,
         root_certs: Option<&Vec<Vec<u8>>>,
+        cipher_suites: Option<&Vec<String>>,
     ) -> Result<SslConnectorBuilder, SslConnectorError> {
         let mut builder =
             SslConnector::builder(SslMethod::tls()).map_err(SslConnectorError::CreateBuilder)?;
@@ -236,6 +279,78 @@ pub mod openssl_tls {
             }
         }
 
+        if let Some(names) = cipher_suites {
+            // TLS 1.3 suites are configured separately, and OpenSSL already uses their IANA names
+            let (tls13, tls12): (Vec<&str>, Vec<&str>) = names
+                .iter()
+                .map(String::as_str)
+                .partition(|name| is_tls13_suite(name));
+            let tls12 = tls12
+                .into_iter()
+                .map(|name| match cipher_name(name) {
+                    "(NONE)" => Err(SslConnectorError::UnknownCipherSuite(name.to_owned())),
+                    openssl_name => Ok(openssl_name),
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            builder
+                .set_ciphersuites(&tls13.join(":"))
+                .map_err(SslConnectorError::SetCipherSuites)?;
+            if tls12.is_empty() {
+                // OpenSSL rejects an empty cipher list, so rule out TLS 1.2 entirely instead
+                builder
+                    .set_min_proto_version(Some(SslVersion::TLS1_3))
+                    .map_err(SslConnectorError::SetCipherSuites)?;
+            } else {
+                builder
+                    .set_cipher_list(&tls12.join(":"))
+                    .map_err(SslConnectorError::SetCipherSuites)?;
+            }
+        }
+
         Ok(builder)
     }
+
+    fn is_tls13_suite(name: &str) -> bool {
+        matches!(
+            name,
+            "TLS_AES_128_GCM_SHA256"
+                | "TLS_AES_256_GCM_SHA384"
+                | "TLS_CHACHA20_POLY1305_SHA256"
+                | "TLS_AES_128_CCM_SHA256"
+                | "TLS_AES_128_CCM_8_SHA256"
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "rustls-tls")]
+    #[test]
+    fn rustls_cipher_suites() {
+        use super::rustls_tls::{rustls_client_config, Error};
+
+        let suites = ["TLS_AES_128_GCM_SHA256", "TLS_ECDHE_RSA_WITH_AES_128_GCM_SHA256"].map(String::from);
+        rustls_client_config(None, Some(&[]), false, Some(&suites)).unwrap();
+        assert!(matches!(
+            rustls_client_config(None, Some(&[]), false, Some(&["TLS_NULL".to_owned()])),
+            Err(Error::UnknownCipherSuite(name)) if name == "TLS_NULL"
+        ));
+    }
+
+    #[cfg(feature = "openssl-tls")]
+    #[test]
+    fn openssl_cipher_suites() {
+        use super::openssl_tls::{ssl_connector_builder, SslConnectorError};
+
+        let suites = vec![
+            "TLS_AES_128_GCM_SHA256".to_owned(),
+            "TLS_ECDHE_RSA_WITH_AES_128_GCM_SHA256".to_owned(),
+        ];
+        ssl_connector_builder(None, None, Some(&suites)).unwrap();
+        ssl_connector_builder(None, None, Some(&vec!["TLS_AES_256_GCM_SHA384".to_owned()])).unwrap();
+        assert!(matches!(
+            ssl_connector_builder(None, None, Some(&vec!["TLS_NULL".to_owned()])),
+            Err(SslConnectorError::UnknownCipherSuite(name)) if name == "TLS_NULL"
+        ));
+    }
 }
@@ -0,0 +1,38 @@
+//! Handling the warnings that the apiserver returns in `Warning` headers
+use kube_core::response::{ResponseMetadata, Warning};
+
+/// Receives the warnings of all responses to a [`Client`](crate::Client)
+///
+/// Set with [`Client::with_warning_handler`](crate::Client::with_warning_handler). Closures that take
+/// the [`Warning`] and the [`ResponseMetadata`] it was received with implement this trait.
+pub trait WarningHandler: Send + Sync + 'static {
+    /// Handle one `warning` of a response with the given `metadata`
+    fn handle_warning(&self, warning: &Warning, metadata: &ResponseMetadata);
+}
+
+impl<F> WarningHandler for F
+where
+    F: Fn(&Warning, &ResponseMetadata) + Send + Sync + 'static,
+{
+    fn handle_warning(&self, warning: &Warning, metadata: &ResponseMetadata) {
+        self(warning, metadata);
+    }
+}
+
+/// [`WarningHandler`] that logs warnings at `WARN` level, which is the default of a [`Client`](crate::Client)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LogWarnings;
+
+impl WarningHandler for LogWarnings {
+    fn handle_warning(&self, warning: &Warning, metadata: &ResponseMetadata) {
+        tracing::warn!(audit_id = metadata.audit_id.as_deref(), "{}", warning.text);
+    }
+}
+
+/// [`WarningHandler`] that ignores all warnings
+#[derive(Debug, Clone, Copy, Default)]
+pub struct IgnoreWarnings;
+
+impl WarningHandler for IgnoreWarnings {
+    fn handle_warning(&self, _warning: &Warning, _metadata: &ResponseMetadata) {}
+}
@@ -188,6 +188,8 @@ pub struct AuthInfo {
     pub token_file: Option<String>,
 
     /// Path to a client cert file for TLS.
+    ///
+    /// The default client stack checks the file for changes once a minute, so that a rotated certificate is used for new connections.
     #[serde(rename = "client-certificate")]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub client_certificate: Option<String>,
@@ -197,6 +199,8 @@ pub struct AuthInfo {
     pub client_certificate_data: Option<String>,
 
     /// Path to a client key file for TLS.
+    ///
+    /// Like `client_certificate`, the file is checked for changes by the default client stack.
     #[serde(rename = "client-key")]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub client_key: Option<String>,
@@ -217,6 +221,14 @@ pub struct AuthInfo {
     #[serde(rename = "as-groups")]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub impersonate_groups: Option<Vec<String>>,
+    /// The uid to impersonate.
+    #[serde(rename = "as-uid")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub impersonate_uid: Option<String>,
+    /// Extra information about the impersonated user, such as scopes.
+    #[serde(rename = "as-user-extra")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub impersonate_user_extra: Option<HashMap<String, Vec<String>>>,
 
     /// Specifies a custom authentication plugin for the kubernetes cluster.
     #[serde(rename = "auth-provider")]
@@ -278,6 +290,86 @@ pub struct ExecConfig {
     #[serde(rename = "interactiveMode")]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub interactive_mode: Option<ExecInteractiveMode>,
+
+    /// ProvideClusterInfo determines whether or not to provide cluster information,
+    /// which could potentially contain very large CA data, to this exec plugin as a
+    /// part of the KUBERNETES_EXEC_INFO environment variable.
+    #[serde(rename = "provideClusterInfo")]
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub provide_cluster_info: bool,
+
+    /// Cluster information to pass to the plugin when `provide_cluster_info` is set.
+    ///
+    /// This is filled in from the kubeconfig's cluster when the kubeconfig is loaded, and cannot be specified on disk.
+    #[serde(skip)]
+    pub cluster: Option<ExecAuthCluster>,
+}
+
+/// Cluster information passed to exec plugins that set `provideClusterInfo`
+///
+/// This corresponds to the `Cluster` of the `client.authentication.k8s.io` `ExecCredential` spec.
+#[derive(Clone, Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(test, derive(PartialEq, Eq))]
+pub struct ExecAuthCluster {
+    /// The address of the kubernetes cluster (https://hostname:port).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub server: Option<String>,
+    /// Name used to check server certificate.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tls_server_name: Option<String>,
+    /// Skips the validity check for the server's certificate.
+    #[serde(rename = "insecureSkipTLSVerify")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub insecure_skip_tls_verify: Option<bool>,
+    /// PEM-encoded certificate authority certificates.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(with = "base64serde")]
+    pub certificate_authority_data: Option<Vec<u8>>,
+    /// URL to the proxy to be used for all requests to this cluster.
+    #[serde(rename = "proxyURL")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proxy_url: Option<String>,
+    /// Plugin specific configuration, from the cluster's `client.authentication.k8s.io/exec` extension.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub config: Option<serde_json::Value>,
+}
+
+/// The name of the cluster extension that holds exec plugin configuration
+const CLUSTER_EXTENSION_KEY: &str = "client.authentication.k8s.io/exec";
+
+impl TryFrom<&Cluster> for ExecAuthCluster {
+    type Error = KubeconfigError;
+
+    fn try_from(cluster: &Cluster) -> Result<Self, KubeconfigError> {
+        Ok(Self {
+            server: cluster.server.clone(),
+            tls_server_name: cluster.tls_server_name.clone(),
+            insecure_skip_tls_verify: cluster.insecure_skip_tls_verify,
+            certificate_authority_data: cluster.load_certificate_authority()?,
+            proxy_url: cluster.proxy_url.clone(),
+            config: cluster.extensions.as_ref().and_then(|extensions| {
+                extensions
+                    .iter()
+                    .find(|extension| extension.name == CLUSTER_EXTENSION_KEY)
+                    .map(|extension| extension.extension.clone())
+            }),
+        })
+    }
+}
+
+mod base64serde {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(v: &Option<Vec<u8>>, s: S) -> Result<S::Ok, S::Error> {
+        v.as_ref().map(base64::encode).serialize(s)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Option<Vec<u8>>, D::Error> {
+        Option::<String>::deserialize(d)?
+            .map(|data| base64::decode(data).map_err(serde::de::Error::custom))
+            .transpose()
+    }
 }
 
 /// ExecInteractiveMode define the interactity of the child process
@@ -396,15 +488,8 @@ impl Kubeconfig {
     ///
     /// Panics if `KUBECONFIG` value contains the NUL character.
     pub fn from_env() -> Result<Option<Self>, KubeconfigError> {
-        match std::env::var_os(KUBECONFIG) {
-            Some(value) => {
-                let paths = std::env::split_paths(&value)
-                    .filter(|p| !p.as_os_str().is_empty())
-                    .collect::<Vec<_>>();
-                if paths.is_empty() {
-                    return Ok(None);
-                }
-
+        match env_paths() {
+            Some(paths) => {
                 let merged = paths.iter().try_fold(Kubeconfig::default(), |m, p| {
                     Kubeconfig::read_from(p).and_then(|c| m.merge(c))
                 })?;
@@ -415,6 +500,12 @@ impl Kubeconfig {
         }
     }
 
+    /// The files that [`Kubeconfig::read`] loads, in order
+    #[cfg(feature = "oidc")]
+    pub(crate) fn paths() -> Vec<PathBuf> {
+        env_paths().unwrap_or_else(|| default_kube_path().into_iter().collect())
+    }
+
     /// Merge kubeconfig file according to the rules described in
     /// <https://kubernetes.io/docs/concepts/configuration/organize-cluster-access-kubeconfig/#merging-kubeconfig-files>
     ///
@@ -525,6 +616,81 @@ impl AuthInfo {
     }
 }
 
+impl AuthProviderConfig {
+    /// An auth provider that authenticates against GKE using Google's application default credentials
+    ///
+    /// Credentials are looked up in the same order as the Go client does:
+    /// the `GOOGLE_APPLICATION_CREDENTIALS` service account key, gcloud's application default credentials,
+    /// and finally the metadata server, which covers workload identity when running on GCP.
+    /// Tokens are cached and only requested again once they are about to expire.
+    ///
+    /// No scopes means `cloud-platform` and `userinfo.email`. This requires the `oauth` feature:
+    ///
+    /// ```no_run
+    /// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+    /// use kube::{config::AuthProviderConfig, Client, Config};
+    ///
+    /// let mut config = Config::new("https://35.1.2.3".parse()?);
+    /// config.auth_info.auth_provider = Some(AuthProviderConfig::gcp(Vec::<String>::new()));
+    /// let client = Client::try_from(config)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn gcp<S: Into<String>>(scopes: impl IntoIterator<Item = S>) -> Self {
+        let scopes = scopes.into_iter().map(Into::into).collect::<Vec<_>>();
+        let mut config = HashMap::new();
+        if !scopes.is_empty() {
+            config.insert("scopes".into(), scopes.join(","));
+        }
+        Self {
+            name: "gcp".into(),
+            config,
+        }
+    }
+
+    /// An auth provider that logs in to Azure AD integrated clusters (such as AKS) with a device code
+    ///
+    /// The login instructions are printed to stderr the first time a token is needed,
+    /// after which the refresh token is used. This requires the `azure` feature.
+    pub fn azure_device_code(tenant_id: impl Into<String>, client_id: impl Into<String>) -> Self {
+        Self::azure([("tenant-id", tenant_id.into()), ("client-id", client_id.into())])
+    }
+
+    /// An auth provider that logs in to Azure AD integrated clusters as a service principal
+    ///
+    /// This requires the `azure` feature.
+    pub fn azure_client_credentials(
+        tenant_id: impl Into<String>,
+        client_id: impl Into<String>,
+        client_secret: impl Into<String>,
+    ) -> Self {
+        Self::azure([
+            ("tenant-id", tenant_id.into()),
+            ("client-id", client_id.into()),
+            ("client-secret", client_secret.into()),
+        ])
+    }
+
+    /// An auth provider that logs in to Azure AD integrated clusters with the managed identity of the host
+    ///
+    /// `client_id` selects a user-assigned identity, otherwise the system-assigned identity is used.
+    /// This requires the `azure` feature.
+    pub fn azure_managed_identity(client_id: Option<String>) -> Self {
+        let mut provider = Self::azure([("use-managed-identity", "true".to_owned())]);
+        if let Some(client_id) = client_id {
+            provider.config.insert("client-id".into(), client_id);
+        }
+        provider
+    }
+
+    fn azure<const N: usize>(config: [(&str, String); N]) -> Self {
+        Self {
+            name: "azure".into(),
+            config: config.into_iter().map(|(k, v)| (k.to_owned(), v)).collect(),
+        }
+    }
+}
+
 fn load_from_base64_or_file<P: AsRef<Path>>(
     value: &Option<&str>,
     file: &Option<P>,
@@ -553,6 +719,15 @@ fn ensure_trailing_newline(mut data: Vec<u8>) -> Vec<u8> {
     data
 }
 
+/// Returns the kubeconfig paths listed in `KUBECONFIG`, if there are any.
+fn env_paths() -> Option<Vec<PathBuf>> {
+    let value = std::env::var_os(KUBECONFIG)?;
+    let paths = std::env::split_paths(&value)
+        .filter(|p| !p.as_os_str().is_empty())
+        .collect::<Vec<_>>();
+    (!paths.is_empty()).then_some(paths)
+}
+
 /// Returns kubeconfig path from `$HOME/.kube/config`.
 fn default_kube_path() -> Option<PathBuf> {
     use dirs::home_dir;
@@ -800,6 +975,25 @@ password:
         assert!(authinfo.password.is_none());
     }
 
+    #[test]
+    fn authinfo_deserialize_impersonation() {
+        let authinfo_yaml = r#"
+as: jane
+as-groups: [developers]
+as-uid: "1234"
+as-user-extra:
+  scopes: [view, edit]
+"#;
+        let authinfo: AuthInfo = serde_yaml::from_str(authinfo_yaml).unwrap();
+        assert_eq!(authinfo.impersonate.as_deref(), Some("jane"));
+        assert_eq!(authinfo.impersonate_groups, Some(vec!["developers".to_string()]));
+        assert_eq!(authinfo.impersonate_uid.as_deref(), Some("1234"));
+        assert_eq!(authinfo.impersonate_user_extra.unwrap()["scopes"], vec![
+            "view".to_string(),
+            "edit".to_string()
+        ]);
+    }
+
     #[test]
     fn authinfo_debug_does_not_output_password() {
         let authinfo_yaml = r#"
@@ -814,11 +1008,78 @@ password: kube_rs
         token: None, token_file: None, client_certificate: None, \
         client_certificate_data: None, client_key: None, \
         client_key_data: None, impersonate: None, \
-        impersonate_groups: None, \
+        impersonate_groups: None, impersonate_uid: None, \
+        impersonate_user_extra: None, \
         auth_provider: None, \
         exec: None \
         }";
 
         assert_eq!(authinfo_debug_output, expected_output)
     }
+
+    #[tokio::test]
+    async fn exec_provide_cluster_info() {
+        let config_yaml = r#"
+apiVersion: v1
+clusters:
+- cluster:
+    certificate-authority-data: aGVsbG8K
+    server: https://eks.amazonaws.com
+    extensions:
+    - name: client.authentication.k8s.io/exec
+      extension:
+        audience: foo
+  name: eks
+contexts:
+- context:
+    cluster: eks
+    user: eks
+  name: eks
+current-context: eks
+kind: Config
+users:
+- name: eks
+  user:
+    exec:
+      apiVersion: client.authentication.k8s.io/v1
+      command: aws
+      provideClusterInfo: true
+"#;
+        let config = Kubeconfig::from_yaml(config_yaml).unwrap();
+        let loader = super::super::file_loader::ConfigLoader::load(config, None, None, None)
+            .await
+            .unwrap();
+        let exec = loader.user.exec.unwrap();
+        assert!(exec.provide_cluster_info);
+        assert_eq!(
+            exec.cluster,
+            Some(ExecAuthCluster {
+                server: Some("https://eks.amazonaws.com".into()),
+                certificate_authority_data: Some(b"hello\n".to_vec()),
+                config: Some(serde_json::json!({"audience": "foo"})),
+                ..Default::default()
+            })
+        );
+        assert_eq!(
+            serde_json::to_value(exec.cluster.unwrap()).unwrap(),
+            serde_json::json!({
+                "server": "https://eks.amazonaws.com",
+                "certificateAuthorityData": "aGVsbG8K",
+                "config": {"audience": "foo"},
+            })
+        );
+    }
+
+    #[test]
+    fn gcp_auth_provider() {
+        let provider = AuthProviderConfig::gcp(Vec::<String>::new());
+        assert_eq!(provider.name, "gcp");
+        assert!(provider.config.is_empty());
+
+        let provider = AuthProviderConfig::gcp(["https://www.googleapis.com/auth/cloud-platform", "openid"]);
+        assert_eq!(
+            provider.config.get("scopes").map(String::as_str),
+            Some("https://www.googleapis.com/auth/cloud-platform,openid")
+        );
+    }
 }
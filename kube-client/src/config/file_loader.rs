@@ -1,3 +1,5 @@
+use std::net::IpAddr;
+
 use super::{
     file_config::{AuthInfo, Cluster, Context, Kubeconfig},
     KubeconfigError,
@@ -83,12 +85,15 @@ impl ConfigLoader {
             .ok_or_else(|| KubeconfigError::LoadClusterOfContext(cluster_name.clone()))?;
 
         let user_name = user.unwrap_or(&current_context.user);
-        let user = config
+        let mut user = config
             .auth_infos
             .iter()
             .find(|named_user| &named_user.name == user_name)
             .and_then(|named_user| named_user.auth_info.clone())
             .ok_or_else(|| KubeconfigError::FindUser(user_name.clone()))?;
+        if let Some(exec) = user.exec.as_mut().filter(|exec| exec.provide_cluster_info) {
+            exec.cluster = Some((&cluster).try_into()?);
+        }
 
         Ok(ConfigLoader {
             current_context,
@@ -109,20 +114,125 @@ impl ConfigLoader {
 
     pub fn proxy_url(&self) -> Result<Option<http::Uri>, KubeconfigError> {
         let nonempty = |o: Option<String>| o.filter(|s| !s.is_empty());
+        let env = |names: [&str; 2]| {
+            names
+                .into_iter()
+                .find_map(|name| nonempty(std::env::var(name).ok()))
+        };
 
-        if let Some(proxy) = nonempty(self.cluster.proxy_url.clone())
-            .or_else(|| nonempty(std::env::var("HTTP_PROXY").ok()))
-            .or_else(|| nonempty(std::env::var("http_proxy").ok()))
-            .or_else(|| nonempty(std::env::var("HTTPS_PROXY").ok()))
-            .or_else(|| nonempty(std::env::var("https_proxy").ok()))
-        {
-            Ok(Some(
-                proxy
-                    .parse::<http::Uri>()
-                    .map_err(KubeconfigError::ParseProxyUrl)?,
-            ))
-        } else {
+        // Like client-go, an explicit proxy-url takes precedence over the environment,
+        // where the variable is picked by the scheme of the cluster url and NO_PROXY is honored
+        let (proxy, from_env) = match nonempty(self.cluster.proxy_url.clone()) {
+            Some(proxy) => (Some(proxy), false),
+            None => {
+                let server = self
+                    .cluster
+                    .server
+                    .as_deref()
+                    .and_then(|server| server.parse::<http::Uri>().ok());
+                let proxy = match server.as_ref().and_then(http::Uri::scheme_str) {
+                    Some("http") => env(["HTTP_PROXY", "http_proxy"]),
+                    _ => env(["HTTPS_PROXY", "https_proxy"]),
+                };
+                let bypass = env(["NO_PROXY", "no_proxy"]).map_or(false, |no_proxy| {
+                    server
+                        .as_ref()
+                        .and_then(http::Uri::host)
+                        .map_or(false, |host| no_proxy_matches(&no_proxy, host))
+                });
+                (proxy.filter(|_| !bypass), true)
+            }
+        };
+
+        proxy
+            .map(|proxy| parse_proxy_url(&proxy, from_env))
+            .transpose()
+            .map(Option::flatten)
+    }
+}
+
+/// Parses a proxy url, checking that its scheme is supported
+///
+/// Proxies from the environment are shared with other tools, which may support more schemes
+/// (like `https://`), so those are ignored with a warning rather than failing the config.
+fn parse_proxy_url(proxy: &str, from_env: bool) -> Result<Option<http::Uri>, KubeconfigError> {
+    let proxy = proxy
+        .parse::<http::Uri>()
+        .map_err(KubeconfigError::ParseProxyUrl)?;
+    match proxy.scheme_str() {
+        Some("http" | "socks5" | "socks5h") => Ok(Some(proxy)),
+        scheme if from_env => {
+            tracing::warn!(
+                ?scheme,
+                "Ignoring proxy from the environment with an unsupported scheme"
+            );
             Ok(None)
         }
+        _ => Err(KubeconfigError::UnsupportedProxyScheme(proxy.to_string())),
+    }
+}
+
+/// Whether `host` is excluded from proxying by a `NO_PROXY` list
+///
+/// Entries are hostnames that also match their subdomains (optionally with a leading `.`),
+/// IP addresses, CIDR ranges, or `*` to disable proxying entirely.
+fn no_proxy_matches(no_proxy: &str, host: &str) -> bool {
+    let host = host
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .to_ascii_lowercase();
+    let ip = host.parse::<IpAddr>().ok();
+    no_proxy
+        .split(',')
+        .map(|entry| entry.trim().to_ascii_lowercase())
+        .filter(|entry| !entry.is_empty())
+        .any(|entry| {
+            if entry == "*" {
+                return true;
+            }
+            if let Some((net, prefix)) = entry.split_once('/') {
+                return match (ip, net.parse::<IpAddr>(), prefix.parse::<u32>()) {
+                    (Some(IpAddr::V4(ip)), Ok(IpAddr::V4(net)), Ok(prefix)) if prefix <= 32 => {
+                        let mask = u32::MAX.checked_shl(32 - prefix).unwrap_or(0);
+                        u32::from(ip) & mask == u32::from(net) & mask
+                    }
+                    (Some(IpAddr::V6(ip)), Ok(IpAddr::V6(net)), Ok(prefix)) if prefix <= 128 => {
+                        let mask = u128::MAX.checked_shl(128 - prefix).unwrap_or(0);
+                        u128::from(ip) & mask == u128::from(net) & mask
+                    }
+                    _ => false,
+                };
+            }
+            let entry = entry.trim_start_matches('.');
+            host == entry || host.ends_with(&format!(".{entry}"))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{no_proxy_matches, parse_proxy_url};
+
+    #[test]
+    fn no_proxy() {
+        assert!(no_proxy_matches("*", "kubernetes.example.com"));
+        assert!(no_proxy_matches(
+            "localhost, example.com",
+            "kubernetes.example.com"
+        ));
+        assert!(no_proxy_matches(".example.com", "example.com"));
+        assert!(!no_proxy_matches("example.com", "notexample.com"));
+        assert!(no_proxy_matches("10.0.0.0/8", "10.96.0.1"));
+        assert!(!no_proxy_matches("10.0.0.0/8", "192.168.0.1"));
+        assert!(no_proxy_matches("fd00::/8", "[fd00::1]"));
+        assert!(no_proxy_matches("192.168.0.1", "192.168.0.1"));
+        assert!(!no_proxy_matches("", "192.168.0.1"));
+    }
+
+    #[test]
+    fn unsupported_proxy_scheme_from_env_is_ignored() {
+        let proxy = parse_proxy_url("socks5h://proxy:1080", true).unwrap();
+        assert_eq!(proxy.unwrap().scheme_str(), Some("socks5h"));
+        assert!(parse_proxy_url("https://proxy:3128", true).unwrap().is_none());
+        assert!(parse_proxy_url("https://proxy:3128", false).is_err());
     }
 }
@@ -1,4 +1,4 @@
-use std::env;
+use std::{env, path::PathBuf};
 use thiserror::Error;
 
 const SERVICE_HOSTENV: &str = "KUBERNETES_SERVICE_HOST";
@@ -91,6 +91,10 @@ pub fn token_file() -> String {
     SERVICE_TOKENFILE.to_owned()
 }
 
+pub fn cert_file() -> PathBuf {
+    PathBuf::from(SERVICE_CERTFILE)
+}
+
 /// Returns certification from specified path in cluster.
 pub fn load_cert() -> Result<Vec<Vec<u8>>, Error> {
     let certs = std::fs::read(SERVICE_CERTFILE).map_err(Error::ReadCertificateBundle)?;
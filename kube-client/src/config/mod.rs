@@ -81,6 +81,10 @@ pub enum KubeconfigError {
     #[error("failed to parse proxy url: {0}")]
     ParseProxyUrl(#[source] http::uri::InvalidUri),
 
+    /// Proxy url has a scheme other than `http`, `socks5`, or `socks5h`
+    #[error("unsupported proxy url scheme: {0}")]
+    UnsupportedProxyScheme(String),
+
     /// Failed to load certificate authority
     #[error("failed to load certificate authority")]
     LoadCertificateAuthority(#[source] LoadDataError),
@@ -132,29 +136,139 @@ pub struct Config {
     pub default_namespace: String,
     /// The configured root certificate
     pub root_cert: Option<Vec<Vec<u8>>>,
+    /// File that the `root_cert` was loaded from
+    ///
+    /// When set, the default client stack checks the file for changes once a minute, and uses the new
+    /// certificates for new connections. This is set for in-cluster configs and for kubeconfigs that reference
+    /// a `certificate-authority` file, so that a rotated cluster CA is picked up.
+    pub root_cert_file: Option<PathBuf>,
     /// Set the timeout for connecting to the Kubernetes API.
     ///
     /// A value of `None` means no timeout
     pub connect_timeout: Option<std::time::Duration>,
     /// Set the timeout for the Kubernetes API response.
     ///
+    /// This limits the time that a single read from the connection may take, rather than the whole response,
+    /// so watches that keep receiving events are not interrupted.
     /// A value of `None` means no timeout
     pub read_timeout: Option<std::time::Duration>,
     /// Set the timeout for the Kubernetes API request.
     ///
+    /// Like the `read_timeout`, this limits a single write to the connection.
     /// A value of `None` means no timeout
     pub write_timeout: Option<std::time::Duration>,
+    /// Set the timeout for a whole request, until its response body has been read.
+    ///
+    /// Watches and followed logs are exempt from this timeout, because they are expected to stream for a long time.
+    /// A value of `None` means no timeout
+    pub request_timeout: Option<std::time::Duration>,
+    /// Close connections that have been idle in the connection pool for this long
+    ///
+    /// A value of `None` means idle connections are kept until the server closes them, which can leave
+    /// dead connections in the pool behind load balancers that drop idle connections silently.
+    pub pool_idle_timeout: Option<std::time::Duration>,
+    /// The maximum number of idle connections that are kept in the connection pool
+    ///
+    /// A value of `None` means no limit
+    pub pool_max_idle_per_host: Option<usize>,
+    /// Send TCP keepalive probes on connections that have been idle for this long
+    ///
+    /// A value of `None` means keepalive probes are not enabled.
+    /// This is only applied by the connector of the default client stack, see [`ClientBuilder::try_from_connector`](crate::client::ClientBuilder::try_from_connector).
+    pub tcp_keepalive: Option<std::time::Duration>,
+    /// Send HTTP/2 pings on connections at this interval, also while they are idle
+    ///
+    /// A value of `None` means no pings are sent. This only applies to HTTP/2 connections, which are used
+    /// when the connector negotiates HTTP/2, like a custom connector given to
+    /// [`ClientBuilder::try_from_connector`](crate::client::ClientBuilder::try_from_connector).
+    /// The TLS connectors of the default client stack only offer HTTP/1.1.
+    pub http2_keep_alive_interval: Option<std::time::Duration>,
+    /// Close HTTP/2 connections when a ping is not acknowledged within this time
+    ///
+    /// This only has an effect with [`Config::http2_keep_alive_interval`].
+    /// A value of `None` means the default of hyper, which is 20 seconds.
+    pub http2_keep_alive_timeout: Option<std::time::Duration>,
     /// Whether to accept invalid certificates
     pub accept_invalid_certs: bool,
+    /// Whether to allow a `cluster_url` with a plain `http` scheme, such as the url of `kubectl proxy`
+    ///
+    /// Requests to such a cluster are sent without TLS, including any credentials in `auth_info`,
+    /// so building a client for it fails unless this is set. See [`Config::allow_insecure_http`].
+    pub allow_insecure_http: bool,
     /// Stores information to tell the cluster who you are.
     pub auth_info: AuthInfo,
-    // TODO Actually support proxy or create an example with custom client
-    /// Optional proxy URL.
+    /// Whether ID tokens refreshed by the `oidc` auth provider are written back to the kubeconfig, like `kubectl` does
+    ///
+    /// The tokens are replaced in the file from `KUBECONFIG` (or `~/.kube/config`) that contains the current
+    /// ID token, keeping the rest of the file as it is. Disabled by default.
+    pub persist_refreshed_tokens: bool,
+    /// Optional proxy URL, with a scheme of `http`, `socks5`, or `socks5h`
+    ///
+    /// From the kubeconfig, this is the `proxy-url` of the cluster, or otherwise `HTTPS_PROXY` (or `HTTP_PROXY`
+    /// for `http` clusters) unless the cluster is excluded by `NO_PROXY`. Proxies from the environment with
+    /// other schemes are ignored with a warning.
+    /// Connections are tunneled through it by the `ProxyConnector` of the default client stack.
     pub proxy_url: Option<http::Uri>,
     /// If set, apiserver certificate will be validated to contain this string
     ///
     /// If not set, the `cluster_url` is used instead
     pub tls_server_name: Option<String>,
+    /// Path of a Unix domain socket that the apiserver is listening on
+    ///
+    /// When set, all connections are made to this socket instead of the host of `cluster_url`,
+    /// which is then only used to build request URLs. See [`Config::unix_socket`].
+    pub unix_socket: Option<PathBuf>,
+    /// The TLS stack to use when more than one is enabled
+    ///
+    /// If not set, `openssl-tls` takes precedence over `rustls-tls`.
+    pub tls_backend: Option<TlsBackend>,
+    /// Restrict the cipher suites that may be negotiated with the apiserver
+    ///
+    /// Suites are given by their IANA names, for example `TLS_AES_128_GCM_SHA256` or
+    /// `TLS_ECDHE_ECDSA_WITH_AES_128_GCM_SHA256`, and are understood by both TLS stacks.
+    /// If not set, the defaults of the TLS stack are used.
+    pub tls_cipher_suites: Option<Vec<String>>,
+    /// Log the headers and bodies of requests and responses at `TRACE` level
+    ///
+    /// Credentials and the data of `Secret`s are redacted. This is meant for debugging, because bodies are
+    /// buffered before they are sent or returned. See [`TraceBodiesLayer`](crate::client::middleware::TraceBodiesLayer).
+    pub trace_bodies: bool,
+    /// Compress request bodies of at least this many bytes with gzip
+    ///
+    /// This reduces the traffic of large requests, like server-side applies of big objects, for servers that accept
+    /// compressed requests. Servers that reject them are detected, after which requests are sent uncompressed.
+    /// This is only applied with the `gzip` feature, and ignored otherwise.
+    #[cfg_attr(
+        feature = "gzip",
+        doc = "See [`RequestCompressionLayer`](crate::client::middleware::RequestCompressionLayer)."
+    )]
+    pub request_compression: Option<usize>,
+    /// The `User-Agent` header to send with requests
+    ///
+    /// If not set, no `User-Agent` header is sent.
+    pub user_agent: Option<String>,
+    /// Limit the requests to this many per second on average, like the `QPS` of client-go
+    ///
+    /// A value of `None`, or one that is not positive, means no limit. See [`RateLimitLayer`](crate::client::middleware::RateLimitLayer).
+    pub qps: Option<f64>,
+    /// The number of requests that can be sent at once before `qps` applies, like the `Burst` of client-go
+    ///
+    /// If not set, bursts of `qps` requests (rounded up) are allowed.
+    pub burst: Option<u32>,
+}
+
+/// A TLS stack that can be selected at runtime with [`Config::tls_backend`]
+///
+/// Only the stacks whose features are enabled are available.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TlsBackend {
+    /// [rustls](https://docs.rs/rustls), enabled by the `rustls-tls` feature
+    #[cfg(feature = "rustls-tls")]
+    Rustls,
+    /// [OpenSSL](https://docs.rs/openssl), enabled by the `openssl-tls` feature
+    #[cfg(feature = "openssl-tls")]
+    OpenSsl,
 }
 
 impl Config {
@@ -168,13 +282,41 @@ impl Config {
             cluster_url,
             default_namespace: String::from("default"),
             root_cert: None,
+            root_cert_file: None,
             connect_timeout: Some(DEFAULT_CONNECT_TIMEOUT),
             read_timeout: Some(DEFAULT_READ_TIMEOUT),
             write_timeout: None,
+            request_timeout: None,
+            pool_idle_timeout: Some(DEFAULT_POOL_IDLE_TIMEOUT),
+            pool_max_idle_per_host: None,
+            tcp_keepalive: None,
+            http2_keep_alive_interval: None,
+            http2_keep_alive_timeout: None,
             accept_invalid_certs: false,
+            allow_insecure_http: false,
             auth_info: AuthInfo::default(),
+            persist_refreshed_tokens: false,
             proxy_url: None,
             tls_server_name: None,
+            unix_socket: None,
+            tls_backend: None,
+            tls_cipher_suites: None,
+            trace_bodies: false,
+            request_compression: None,
+            user_agent: None,
+            qps: None,
+            burst: None,
+        }
+    }
+
+    /// Construct a new config for an apiserver that is listening on the Unix domain socket at `path`
+    ///
+    /// This is the equivalent of a kubeconfig with `server: unix:///path/to/socket`, and is useful for
+    /// local (aggregated) apiservers and test harnesses that do not expose TCP.
+    pub fn unix_socket(path: impl Into<PathBuf>) -> Self {
+        Self {
+            unix_socket: Some(path.into()),
+            ..Self::new(http::Uri::from_static(UNIX_SOCKET_CLUSTER_URL))
         }
     }
 
@@ -247,16 +389,33 @@ impl Config {
             cluster_url,
             default_namespace,
             root_cert: Some(root_cert),
+            root_cert_file: Some(incluster_config::cert_file()),
             connect_timeout: Some(DEFAULT_CONNECT_TIMEOUT),
             read_timeout: Some(DEFAULT_READ_TIMEOUT),
             write_timeout: None,
+            request_timeout: None,
+            pool_idle_timeout: Some(DEFAULT_POOL_IDLE_TIMEOUT),
+            pool_max_idle_per_host: None,
+            tcp_keepalive: None,
+            http2_keep_alive_interval: None,
+            http2_keep_alive_timeout: None,
             accept_invalid_certs: false,
+            allow_insecure_http: false,
             auth_info: AuthInfo {
                 token_file: Some(incluster_config::token_file()),
                 ..Default::default()
             },
+            persist_refreshed_tokens: false,
             proxy_url: None,
             tls_server_name: None,
+            unix_socket: None,
+            tls_backend: None,
+            tls_cipher_suites: None,
+            trace_bodies: false,
+            request_compression: None,
+            user_agent: None,
+            qps: None,
+            burst: None,
         })
     }
 
@@ -282,13 +441,24 @@ impl Config {
     }
 
     async fn new_from_loader(loader: ConfigLoader) -> Result<Self, KubeconfigError> {
-        let cluster_url = loader
+        let server = loader
             .cluster
             .server
             .clone()
-            .ok_or(KubeconfigError::MissingClusterUrl)?
-            .parse::<http::Uri>()
-            .map_err(KubeconfigError::ParseClusterUrl)?;
+            .ok_or(KubeconfigError::MissingClusterUrl)?;
+        // `unix:///path/to/socket` is not a valid `http::Uri`, so requests are addressed to localhost instead
+        let (cluster_url, unix_socket) = match server.strip_prefix("unix://") {
+            Some(path) => (
+                http::Uri::from_static(UNIX_SOCKET_CLUSTER_URL),
+                Some(PathBuf::from(path)),
+            ),
+            None => (
+                server
+                    .parse::<http::Uri>()
+                    .map_err(KubeconfigError::ParseClusterUrl)?,
+                None,
+            ),
+        };
 
         let default_namespace = loader
             .current_context
@@ -302,18 +472,44 @@ impl Config {
         if let Some(ca_bundle) = loader.ca_bundle()? {
             root_cert = Some(ca_bundle);
         }
+        // Inline data takes precedence over the file
+        let root_cert_file = match &loader.cluster.certificate_authority_data {
+            Some(_) => None,
+            None => loader.cluster.certificate_authority.clone().map(PathBuf::from),
+        };
 
         Ok(Self {
             cluster_url,
             default_namespace,
             root_cert,
+            root_cert_file,
             connect_timeout: Some(DEFAULT_CONNECT_TIMEOUT),
             read_timeout: Some(DEFAULT_READ_TIMEOUT),
             write_timeout: None,
+            request_timeout: None,
+            pool_idle_timeout: Some(DEFAULT_POOL_IDLE_TIMEOUT),
+            pool_max_idle_per_host: None,
+            tcp_keepalive: None,
+            http2_keep_alive_interval: None,
+            http2_keep_alive_timeout: None,
             accept_invalid_certs,
-            proxy_url: loader.proxy_url()?,
+            allow_insecure_http: false,
+            proxy_url: if unix_socket.is_some() {
+                None
+            } else {
+                loader.proxy_url()?
+            },
             auth_info: loader.user,
+            persist_refreshed_tokens: false,
             tls_server_name: loader.cluster.tls_server_name,
+            unix_socket,
+            tls_backend: None,
+            tls_cipher_suites: None,
+            trace_bodies: false,
+            request_compression: None,
+            user_agent: None,
+            qps: None,
+            burst: None,
         })
     }
 
@@ -327,6 +523,7 @@ impl Config {
     /// - `KUBE_RS_DEBUG_IMPERSONATE_USER`: A Kubernetes user to impersonate, for example: `system:serviceaccount:default:foo` will impersonate the `ServiceAccount` `foo` in the `Namespace` `default`
     /// - `KUBE_RS_DEBUG_IMPERSONATE_GROUP`: A Kubernetes group to impersonate, multiple groups may be specified by separating them with commas
     /// - `KUBE_RS_DEBUG_OVERRIDE_URL`: A Kubernetes cluster URL to use rather than the one specified in the config, useful for proxying traffic through `kubectl proxy`
+    /// - `KUBE_RS_DEBUG_TRACE_BODIES`: Log request and response bodies at `TRACE` level, see [`Config::trace_bodies`]
     pub fn apply_debug_overrides(&mut self) {
         // Log these overrides loudly, to emphasize that this is only a debugging aid, and should not be relied upon in production
         if let Ok(impersonate_user) = std::env::var("KUBE_RS_DEBUG_IMPERSONATE_USER") {
@@ -338,6 +535,10 @@ impl Config {
             tracing::warn!(?impersonate_groups, "impersonating groups");
             self.auth_info.impersonate_groups = Some(impersonate_groups);
         }
+        if std::env::var_os("KUBE_RS_DEBUG_TRACE_BODIES").is_some() {
+            tracing::warn!("tracing request and response bodies");
+            self.trace_bodies = true;
+        }
         if let Ok(url) = std::env::var("KUBE_RS_DEBUG_OVERRIDE_URL") {
             tracing::warn!(?url, "overriding cluster URL");
             match url.parse() {
@@ -355,13 +556,143 @@ impl Config {
         }
     }
 
+    /// Set the namespace that is used when none is given, see [`Api::default_namespaced`](crate::Api::default_namespaced)
+    #[must_use]
+    pub fn default_namespace(mut self, namespace: impl Into<String>) -> Self {
+        self.default_namespace = namespace.into();
+        self
+    }
+
+    /// Authenticate with a bearer `token`
+    #[must_use]
+    pub fn token(mut self, token: impl Into<String>) -> Self {
+        self.auth_info.token = Some(token.into().into());
+        self
+    }
+
+    /// Authenticate with the bearer token in the file at `path`, which is re-read when it is rotated
+    #[must_use]
+    pub fn token_file(mut self, path: impl Into<String>) -> Self {
+        self.auth_info.token_file = Some(path.into());
+        self
+    }
+
+    /// Trust the PEM-encoded certificates in `bundle` as the certificate authorities of the apiserver
+    pub fn root_cert_pem(mut self, bundle: &[u8]) -> Result<Self, KubeconfigError> {
+        self.root_cert = Some(certs(bundle).map_err(KubeconfigError::ParseCertificates)?);
+        self.root_cert_file = None;
+        Ok(self)
+    }
+
+    /// Send `user_agent` as the `User-Agent` header of all requests
+    #[must_use]
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = Some(user_agent.into());
+        self
+    }
+
+    /// Set the timeout for connecting to the apiserver, or `None` for no timeout
+    #[must_use]
+    pub fn connect_timeout(mut self, timeout: impl Into<Option<Duration>>) -> Self {
+        self.connect_timeout = timeout.into();
+        self
+    }
+
+    /// Set the timeout for each read from a connection, or `None` for no timeout
+    #[must_use]
+    pub fn read_timeout(mut self, timeout: impl Into<Option<Duration>>) -> Self {
+        self.read_timeout = timeout.into();
+        self
+    }
+
+    /// Set the timeout for each write to a connection, or `None` for no timeout
+    #[must_use]
+    pub fn write_timeout(mut self, timeout: impl Into<Option<Duration>>) -> Self {
+        self.write_timeout = timeout.into();
+        self
+    }
+
+    /// Set the timeout for whole requests that are not watches, or `None` for no timeout
+    #[must_use]
+    pub fn request_timeout(mut self, timeout: impl Into<Option<Duration>>) -> Self {
+        self.request_timeout = timeout.into();
+        self
+    }
+
+    /// Allow connecting to a `cluster_url` with a plain `http` scheme, without TLS
+    ///
+    /// This is meant for `kubectl proxy`, which authenticates requests on behalf of the client,
+    /// and for apiservers that only run in tests:
+    ///
+    /// ```rust
+    /// # async fn doc() -> Result<(), Box<dyn std::error::Error>> {
+    /// use kube::{Client, Config};
+    /// let config = Config::new("http://127.0.0.1:8001".parse()?).allow_insecure_http(true);
+    /// let client = Client::try_from(config)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn allow_insecure_http(mut self, allow: bool) -> Self {
+        self.allow_insecure_http = allow;
+        self
+    }
+
+    /// Limit requests to `qps` per second on average, with bursts of up to `burst` requests
+    #[must_use]
+    pub fn rate_limit(mut self, qps: f64, burst: u32) -> Self {
+        self.qps = Some(qps);
+        self.burst = Some(burst);
+        self
+    }
+
+    /// Impersonate `user` on all requests, like `kubectl --as`
+    ///
+    /// The authenticated user needs to be allowed to `impersonate` the user (and any groups, uid, and extras)
+    /// for the apiserver to accept the requests.
+    #[must_use]
+    pub fn impersonate(mut self, user: impl Into<String>) -> Self {
+        self.auth_info.impersonate = Some(user.into());
+        self
+    }
+
+    /// Impersonate membership of `groups` on all requests, like `kubectl --as-group`
+    #[must_use]
+    pub fn impersonate_groups<S: Into<String>>(mut self, groups: impl IntoIterator<Item = S>) -> Self {
+        self.auth_info.impersonate_groups = Some(groups.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Impersonate the user with `uid` on all requests, like `kubectl --as-uid`
+    #[must_use]
+    pub fn impersonate_uid(mut self, uid: impl Into<String>) -> Self {
+        self.auth_info.impersonate_uid = Some(uid.into());
+        self
+    }
+
+    /// Add an extra field for the impersonated user, which is sent as an `Impersonate-Extra-<key>` header
+    #[must_use]
+    pub fn impersonate_extra<S: Into<String>>(
+        mut self,
+        key: impl Into<String>,
+        values: impl IntoIterator<Item = S>,
+    ) -> Self {
+        self.auth_info
+            .impersonate_user_extra
+            .get_or_insert_with(Default::default)
+            .entry(key.into())
+            .or_default()
+            .extend(values.into_iter().map(Into::into));
+        self
+    }
+
     /// Client certificate and private key in PEM.
     pub(crate) fn identity_pem(&self) -> Option<Vec<u8>> {
         self.auth_info.identity_pem().ok()
     }
 }
 
-fn certs(data: &[u8]) -> Result<Vec<Vec<u8>>, pem::PemError> {
+pub(crate) fn certs(data: &[u8]) -> Result<Vec<Vec<u8>>, pem::PemError> {
     Ok(pem::parse_many(data)?
         .into_iter()
         .filter_map(|p| {
@@ -377,11 +708,15 @@ fn certs(data: &[u8]) -> Result<Vec<Vec<u8>>, pem::PemError> {
 // https://github.com/kube-rs/kube/issues/146#issuecomment-590924397
 const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
 const DEFAULT_READ_TIMEOUT: Duration = Duration::from_secs(295);
+// Same as the default of hyper
+const DEFAULT_POOL_IDLE_TIMEOUT: Duration = Duration::from_secs(90);
+/// The cluster url that requests are addressed to when connecting over a Unix domain socket
+const UNIX_SOCKET_CLUSTER_URL: &str = "http://localhost";
 
 // Expose raw config structs
 pub use file_config::{
-    AuthInfo, AuthProviderConfig, Cluster, Context, ExecConfig, ExecInteractiveMode, Kubeconfig,
-    NamedAuthInfo, NamedCluster, NamedContext, NamedExtension, Preferences,
+    AuthInfo, AuthProviderConfig, Cluster, Context, ExecAuthCluster, ExecConfig, ExecInteractiveMode,
+    Kubeconfig, NamedAuthInfo, NamedCluster, NamedContext, NamedExtension, Preferences,
 };
 
 #[cfg(test)]
@@ -0,0 +1,167 @@
+//! The aggregated discovery format of `apidiscovery.k8s.io`
+//!
+//! With aggregated discovery, `/api` and `/apis` return all groups along with their resources,
+//! instead of one request being needed per group version. Apiservers that do not support it
+//! ignore the aggregated media types in the `Accept` header, and return the regular lists.
+use super::parse::GroupVersionData;
+use crate::{Client, Error, Result};
+use http::{header::ACCEPT, Request};
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::ObjectMeta;
+use kube_core::discovery::{ApiCapabilities, ApiResource, Scope};
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::Value;
+
+/// Asks for aggregated discovery at the versions we understand, and for the regular lists otherwise
+const AGGREGATED_DISCOVERY_MIME: &str =
+    "application/json;g=apidiscovery.k8s.io;v=v2;as=APIGroupDiscoveryList,\
+     application/json;g=apidiscovery.k8s.io;v=v2beta1;as=APIGroupDiscoveryList,\
+     application/json";
+
+/// The response of a discovery endpoint, depending on the support of the apiserver
+pub(crate) enum Discovered<T> {
+    /// All groups of the endpoint with their resources
+    Aggregated(APIGroupDiscoveryList),
+    /// The regular response of the endpoint, like an `APIGroupList` for `/apis`
+    Legacy(T),
+}
+
+/// Queries a discovery endpoint (`/api` or `/apis`), preferring the aggregated format
+pub(crate) async fn discover<T: DeserializeOwned>(client: &Client, path: &str) -> Result<Discovered<T>> {
+    let req = Request::builder()
+        .uri(path)
+        .header(ACCEPT, AGGREGATED_DISCOVERY_MIME)
+        .body(vec![])
+        .map_err(Error::HttpError)?;
+    let value: Value = client.request(req).await?;
+    if value["kind"] == "APIGroupDiscoveryList" {
+        tracing::debug!(path, "Using aggregated discovery");
+        serde_json::from_value(value).map(Discovered::Aggregated)
+    } else {
+        serde_json::from_value(value).map(Discovered::Legacy)
+    }
+    .map_err(Error::SerdeError)
+}
+
+/// A list of groups with their resources
+#[derive(Deserialize)]
+pub(crate) struct APIGroupDiscoveryList {
+    pub(crate) items: Vec<APIGroupDiscovery>,
+}
+
+/// A group with its resources at all served versions, in order of preference
+#[derive(Deserialize)]
+pub(crate) struct APIGroupDiscovery {
+    #[serde(default)]
+    pub(crate) metadata: ObjectMeta,
+    #[serde(default)]
+    pub(crate) versions: Vec<APIVersionDiscovery>,
+}
+
+impl APIGroupDiscovery {
+    /// Name of the group, empty for the core group
+    pub(crate) fn name(&self) -> &str {
+        self.metadata.name.as_deref().unwrap_or_default()
+    }
+}
+
+/// The resources of a group at a version
+#[derive(Deserialize)]
+pub(crate) struct APIVersionDiscovery {
+    pub(crate) version: String,
+    #[serde(default)]
+    pub(crate) resources: Vec<APIResourceDiscovery>,
+    /// `Stale` when the apiserver serving the version could not be reached,
+    /// in which case the resources may be outdated or missing
+    pub(crate) freshness: Option<String>,
+}
+
+impl APIVersionDiscovery {
+    pub(crate) fn is_stale(&self) -> bool {
+        self.freshness.as_deref() == Some("Stale")
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct APIResourceDiscovery {
+    pub(crate) resource: String,
+    pub(crate) response_kind: Option<ResponseKind>,
+    pub(crate) scope: Scope,
+    #[serde(default)]
+    pub(crate) verbs: Vec<String>,
+    #[serde(default)]
+    pub(crate) short_names: Vec<String>,
+    #[serde(default)]
+    pub(crate) categories: Vec<String>,
+    #[serde(default)]
+    pub(crate) subresources: Vec<APISubresourceDiscovery>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct APISubresourceDiscovery {
+    pub(crate) subresource: String,
+    pub(crate) response_kind: Option<ResponseKind>,
+    #[serde(default)]
+    pub(crate) verbs: Vec<String>,
+}
+
+/// The group, version and kind of the objects that are returned for a resource
+#[derive(Deserialize)]
+pub(crate) struct ResponseKind {
+    #[serde(default)]
+    group: String,
+    #[serde(default)]
+    version: String,
+    kind: String,
+}
+
+impl GroupVersionData {
+    /// Extract the resources of a version of a group from its aggregated discovery
+    pub(crate) fn from_aggregated(group: &str, discovery: APIVersionDiscovery) -> Self {
+        let mut resources = vec![];
+        for res in discovery.resources {
+            // resources without kind are not served as objects
+            let kind = match res.response_kind {
+                Some(kind) => kind,
+                None => continue,
+            };
+            let scope = res.scope;
+            let ar = api_resource(group, &discovery.version, &kind, res.resource);
+            let subresources = res
+                .subresources
+                .into_iter()
+                .map(|sub| {
+                    let kind = sub.response_kind.as_ref().unwrap_or(&kind);
+                    let ar = api_resource(group, &discovery.version, kind, sub.subresource);
+                    (ar, ApiCapabilities::new(scope.clone(), vec![], sub.verbs))
+                })
+                .collect();
+            let caps = ApiCapabilities::new(scope, subresources, res.verbs)
+                .with_short_names(res.short_names)
+                .with_categories(res.categories);
+            resources.push((ar, caps));
+        }
+        GroupVersionData {
+            version: discovery.version,
+            resources,
+        }
+    }
+}
+
+/// Creates an `ApiResource` for a resource of a group version, like `parse::parse_apiresource`
+fn api_resource(group: &str, version: &str, kind: &ResponseKind, plural: String) -> ApiResource {
+    let api_version = if group.is_empty() {
+        version.to_string()
+    } else {
+        format!("{group}/{version}")
+    };
+    let or = |field: &str, default: &str| if field.is_empty() { default } else { field }.to_string();
+    ApiResource {
+        group: or(&kind.group, group),
+        version: or(&kind.version, version),
+        api_version,
+        kind: kind.kind.clone(),
+        plural,
+    }
+}
@@ -1,11 +1,16 @@
-use super::parse::{self, GroupVersionData};
+use super::{
+    aggregated::APIGroupDiscovery,
+    parse::{self, GroupVersionData},
+};
 use crate::{error::DiscoveryError, Client, Error, Result};
 use k8s_openapi::apimachinery::pkg::apis::meta::v1::{APIGroup, APIVersions};
+#[allow(unused_imports)]
 pub use kube_core::discovery::{verbs, ApiCapabilities, ApiResource, Scope};
 use kube_core::{
     gvk::{GroupVersion, GroupVersionKind, ParseGroupVersionError},
     Version,
 };
+use serde::{Deserialize, Serialize};
 use std::{cmp::Reverse, collections::HashMap, iter::Iterator};
 
 /// Describes one API groups collected resources and capabilities.
@@ -61,6 +66,7 @@ use std::{cmp::Reverse, collections::HashMap, iter::Iterator};
 /// [`ApiGroup::versioned_resources`]: crate::discovery::ApiGroup::versioned_resources
 /// [`ApiGroup::recommended_resources`]: crate::discovery::ApiGroup::recommended_resources
 /// [`ApiGroup::recommended_kind`]: crate::discovery::ApiGroup::recommended_kind
+#[derive(Clone, Serialize, Deserialize)]
 pub struct ApiGroup {
     /// Name of the group e.g. apiregistration.k8s.io
     name: String,
@@ -115,6 +121,52 @@ impl ApiGroup {
         Ok(group)
     }
 
+    /// Converts an aggregated discovery of a group, querying the versions that were stale in it
+    pub(crate) async fn query_aggregated(client: &Client, g: APIGroupDiscovery) -> Result<Self> {
+        let key = g.name().to_string();
+        // versions are listed in order of preference
+        let preferred = match g.versions.first() {
+            Some(vers) => vers.version.clone(),
+            None => return Err(Error::Discovery(DiscoveryError::EmptyApiGroup(key))),
+        };
+        let mut data = vec![];
+        for vers in g.versions {
+            if vers.is_stale() {
+                tracing::debug!(
+                    name = key.as_str(),
+                    version = vers.version.as_str(),
+                    "Listing stale group version"
+                );
+                let gv = GroupVersion::gv(&key, &vers.version);
+                data.push(Self::query_gv(client, &gv).await?.data.remove(0));
+            } else {
+                data.push(GroupVersionData::from_aggregated(&key, vers));
+            }
+        }
+        let mut group = ApiGroup {
+            name: key,
+            data,
+            preferred: Some(preferred),
+        };
+        group.sort_versions();
+        Ok(group)
+    }
+
+    /// Keeps the resources that match a predicate, dropping versions without resources
+    ///
+    /// Returns whether any resources are left, as groups must have versions.
+    pub(crate) fn retain(
+        &mut self,
+        predicate: impl Fn(&str, &ApiResource, &ApiCapabilities) -> bool,
+    ) -> bool {
+        let name = &self.name;
+        self.data.retain_mut(|gvd| {
+            gvd.resources.retain(|(ar, caps)| predicate(name, ar, caps));
+            !gvd.resources.is_empty()
+        });
+        !self.data.is_empty()
+    }
+
     fn sort_versions(&mut self) {
         self.data
             .sort_by_cached_key(|gvd| Reverse(Version::parse(gvd.version.as_str()).priority()))
@@ -315,11 +367,7 @@ mod tests {
 
     #[test]
     fn test_resources_by_stability() {
-        let ac = ApiCapabilities {
-            scope: Scope::Namespaced,
-            subresources: vec![],
-            operations: vec![],
-        };
+        let ac = ApiCapabilities::new(Scope::Namespaced, vec![], vec![]);
 
         let testlowversioncr_v1alpha1 = ApiResource {
             group: String::from("kube.rs"),
@@ -0,0 +1,90 @@
+//! On-disk persistence of discovery results
+use super::{ApiGroup, DiscoveryMode};
+use serde::{Deserialize, Serialize};
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    io,
+    path::Path,
+    time::{Duration, SystemTime},
+};
+use tokio::fs;
+
+/// The contents of a discovery cache file
+///
+/// The mode is stored along with the groups, so that a cache written with one set of
+/// filters is not used by a discovery client with another.
+#[derive(Serialize, Deserialize)]
+struct CacheFile<'a> {
+    mode: Cow<'a, DiscoveryMode>,
+    groups: Cow<'a, [ApiGroup]>,
+}
+
+/// Loads the groups from a cache file, if it is younger than `ttl` and was written for `mode`
+///
+/// Returns the time the cache file was written along with the groups.
+pub(crate) async fn load(
+    path: &Path,
+    ttl: Duration,
+    mode: &DiscoveryMode,
+) -> Option<(SystemTime, HashMap<String, ApiGroup>)> {
+    let written = match fs::metadata(path).await.and_then(|m| m.modified()) {
+        Ok(written) => written,
+        Err(err) => {
+            tracing::debug!(?path, %err, "No discovery cache");
+            return None;
+        }
+    };
+    if !written.elapsed().map_or(false, |age| age < ttl) {
+        tracing::debug!(?path, "Discovery cache is stale");
+        return None;
+    }
+    let data = fs::read(path).await;
+    let file: CacheFile = match data.and_then(|data| Ok(serde_json::from_slice(&data)?)) {
+        Ok(file) => file,
+        Err(err) => {
+            tracing::warn!(?path, %err, "Ignoring unreadable discovery cache");
+            return None;
+        }
+    };
+    if file.mode.as_ref() != mode {
+        tracing::debug!(?path, "Discovery cache was written with other filters");
+        return None;
+    }
+    let groups = file
+        .groups
+        .into_owned()
+        .into_iter()
+        .map(|g| (g.name().to_string(), g))
+        .collect();
+    Some((written, groups))
+}
+
+/// Writes the groups to a cache file
+///
+/// The file is replaced atomically, so that concurrent readers never see a partial cache.
+pub(crate) async fn store(
+    path: &Path,
+    mode: &DiscoveryMode,
+    groups: &HashMap<String, ApiGroup>,
+) -> io::Result<()> {
+    let file = CacheFile {
+        mode: Cow::Borrowed(mode),
+        groups: Cow::Owned(groups.values().cloned().collect()),
+    };
+    let data = serde_json::to_vec(&file)?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).await?;
+    }
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, data).await?;
+    fs::rename(&tmp, path).await
+}
+
+/// Removes a cache file, if there is one
+pub(crate) async fn remove(path: &Path) -> io::Result<()> {
+    match fs::remove_file(path).await {
+        Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+        _ => Ok(()),
+    }
+}
@@ -1,10 +1,19 @@
 //! High-level utilities for runtime API discovery.
 
 use crate::{Client, Result};
+use aggregated::{APIGroupDiscoveryList, Discovered};
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::{APIGroupList, APIVersions};
 pub use kube_core::discovery::{verbs, ApiCapabilities, ApiResource, Scope};
 use kube_core::gvk::GroupVersionKind;
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    time::{Duration, SystemTime},
+};
+mod aggregated;
 mod apigroup;
+mod cache;
 pub mod oneshot;
 pub use apigroup::ApiGroup;
 mod parse;
@@ -12,7 +21,11 @@ mod parse;
 // re-export one-shots
 pub use oneshot::{group, pinned_group, pinned_kind};
 
+/// Predicate for the resources to keep in [`Discovery::filter`]
+type ResourcePredicate = dyn Fn(&str, &ApiResource, &ApiCapabilities) -> bool + Send + Sync;
+
 /// How the Discovery client decides what api groups to scan
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
 enum DiscoveryMode {
     /// Only allow explicitly listed apigroups
     Allow(Vec<String>),
@@ -40,7 +53,13 @@ impl DiscoveryMode {
 ///
 /// Discovery can be performed on:
 /// - all api groups (default)
-/// - a subset of api groups (by setting Discovery::filter)
+/// - a subset of api groups (by setting [`Discovery::filter_groups`] or [`Discovery::exclude`])
+///
+/// The discovered resources can be narrowed down further with [`Discovery::filter`].
+///
+/// By default, every [`Discovery::run`] queries the apiserver again. With a [`Discovery::cache_ttl`],
+/// results are reused until they are older than the ttl, and with a [`Discovery::cache_file`] they are
+/// also persisted to disk, so that other processes can reuse them like kubectl's discovery cache.
 ///
 /// To make use of discovered apis, extract one or more [`ApiGroup`]s from it,
 /// or resolve a precise one using [`Discovery::resolve_gvk`](crate::discovery::Discovery::resolve_gvk).
@@ -53,6 +72,10 @@ pub struct Discovery {
     client: Client,
     groups: HashMap<String, ApiGroup>,
     mode: DiscoveryMode,
+    predicate: Option<Box<ResourcePredicate>>,
+    ttl: Duration,
+    cache_file: Option<PathBuf>,
+    refreshed: Option<SystemTime>,
 }
 
 /// Caching discovery interface
@@ -64,13 +87,22 @@ impl Discovery {
     pub fn new(client: Client) -> Self {
         let groups = HashMap::new();
         let mode = DiscoveryMode::Block(vec![]);
-        Self { client, groups, mode }
+        Self {
+            client,
+            groups,
+            mode,
+            predicate: None,
+            ttl: Duration::ZERO,
+            cache_file: None,
+            refreshed: None,
+        }
     }
 
     /// Configure the discovery client to only look for the listed apigroups
     #[must_use]
-    pub fn filter(mut self, allow: &[&str]) -> Self {
+    pub fn filter_groups(mut self, allow: &[&str]) -> Self {
         self.mode = DiscoveryMode::Allow(allow.iter().map(ToString::to_string).collect());
+        self.refreshed = None;
         self
     }
 
@@ -78,12 +110,70 @@ impl Discovery {
     #[must_use]
     pub fn exclude(mut self, deny: &[&str]) -> Self {
         self.mode = DiscoveryMode::Block(deny.iter().map(ToString::to_string).collect());
+        self.refreshed = None;
+        self
+    }
+
+    /// Configure the discovery client to only keep the resources that match a predicate
+    ///
+    /// The predicate is given the name of the group along with the resource and its capabilities.
+    /// Versions and groups without matching resources are dropped.
+    ///
+    /// ```no_run
+    /// use kube::{Client, discovery::{Discovery, verbs, Scope}};
+    /// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = Client::try_default().await?;
+    /// // namespaced resources that can be watched, outside of the metrics apis
+    /// let discovery = Discovery::new(client)
+    ///     .exclude(&["metrics.k8s.io"])
+    ///     .filter(|_group, _ar, caps| {
+    ///         caps.scope == Scope::Namespaced && caps.supports_operations(&[verbs::LIST, verbs::WATCH])
+    ///     })
+    ///     .run()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// Unlike [`Discovery::filter_groups`] and [`Discovery::exclude`], this does not avoid any queries,
+    /// as the resources of a group are only known after querying it.
+    #[must_use]
+    pub fn filter<F>(mut self, predicate: F) -> Self
+    where
+        F: Fn(&str, &ApiResource, &ApiCapabilities) -> bool + Send + Sync + 'static,
+    {
+        self.predicate = Some(Box::new(predicate));
+        self.refreshed = None;
+        self
+    }
+
+    /// Configure how long discovered apis are reused before querying the apiserver again
+    ///
+    /// The default is zero, which makes every [`Discovery::run`] query the apiserver.
+    #[must_use]
+    pub fn cache_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Configure a file to persist discovered apis in
+    ///
+    /// When the file is younger than the [`Discovery::cache_ttl`] and was written with the same filters,
+    /// discovery reads it instead of querying the apiserver. Otherwise the file is replaced after querying.
+    ///
+    /// The file is specific to a cluster, so it should be named after the cluster, like kubectl's
+    /// `~/.kube/cache/discovery/<host>` directories.
+    #[must_use]
+    pub fn cache_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.cache_file = Some(path.into());
         self
     }
 
     /// Runs or re-runs the configured discovery algorithm and updates/populates the cache
     ///
-    /// The cache is empty cleared when this is started. By default, every api group found is checked,
+    /// This is a [`Discovery::refresh`] that consumes and returns the discovery client.
+    /// When the apiserver supports aggregated discovery (Kubernetes 1.26+ with the `AggregatedDiscoveryEndpoint` feature),
+    /// this takes 2 queries to the api server. Otherwise, every api group found is checked,
     /// causing `N+2` queries to the api server (where `N` is number of api groups).
     ///
     /// ```no_run
@@ -109,24 +199,104 @@ impl Discovery {
     /// ```
     /// See a bigger example in [examples/dynamic.api](https://github.com/kube-rs/kube/blob/main/examples/dynamic_api.rs)
     pub async fn run(mut self) -> Result<Self> {
+        self.refresh().await?;
+        Ok(self)
+    }
+
+    /// Whether the discovered apis are younger than the [`Discovery::cache_ttl`]
+    pub fn is_fresh(&self) -> bool {
+        self.refreshed
+            .and_then(|refreshed| refreshed.elapsed().ok())
+            .map_or(false, |age| age < self.ttl)
+    }
+
+    /// Updates the discovered apis, unless they are still fresh
+    ///
+    /// Stale apis are read from the [`Discovery::cache_file`] when it is fresh,
+    /// and queried from the apiserver otherwise.
+    pub async fn refresh(&mut self) -> Result<()> {
+        if self.is_fresh() {
+            return Ok(());
+        }
+        if let Some(path) = &self.cache_file {
+            if let Some((refreshed, groups)) = cache::load(path, self.ttl, &self.mode).await {
+                tracing::debug!(?path, "Using cached discovery");
+                self.groups = groups;
+                self.refreshed = Some(refreshed);
+                self.retain_matching();
+                return Ok(());
+            }
+        }
+        self.query().await?;
+        self.refreshed = Some(SystemTime::now());
+        if let Some(path) = &self.cache_file {
+            if let Err(err) = cache::store(path, &self.mode, &self.groups).await {
+                tracing::warn!(?path, %err, "Failed to write discovery cache");
+            }
+        }
+        self.retain_matching();
+        Ok(())
+    }
+
+    /// Marks the discovered apis as stale, and removes the [`Discovery::cache_file`]
+    ///
+    /// The next [`Discovery::refresh`] queries the apiserver, like after a resolution failure for a new CRD.
+    pub async fn invalidate(&mut self) {
+        self.refreshed = None;
+        if let Some(path) = &self.cache_file {
+            if let Err(err) = cache::remove(path).await {
+                tracing::warn!(?path, %err, "Failed to remove discovery cache");
+            }
+        }
+    }
+
+    async fn query(&mut self) -> Result<()> {
         self.groups.clear();
-        let api_groups = self.client.list_api_groups().await?;
         // query regular groups + crds under /apis
-        for g in api_groups.groups {
-            let key = g.name.clone();
-            if self.mode.is_queryable(&key) {
-                let apigroup = ApiGroup::query_apis(&self.client, g).await?;
-                self.groups.insert(key, apigroup);
+        match aggregated::discover::<APIGroupList>(&self.client, "/apis").await? {
+            Discovered::Aggregated(list) => self.insert_aggregated(list).await?,
+            Discovered::Legacy(api_groups) => {
+                for g in api_groups.groups {
+                    let key = g.name.clone();
+                    if self.mode.is_queryable(&key) {
+                        let apigroup = ApiGroup::query_apis(&self.client, g).await?;
+                        self.groups.insert(key, apigroup);
+                    }
+                }
             }
         }
         // query core versions under /api
         let corekey = ApiGroup::CORE_GROUP.to_string();
         if self.mode.is_queryable(&corekey) {
-            let coreapis = self.client.list_core_api_versions().await?;
-            let apigroup = ApiGroup::query_core(&self.client, coreapis).await?;
-            self.groups.insert(corekey, apigroup);
+            match aggregated::discover::<APIVersions>(&self.client, "/api").await? {
+                Discovered::Aggregated(list) => self.insert_aggregated(list).await?,
+                Discovered::Legacy(coreapis) => {
+                    let apigroup = ApiGroup::query_core(&self.client, coreapis).await?;
+                    self.groups.insert(corekey, apigroup);
+                }
+            }
         }
-        Ok(self)
+        Ok(())
+    }
+
+    /// Drops the resources that do not match the predicate of [`Discovery::filter`]
+    ///
+    /// This happens after writing the cache file, so that it can be shared by clients with other predicates.
+    fn retain_matching(&mut self) {
+        if let Some(predicate) = &self.predicate {
+            self.groups.retain(|_, group| group.retain(predicate));
+        }
+    }
+
+    async fn insert_aggregated(&mut self, list: APIGroupDiscoveryList) -> Result<()> {
+        for g in list.items {
+            let key = g.name().to_string();
+            if self.mode.is_queryable(&key) {
+                let apigroup = ApiGroup::query_aggregated(&self.client, g).await?;
+                self.groups.insert(key, apigroup);
+            }
+        }
+        Ok(())
     }
 }
 
@@ -167,4 +337,325 @@ impl Discovery {
             .into_iter()
             .find(|res| res.0.kind == gvk.kind)
     }
+
+    /// Finds an [`ApiResource`] and its [`ApiCapabilities`] by a name as accepted by kubectl
+    ///
+    /// The name is matched case-insensitively against the kind, plural and short names of resources,
+    /// like `Deployment`, `deployments` or `deploy`. It can be qualified by the group, like `deploy.apps`
+    /// or `documents.kube.rs`, to pick a resource from a particular group.
+    ///
+    /// Kinds and plurals take precedence over short names. When several groups have a matching resource,
+    /// the core group is preferred and the others are ordered alphabetically. Within a group,
+    /// the resource is picked at its most stable version, like [`ApiGroup::resources_by_stability`].
+    ///
+    /// ```no_run
+    /// use kube::{Client, api::{Api, DynamicObject}, discovery::Discovery};
+    /// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = Client::try_default().await?;
+    /// let discovery = Discovery::new(client.clone()).run().await?;
+    /// let (ar, _caps) = discovery.resolve_alias("deploy").unwrap();
+    /// assert_eq!(ar.kind, "Deployment");
+    /// let api: Api<DynamicObject> = Api::all_with(client, &ar);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn resolve_alias(&self, name: &str) -> Option<(ApiResource, ApiCapabilities)> {
+        self.resolve_alias_in(name, None).or_else(|| {
+            let (name, group) = name.split_once('.')?;
+            self.resolve_alias_in(name, Some(group))
+        })
+    }
+
+    fn resolve_alias_in(&self, name: &str, group: Option<&str>) -> Option<(ApiResource, ApiCapabilities)> {
+        let mut resources: Vec<_> = self
+            .groups_alphabetical()
+            .into_iter()
+            .filter(|g| group.map_or(true, |group| g.name() == group))
+            .flat_map(ApiGroup::resources_by_stability)
+            .collect();
+        let position = resources
+            .iter()
+            .position(|(ar, _)| name.eq_ignore_ascii_case(&ar.kind) || name.eq_ignore_ascii_case(&ar.plural))
+            .or_else(|| {
+                resources.iter().position(|(_, caps)| {
+                    caps.short_names
+                        .iter()
+                        .any(|short| name.eq_ignore_ascii_case(short))
+                })
+            })?;
+        Some(resources.swap_remove(position))
+    }
+
+    /// Returns the resources in a category, like `kubectl get all`
+    ///
+    /// The resources are ordered by group like [`Discovery::groups_alphabetical`], and then by kind,
+    /// at their most stable version like [`ApiGroup::resources_by_stability`].
+    pub fn resources_in_category(&self, category: &str) -> Vec<(ApiResource, ApiCapabilities)> {
+        self.groups_alphabetical()
+            .into_iter()
+            .flat_map(|g| {
+                let mut resources = g.resources_by_stability();
+                resources.retain(|(_, caps)| caps.categories.iter().any(|c| c == category));
+                resources.sort_by(|(a, _), (b, _)| a.kind.cmp(&b.kind));
+                resources
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{verbs, Discovery, Scope};
+    use crate::Client;
+    use futures::pin_mut;
+    use http::{Request, Response};
+    use hyper::Body;
+    use kube_core::GroupVersionKind;
+    use serde_json::{json, Value};
+    use std::{
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        time::Duration,
+    };
+    use tower_test::mock;
+
+    /// A client for an apiserver that responds to discovery with `respond`, counting requests
+    fn counting_client(respond: fn(&str) -> Value) -> (Client, Arc<AtomicUsize>) {
+        let (mock_service, handle) = mock::pair::<Request<Body>, Response<Body>>();
+        let requests = Arc::new(AtomicUsize::new(0));
+        let counter = requests.clone();
+        tokio::spawn(async move {
+            pin_mut!(handle);
+            while let Some((request, send)) = handle.next_request().await {
+                counter.fetch_add(1, Ordering::SeqCst);
+                let body = respond(request.uri().path());
+                send.send_response(Response::new(Body::from(serde_json::to_vec(&body).unwrap())));
+            }
+        });
+        (Client::new(mock_service, "default"), requests)
+    }
+
+    /// Regular discovery of pods and a custom resource
+    fn legacy(path: &str) -> Value {
+        match path {
+            "/api" => json!({ "versions": ["v1"], "serverAddressByClientCIDRs": [] }),
+            "/api/v1" => json!({
+                "groupVersion": "v1",
+                "resources": [{
+                    "name": "pods", "singularName": "pod", "namespaced": true, "kind": "Pod",
+                    "verbs": ["get", "list", "watch"], "shortNames": ["po"], "categories": ["all"]
+                }]
+            }),
+            "/apis" => json!({
+                "groups": [{
+                    "name": "kube.rs",
+                    "versions": [{ "groupVersion": "kube.rs/v1", "version": "v1" }],
+                    "preferredVersion": { "groupVersion": "kube.rs/v1", "version": "v1" }
+                }]
+            }),
+            "/apis/kube.rs/v1" => json!({
+                "groupVersion": "kube.rs/v1",
+                "resources": [{
+                    "name": "documents", "singularName": "document", "namespaced": true, "kind": "Document",
+                    "verbs": ["get", "list"], "shortNames": ["doc"], "categories": ["all", "kube"]
+                }]
+            }),
+            path => panic!("unexpected request for {path}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn discovery_is_cached_in_memory_and_on_disk() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("discovery.json");
+        let document = GroupVersionKind::gvk("kube.rs", "v1", "Document");
+        let (client, requests) = counting_client(legacy);
+
+        let mut discovery = Discovery::new(client.clone())
+            .cache_ttl(Duration::from_secs(60))
+            .cache_file(&path)
+            .run()
+            .await
+            .unwrap();
+        assert_eq!(requests.load(Ordering::SeqCst), 4);
+        assert!(discovery.is_fresh());
+        assert!(discovery.resolve_gvk(&document).is_some());
+        assert!(path.exists());
+
+        // fresh apis are reused
+        discovery.refresh().await.unwrap();
+        assert_eq!(requests.load(Ordering::SeqCst), 4);
+
+        // other clients read the cache file
+        let cached = Discovery::new(client.clone())
+            .cache_ttl(Duration::from_secs(60))
+            .cache_file(&path)
+            .run()
+            .await
+            .unwrap();
+        assert_eq!(requests.load(Ordering::SeqCst), 4);
+        assert!(cached.resolve_gvk(&document).is_some());
+        assert!(cached.has_group(""));
+
+        // but not when it was written with other filters
+        let filtered = Discovery::new(client.clone())
+            .filter_groups(&["kube.rs"])
+            .cache_ttl(Duration::from_secs(60))
+            .cache_file(&path)
+            .run()
+            .await
+            .unwrap();
+        assert_eq!(requests.load(Ordering::SeqCst), 6);
+        assert!(!filtered.has_group(""));
+
+        // invalidation queries the apiserver again
+        discovery.invalidate().await;
+        assert!(!discovery.is_fresh());
+        discovery.refresh().await.unwrap();
+        assert_eq!(requests.load(Ordering::SeqCst), 10);
+    }
+
+    #[tokio::test]
+    async fn discovery_is_not_cached_by_default() {
+        let (client, requests) = counting_client(legacy);
+        let mut discovery = Discovery::new(client).run().await.unwrap();
+        assert!(!discovery.is_fresh());
+        discovery.refresh().await.unwrap();
+        assert_eq!(requests.load(Ordering::SeqCst), 8);
+    }
+
+    /// Aggregated discovery of pods and a custom resource, where the discovery of one version is stale
+    fn aggregated(path: &str) -> Value {
+        let pods = json!({
+            "resource": "pods", "responseKind": { "group": "", "version": "v1", "kind": "Pod" },
+            "scope": "Namespaced", "verbs": ["get", "list", "watch"], "shortNames": ["po"], "categories": ["all"],
+            "subresources": [
+                { "subresource": "status", "responseKind": { "group": "", "version": "v1", "kind": "Pod" }, "verbs": ["get", "patch"] },
+                { "subresource": "eviction", "responseKind": { "group": "policy", "version": "v1", "kind": "Eviction" }, "verbs": ["create"] },
+            ]
+        });
+        match path {
+            "/api" => json!({
+                "kind": "APIGroupDiscoveryList", "apiVersion": "apidiscovery.k8s.io/v2beta1",
+                "items": [{ "metadata": {}, "versions": [{ "version": "v1", "resources": [pods], "freshness": "Current" }] }]
+            }),
+            "/apis" => json!({
+                "kind": "APIGroupDiscoveryList", "apiVersion": "apidiscovery.k8s.io/v2beta1",
+                "items": [{
+                    "metadata": { "name": "kube.rs" },
+                    "versions": [
+                        { "version": "v2", "freshness": "Stale" },
+                        { "version": "v1", "freshness": "Current", "resources": [{
+                            "resource": "documents", "responseKind": { "group": "kube.rs", "version": "v1", "kind": "Document" },
+                            "scope": "Cluster", "verbs": ["get", "list"]
+                        }] },
+                    ]
+                }]
+            }),
+            "/apis/kube.rs/v2" => json!({
+                "groupVersion": "kube.rs/v2",
+                "resources": [{
+                    "name": "documents", "singularName": "document", "namespaced": false, "kind": "Document",
+                    "verbs": ["get", "list", "watch"]
+                }]
+            }),
+            path => panic!("unexpected request for {path}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn aggregated_discovery() {
+        let (client, requests) = counting_client(aggregated);
+        let discovery = Discovery::new(client).run().await.unwrap();
+        assert_eq!(requests.load(Ordering::SeqCst), 3);
+
+        let group = discovery.get("kube.rs").unwrap();
+        assert_eq!(group.versions().collect::<Vec<_>>(), ["v2", "v1"]);
+        assert_eq!(group.preferred_version(), Some("v2"));
+        let (ar, caps) = group.recommended_kind("Document").unwrap();
+        assert_eq!(ar.api_version, "kube.rs/v2");
+        assert!(caps.supports_operation(verbs::WATCH));
+        let (ar, caps) = discovery
+            .resolve_gvk(&GroupVersionKind::gvk("kube.rs", "v1", "Document"))
+            .unwrap();
+        assert_eq!((ar.plural.as_str(), caps.scope), ("documents", Scope::Cluster));
+
+        let (ar, caps) = discovery
+            .resolve_gvk(&GroupVersionKind::gvk("", "v1", "Pod"))
+            .unwrap();
+        assert_eq!(ar.api_version, "v1");
+        assert_eq!(caps.scope, Scope::Namespaced);
+        assert_eq!(caps.short_names, ["po"]);
+        assert_eq!(discovery.resources_in_category("all").len(), 1);
+        let subresources: Vec<_> = caps
+            .subresources
+            .iter()
+            .map(|(ar, _)| (ar.plural.as_str(), ar.kind.as_str(), ar.api_version.as_str()))
+            .collect();
+        let expected = [("status", "Pod", "v1"), ("eviction", "Eviction", "v1")];
+        assert_eq!(subresources, expected);
+    }
+
+    #[tokio::test]
+    async fn discovery_filters_resources() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("discovery.json");
+        let (client, requests) = counting_client(legacy);
+        let watchable = Discovery::new(client.clone())
+            .filter(|_, _, caps| caps.supports_operations(&[verbs::LIST, verbs::WATCH]))
+            .cache_ttl(Duration::from_secs(60))
+            .cache_file(&path)
+            .run()
+            .await
+            .unwrap();
+        assert_eq!(watchable.groups().count(), 1);
+        assert!(watchable
+            .resolve_gvk(&GroupVersionKind::gvk("", "v1", "Pod"))
+            .is_some());
+        assert!(!watchable.has_group("kube.rs"));
+
+        // the cache file has all resources, for clients with other predicates
+        let documents = Discovery::new(client)
+            .filter(|group, ar, _| group == "kube.rs" && ar.kind == "Document")
+            .cache_ttl(Duration::from_secs(60))
+            .cache_file(&path)
+            .run()
+            .await
+            .unwrap();
+        assert_eq!(requests.load(Ordering::SeqCst), 4);
+        assert_eq!(documents.groups().count(), 1);
+        assert!(documents
+            .get("kube.rs")
+            .unwrap()
+            .recommended_kind("Document")
+            .is_some());
+    }
+
+    #[tokio::test]
+    async fn discovery_resolves_aliases() {
+        let (client, _) = counting_client(legacy);
+        let discovery = Discovery::new(client).run().await.unwrap();
+        let resolve = |name| discovery.resolve_alias(name).map(|(ar, _)| ar.kind);
+        for name in ["po", "pods", "Pod", "PODS"] {
+            assert_eq!(resolve(name).as_deref(), Some("Pod"), "{name}");
+        }
+        for name in ["doc", "document", "documents.kube.rs", "doc.kube.rs"] {
+            assert_eq!(resolve(name).as_deref(), Some("Document"), "{name}");
+        }
+        assert_eq!(resolve("po.kube.rs"), None);
+        assert_eq!(resolve("unknown"), None);
+
+        let kinds = |category| {
+            discovery
+                .resources_in_category(category)
+                .into_iter()
+                .map(|(ar, _)| ar.kind)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(kinds("all"), ["Pod", "Document"]);
+        assert_eq!(kinds("kube"), ["Document"]);
+        assert!(kinds("unknown").is_empty());
+    }
 }
@@ -5,6 +5,7 @@ use kube_core::{
     discovery::{ApiCapabilities, ApiResource, Scope},
     gvk::{GroupVersion, ParseGroupVersionError},
 };
+use serde::{Deserialize, Serialize};
 
 /// Creates an `ApiResource` from a `meta::v1::APIResource` instance + its groupversion.
 ///
@@ -52,14 +53,13 @@ pub(crate) fn parse_apicapabilities(list: &APIResourceList, name: &str) -> Resul
             subresources.push((api_resource, caps));
         }
     }
-    Ok(ApiCapabilities {
-        scope,
-        subresources,
-        operations: ar.verbs.clone(),
-    })
+    Ok(ApiCapabilities::new(scope, subresources, ar.verbs.clone())
+        .with_short_names(ar.short_names.clone().unwrap_or_default())
+        .with_categories(ar.categories.clone().unwrap_or_default()))
 }
 
 /// Internal resource information and capabilities for a particular ApiGroup at a particular version
+#[derive(Clone, Serialize, Deserialize)]
 pub(crate) struct GroupVersionData {
     /// Pinned api version
     pub(crate) version: String,
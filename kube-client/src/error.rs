@@ -1,7 +1,7 @@
 //! Error handling in [`kube`][crate]
 use thiserror::Error;
 
-pub use kube_core::ErrorResponse;
+pub use kube_core::{ApplyConflict, ErrorResponse};
 
 /// Possible errors when working with [`kube`][crate]
 #[cfg_attr(docsrs, doc(cfg(any(feature = "config", feature = "client"))))]
@@ -77,6 +77,12 @@ pub enum Error {
     #[error("failed to upgrade to a WebSocket connection: {0}")]
     UpgradeConnection(#[source] crate::client::UpgradeConnectionError),
 
+    /// The `cluster_url` has a plain `http` scheme, but [`Config::allow_insecure_http`](crate::Config::allow_insecure_http) is not set
+    #[cfg(feature = "client")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "client")))]
+    #[error("refusing to connect to {0} without TLS, as Config::allow_insecure_http is not set")]
+    InsecureHttp(http::Uri),
+
     /// Errors related to client auth
     #[cfg(feature = "client")]
     #[cfg_attr(docsrs, doc(cfg(feature = "client")))]
@@ -387,7 +387,7 @@ mod test {
     #[ignore = "needs cluster (will create and tail logs from a pod)"]
     async fn can_get_pod_logs_and_evict() -> Result<(), Box<dyn std::error::Error>> {
         use crate::{
-            api::{DeleteParams, EvictParams, ListParams, Patch, PatchParams, WatchEvent},
+            api::{DeleteParams, EvictParams, EvictionOutcome, ListParams, Patch, PatchParams, WatchEvent},
             core::subresource::LogParams,
         };
 
@@ -461,7 +461,10 @@ mod test {
 
         // evict the pod
         let ep = EvictParams::default();
-        let eres = pods.evict("busybox-kube3", &ep).await?;
+        let eres = match pods.evict("busybox-kube3", &ep).await? {
+            EvictionOutcome::Evicted(status) => status,
+            blocked => panic!("eviction was not accepted: {blocked:?}"),
+        };
         assert_eq!(eres.code, 201); // created
         assert!(eres.is_success());
 
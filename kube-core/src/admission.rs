@@ -353,9 +353,12 @@ mod test {
     const WEBHOOK_BODY: &str = r#"{"kind":"AdmissionReview","apiVersion":"admission.k8s.io/v1","request":{"uid":"0c9a8d74-9cb7-44dd-b98e-09fd62def2f4","kind":{"group":"","version":"v1","kind":"Pod"},"resource":{"group":"","version":"v1","resource":"pods"},"requestKind":{"group":"","version":"v1","kind":"Pod"},"requestResource":{"group":"","version":"v1","resource":"pods"},"name":"echo-pod","namespace":"colin-coder","operation":"CREATE","userInfo":{"username":"colin@coder.com","groups":["system:authenticated"],"extra":{"iam.gke.io/user-assertion":["REDACTED"],"user-assertion.cloud.google.com":["REDACTED"]}},"object":{"kind":"Pod","apiVersion":"v1","metadata":{"name":"echo-pod","namespace":"colin-coder","creationTimestamp":null,"labels":{"app":"echo-server"},"annotations":{"kubectl.kubernetes.io/last-applied-configuration":"{\"apiVersion\":\"v1\",\"kind\":\"Pod\",\"metadata\":{\"annotations\":{},\"labels\":{\"app\":\"echo-server\"},\"name\":\"echo-pod\",\"namespace\":\"colin-coder\"},\"spec\":{\"containers\":[{\"image\":\"jmalloc/echo-server\",\"name\":\"echo-server\",\"ports\":[{\"containerPort\":8080,\"name\":\"http-port\"}]}]}}\n"},"managedFields":[{"manager":"kubectl","operation":"Update","apiVersion":"v1","time":"2021-03-29T23:02:16Z","fieldsType":"FieldsV1","fieldsV1":{"f:metadata":{"f:annotations":{".":{},"f:kubectl.kubernetes.io/last-applied-configuration":{}},"f:labels":{".":{},"f:app":{}}},"f:spec":{"f:containers":{"k:{\"name\":\"echo-server\"}":{".":{},"f:image":{},"f:imagePullPolicy":{},"f:name":{},"f:ports":{".":{},"k:{\"containerPort\":8080,\"protocol\":\"TCP\"}":{".":{},"f:containerPort":{},"f:name":{},"f:protocol":{}}},"f:resources":{},"f:terminationMessagePath":{},"f:terminationMessagePolicy":{}}},"f:dnsPolicy":{},"f:enableServiceLinks":{},"f:restartPolicy":{},"f:schedulerName":{},"f:securityContext":{},"f:terminationGracePeriodSeconds":{}}}}]},"spec":{"volumes":[{"name":"default-token-rxbqq","secret":{"secretName":"default-token-rxbqq"}}],"containers":[{"name":"echo-server","image":"jmalloc/echo-server","ports":[{"name":"http-port","containerPort":8080,"protocol":"TCP"}],"resources":{},"volumeMounts":[{"name":"default-token-rxbqq","readOnly":true,"mountPath":"/var/run/secrets/kubernetes.io/serviceaccount"}],"terminationMessagePath":"/dev/termination-log","terminationMessagePolicy":"File","imagePullPolicy":"Always"}],"restartPolicy":"Always","terminationGracePeriodSeconds":30,"dnsPolicy":"ClusterFirst","serviceAccountName":"default","serviceAccount":"default","securityContext":{},"schedulerName":"default-scheduler","tolerations":[{"key":"node.kubernetes.io/not-ready","operator":"Exists","effect":"NoExecute","tolerationSeconds":300},{"key":"node.kubernetes.io/unreachable","operator":"Exists","effect":"NoExecute","tolerationSeconds":300}],"priority":0,"enableServiceLinks":true},"status":{}},"oldObject":null,"dryRun":false,"options":{"kind":"CreateOptions","apiVersion":"meta.k8s.io/v1"}}}"#;
 
     use crate::{
-        admission::{AdmissionResponse, AdmissionReview, ConvertAdmissionReviewError},
+        admission::{
+            AdmissionRequest, AdmissionResponse, AdmissionReview, ConvertAdmissionReviewError, Operation,
+        },
         DynamicObject,
     };
+    use k8s_openapi::api::core::v1::Pod;
 
     #[test]
     fn v1_webhook_unmarshals() {
@@ -375,4 +378,53 @@ mod test {
         assert_eq!(&rev_typ, &res.types);
         Ok(())
     }
+
+    #[test]
+    fn typed_objects_deserialize() -> Result<(), ConvertAdmissionReviewError> {
+        let rev = serde_json::from_str::<AdmissionReview<Pod>>(WEBHOOK_BODY).unwrap();
+        let req: AdmissionRequest<Pod> = rev.try_into()?;
+        assert_eq!(req.operation, Operation::Create);
+        let pod = req.object.expect("object is set on CREATE");
+        assert_eq!(pod.metadata.name.as_deref(), Some("echo-pod"));
+        assert_eq!(
+            pod.spec.unwrap().containers[0].image.as_deref(),
+            Some("jmalloc/echo-server")
+        );
+        assert!(req.old_object.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn responses_serialize() -> Result<(), ConvertAdmissionReviewError> {
+        use json_patch::{AddOperation, Patch, PatchOperation};
+
+        let rev = serde_json::from_str::<AdmissionReview<DynamicObject>>(WEBHOOK_BODY).unwrap();
+        let req: AdmissionRequest<_> = rev.try_into()?;
+
+        let denied = serde_json::to_value(
+            AdmissionResponse::from(&req)
+                .deny("no echo servers")
+                .into_review(),
+        )
+        .unwrap();
+        assert_eq!(denied["apiVersion"], "admission.k8s.io/v1");
+        assert_eq!(denied["response"]["uid"], "0c9a8d74-9cb7-44dd-b98e-09fd62def2f4");
+        assert_eq!(denied["response"]["allowed"], false);
+        assert_eq!(denied["response"]["status"]["message"], "no echo servers");
+        assert!(denied["response"].get("patch").is_none());
+
+        let patch = Patch(vec![PatchOperation::Add(AddOperation {
+            path: "/metadata/labels/my-label".to_owned(),
+            value: serde_json::Value::String("my-value".to_owned()),
+        })]);
+        let patched = AdmissionResponse::from(&req).with_patch(patch.clone()).unwrap();
+        assert!(patched.allowed);
+        assert_eq!(
+            serde_json::from_slice::<Patch>(patched.patch.as_ref().unwrap()).unwrap(),
+            patch
+        );
+        let patched = serde_json::to_value(patched.into_review()).unwrap();
+        assert_eq!(patched["response"]["patchType"], "JSONPatch");
+        Ok(())
+    }
 }
@@ -0,0 +1,318 @@
+//! Helpers for the `conditions` in the status of resources
+//!
+//! These follow the [API conventions for conditions](https://github.com/kubernetes/community/blob/master/contributors/devel/sig-architecture/api-conventions.md#typical-status-properties):
+//! there is at most one condition per type, and `lastTransitionTime` only moves when the status of a condition changes.
+use crate::Resource;
+use chrono::Utc;
+use k8s_openapi::{
+    api::{
+        apps::v1::{
+            DaemonSet, DaemonSetCondition, DaemonSetStatus, Deployment, DeploymentCondition,
+            DeploymentStatus, ReplicaSet, ReplicaSetCondition, ReplicaSetStatus, StatefulSet,
+            StatefulSetCondition, StatefulSetStatus,
+        },
+        batch::v1::{Job, JobCondition, JobStatus},
+        core::v1::{
+            Namespace, NamespaceCondition, NamespaceStatus, Node, NodeCondition, NodeStatus,
+            PersistentVolumeClaim, PersistentVolumeClaimCondition, PersistentVolumeClaimStatus, Pod,
+            PodCondition, PodStatus, Service, ServiceStatus,
+        },
+    },
+    apiextensions_apiserver::pkg::apis::apiextensions::v1::{
+        CustomResourceDefinition, CustomResourceDefinitionCondition, CustomResourceDefinitionStatus,
+    },
+    apimachinery::pkg::apis::meta::v1::{Condition, Time},
+};
+
+/// Access to the fields that all kinds of conditions have
+///
+/// This is implemented for the generic [`Condition`] used by custom resources and newer kinds,
+/// and for the kind-specific conditions of the common `k8s_openapi` kinds, like [`PodCondition`].
+pub trait ConditionLike {
+    /// The type of the condition, like `Ready`
+    fn type_(&self) -> &str;
+
+    /// The status of the condition, one of `True`, `False` or `Unknown`
+    fn status(&self) -> &str;
+
+    /// The last time the status of the condition changed
+    fn last_transition_time(&self) -> Option<&Time>;
+
+    /// Set the last time the status of the condition changed
+    fn set_last_transition_time(&mut self, time: Time);
+
+    /// The generation of the object that the condition was set for, when the condition has one
+    fn observed_generation(&self) -> Option<i64> {
+        None
+    }
+
+    /// Set the generation of the object that the condition was set for, when the condition has one
+    fn set_observed_generation(&mut self, _generation: Option<i64>) {}
+}
+
+impl ConditionLike for Condition {
+    fn type_(&self) -> &str {
+        &self.type_
+    }
+
+    fn status(&self) -> &str {
+        &self.status
+    }
+
+    fn last_transition_time(&self) -> Option<&Time> {
+        Some(&self.last_transition_time)
+    }
+
+    fn set_last_transition_time(&mut self, time: Time) {
+        self.last_transition_time = time;
+    }
+
+    fn observed_generation(&self) -> Option<i64> {
+        self.observed_generation
+    }
+
+    fn set_observed_generation(&mut self, generation: Option<i64>) {
+        self.observed_generation = generation;
+    }
+}
+
+macro_rules! impl_condition_like {
+    ($($condition:ty),*) => {
+        $(
+            impl ConditionLike for $condition {
+                fn type_(&self) -> &str {
+                    &self.type_
+                }
+
+                fn status(&self) -> &str {
+                    &self.status
+                }
+
+                fn last_transition_time(&self) -> Option<&Time> {
+                    self.last_transition_time.as_ref()
+                }
+
+                fn set_last_transition_time(&mut self, time: Time) {
+                    self.last_transition_time = Some(time);
+                }
+            }
+        )*
+    };
+}
+
+impl_condition_like!(
+    CustomResourceDefinitionCondition,
+    DaemonSetCondition,
+    DeploymentCondition,
+    JobCondition,
+    NamespaceCondition,
+    NodeCondition,
+    PersistentVolumeClaimCondition,
+    PodCondition,
+    ReplicaSetCondition,
+    StatefulSetCondition
+);
+
+/// A resource with `conditions` in its status
+///
+/// This is implemented for the common `k8s_openapi` kinds with conditions, and by kube-derive
+/// for custom resources with `#[kube(conditions)]`.
+///
+/// ```
+/// use k8s_openapi::api::core::v1::{Pod, PodCondition};
+/// use kube::core::conditions::HasConditions;
+///
+/// let mut pod = Pod::default();
+/// assert!(!pod.is_condition_true("Ready"));
+/// pod.set_condition(PodCondition {
+///     type_: "Ready".into(),
+///     status: "True".into(),
+///     ..PodCondition::default()
+/// });
+/// assert!(pod.is_condition_true("Ready"));
+/// ```
+pub trait HasConditions: Resource {
+    /// The type of the conditions of the resource
+    type Condition: ConditionLike;
+
+    /// The conditions in the status of the resource
+    fn conditions(&self) -> &[Self::Condition];
+
+    /// Mutable access to the conditions in the status of the resource, creating the status if there is none
+    fn conditions_mut(&mut self) -> &mut Vec<Self::Condition>;
+
+    /// Set a condition, see [`set_condition`]
+    ///
+    /// The `observedGeneration` of the condition is set to the generation of the resource,
+    /// unless it is set already or the kind of condition has none.
+    fn set_condition(&mut self, mut condition: Self::Condition) {
+        if condition.observed_generation().is_none() {
+            condition.set_observed_generation(self.meta().generation);
+        }
+        set_condition(self.conditions_mut(), condition);
+    }
+
+    /// Get the condition of a type
+    fn get_condition(&self, type_: &str) -> Option<&Self::Condition> {
+        get_condition(self.conditions(), type_)
+    }
+
+    /// Whether the condition of a type is `True` for the current generation of the resource
+    ///
+    /// Conditions with an `observedGeneration` older than the generation of the resource are
+    /// stale, and are not considered true.
+    fn is_condition_true(&self, type_: &str) -> bool {
+        self.get_condition(type_).map_or(false, |c| {
+            c.status() == "True"
+                && (c.observed_generation().is_none() || c.observed_generation() == self.meta().generation)
+        })
+    }
+}
+
+macro_rules! impl_has_conditions {
+    ($($kind:ty => $status:ty, $condition:ty);* $(;)?) => {
+        $(
+            impl HasConditions for $kind {
+                type Condition = $condition;
+
+                fn conditions(&self) -> &[$condition] {
+                    self.status.as_ref().and_then(|s| s.conditions.as_deref()).unwrap_or_default()
+                }
+
+                fn conditions_mut(&mut self) -> &mut Vec<$condition> {
+                    self.status
+                        .get_or_insert_with(<$status>::default)
+                        .conditions
+                        .get_or_insert_with(Vec::new)
+                }
+            }
+        )*
+    };
+}
+
+impl_has_conditions!(
+    CustomResourceDefinition => CustomResourceDefinitionStatus, CustomResourceDefinitionCondition;
+    DaemonSet => DaemonSetStatus, DaemonSetCondition;
+    Deployment => DeploymentStatus, DeploymentCondition;
+    Job => JobStatus, JobCondition;
+    Namespace => NamespaceStatus, NamespaceCondition;
+    Node => NodeStatus, NodeCondition;
+    PersistentVolumeClaim => PersistentVolumeClaimStatus, PersistentVolumeClaimCondition;
+    Pod => PodStatus, PodCondition;
+    ReplicaSet => ReplicaSetStatus, ReplicaSetCondition;
+    Service => ServiceStatus, Condition;
+    StatefulSet => StatefulSetStatus, StatefulSetCondition;
+);
+
+/// Set a condition in a list of conditions
+///
+/// This replaces the condition of the same type, or adds it when there is none.
+/// The `last_transition_time` of the condition is the one of the condition it replaces when the
+/// status is unchanged, and now when the status changes or the condition is new.
+pub fn set_condition<C: ConditionLike>(conditions: &mut Vec<C>, mut condition: C) {
+    match conditions.iter_mut().find(|c| c.type_() == condition.type_()) {
+        Some(existing) => {
+            let time = match existing.last_transition_time() {
+                Some(time) if existing.status() == condition.status() => time.clone(),
+                _ => Time(Utc::now()),
+            };
+            condition.set_last_transition_time(time);
+            *existing = condition;
+        }
+        None => {
+            condition.set_last_transition_time(Time(Utc::now()));
+            conditions.push(condition);
+        }
+    }
+}
+
+/// Get the condition of a type from a list of conditions
+pub fn get_condition<'a, C: ConditionLike>(conditions: &'a [C], type_: &str) -> Option<&'a C> {
+    conditions.iter().find(|c| c.type_() == type_)
+}
+
+/// Whether the condition of a type has the status `True` in a list of conditions
+pub fn is_condition_true<C: ConditionLike>(conditions: &[C], type_: &str) -> bool {
+    get_condition(conditions, type_).map_or(false, |c| c.status() == "True")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{get_condition, is_condition_true, set_condition, HasConditions};
+    use chrono::{TimeZone, Utc};
+    use k8s_openapi::{
+        api::apps::v1::{Deployment, DeploymentCondition},
+        apimachinery::pkg::apis::meta::v1::{Condition, Time},
+    };
+
+    fn condition(type_: &str, status: &str) -> Condition {
+        Condition {
+            type_: type_.into(),
+            status: status.into(),
+            reason: "Test".into(),
+            message: String::new(),
+            observed_generation: None,
+            last_transition_time: Time(Utc.timestamp_opt(0, 0).unwrap()),
+        }
+    }
+
+    #[test]
+    fn set_condition_tracks_transitions() {
+        let mut conditions = vec![];
+        set_condition(&mut conditions, condition("Ready", "False"));
+        let added = get_condition(&conditions, "Ready")
+            .unwrap()
+            .last_transition_time
+            .clone();
+        assert!(added.0.timestamp() > 0);
+
+        // Same status keeps the transition time, but updates the rest
+        let mut unchanged = condition("Ready", "False");
+        unchanged.reason = "StillWaiting".into();
+        set_condition(&mut conditions, unchanged);
+        assert_eq!(conditions.len(), 1);
+        assert_eq!(conditions[0].reason, "StillWaiting");
+        assert_eq!(conditions[0].last_transition_time, added);
+
+        // Other types are added next to it
+        set_condition(&mut conditions, condition("Degraded", "False"));
+        assert_eq!(conditions.len(), 2);
+        assert!(get_condition(&conditions, "Missing").is_none());
+    }
+
+    #[test]
+    fn k8s_openapi_kinds_have_conditions() {
+        let mut deploy = Deployment::default();
+        assert!(deploy.conditions().is_empty());
+        let available = |status: &str| DeploymentCondition {
+            type_: "Available".into(),
+            status: status.into(),
+            ..DeploymentCondition::default()
+        };
+        deploy.set_condition(available("False"));
+        assert!(!deploy.is_condition_true("Available"));
+        let since = deploy.conditions()[0].last_transition_time.clone();
+        assert!(since.is_some());
+
+        deploy.set_condition(available("True"));
+        assert!(deploy.is_condition_true("Available"));
+        assert!(is_condition_true(deploy.conditions(), "Available"));
+        assert!(deploy.get_condition("Available").unwrap().last_transition_time >= since);
+        assert_eq!(deploy.conditions().len(), 1);
+    }
+
+    #[test]
+    fn observed_generations_are_tracked() {
+        use k8s_openapi::api::core::v1::Service;
+        let mut svc = Service::default();
+        svc.metadata.generation = Some(2);
+        svc.set_condition(condition("Ready", "True"));
+        assert_eq!(svc.conditions()[0].observed_generation, Some(2));
+        assert!(svc.is_condition_true("Ready"));
+
+        // the condition is stale once the generation moves on
+        svc.metadata.generation = Some(3);
+        assert!(!svc.is_condition_true("Ready"));
+        assert!(is_condition_true(svc.conditions(), "Ready"));
+    }
+}
@@ -54,6 +54,36 @@ impl ConversionRequest {
     pub fn from_review(review: ConversionReview) -> Result<Self, ConvertConversionReviewError> {
         ConversionRequest::try_from(review)
     }
+
+    /// Converts every object in the request and creates the matching response
+    ///
+    /// `convert` receives each object along with the desired apiVersion. The `apiVersion`
+    /// of every converted object is set to the desired apiVersion afterwards.
+    ///
+    /// The response is a [`failure`](ConversionResponse::failure) with the first error returned
+    /// by `convert`, and a [`success`](ConversionResponse::success) otherwise.
+    pub fn convert<F, E>(mut self, mut convert: F) -> ConversionResponse
+    where
+        F: FnMut(serde_json::Value, &str) -> Result<serde_json::Value, E>,
+        E: std::fmt::Display,
+    {
+        let objects = std::mem::take(&mut self.objects);
+        let desired_api_version = self.desired_api_version.clone();
+        let response = ConversionResponse::for_request(self);
+        let mut converted_objects = Vec::with_capacity(objects.len());
+        for object in objects {
+            match convert(object, &desired_api_version) {
+                Ok(mut converted) => {
+                    if let Some(obj) = converted.as_object_mut() {
+                        obj.insert("apiVersion".into(), desired_api_version.clone().into());
+                    }
+                    converted_objects.push(converted);
+                }
+                Err(err) => return response.failure(Status::failure(&err.to_string(), "ConversionFailed")),
+            }
+        }
+        response.success(converted_objects)
+    }
 }
 
 impl TryFrom<ConversionReview> for ConversionRequest {
@@ -198,6 +228,7 @@ impl From<ConversionResponse> for ConversionReview {
 #[cfg(test)]
 mod tests {
     use super::{ConversionRequest, ConversionResponse};
+    use crate::response::StatusSummary;
 
     #[test]
     fn simple_request_parses() {
@@ -209,4 +240,30 @@ mod tests {
         let res = ConversionResponse::for_request(req);
         let _ = res.into_review();
     }
+
+    #[test]
+    fn objects_are_converted() {
+        let data = include_str!("./test_data/simple.json");
+        let req = ConversionRequest::from_review(serde_json::from_str(data).unwrap()).unwrap();
+        let count = req.objects.len();
+        let res = req.convert(|mut obj, desired| {
+            assert_eq!(desired, "nullable.se/v1");
+            obj["spec"]["converted"] = true.into();
+            Ok::<_, String>(obj)
+        });
+        assert_eq!(res.result.status, Some(StatusSummary::Success));
+        assert_eq!(res.converted_objects.len(), count);
+        for obj in &res.converted_objects {
+            assert_eq!(obj["apiVersion"], "nullable.se/v1");
+            assert_eq!(obj["spec"]["converted"], true);
+        }
+
+        let req = ConversionRequest::from_review(serde_json::from_str(data).unwrap()).unwrap();
+        let res = req.convert(|_, _| Err("unsupported version"));
+        assert_eq!(res.result.status, Some(StatusSummary::Failure));
+        assert_eq!(res.result.message, "unsupported version");
+        assert!(res.converted_objects.is_empty());
+        let review = serde_json::to_value(res.into_review()).unwrap();
+        assert_eq!(review["response"]["uid"], "f263987e-4d58-465a-9195-bf72a1c83623");
+    }
 }
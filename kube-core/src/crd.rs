@@ -4,7 +4,7 @@ use k8s_openapi::apiextensions_apiserver::pkg::apis::apiextensions as apiexts;
 
 /// Types for v1 CustomResourceDefinitions
 pub mod v1 {
-    use super::apiexts::v1::CustomResourceDefinition as Crd;
+    use super::apiexts::v1::{CustomResourceDefinition as Crd, JSONSchemaProps};
     /// Extension trait that is implemented by kube-derive
     pub trait CustomResourceExt {
         /// Helper to generate the CRD including the JsonSchema
@@ -28,6 +28,37 @@ pub mod v1 {
         fn shortnames() -> &'static [&'static str];
     }
 
+    /// Extension trait for custom resources converted between versions by a webhook
+    ///
+    /// This is implemented by kube-derive for types with `#[kube(conversion(..))]`, for every version of the kind.
+    pub trait HasConversion: CustomResourceExt {
+        /// The `spec.conversion` of the generated CRD
+        fn conversion() -> super::apiexts::v1::CustomResourceConversion;
+    }
+
+    /// Marker trait for the hub version of a kind, that its other versions are converted to and from
+    ///
+    /// This is implemented by kube-derive for types with `#[kube(conversion(.., hub))]`.
+    pub trait Hub: HasConversion {}
+
+    /// Marker trait linking a version of a kind to the [`Hub`] version that it is converted to and from
+    ///
+    /// This is implemented by kube-derive for types with `#[kube(conversion(.., hub = "path::to::Hub"))]`,
+    /// and for the hub version itself.
+    pub trait Convertible<H: Hub>: HasConversion {}
+
+    /// Extension trait for custom resources with the scale subresource
+    ///
+    /// This is implemented by kube-derive for types with `#[kube(scale(..))]`.
+    pub trait HasScale: CustomResourceExt {
+        /// The JSON path of the desired replicas, the `specReplicasPath` of the scale subresource
+        fn scale_spec_path() -> &'static str;
+        /// The JSON path of the observed replicas, the `statusReplicasPath` of the scale subresource
+        fn scale_status_path() -> &'static str;
+        /// The JSON path of the label selector, the `labelSelectorPath` of the scale subresource
+        fn scale_label_selector_path() -> Option<&'static str>;
+    }
+
     /// Possible errors when merging CRDs
     #[derive(Debug, thiserror::Error)]
     pub enum MergeError {
@@ -54,6 +85,10 @@ pub mod v1 {
         /// Mismatching spec properties on crds
         #[error("mismatching {0} property from given CRDs")]
         PropertyMismatch(String),
+
+        /// The same version given in several crds
+        #[error("api version {0} given in multiple CRDs")]
+        DuplicateVersion(String),
     }
 
     /// Merge a collection of crds into a single multiversion crd
@@ -66,6 +101,8 @@ pub mod v1 {
     ///
     /// - exposes exactly one [`CRDVersion`]
     /// - uses identical values for `spec.group`, `spec.scope`, and `spec.names.kind`
+    /// - has either no `spec.conversion` or the one of the stored version
+    /// - has a version name that is unique among the given [`CRD`]s
     ///
     /// This is always true for [`CustomResource`] derives.
     ///
@@ -101,6 +138,12 @@ pub mod v1 {
                 return Err(MergeError::MultiVersionCrd);
             }
         }
+        for (i, crd) in crds.iter().enumerate() {
+            let name = &crd.spec.versions[0].name;
+            if crds[..i].iter().any(|c| &c.spec.versions[0].name == name) {
+                return Err(MergeError::DuplicateVersion(name.clone()));
+            }
+        }
         let ver = stored_apiversion;
         let found = crds.iter().position(|c| c.spec.versions[0].name == ver);
         // Extract the root/first object to start with (the one we will merge into)
@@ -125,6 +168,9 @@ pub mod v1 {
             if &crd.spec.scope != scope {
                 return Err(MergeError::PropertyMismatch("scope".to_string()));
             }
+            if crd.spec.conversion.is_some() && crd.spec.conversion != root.spec.conversion {
+                return Err(MergeError::PropertyMismatch("conversion".to_string()));
+            }
         }
 
         // combine all version objects into the root object
@@ -138,7 +184,198 @@ pub mod v1 {
         Ok(root)
     }
 
+    /// The CRD to server-side apply to install `crd` over the `existing` CRD in the cluster
+    ///
+    /// This strips the `status` and server-managed metadata from `crd`, and preserves what other actors
+    /// manage on the `existing` CRD, so that operators can safely install their own CRDs on startup:
+    ///
+    /// - the `spec.conversion` of the existing CRD when `crd` has none
+    /// - the `caBundle` of the existing conversion webhook when `crd` has the same webhook without one,
+    ///   as it is commonly injected by a certificate manager
+    /// - the versions in `status.storedVersions` of the existing CRD that `crd` no longer has,
+    ///   as the apiserver rejects removing versions that objects may still be stored in
+    ///
+    /// ```no_run
+    /// use kube::{api::{Api, Patch, PatchParams}, core::crd::crd_apply_patch, CustomResourceExt};
+    /// use k8s_openapi::apiextensions_apiserver::pkg::apis::apiextensions::v1::CustomResourceDefinition;
+    /// # async fn wrapper<Foo: CustomResourceExt>(client: kube::Client) -> Result<(), kube::Error> {
+    /// let crds: Api<CustomResourceDefinition> = Api::all(client);
+    /// let existing = crds.get_opt(Foo::crd_name()).await?;
+    /// let patch = crd_apply_patch(&Foo::crd(), existing.as_ref());
+    /// let pp = PatchParams::apply("foo-operator").force();
+    /// crds.patch(Foo::crd_name(), &pp, &Patch::Apply(patch)).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn crd_apply_patch(crd: &Crd, existing: Option<&Crd>) -> Crd {
+        use k8s_openapi::apimachinery::pkg::apis::meta::v1::ObjectMeta;
+        let mut patch = Crd {
+            metadata: ObjectMeta {
+                name: crd.metadata.name.clone(),
+                labels: crd.metadata.labels.clone(),
+                annotations: crd.metadata.annotations.clone(),
+                ..ObjectMeta::default()
+            },
+            spec: crd.spec.clone(),
+            status: None,
+        };
+        let existing = match existing {
+            Some(existing) => existing,
+            None => return patch,
+        };
+
+        match (&mut patch.spec.conversion, &existing.spec.conversion) {
+            (conversion @ None, Some(current)) => *conversion = Some(current.clone()),
+            (Some(desired), Some(current)) => {
+                let desired_config = desired.webhook.as_mut().and_then(|w| w.client_config.as_mut());
+                let current_config = current.webhook.as_ref().and_then(|w| w.client_config.as_ref());
+                if let (Some(desired), Some(current)) = (desired_config, current_config) {
+                    if desired.ca_bundle.is_none()
+                        && desired.service == current.service
+                        && desired.url == current.url
+                    {
+                        desired.ca_bundle = current.ca_bundle.clone();
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        let stored_versions = existing.status.as_ref().and_then(|s| s.stored_versions.as_ref());
+        for stored in stored_versions.into_iter().flatten() {
+            if patch.spec.versions.iter().any(|v| &v.name == stored) {
+                continue;
+            }
+            if let Some(version) = existing.spec.versions.iter().find(|v| &v.name == stored) {
+                patch
+                    .spec
+                    .versions
+                    .push(super::apiexts::v1::CustomResourceDefinitionVersion {
+                        storage: false,
+                        ..version.clone()
+                    });
+            }
+        }
+        patch
+    }
+
+    /// An example object of the storage version of a crd, for sample manifests
+    ///
+    /// The object is named after the singular name of the crd, and its `spec` is the
+    /// [`schema_example`] of the `spec` in the schema, or empty without a schema.
+    pub fn crd_example(crd: &Crd) -> serde_json::Value {
+        let version = crd
+            .spec
+            .versions
+            .iter()
+            .find(|v| v.storage)
+            .or_else(|| crd.spec.versions.first());
+        let spec = version
+            .and_then(|v| v.schema.as_ref())
+            .and_then(|s| s.open_api_v3_schema.as_ref())
+            .and_then(|s| s.properties.as_ref())
+            .and_then(|props| props.get("spec"))
+            .map_or_else(|| serde_json::json!({}), schema_example);
+        let singular = crd
+            .spec
+            .names
+            .singular
+            .as_deref()
+            .unwrap_or(&crd.spec.names.plural);
+        serde_json::json!({
+            "apiVersion": format!("{}/{}", crd.spec.group, version.map_or("", |v| v.name.as_str())),
+            "kind": crd.spec.names.kind,
+            "metadata": { "name": format!("{singular}-example") },
+            "spec": spec,
+        })
+    }
+
+    /// An example value of a schema
+    ///
+    /// Objects get the examples of their required properties, and of the optional properties that have an
+    /// `example` or `default`, overlaid with their own `example` or `default`. Other values are their `example`,
+    /// their `default`, or else the first value of an `enum`, the `minimum` of a number, or an empty value of the type.
+    pub fn schema_example(schema: &JSONSchemaProps) -> serde_json::Value {
+        use serde_json::Value;
+        let given = schema
+            .example
+            .as_ref()
+            .or(schema.default.as_ref())
+            .map(|json| json.0.clone());
+        if let Some(props) = &schema.properties {
+            let required = schema.required.as_deref().unwrap_or_default();
+            let mut object: serde_json::Map<String, Value> = props
+                .iter()
+                .filter(|(name, prop)| {
+                    required.contains(name) || prop.example.is_some() || prop.default.is_some()
+                })
+                .map(|(name, prop)| (name.clone(), schema_example(prop)))
+                .collect();
+            if let Some(Value::Object(given)) = given {
+                object.extend(given);
+            }
+            return Value::Object(object);
+        }
+        if let Some(given) = given {
+            return given;
+        }
+        if let Some(first) = schema.enum_.as_ref().and_then(|values| values.first()) {
+            return first.0.clone();
+        }
+        if let Some(first) = schema
+            .one_of
+            .as_ref()
+            .or(schema.any_of.as_ref())
+            .and_then(|s| s.first())
+        {
+            return schema_example(first);
+        }
+        if schema.x_kubernetes_int_or_string == Some(true) {
+            return 0.into();
+        }
+        match schema.type_.as_deref() {
+            Some("string") => "".into(),
+            Some("integer") => schema.minimum.map_or(0, |min| min.ceil() as i64).into(),
+            Some("number") => schema.minimum.unwrap_or_default().into(),
+            Some("boolean") => false.into(),
+            Some("array") => Value::Array(vec![]),
+            Some("object") => Value::Object(serde_json::Map::new()),
+            _ => Value::Null,
+        }
+    }
+
     mod tests {
+        #[test]
+        fn schema_examples() {
+            use super::{schema_example, JSONSchemaProps};
+            let schema: JSONSchemaProps = serde_json::from_value(serde_json::json!({
+                "type": "object",
+                "required": ["name", "replicas", "mode", "ports"],
+                "properties": {
+                    "name": {"type": "string"},
+                    "replicas": {"type": "integer", "minimum": 1},
+                    "mode": {"type": "string", "enum": ["fast", "slow"]},
+                    "ports": {"type": "array", "items": {"type": "integer"}},
+                    "image": {"type": "string", "default": "nginx"},
+                    "paused": {"type": "boolean"},
+                    "limits": {"type": "object", "example": {"cpu": "1"}, "properties": {"cpu": {"type": "string"}}}
+                },
+                "example": {"name": "web"}
+            }))
+            .unwrap();
+            assert_eq!(
+                schema_example(&schema),
+                serde_json::json!({
+                    "name": "web",
+                    "replicas": 1,
+                    "mode": "fast",
+                    "ports": [],
+                    "image": "nginx",
+                    "limits": {"cpu": "1"}
+                })
+            );
+        }
+
         #[test]
         fn crd_merge() {
             use super::{merge_crds, Crd};
@@ -231,8 +468,106 @@ pub mod v1 {
             let exp_json = serde_json::to_value(&ce).unwrap();
             assert_json_diff::assert_json_eq!(combo_json, exp_json);
         }
+
+        #[test]
+        fn crd_apply_patches_preserve_cluster_state() {
+            use super::{crd_apply_patch, Crd};
+            let crd: Crd = serde_yaml::from_str(
+                r#"
+            apiVersion: apiextensions.k8s.io/v1
+            kind: CustomResourceDefinition
+            metadata:
+              name: foos.clux.dev
+              resourceVersion: "12"
+            spec:
+              group: clux.dev
+              names:
+                kind: Foo
+                plural: foos
+              scope: Namespaced
+              conversion:
+                strategy: Webhook
+                webhook:
+                  conversionReviewVersions: ["v1"]
+                  clientConfig:
+                    service: {name: foo-conversion, namespace: foo}
+              versions:
+              - name: v2
+                served: true
+                storage: true
+            status:
+              storedVersions: ["v2"]"#,
+            )
+            .unwrap();
+
+            let fresh = crd_apply_patch(&crd, None);
+            assert!(fresh.status.is_none());
+            assert!(fresh.metadata.resource_version.is_none());
+            assert_eq!(fresh.spec, crd.spec);
+
+            let mut existing = crd.clone();
+            let webhook = existing
+                .spec
+                .conversion
+                .as_mut()
+                .unwrap()
+                .webhook
+                .as_mut()
+                .unwrap();
+            webhook.client_config.as_mut().unwrap().ca_bundle =
+                Some(k8s_openapi::ByteString(b"injected".to_vec()));
+            existing.spec.versions[0].name = "v1".into();
+            existing.status.as_mut().unwrap().stored_versions = Some(vec!["v1".into()]);
+
+            let patch = crd_apply_patch(&crd, Some(&existing));
+            let conversion = patch.spec.conversion.as_ref().unwrap();
+            let client_config = conversion
+                .webhook
+                .as_ref()
+                .unwrap()
+                .client_config
+                .as_ref()
+                .unwrap();
+            assert_eq!(client_config.ca_bundle.as_ref().unwrap().0, b"injected");
+            let versions = patch.spec.versions.iter().map(|v| (v.name.as_str(), v.storage));
+            assert_eq!(versions.collect::<Vec<_>>(), [("v2", true), ("v1", false)]);
+
+            let mut unconverted = crd.clone();
+            unconverted.spec.conversion = None;
+            let patch = crd_apply_patch(&unconverted, Some(&existing));
+            assert_eq!(patch.spec.conversion, existing.spec.conversion);
+        }
+
+        #[test]
+        fn crd_merge_rejects_duplicate_versions() {
+            use super::{merge_crds, Crd, MergeError};
+            let crd = r#"
+            apiVersion: apiextensions.k8s.io/v1
+            kind: CustomResourceDefinition
+            metadata:
+              name: multiversions.kube.rs
+            spec:
+              group: kube.rs
+              names:
+                kind: MultiVersion
+                plural: multiversions
+              scope: Namespaced
+              versions:
+              - name: v1
+                served: true
+                storage: true"#;
+            let c1: Crd = serde_yaml::from_str(crd).unwrap();
+            let c2 = c1.clone();
+            assert!(matches!(
+                merge_crds(vec![c1, c2], "v1"),
+                Err(MergeError::DuplicateVersion(v)) if v == "v1"
+            ));
+        }
     }
 }
 
 // re-export current latest (v1)
-pub use v1::{merge_crds, CustomResourceExt, MergeError};
+pub use v1::{
+    crd_apply_patch, crd_example, merge_crds, schema_example, Convertible, CustomResourceExt, HasConversion,
+    HasScale, Hub, MergeError,
+};
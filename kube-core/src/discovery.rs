@@ -1,5 +1,8 @@
 //! Type information structs for API discovery
-use crate::{gvk::GroupVersionKind, resource::Resource};
+use crate::{
+    gvk::{GroupVersionKind, GroupVersionResource},
+    resource::Resource,
+};
 use serde::{Deserialize, Serialize};
 
 /// Information about a Kubernetes API resource
@@ -57,8 +60,20 @@ impl ApiResource {
     }
 }
 
+impl From<&ApiResource> for GroupVersionKind {
+    fn from(ar: &ApiResource) -> Self {
+        GroupVersionKind::gvk(&ar.group, &ar.version, &ar.kind)
+    }
+}
+
+impl From<&ApiResource> for GroupVersionResource {
+    fn from(ar: &ApiResource) -> Self {
+        GroupVersionResource::gvr(&ar.group, &ar.version, &ar.plural)
+    }
+}
+
 /// Resource scope
-#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+#[derive(Debug, Clone, Hash, Eq, PartialEq, Serialize, Deserialize)]
 pub enum Scope {
     /// Objects are global
     Cluster,
@@ -87,7 +102,8 @@ pub mod verbs {
 }
 
 /// Contains the capabilities of an API resource
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
 pub struct ApiCapabilities {
     /// Scope of the resource
     pub scope: Scope,
@@ -99,13 +115,61 @@ pub struct ApiCapabilities {
     pub subresources: Vec<(ApiResource, ApiCapabilities)>,
     /// Supported operations on this resource
     pub operations: Vec<String>,
+    /// Short names of the resource, like `deploy` for deployments
+    #[serde(default)]
+    pub short_names: Vec<String>,
+    /// Categories the resource belongs to, like `all`
+    #[serde(default)]
+    pub categories: Vec<String>,
 }
 
 impl ApiCapabilities {
+    /// Creates the capabilities of a resource, without short names or categories
+    pub fn new(
+        scope: Scope,
+        subresources: Vec<(ApiResource, ApiCapabilities)>,
+        operations: Vec<String>,
+    ) -> Self {
+        Self {
+            scope,
+            subresources,
+            operations,
+            short_names: vec![],
+            categories: vec![],
+        }
+    }
+
+    /// Sets the short names of the resource
+    #[must_use]
+    pub fn with_short_names(mut self, short_names: Vec<String>) -> Self {
+        self.short_names = short_names;
+        self
+    }
+
+    /// Sets the categories of the resource
+    #[must_use]
+    pub fn with_categories(mut self, categories: Vec<String>) -> Self {
+        self.categories = categories;
+        self
+    }
+
     /// Checks that given verb is supported on this resource.
     pub fn supports_operation(&self, operation: &str) -> bool {
         self.operations.iter().any(|op| op == operation)
     }
+
+    /// Checks that all given verbs are supported on this resource.
+    ///
+    /// ```
+    /// use kube_core::discovery::{verbs, ApiCapabilities, Scope};
+    /// let operations = vec![verbs::GET.into(), verbs::LIST.into(), verbs::WATCH.into()];
+    /// let caps = ApiCapabilities::new(Scope::Namespaced, vec![], operations);
+    /// assert!(caps.supports_operations(&[verbs::LIST, verbs::WATCH]));
+    /// assert!(!caps.supports_operations(&[verbs::LIST, verbs::PATCH]));
+    /// ```
+    pub fn supports_operations(&self, operations: &[&str]) -> bool {
+        operations.iter().all(|op| self.supports_operation(op))
+    }
 }
 
 // Simple pluralizer. Handles the special cases.
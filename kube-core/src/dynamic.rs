@@ -12,11 +12,21 @@ use std::borrow::Cow;
 use thiserror::Error;
 
 #[derive(Debug, Error)]
-#[error("failed to parse this DynamicObject into a Resource: {source}")]
 /// Failed to parse `DynamicObject` into `Resource`
-pub struct ParseDynamicObjectError {
-    #[from]
-    source: serde_json::Error,
+#[non_exhaustive]
+pub enum ParseDynamicObjectError {
+    /// The object could not be (de)serialized as the `Resource`
+    #[error("failed to parse this DynamicObject into a Resource: {0}")]
+    Serde(#[from] serde_json::Error),
+
+    /// The object is of a different type than the `Resource`
+    #[error("failed to parse this DynamicObject into a Resource: expected {expected}, found {found}")]
+    TypeMismatch {
+        /// The `apiVersion` and `kind` of the `Resource`
+        expected: String,
+        /// The `apiVersion` and `kind` of the object
+        found: String,
+    },
 }
 
 /// A dynamic representation of a kubernetes object
@@ -68,11 +78,32 @@ impl DynamicObject {
     }
 
     /// Attempt to convert this `DynamicObject` to a `Resource`
+    ///
+    /// This does not check the type of the object, see [`DynamicObject::try_parse_with`] for that.
     pub fn try_parse<K: Resource + for<'a> serde::Deserialize<'a>>(
         self,
     ) -> Result<K, ParseDynamicObjectError> {
         Ok(serde_json::from_value(serde_json::to_value(self)?)?)
     }
+
+    /// Attempt to convert this `DynamicObject` to a `Resource`, checking its type against the dynamic type of `K`
+    ///
+    /// Fails with [`ParseDynamicObjectError::TypeMismatch`] if the object has `TypeMeta` that does not match `K`.
+    pub fn try_parse_with<K: Resource + for<'a> serde::Deserialize<'a>>(
+        self,
+        dt: &K::DynamicType,
+    ) -> Result<K, ParseDynamicObjectError> {
+        if let Some(types) = &self.types {
+            let (api_version, kind) = (K::api_version(dt), K::kind(dt));
+            if types.api_version != api_version || types.kind != kind {
+                return Err(ParseDynamicObjectError::TypeMismatch {
+                    expected: format!("{api_version}/{kind}"),
+                    found: format!("{}/{}", types.api_version, types.kind),
+                });
+            }
+        }
+        Ok(serde_json::from_value(serde_json::to_value(self)?)?)
+    }
 }
 
 impl Resource for DynamicObject {
@@ -111,11 +142,11 @@ impl Resource for DynamicObject {
 #[cfg(test)]
 mod test {
     use crate::{
-        dynamic::{ApiResource, DynamicObject},
+        dynamic::{ApiResource, DynamicObject, ParseDynamicObjectError},
         gvk::GroupVersionKind,
         params::{Patch, PatchParams, PostParams},
         request::Request,
-        resource::Resource,
+        resource::{Resource, ResourceExt},
     };
     use k8s_openapi::api::core::v1::Pod;
 
@@ -168,4 +199,30 @@ mod test {
 
         Ok(())
     }
+
+    #[test]
+    fn try_parse_validates_type() {
+        let ar = ApiResource::erase::<Pod>(&());
+        let mut pod =
+            DynamicObject::new("example", &ar).data(serde_json::json!({ "spec": { "containers": [] } }));
+        assert!(pod.clone().try_parse_with::<Pod>(&()).is_ok());
+
+        pod.types.as_mut().unwrap().kind = "Service".into();
+        let err = pod.try_parse_with::<Pod>(&()).unwrap_err();
+        assert!(matches!(
+            err,
+            ParseDynamicObjectError::TypeMismatch { ref expected, ref found }
+                if expected == "v1/Pod" && found == "v1/Service"
+        ));
+    }
+
+    #[test]
+    fn resources_convert_into_dynamic_objects() {
+        let mut pod = Pod::default();
+        pod.metadata.name = Some("example".into());
+        let dynamic_pod = pod.clone().into_dynamic().unwrap();
+        assert_eq!(dynamic_pod.types.as_ref().unwrap().kind, "Pod");
+        assert_eq!(dynamic_pod.metadata.name.as_deref(), Some("example"));
+        assert_eq!(dynamic_pod.try_parse::<Pod>().unwrap(), pod);
+    }
 }
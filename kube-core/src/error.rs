@@ -1,8 +1,13 @@
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+use crate::response::{PriorityAndFairness, StatusDetails};
+
 /// An error response from the API.
-#[derive(Error, Deserialize, Serialize, Debug, Clone, Eq, PartialEq)]
+///
+/// Responses also carry metadata that is not part of the error itself, like its
+/// [`priority_and_fairness`](ErrorResponse::priority_and_fairness), which is not compared by `PartialEq`.
+#[derive(Error, Deserialize, Serialize, Debug, Clone, Eq)]
 #[error("{message}: {reason}")]
 pub struct ErrorResponse {
     /// The status
@@ -15,4 +20,203 @@ pub struct ErrorResponse {
     pub reason: String,
     /// The error code
     pub code: u16,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    details: Option<Box<StatusDetails>>,
+    #[serde(skip)]
+    priority_and_fairness: Option<Box<PriorityAndFairness>>,
+}
+
+impl PartialEq for ErrorResponse {
+    fn eq(&self, other: &Self) -> bool {
+        self.status == other.status
+            && self.message == other.message
+            && self.reason == other.reason
+            && self.code == other.code
+            && self.details == other.details
+    }
+}
+
+impl ErrorResponse {
+    /// Create an error response, like the ones returned by the API
+    pub fn new(
+        status: impl Into<String>,
+        message: impl Into<String>,
+        reason: impl Into<String>,
+        code: u16,
+    ) -> Self {
+        Self {
+            status: status.into(),
+            message: message.into(),
+            reason: reason.into(),
+            code,
+            details: None,
+            priority_and_fairness: None,
+        }
+    }
+
+    /// Extended data associated with the reason, such as the conflicts of a server-side apply
+    pub fn details(&self) -> Option<&StatusDetails> {
+        self.details.as_deref()
+    }
+
+    /// Attach extended data associated with the reason
+    #[must_use]
+    pub fn with_details(mut self, details: StatusDetails) -> Self {
+        self.details = Some(Box::new(details));
+        self
+    }
+
+    /// The API Priority and Fairness metadata of the response that carried the error
+    ///
+    /// `None` for errors that were not received as a response, such as the errors of watch events.
+    pub fn priority_and_fairness(&self) -> Option<&PriorityAndFairness> {
+        self.priority_and_fairness.as_deref()
+    }
+
+    /// Attach the API Priority and Fairness metadata of the response that carried the error
+    #[must_use]
+    pub fn with_priority_and_fairness(mut self, apf: PriorityAndFairness) -> Self {
+        self.priority_and_fairness = Some(Box::new(apf));
+        self
+    }
+
+    /// The fields that a server-side apply conflicted on, if this is the error of such a conflict
+    ///
+    /// A server-side apply fails with a conflict when it changes fields that are managed by another field manager.
+    /// It can be forced through with [`PatchParams::force`](crate::params::PatchParams::force), which makes the
+    /// applying manager the owner of the fields.
+    pub fn apply_conflicts(&self) -> Option<Vec<ApplyConflict>> {
+        if self.code != 409 || self.reason != "Conflict" {
+            return None;
+        }
+        let conflicts = self
+            .details
+            .as_ref()?
+            .causes
+            .iter()
+            .filter(|cause| cause.reason == "FieldManagerConflict")
+            .map(|cause| ApplyConflict::from_cause(&cause.message, &cause.field))
+            .collect::<Vec<_>>();
+        (!conflicts.is_empty()).then_some(conflicts)
+    }
+
+    /// Whether this is a `409 Conflict`
+    ///
+    /// This is the error of an update with an outdated `resourceVersion`, and of a request with [`Preconditions`]
+    /// that did not hold, like a delete of an object that was recreated with the same name since it was read.
+    ///
+    /// [`Preconditions`]: crate::params::Preconditions
+    pub fn is_conflict(&self) -> bool {
+        self.code == 409 && self.reason == "Conflict"
+    }
+}
+
+/// A field of a server-side apply that is managed by another field manager, see [`ErrorResponse::apply_conflicts`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApplyConflict {
+    /// The field manager that manages the field
+    pub manager: String,
+    /// The API version that the field was last applied with by `manager`
+    pub api_version: Option<String>,
+    /// The path of the field, like `.spec.replicas`
+    pub field: String,
+}
+
+impl ApplyConflict {
+    /// Parses a cause with a message like `conflict with "kubectl" using apps/v1`
+    fn from_cause(message: &str, field: &str) -> Self {
+        let manager = message
+            .split_once('"')
+            .and_then(|(_, rest)| rest.split_once('"'))
+            .map_or(message, |(manager, _)| manager);
+        let api_version = message
+            .rsplit_once(" using ")
+            .map(|(_, version)| version.trim().to_owned());
+        Self {
+            manager: manager.to_owned(),
+            api_version,
+            field: field.to_owned(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ApplyConflict, ErrorResponse};
+    use crate::response::PriorityAndFairness;
+
+    #[test]
+    fn priority_and_fairness_is_not_compared() {
+        let err = ErrorResponse::new("Failure", "too many requests", "TooManyRequests", 429);
+        let apf = PriorityAndFairness {
+            flow_schema_uid: Some("flow".into()),
+            ..PriorityAndFairness::default()
+        };
+        let with_apf = err.clone().with_priority_and_fairness(apf.clone());
+        assert_eq!(with_apf.priority_and_fairness(), Some(&apf));
+        assert_eq!(err.priority_and_fairness(), None);
+        assert_eq!(err, with_apf);
+    }
+
+    #[test]
+    fn apply_conflicts() {
+        let err: ErrorResponse = serde_json::from_value(serde_json::json!({
+            "kind": "Status",
+            "status": "Failure",
+            "message": "Apply failed with 2 conflicts: conflict with \"kubectl-client-side-apply\" using apps/v1: .spec.replicas",
+            "reason": "Conflict",
+            "details": {
+                "causes": [{
+                    "reason": "FieldManagerConflict",
+                    "message": "conflict with \"kubectl-client-side-apply\" using apps/v1",
+                    "field": ".spec.replicas"
+                }, {
+                    "reason": "FieldManagerConflict",
+                    "message": "conflict with \"hpa\" with subresource \"scale\" using autoscaling/v1",
+                    "field": ".spec.replicas"
+                }]
+            },
+            "code": 409
+        }))
+        .unwrap();
+        let expected = [
+            ApplyConflict {
+                manager: "kubectl-client-side-apply".into(),
+                api_version: Some("apps/v1".into()),
+                field: ".spec.replicas".into(),
+            },
+            ApplyConflict {
+                manager: "hpa".into(),
+                api_version: Some("autoscaling/v1".into()),
+                field: ".spec.replicas".into(),
+            },
+        ];
+        assert_eq!(err.details().unwrap().causes.len(), 2);
+        assert_eq!(err.apply_conflicts().unwrap(), expected);
+
+        // Conflicts of updates with an outdated resourceVersion are not apply conflicts
+        let err: ErrorResponse = serde_json::from_value(serde_json::json!({
+            "status": "Failure",
+            "message": "the object has been modified; please apply your changes to the latest version and try again",
+            "reason": "Conflict",
+            "details": {"name": "foo", "kind": "configmaps"},
+            "code": 409
+        }))
+        .unwrap();
+        assert_eq!(err.apply_conflicts(), None);
+    }
+
+    #[test]
+    fn conflicts() {
+        let err: ErrorResponse = serde_json::from_value(serde_json::json!({
+            "status": "Failure",
+            "message": "Operation cannot be fulfilled on pods \"web\": Precondition failed: UID in precondition: 1f0a, UID in object meta: 9c2b",
+            "reason": "Conflict",
+            "details": {"name": "web", "kind": "pods"},
+            "code": 409
+        }))
+        .unwrap();
+        assert!(err.is_conflict());
+        assert!(!ErrorResponse::new("Failure", "pods \"web\" not found", "NotFound", 404).is_conflict());
+    }
 }
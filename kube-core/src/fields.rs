@@ -0,0 +1,304 @@
+//! Typed field selectors, with the syntax of the `fieldSelector` query parameter
+use std::{fmt, ops::BitAnd, str::FromStr};
+
+use serde_json::Value;
+use thiserror::Error;
+
+/// An invalid field selector
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("invalid field selector {selector:?}: {reason}")]
+pub struct ParseSelectorError {
+    selector: String,
+    reason: String,
+}
+
+/// A requirement on a field, of which a [`Selector`] is made
+///
+/// Fields are named by their path in the serialized object, like `spec.nodeName`. The apiserver only
+/// supports a few fields per kind, and `metadata.name` and `metadata.namespace` for every kind.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expression {
+    /// The field has the value: `field=value`
+    Equal(String, String),
+    /// The field has another value: `field!=value`
+    NotEqual(String, String),
+}
+
+impl Expression {
+    /// The path of the field, like `spec.nodeName`
+    pub fn field(&self) -> &str {
+        match self {
+            Self::Equal(field, _) | Self::NotEqual(field, _) => field,
+        }
+    }
+
+    /// Whether the field of the serialized `object` fulfills this requirement
+    ///
+    /// Missing fields match the empty value, like they do on the apiserver.
+    pub fn matches(&self, object: &Value) -> bool {
+        let (field, expected, equal) = match self {
+            Self::Equal(field, value) => (field, value, true),
+            Self::NotEqual(field, value) => (field, value, false),
+        };
+        let value = field.split('.').try_fold(object, |value, name| value.get(name));
+        let matches = match value {
+            None | Some(Value::Null) => expected.is_empty(),
+            Some(Value::String(value)) => value == expected,
+            Some(value) => &value.to_string() == expected,
+        };
+        matches == equal
+    }
+}
+
+/// Start building an [`Expression`] on the field at `path`, like `spec.nodeName`
+///
+/// The apiserver only supports equality on fields, so the builder only offers [`Field::eq`] and [`Field::ne`].
+/// Expressions and selectors can be combined with `&`:
+///
+/// ```
+/// use kube::core::fields::{field, Selector};
+/// let selector: Selector = field("status.phase").ne("Succeeded") & field("spec.nodeName").eq("node-1");
+/// assert_eq!(selector.to_string(), "status.phase!=Succeeded,spec.nodeName=node-1");
+/// ```
+pub fn field(path: &str) -> Field {
+    Field(path.to_string())
+}
+
+/// A field to build an [`Expression`] on, see [`field`]
+#[derive(Debug, Clone)]
+pub struct Field(String);
+
+impl Field {
+    /// The field has the value: `field=value`
+    #[allow(clippy::should_implement_trait)]
+    pub fn eq(self, value: impl Into<String>) -> Expression {
+        Expression::Equal(self.0, value.into())
+    }
+
+    /// The field has another value: `field!=value`
+    #[allow(clippy::should_implement_trait)]
+    pub fn ne(self, value: impl Into<String>) -> Expression {
+        Expression::NotEqual(self.0, value.into())
+    }
+}
+
+impl fmt::Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let escape = |value: &str| {
+            value
+                .replace('\\', "\\\\")
+                .replace(',', "\\,")
+                .replace('=', "\\=")
+        };
+        match self {
+            Self::Equal(field, value) => write!(f, "{field}={}", escape(value)),
+            Self::NotEqual(field, value) => write!(f, "{field}!={}", escape(value)),
+        }
+    }
+}
+
+/// A field selector, which selects the objects whose fields fulfill all of its [`Expression`]s
+///
+/// ```
+/// use kube::core::fields::{Expression, Selector};
+/// let selector: Selector = "spec.nodeName=node-1,status.phase!=Succeeded".parse().unwrap();
+/// assert!(selector.matches(&serde_json::json!({"spec": {"nodeName": "node-1"}})));
+/// assert_eq!(selector.expressions()[0], Expression::Equal("spec.nodeName".into(), "node-1".into()));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Selector(Vec<Expression>);
+
+impl Selector {
+    /// Add a requirement to the selector
+    #[must_use]
+    pub fn with(mut self, expression: Expression) -> Self {
+        self.0.push(expression);
+        self
+    }
+
+    /// Whether the selector selects every object
+    pub fn selects_all(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Whether the fields of the serialized `object` fulfill all requirements of the selector
+    pub fn matches(&self, object: &Value) -> bool {
+        self.0.iter().all(|expression| expression.matches(object))
+    }
+
+    /// The requirements of the selector
+    pub fn expressions(&self) -> &[Expression] {
+        &self.0
+    }
+}
+
+impl fmt::Display for Selector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, expression) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_str(",")?;
+            }
+            expression.fmt(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl From<Expression> for Selector {
+    fn from(expression: Expression) -> Self {
+        Self(vec![expression])
+    }
+}
+
+impl FromIterator<Expression> for Selector {
+    fn from_iter<T: IntoIterator<Item = Expression>>(iter: T) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl BitAnd for Expression {
+    type Output = Selector;
+
+    fn bitand(self, rhs: Self) -> Selector {
+        Selector(vec![self, rhs])
+    }
+}
+
+impl BitAnd<Expression> for Selector {
+    type Output = Selector;
+
+    fn bitand(self, rhs: Expression) -> Selector {
+        self.with(rhs)
+    }
+}
+
+impl BitAnd for Selector {
+    type Output = Selector;
+
+    fn bitand(mut self, rhs: Self) -> Selector {
+        self.0.extend(rhs.0);
+        self
+    }
+}
+
+impl FromStr for Selector {
+    type Err = ParseSelectorError;
+
+    /// Parses every term of a field selector, whose values escape `\`, `,` and `=` like the apiserver expects
+    fn from_str(selector: &str) -> Result<Self, Self::Err> {
+        let invalid = |reason: String| ParseSelectorError {
+            selector: selector.to_string(),
+            reason,
+        };
+        let mut terms = Vec::new();
+        let (mut start, mut escaped) = (0, false);
+        for (i, c) in selector.char_indices() {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                ',' => {
+                    terms.push(&selector[start..i]);
+                    start = i + 1;
+                }
+                _ => {}
+            }
+        }
+        terms.push(&selector[start..]);
+
+        let mut expressions = Vec::new();
+        for term in terms.into_iter().filter(|term| !term.is_empty()) {
+            let (field, op, value) = ["!=", "==", "="]
+                .iter()
+                .filter_map(|op| term.find(op).map(|i| (i, *op)))
+                // The first operator, where `==` is preferred over `=`
+                .min_by_key(|&(i, op)| (i, std::cmp::Reverse(op.len())))
+                .map(|(i, op)| (&term[..i], op, &term[i + op.len()..]))
+                .ok_or_else(|| invalid(format!("{term:?} has no operator")))?;
+            if field.trim().is_empty() {
+                return Err(invalid(format!("{term:?} has no field")));
+            }
+            let mut unescaped = String::with_capacity(value.len());
+            let mut chars = value.chars();
+            while let Some(c) = chars.next() {
+                match c {
+                    '\\' => match chars.next() {
+                        Some(c @ ('\\' | ',' | '=')) => unescaped.push(c),
+                        _ => return Err(invalid("only '\\', ',' and '=' can be escaped".into())),
+                    },
+                    ',' | '=' => return Err(invalid(format!("'{c}' must be escaped in {value:?}"))),
+                    _ => unescaped.push(c),
+                }
+            }
+            expressions.push(if op == "!=" {
+                Expression::NotEqual(field.to_string(), unescaped)
+            } else {
+                Expression::Equal(field.to_string(), unescaped)
+            });
+        }
+        Ok(Self(expressions))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn parses_and_formats_selectors() {
+        let selector: Selector = "metadata.name==a\\,b\\=c\\\\,status.phase!=Running,spec.nodeName="
+            .parse()
+            .unwrap();
+        let expected = [
+            Expression::Equal("metadata.name".into(), "a,b=c\\".into()),
+            Expression::NotEqual("status.phase".into(), "Running".into()),
+            Expression::Equal("spec.nodeName".into(), String::new()),
+        ];
+        assert_eq!(selector.expressions(), expected);
+        assert_eq!(
+            selector.to_string(),
+            "metadata.name=a\\,b\\=c\\\\,status.phase!=Running,spec.nodeName="
+        );
+        assert!("".parse::<Selector>().unwrap().selects_all());
+        let err = "metadata.name=pod=1".parse::<Selector>().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid field selector \"metadata.name=pod=1\": '=' must be escaped in \"pod=1\""
+        );
+    }
+
+    #[test]
+    fn matches_objects() {
+        let pod = json!({
+            "metadata": {"name": "web", "namespace": "apps"},
+            "spec": {"nodeName": "node-1", "hostNetwork": true},
+            "status": {"phase": "Running"},
+        });
+        let selects = |selector: &str| selector.parse::<Selector>().unwrap().matches(&pod);
+        assert!(selects("spec.nodeName=node-1,metadata.namespace=apps"));
+        assert!(selects("status.phase!=Succeeded"));
+        assert!(selects("spec.hostNetwork=true"));
+        assert!(selects("spec.schedulerName="));
+        assert!(!selects("spec.nodeName=node-2"));
+        assert!(!selects("spec.nodeName="));
+    }
+
+    #[test]
+    fn builds_selectors() {
+        let node = String::from("node-1");
+        let selector = field("status.phase").ne("Succeeded") & field("spec.nodeName").eq(node);
+        let expected = [
+            Expression::NotEqual("status.phase".into(), "Succeeded".into()),
+            Expression::Equal("spec.nodeName".into(), "node-1".into()),
+        ];
+        assert_eq!(selector.expressions(), expected);
+        let selector = selector & field("metadata.name").eq("a,b");
+        assert_eq!(
+            selector.to_string(),
+            "status.phase!=Succeeded,spec.nodeName=node-1,metadata.name=a\\,b"
+        );
+        assert_eq!(selector.to_string().parse::<Selector>().unwrap(), selector);
+        let combined = Selector::from(field("a").eq("1")) & Selector::from(field("b").eq("2"));
+        assert_eq!(combined.to_string(), "a=1,b=2");
+    }
+}
@@ -1,5 +1,5 @@
 //! Type information structs for dynamic resources.
-use std::str::FromStr;
+use std::{fmt, str::FromStr};
 
 use crate::TypeMeta;
 use serde::{Deserialize, Serialize};
@@ -32,6 +32,32 @@ impl GroupVersionKind {
     }
 }
 
+/// Parses the `apiVersion/kind` form used by [`Display`](fmt::Display), e.g. `apps/v1/Deployment` or `v1/Pod`
+impl FromStr for GroupVersionKind {
+    type Err = ParseGroupVersionError;
+
+    fn from_str(gvk: &str) -> Result<Self, Self::Err> {
+        match gvk.rsplit_once('/') {
+            Some((gv, kind)) if !kind.is_empty() => Ok(GroupVersion::from_str(gv)?.with_kind(kind)),
+            _ => Err(ParseGroupVersionError(gvk.into())),
+        }
+    }
+}
+
+impl TryFrom<&str> for GroupVersionKind {
+    type Error = ParseGroupVersionError;
+
+    fn try_from(gvk: &str) -> Result<Self, Self::Error> {
+        gvk.parse()
+    }
+}
+
+impl fmt::Display for GroupVersionKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.api_version(), self.kind)
+    }
+}
+
 impl TryFrom<&TypeMeta> for GroupVersionKind {
     type Error = ParseGroupVersionError;
 
@@ -72,6 +98,11 @@ impl GroupVersion {
             kind: kind.into(),
         }
     }
+
+    /// Upgrade a GroupVersion to a GroupVersionResource
+    pub fn with_resource(self, resource: &str) -> GroupVersionResource {
+        GroupVersionResource::gvr(&self.group, &self.version, resource)
+    }
 }
 
 impl FromStr for GroupVersion {
@@ -84,10 +115,19 @@ impl FromStr for GroupVersion {
             [v] => ("".to_string(), v.to_string()),   // core v1 case
             _ => return Err(ParseGroupVersionError(gv.into())),
         };
+        if version.is_empty() || version.contains('/') {
+            return Err(ParseGroupVersionError(gv.into()));
+        }
         Ok(Self { group, version })
     }
 }
 
+impl fmt::Display for GroupVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.api_version())
+    }
+}
+
 impl GroupVersion {
     /// Generate the apiVersion string used in a kind's yaml
     pub fn api_version(&self) -> String {
@@ -107,6 +147,11 @@ impl GroupVersionKind {
             format!("{}/{}", self.group, self.version)
         }
     }
+
+    /// The GroupVersion of this kind
+    pub fn group_version(&self) -> GroupVersion {
+        GroupVersion::gv(&self.group, &self.version)
+    }
 }
 
 /// Represents a type-erased object resource.
@@ -142,6 +187,44 @@ impl GroupVersionResource {
             api_version,
         }
     }
+
+    /// The apiVersion string of the resource
+    pub fn api_version(&self) -> &str {
+        &self.api_version
+    }
+
+    /// The GroupVersion of this resource
+    pub fn group_version(&self) -> GroupVersion {
+        GroupVersion::gv(&self.group, &self.version)
+    }
+}
+
+/// Parses the `apiVersion/resource` form used by [`Display`](fmt::Display), e.g. `apps/v1/deployments` or `v1/pods`
+impl FromStr for GroupVersionResource {
+    type Err = ParseGroupVersionError;
+
+    fn from_str(gvr: &str) -> Result<Self, Self::Err> {
+        match gvr.rsplit_once('/') {
+            Some((gv, resource)) if !resource.is_empty() => {
+                Ok(GroupVersion::from_str(gv)?.with_resource(resource))
+            }
+            _ => Err(ParseGroupVersionError(gvr.into())),
+        }
+    }
+}
+
+impl TryFrom<&str> for GroupVersionResource {
+    type Error = ParseGroupVersionError;
+
+    fn try_from(gvr: &str) -> Result<Self, Self::Error> {
+        gvr.parse()
+    }
+}
+
+impl fmt::Display for GroupVersionResource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.api_version(), self.resource)
+    }
 }
 
 #[cfg(test)]
@@ -163,4 +246,46 @@ metadata:
         assert_eq!(gvk.version, "v1");
         assert_eq!(gvk.kind, gvk2.kind);
     }
+
+    #[test]
+    fn gvk_string_round_trip() {
+        use crate::{GroupVersion, GroupVersionKind};
+        let gvk = GroupVersionKind::try_from("apps/v1/Deployment").unwrap();
+        assert_eq!(gvk, GroupVersionKind::gvk("apps", "v1", "Deployment"));
+        assert_eq!(gvk.api_version(), "apps/v1");
+        assert_eq!(gvk.to_string(), "apps/v1/Deployment");
+        assert_eq!(gvk.group_version(), GroupVersion::gv("apps", "v1"));
+
+        let core: GroupVersionKind = "v1/Pod".parse().unwrap();
+        assert_eq!(core, GroupVersionKind::gvk("", "v1", "Pod"));
+        assert_eq!(core.to_string(), "v1/Pod");
+
+        for invalid in ["Pod", "v1/", "/Pod", "a/b/c/Kind"] {
+            assert!(
+                GroupVersionKind::try_from(invalid).is_err(),
+                "{invalid} should not parse"
+            );
+        }
+    }
+
+    #[test]
+    fn gvr_string_round_trip() {
+        use crate::{GroupVersion, GroupVersionResource};
+        let gvr: GroupVersionResource = "apps/v1/deployments".parse().unwrap();
+        assert_eq!(gvr, GroupVersionResource::gvr("apps", "v1", "deployments"));
+        assert_eq!(gvr.api_version(), "apps/v1");
+        assert_eq!(gvr.to_string(), "apps/v1/deployments");
+        assert_eq!(
+            GroupVersion::gv("", "v1").with_resource("pods").to_string(),
+            "v1/pods"
+        );
+        assert!(GroupVersionResource::try_from("deployments").is_err());
+
+        let ar = crate::ApiResource::erase::<k8s_openapi::api::apps::v1::Deployment>(&());
+        assert_eq!(GroupVersionResource::from(&ar), gvr);
+        assert_eq!(
+            crate::GroupVersionKind::from(&ar).to_string(),
+            "apps/v1/Deployment"
+        );
+    }
 }
@@ -0,0 +1,261 @@
+//! Builders for [RFC 6902](https://datatracker.ietf.org/doc/html/rfc6902) JSON patches, as sent with [`Patch::Json`](crate::params::Patch::Json)
+use std::fmt;
+
+use json_patch::{
+    AddOperation, CopyOperation, MoveOperation, RemoveOperation, ReplaceOperation, TestOperation,
+};
+use serde::Serialize;
+use serde_json::Value;
+
+pub use json_patch::{Patch, PatchOperation};
+
+/// A path to a value in an object, as an [RFC 6901](https://datatracker.ietf.org/doc/html/rfc6901) JSON pointer
+///
+/// Fields are named as they are serialized, so `spec.containers[0].image` is
+/// `Pointer::root().field("spec").field("containers").index(0).field("image")`.
+/// Field names are escaped, which matters for labels and annotations with a prefix:
+///
+/// ```
+/// use kube::core::jsonpatch::Pointer;
+/// let path = Pointer::label("app.kubernetes.io/name");
+/// assert_eq!(path.to_string(), "/metadata/labels/app.kubernetes.io~1name");
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Pointer(String);
+
+impl Pointer {
+    /// The whole object
+    pub fn root() -> Self {
+        Self::default()
+    }
+
+    /// The label of an object with the given key
+    pub fn label(key: &str) -> Self {
+        Self::root().field("metadata").field("labels").field(key)
+    }
+
+    /// The annotation of an object with the given key
+    pub fn annotation(key: &str) -> Self {
+        Self::root().field("metadata").field("annotations").field(key)
+    }
+
+    /// The field of an object with the given name
+    #[must_use]
+    pub fn field(mut self, name: &str) -> Self {
+        self.0.push('/');
+        self.0.push_str(&name.replace('~', "~0").replace('/', "~1"));
+        self
+    }
+
+    /// The element of an array at the given index
+    #[must_use]
+    pub fn index(mut self, index: usize) -> Self {
+        self.0.push('/');
+        self.0.push_str(&index.to_string());
+        self
+    }
+
+    /// The position after the last element of an array, to append an element with [`PatchBuilder::add`]
+    #[must_use]
+    pub fn end(mut self) -> Self {
+        self.0.push_str("/-");
+        self
+    }
+
+    /// The pointer as a string
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for Pointer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<Pointer> for String {
+    fn from(pointer: Pointer) -> Self {
+        pointer.0
+    }
+}
+
+/// Builds a [`Patch`] from typed values
+///
+/// Values are serialized as they are added, and the first error is returned by [`PatchBuilder::build`].
+/// The `test` operations guard the patch: the apiserver rejects the whole patch when one of them does not hold.
+///
+/// ```
+/// use kube::core::jsonpatch::{PatchBuilder, Pointer};
+/// let containers = Pointer::root().field("spec").field("template").field("spec").field("containers");
+/// let patch = PatchBuilder::new()
+///     .test(containers.clone().index(0).field("name"), "app")
+///     .replace(containers.index(0).field("image"), "app:2.0")
+///     .add(Pointer::label("app.kubernetes.io/version"), "2.0")
+///     .build()
+///     .unwrap();
+/// assert_eq!(patch.0.len(), 3);
+/// ```
+#[derive(Debug, Default)]
+pub struct PatchBuilder {
+    operations: Vec<PatchOperation>,
+    error: Option<serde_json::Error>,
+}
+
+impl PatchBuilder {
+    /// An empty patch
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn push_value(mut self, value: impl Serialize, operation: impl FnOnce(Value) -> PatchOperation) -> Self {
+        match serde_json::to_value(value) {
+            Ok(value) => self.operations.push(operation(value)),
+            Err(err) => {
+                self.error.get_or_insert(err);
+            }
+        }
+        self
+    }
+
+    /// Add a value, or insert it into an array
+    #[must_use]
+    pub fn add(self, path: Pointer, value: impl Serialize) -> Self {
+        self.push_value(value, |value| {
+            PatchOperation::Add(AddOperation {
+                path: path.into(),
+                value,
+            })
+        })
+    }
+
+    /// Remove a value, which must exist
+    #[must_use]
+    pub fn remove(mut self, path: Pointer) -> Self {
+        self.operations
+            .push(PatchOperation::Remove(RemoveOperation { path: path.into() }));
+        self
+    }
+
+    /// Replace a value, which must exist
+    #[must_use]
+    pub fn replace(self, path: Pointer, value: impl Serialize) -> Self {
+        self.push_value(value, |value| {
+            PatchOperation::Replace(ReplaceOperation {
+                path: path.into(),
+                value,
+            })
+        })
+    }
+
+    /// Move the value at `from` to `path`
+    #[must_use]
+    pub fn move_value(mut self, from: Pointer, path: Pointer) -> Self {
+        self.operations.push(PatchOperation::Move(MoveOperation {
+            from: from.into(),
+            path: path.into(),
+        }));
+        self
+    }
+
+    /// Copy the value at `from` to `path`
+    #[must_use]
+    pub fn copy_value(mut self, from: Pointer, path: Pointer) -> Self {
+        self.operations.push(PatchOperation::Copy(CopyOperation {
+            from: from.into(),
+            path: path.into(),
+        }));
+        self
+    }
+
+    /// Only apply the patch if the value at `path` is equal to `value`
+    #[must_use]
+    pub fn test(self, path: Pointer, value: impl Serialize) -> Self {
+        self.push_value(value, |value| {
+            PatchOperation::Test(TestOperation {
+                path: path.into(),
+                value,
+            })
+        })
+    }
+
+    /// Only apply the patch if the object has not changed since it had the `resource_version`
+    #[must_use]
+    pub fn test_resource_version(self, resource_version: &str) -> Self {
+        self.test(
+            Pointer::root().field("metadata").field("resourceVersion"),
+            resource_version,
+        )
+    }
+
+    /// The patch, or the first error that occurred while serializing its values
+    pub fn build(self) -> Result<Patch, serde_json::Error> {
+        match self.error {
+            Some(err) => Err(err),
+            None => Ok(Patch(self.operations)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escapes_pointers() {
+        assert_eq!(Pointer::root().as_str(), "");
+        let path = Pointer::root().field("a/b").field("m~n").index(3).end();
+        assert_eq!(path.as_str(), "/a~1b/m~0n/3/-");
+        assert_eq!(
+            Pointer::annotation("example.com/a~b").as_str(),
+            "/metadata/annotations/example.com~1a~0b"
+        );
+    }
+
+    #[test]
+    fn builds_patches() {
+        let patch = PatchBuilder::new()
+            .test_resource_version("12")
+            .add(Pointer::root().field("spec").field("args").end(), "--verbose")
+            .remove(Pointer::label("legacy"))
+            .replace(Pointer::root().field("spec").field("replicas"), 3)
+            .move_value(Pointer::annotation("old"), Pointer::annotation("new"))
+            .copy_value(Pointer::label("app"), Pointer::annotation("app"))
+            .build()
+            .unwrap();
+        assert_eq!(
+            serde_json::to_value(&patch).unwrap(),
+            serde_json::json!([
+                {"op": "test", "path": "/metadata/resourceVersion", "value": "12"},
+                {"op": "add", "path": "/spec/args/-", "value": "--verbose"},
+                {"op": "remove", "path": "/metadata/labels/legacy"},
+                {"op": "replace", "path": "/spec/replicas", "value": 3},
+                {"op": "move", "from": "/metadata/annotations/old", "path": "/metadata/annotations/new"},
+                {"op": "copy", "from": "/metadata/labels/app", "path": "/metadata/annotations/app"},
+            ])
+        );
+
+        // The patch applies where the guard holds
+        let mut doc = serde_json::json!({"metadata": {"resourceVersion": "12"}, "spec": {"replicas": 1}});
+        let patch = PatchBuilder::new()
+            .test_resource_version("12")
+            .replace(Pointer::root().field("spec").field("replicas"), 3)
+            .build()
+            .unwrap();
+        json_patch::patch(&mut doc, &patch).unwrap();
+        assert_eq!(doc["spec"]["replicas"], 3);
+        let patch = PatchBuilder::new().test_resource_version("11").build().unwrap();
+        assert!(json_patch::patch(&mut doc, &patch).is_err());
+    }
+
+    #[test]
+    fn reports_serialization_errors() {
+        let mut invalid = std::collections::HashMap::new();
+        invalid.insert((1, 2), "tuple keys cannot be serialized");
+        let result = PatchBuilder::new()
+            .add(Pointer::root().field("a"), 1)
+            .add(Pointer::root().field("b"), invalid)
+            .build();
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,606 @@
+//! Typed label selectors, with the syntax of the `labelSelector` query parameter
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+    iter::Peekable,
+    str::FromStr,
+};
+
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::{LabelSelector, LabelSelectorRequirement};
+use thiserror::Error;
+
+/// An invalid label selector
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("invalid label selector: {0}")]
+pub struct ParseSelectorError(String);
+
+/// A requirement on a label, of which a [`Selector`] is made
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expression {
+    /// The label has one of the values: `key in (a,b)`
+    In(String, BTreeSet<String>),
+    /// The label is missing, or has none of the values: `key notin (a,b)`
+    NotIn(String, BTreeSet<String>),
+    /// The label has the value: `key=value`
+    Equal(String, String),
+    /// The label is missing, or has another value: `key!=value`
+    NotEqual(String, String),
+    /// The label exists: `key`
+    Exists(String),
+    /// The label does not exist: `!key`
+    DoesNotExist(String),
+    /// The label is an integer greater than the value: `key>1`
+    GreaterThan(String, i64),
+    /// The label is an integer less than the value: `key<1`
+    LessThan(String, i64),
+}
+
+impl Expression {
+    fn key(&self) -> &str {
+        match self {
+            Self::In(key, _)
+            | Self::NotIn(key, _)
+            | Self::Equal(key, _)
+            | Self::NotEqual(key, _)
+            | Self::Exists(key)
+            | Self::DoesNotExist(key)
+            | Self::GreaterThan(key, _)
+            | Self::LessThan(key, _) => key,
+        }
+    }
+
+    /// Whether the `labels` fulfill this requirement
+    pub fn matches(&self, labels: &BTreeMap<String, String>) -> bool {
+        let value = labels.get(self.key());
+        match self {
+            Self::In(_, values) => value.map_or(false, |value| values.contains(value)),
+            Self::NotIn(_, values) => value.map_or(true, |value| !values.contains(value)),
+            Self::Equal(_, expected) => value == Some(expected),
+            Self::NotEqual(_, expected) => value != Some(expected),
+            Self::Exists(_) => value.is_some(),
+            Self::DoesNotExist(_) => value.is_none(),
+            Self::GreaterThan(_, bound) => parse_int(value).map_or(false, |value| value > *bound),
+            Self::LessThan(_, bound) => parse_int(value).map_or(false, |value| value < *bound),
+        }
+    }
+
+    /// Checks the key and values like the apiserver does
+    fn validate(&self) -> Result<(), ParseSelectorError> {
+        validate_key(self.key())?;
+        match self {
+            Self::In(key, values) | Self::NotIn(key, values) => {
+                if values.is_empty() {
+                    return Err(ParseSelectorError(format!("{key:?} needs at least one value")));
+                }
+                values.iter().try_for_each(|value| validate_value(value))
+            }
+            Self::Equal(_, value) | Self::NotEqual(_, value) => validate_value(value),
+            _ => Ok(()),
+        }
+    }
+}
+
+fn parse_int(value: Option<&String>) -> Option<i64> {
+    value.and_then(|value| value.parse().ok())
+}
+
+impl fmt::Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let join =
+            |values: &BTreeSet<String>| values.iter().map(String::as_str).collect::<Vec<_>>().join(",");
+        match self {
+            Self::In(key, values) => write!(f, "{key} in ({})", join(values)),
+            Self::NotIn(key, values) => write!(f, "{key} notin ({})", join(values)),
+            Self::Equal(key, value) => write!(f, "{key}={value}"),
+            Self::NotEqual(key, value) => write!(f, "{key}!={value}"),
+            Self::Exists(key) => write!(f, "{key}"),
+            Self::DoesNotExist(key) => write!(f, "!{key}"),
+            Self::GreaterThan(key, value) => write!(f, "{key}>{value}"),
+            Self::LessThan(key, value) => write!(f, "{key}<{value}"),
+        }
+    }
+}
+
+/// A label selector, which selects the objects whose labels fulfill all of its [`Expression`]s
+///
+/// Selectors can be built from expressions, converted to and from the `matchLabels` and `matchExpressions` of a [`LabelSelector`],
+/// or parsed from a string, and are formatted as a `labelSelector` for [`ListParams::labels_from`](crate::params::ListParams::labels_from).
+///
+/// ```
+/// use kube::core::labels::{Expression, Selector};
+/// let selector = Selector::default()
+///     .with(Expression::Equal("app".into(), "web".into()))
+///     .with(Expression::In("tier".into(), ["frontend".into(), "edge".into()].into()));
+/// assert_eq!(selector.to_string(), "app=web,tier in (edge,frontend)");
+/// assert_eq!(selector, "app=web, tier in (frontend, edge)".parse().unwrap());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Selector(Vec<Expression>);
+
+impl Selector {
+    /// Add a requirement to the selector
+    #[must_use]
+    pub fn with(mut self, expression: Expression) -> Self {
+        self.0.push(expression);
+        self
+    }
+
+    /// Whether the selector selects every object
+    pub fn selects_all(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Whether the `labels` fulfill all requirements of the selector
+    pub fn matches(&self, labels: &BTreeMap<String, String>) -> bool {
+        self.0.iter().all(|expression| expression.matches(labels))
+    }
+
+    /// The requirements of the selector
+    pub fn expressions(&self) -> &[Expression] {
+        &self.0
+    }
+}
+
+impl fmt::Display for Selector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, expression) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_str(",")?;
+            }
+            expression.fmt(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl From<Expression> for Selector {
+    fn from(expression: Expression) -> Self {
+        Self(vec![expression])
+    }
+}
+
+impl FromIterator<Expression> for Selector {
+    fn from_iter<T: IntoIterator<Item = Expression>>(iter: T) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+/// Selects the objects with all of the labels, like the `matchLabels` of a [`LabelSelector`]
+impl From<BTreeMap<String, String>> for Selector {
+    fn from(labels: BTreeMap<String, String>) -> Self {
+        labels
+            .into_iter()
+            .map(|(key, value)| Expression::Equal(key, value))
+            .collect()
+    }
+}
+
+impl TryFrom<LabelSelector> for Selector {
+    type Error = ParseSelectorError;
+
+    fn try_from(selector: LabelSelector) -> Result<Self, Self::Error> {
+        let mut expressions: Vec<_> = selector
+            .match_labels
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(key, value)| Expression::Equal(key, value))
+            .collect();
+        for requirement in selector.match_expressions.unwrap_or_default() {
+            expressions.push(Expression::try_from(requirement)?);
+        }
+        expressions.iter().try_for_each(Expression::validate)?;
+        Ok(Self(expressions))
+    }
+}
+
+/// Converts into the `matchLabels` and `matchExpressions` of a [`LabelSelector`]
+///
+/// Fails for [`Expression::GreaterThan`] and [`Expression::LessThan`], which a [`LabelSelector`] cannot express.
+impl TryFrom<Selector> for LabelSelector {
+    type Error = ParseSelectorError;
+
+    fn try_from(selector: Selector) -> Result<Self, Self::Error> {
+        let mut match_labels = BTreeMap::new();
+        let mut match_expressions = Vec::new();
+        let mut requirement = |key: String, operator: &str, values: Option<Vec<String>>| {
+            match_expressions.push(LabelSelectorRequirement {
+                key,
+                operator: operator.into(),
+                values,
+            })
+        };
+        for expression in selector.0 {
+            match expression {
+                Expression::Equal(key, value) if !match_labels.contains_key(&key) => {
+                    match_labels.insert(key, value);
+                }
+                Expression::Equal(key, value) => requirement(key, "In", Some(vec![value])),
+                Expression::In(key, values) => requirement(key, "In", Some(values.into_iter().collect())),
+                Expression::NotIn(key, values) => {
+                    requirement(key, "NotIn", Some(values.into_iter().collect()))
+                }
+                Expression::NotEqual(key, value) => requirement(key, "NotIn", Some(vec![value])),
+                Expression::Exists(key) => requirement(key, "Exists", None),
+                Expression::DoesNotExist(key) => requirement(key, "DoesNotExist", None),
+                expression @ (Expression::GreaterThan(..) | Expression::LessThan(..)) => {
+                    return Err(ParseSelectorError(format!(
+                        "{expression} cannot be expressed in a LabelSelector"
+                    )));
+                }
+            }
+        }
+        Ok(LabelSelector {
+            match_labels: (!match_labels.is_empty()).then_some(match_labels),
+            match_expressions: (!match_expressions.is_empty()).then_some(match_expressions),
+        })
+    }
+}
+
+impl TryFrom<LabelSelectorRequirement> for Expression {
+    type Error = ParseSelectorError;
+
+    fn try_from(requirement: LabelSelectorRequirement) -> Result<Self, Self::Error> {
+        let LabelSelectorRequirement {
+            key,
+            operator,
+            values,
+        } = requirement;
+        let values = values.unwrap_or_default();
+        let expression = match operator.as_str() {
+            "In" => Self::In(key, values.into_iter().collect()),
+            "NotIn" => Self::NotIn(key, values.into_iter().collect()),
+            "Exists" | "DoesNotExist" if !values.is_empty() => {
+                return Err(ParseSelectorError(format!(
+                    "{operator} on {key:?} cannot have values"
+                )));
+            }
+            "Exists" => Self::Exists(key),
+            "DoesNotExist" => Self::DoesNotExist(key),
+            _ => {
+                return Err(ParseSelectorError(format!(
+                    "unknown operator {operator:?} on {key:?}"
+                )))
+            }
+        };
+        expression.validate()?;
+        Ok(expression)
+    }
+}
+
+impl FromStr for Selector {
+    type Err = ParseSelectorError;
+
+    fn from_str(selector: &str) -> Result<Self, Self::Err> {
+        let mut tokens = lex(selector).into_iter().peekable();
+        let mut expressions = Vec::new();
+        if tokens.peek().is_none() {
+            return Ok(Self(expressions));
+        }
+        loop {
+            let expression = parse_expression(&mut tokens)?;
+            expression.validate()?;
+            expressions.push(expression);
+            match tokens.next() {
+                None => return Ok(Self(expressions)),
+                Some(Token::Op(",")) => {}
+                Some(token) => return Err(unexpected(&token, "','")),
+            }
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Token<'a> {
+    Ident(&'a str),
+    Op(&'static str),
+}
+
+impl fmt::Display for Token<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Ident(ident) | Token::Op(ident) => write!(f, "'{ident}'"),
+        }
+    }
+}
+
+fn unexpected(token: &Token<'_>, expected: &str) -> ParseSelectorError {
+    ParseSelectorError(format!("found {token}, expected {expected}"))
+}
+
+fn is_special(c: char) -> bool {
+    c.is_whitespace() || "!=(),<>".contains(c)
+}
+
+/// Splits a selector into identifiers and operators, like the lexer of the apiserver
+fn lex(selector: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut chars = selector.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        let op = match c {
+            c if c.is_whitespace() => continue,
+            '!' | '=' if chars.next_if(|&(_, next)| next == '=').is_some() => {
+                if c == '!' {
+                    "!="
+                } else {
+                    "=="
+                }
+            }
+            '!' => "!",
+            '=' => "=",
+            '(' => "(",
+            ')' => ")",
+            ',' => ",",
+            '<' => "<",
+            '>' => ">",
+            _ => {
+                let mut end = start + c.len_utf8();
+                while let Some((i, c)) = chars.next_if(|&(_, c)| !is_special(c)) {
+                    end = i + c.len_utf8();
+                }
+                tokens.push(Token::Ident(&selector[start..end]));
+                continue;
+            }
+        };
+        tokens.push(Token::Op(op));
+    }
+    tokens
+}
+
+type Tokens<'a> = Peekable<std::vec::IntoIter<Token<'a>>>;
+
+fn parse_expression(tokens: &mut Tokens<'_>) -> Result<Expression, ParseSelectorError> {
+    let key = match tokens.next() {
+        Some(Token::Op("!")) => return Ok(Expression::DoesNotExist(parse_ident(tokens)?.to_owned())),
+        Some(Token::Ident(key)) => key.to_owned(),
+        Some(token) => return Err(unexpected(&token, "'!' or a label key")),
+        None => return Err(ParseSelectorError("expected a label key at the end".into())),
+    };
+    let op = match tokens.peek() {
+        None | Some(Token::Op(",")) => return Ok(Expression::Exists(key)),
+        Some(Token::Op(op)) => *op,
+        Some(Token::Ident(op)) => *op,
+    };
+    tokens.next();
+    match op {
+        "=" | "==" => Ok(Expression::Equal(key, parse_exact_value(tokens)?)),
+        "!=" => Ok(Expression::NotEqual(key, parse_exact_value(tokens)?)),
+        "in" => Ok(Expression::In(key, parse_set(tokens)?)),
+        "notin" => Ok(Expression::NotIn(key, parse_set(tokens)?)),
+        ">" | "<" => {
+            let value = parse_ident(tokens)?;
+            let value = value
+                .parse()
+                .map_err(|_| ParseSelectorError(format!("{value:?} for {key:?} is not an integer")))?;
+            Ok(if op == ">" {
+                Expression::GreaterThan(key, value)
+            } else {
+                Expression::LessThan(key, value)
+            })
+        }
+        _ => Err(ParseSelectorError(format!(
+            "found '{op}', expected an operator after {key:?}"
+        ))),
+    }
+}
+
+fn parse_ident<'a>(tokens: &mut Tokens<'a>) -> Result<&'a str, ParseSelectorError> {
+    match tokens.next() {
+        Some(Token::Ident(ident)) => Ok(ident),
+        Some(token) => Err(unexpected(&token, "an identifier")),
+        None => Err(ParseSelectorError("expected an identifier at the end".into())),
+    }
+}
+
+/// The value of `=`, `==` and `!=`, which may be empty
+fn parse_exact_value(tokens: &mut Tokens<'_>) -> Result<String, ParseSelectorError> {
+    match tokens.peek() {
+        None | Some(Token::Op(",")) => Ok(String::new()),
+        _ => parse_ident(tokens).map(str::to_owned),
+    }
+}
+
+/// The values of `in` and `notin`, where empty values are allowed like in `(a,)`
+fn parse_set(tokens: &mut Tokens<'_>) -> Result<BTreeSet<String>, ParseSelectorError> {
+    match tokens.next() {
+        Some(Token::Op("(")) => {}
+        Some(token) => return Err(unexpected(&token, "'('")),
+        None => return Err(ParseSelectorError("expected '(' at the end".into())),
+    }
+    let mut values = BTreeSet::new();
+    let mut expect_value = true;
+    loop {
+        match tokens.next() {
+            Some(Token::Ident(value)) if expect_value => {
+                values.insert(value.to_owned());
+                expect_value = false;
+            }
+            Some(Token::Op(op @ ("," | ")"))) => {
+                if expect_value {
+                    values.insert(String::new());
+                }
+                if op == ")" {
+                    return Ok(values);
+                }
+                expect_value = true;
+            }
+            Some(token) => return Err(unexpected(&token, "',', ')' or a value")),
+            None => return Err(ParseSelectorError("expected ')' at the end".into())),
+        }
+    }
+}
+
+/// Whether `name` is alphanumeric with `-`, `_` and `.` in between, like the name of a label
+fn is_label_name(name: &str) -> bool {
+    let bytes = name.as_bytes();
+    match (bytes.first(), bytes.last()) {
+        (Some(first), Some(last)) => {
+            first.is_ascii_alphanumeric()
+                && last.is_ascii_alphanumeric()
+                && bytes
+                    .iter()
+                    .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.'))
+        }
+        _ => false,
+    }
+}
+
+fn is_dns_subdomain(name: &str) -> bool {
+    name.len() <= 253
+        && name.split('.').all(|label| {
+            let bytes = label.as_bytes();
+            match (bytes.first(), bytes.last()) {
+                (Some(first), Some(last)) => {
+                    first.is_ascii_alphanumeric()
+                        && last.is_ascii_alphanumeric()
+                        && bytes
+                            .iter()
+                            .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || *b == b'-')
+                }
+                _ => false,
+            }
+        })
+}
+
+/// Checks that a key is a qualified name, with an optional DNS subdomain prefix: `example.com/name`
+fn validate_key(key: &str) -> Result<(), ParseSelectorError> {
+    let (prefix, name) = match key.split_once('/') {
+        Some((prefix, name)) => (Some(prefix), name),
+        None => (None, key),
+    };
+    if prefix.map_or(false, |prefix| !is_dns_subdomain(prefix)) {
+        return Err(ParseSelectorError(format!(
+            "the prefix of {key:?} must be a DNS subdomain"
+        )));
+    }
+    if name.len() > 63 || !is_label_name(name) {
+        return Err(ParseSelectorError(format!(
+            "{key:?} must be an alphanumeric name of at most 63 characters, with '-', '_' or '.' in between"
+        )));
+    }
+    Ok(())
+}
+
+fn validate_value(value: &str) -> Result<(), ParseSelectorError> {
+    if !value.is_empty() && (value.len() > 63 || !is_label_name(value)) {
+        return Err(ParseSelectorError(format!(
+            "{value:?} must be empty or an alphanumeric value of at most 63 characters, with '-', '_' or '.' in between"
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(selector: &str) -> Result<Selector, ParseSelectorError> {
+        selector.parse()
+    }
+
+    #[test]
+    fn parses_all_operators() {
+        let selector =
+            parse("app=web,env==prod,tier!=db, track in (a,b), zone notin (x), ready, !legacy,gen>1,size<10")
+                .unwrap();
+        assert_eq!(
+            selector.to_string(),
+            "app=web,env=prod,tier!=db,track in (a,b),zone notin (x),ready,!legacy,gen>1,size<10"
+        );
+        assert_eq!(parse(&selector.to_string()).unwrap(), selector);
+        assert!(parse("").unwrap().selects_all());
+        assert!(parse("  ").unwrap().selects_all());
+        assert_eq!(
+            parse("app.kubernetes.io/name=,x in (a,)").unwrap().to_string(),
+            "app.kubernetes.io/name=,x in (,a)"
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_selectors() {
+        for selector in [
+            "app=web,",
+            ",app",
+            "app=web=x",
+            "app in a",
+            "app in (a b)",
+            "app in (a",
+            "app > x",
+            "app == -web",
+            "-app=web",
+            "Example.com/app=web",
+            "a/b/c",
+            "app=web)",
+            "!",
+            "app >",
+        ] {
+            assert!(parse(selector).is_err(), "{selector:?} was accepted");
+        }
+        let long = format!("app={}", "a".repeat(64));
+        assert!(parse(&long).is_err());
+    }
+
+    #[test]
+    fn matches_labels() {
+        let labels: BTreeMap<String, String> = [("app", "web"), ("gen", "3")]
+            .into_iter()
+            .map(|(key, value)| (key.to_owned(), value.to_owned()))
+            .collect();
+        for (selector, matches) in [
+            ("app=web", true),
+            ("app!=web", false),
+            ("tier!=db", true),
+            ("app in (web,api)", true),
+            ("tier notin (db)", true),
+            ("app,!tier", true),
+            ("!app", false),
+            ("gen>2,gen<4", true),
+            ("app>2", false),
+            ("", true),
+        ] {
+            assert_eq!(parse(selector).unwrap().matches(&labels), matches, "{selector}");
+        }
+    }
+
+    #[test]
+    fn from_label_selector() {
+        let selector = LabelSelector {
+            match_labels: Some([("app".to_owned(), "web".to_owned())].into()),
+            match_expressions: Some(vec![LabelSelectorRequirement {
+                key: "tier".into(),
+                operator: "NotIn".into(),
+                values: Some(vec!["db".into()]),
+            }]),
+        };
+        let selector = Selector::try_from(selector).unwrap();
+        assert_eq!(selector.to_string(), "app=web,tier notin (db)");
+
+        // and back again, with the semantics preserved
+        let roundtrip =
+            LabelSelector::try_from(parse("app=web,app=api,tier!=db,ready,!legacy").unwrap()).unwrap();
+        assert_eq!(
+            roundtrip.match_labels,
+            Some([("app".to_owned(), "web".to_owned())].into())
+        );
+        assert_eq!(
+            Selector::try_from(roundtrip).unwrap().to_string(),
+            "app=web,app in (api),tier notin (db),ready,!legacy"
+        );
+        assert_eq!(
+            LabelSelector::try_from(Selector::default()).unwrap(),
+            LabelSelector::default()
+        );
+        assert!(LabelSelector::try_from(parse("gen>1").unwrap()).is_err());
+
+        for (operator, values) in [("In", None), ("Exists", Some(vec!["a".into()])), ("Gt", None)] {
+            let requirement = LabelSelectorRequirement {
+                key: "tier".into(),
+                operator: operator.into(),
+                values,
+            };
+            assert!(
+                Expression::try_from(requirement).is_err(),
+                "{operator} was accepted"
+            );
+        }
+    }
+}
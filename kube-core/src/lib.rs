@@ -14,6 +14,9 @@
 #[cfg(feature = "admission")]
 pub mod admission;
 
+pub mod conditions;
+pub use conditions::HasConditions;
+
 pub mod conversion;
 
 pub mod discovery;
@@ -27,33 +30,48 @@ pub use dynamic::{ApiResource, DynamicObject};
 pub mod crd;
 pub use crd::CustomResourceExt;
 
+pub mod fields;
+
 pub mod gvk;
 pub use gvk::{GroupVersion, GroupVersionKind, GroupVersionResource};
 
+#[cfg_attr(docsrs, doc(cfg(feature = "jsonpatch")))]
+#[cfg(feature = "jsonpatch")]
+pub mod jsonpatch;
+
+pub mod labels;
+
 pub mod metadata;
 pub use metadata::{ListMeta, ObjectMeta, PartialObjectMeta, PartialObjectMetaExt, TypeMeta};
 
 pub mod object;
-pub use object::{NotUsed, Object, ObjectList};
+pub use object::{HasSpec, HasStatus, NotUsed, Object, ObjectList};
 
 pub mod params;
 
+pub mod prune;
+
+pub mod quantity;
+pub use quantity::ParsedQuantity;
+
 pub mod request;
 pub use request::Request;
 
 mod resource;
 pub use resource::{
-    ClusterResourceScope, DynamicResourceScope, NamespaceResourceScope, Resource, ResourceExt, ResourceScope,
-    SubResourceScope,
+    AnyResource, ClusterResourceScope, DynamicResourceScope, NamespaceResourceScope, Resource, ResourceExt,
+    ResourceScope, SubResourceScope,
 };
 
 pub mod response;
-pub use response::Status;
+pub use response::{PriorityAndFairness, ResponseMetadata, Status};
 
 #[cfg_attr(docsrs, doc(cfg(feature = "schema")))]
 #[cfg(feature = "schema")]
 pub mod schema;
 
+pub mod strategic;
+
 pub mod subresource;
 
 pub mod util;
@@ -62,7 +80,7 @@ pub mod watch;
 pub use watch::WatchEvent;
 
 mod error;
-pub use error::ErrorResponse;
+pub use error::{ApplyConflict, ErrorResponse};
 
 mod version;
 pub use version::Version;
@@ -64,6 +64,24 @@ pub trait PartialObjectMetaExt: private::Sealed {
     /// ```
     fn into_request_partial<K: Resource<DynamicType = ()>>(self) -> PartialObjectMeta<K>;
 
+    /// Convert `ObjectMeta` into a Patch-serializable `PartialObjectMeta` for a dynamic type
+    ///
+    /// Equivalent to [`into_request_partial`](PartialObjectMetaExt::into_request_partial),
+    /// but takes the `TypeMeta` from the `DynamicType` of `K`, so it can be used with `Api<DynamicObject>`:
+    ///
+    /// ```
+    /// # use kube::core::{ApiResource, DynamicObject, GroupVersionKind, ObjectMeta, PartialObjectMetaExt};
+    /// let gvk = GroupVersionKind::gvk("clux.dev", "v1", "Foo");
+    /// let ar = ApiResource::from_gvk(&gvk);
+    /// let partial = ObjectMeta {
+    ///     finalizers: Some(vec!["clux.dev/cleanup".to_string()]),
+    ///     ..Default::default()
+    /// }.into_request_partial_with::<DynamicObject>(&ar);
+    ///
+    /// assert_eq!(partial.types.unwrap().api_version, "clux.dev/v1");
+    /// ```
+    fn into_request_partial_with<K: Resource>(self, dt: &K::DynamicType) -> PartialObjectMeta<K>;
+
     /// Convert `ObjectMeta` into a response object for a specific `Resource`
     ///
     /// This object emulates a response object and **cannot** be used in request bodies
@@ -88,10 +106,14 @@ pub trait PartialObjectMetaExt: private::Sealed {
 
 impl PartialObjectMetaExt for ObjectMeta {
     fn into_request_partial<K: Resource<DynamicType = ()>>(self) -> PartialObjectMeta<K> {
+        self.into_request_partial_with(&())
+    }
+
+    fn into_request_partial_with<K: Resource>(self, dt: &K::DynamicType) -> PartialObjectMeta<K> {
         PartialObjectMeta {
             types: Some(TypeMeta {
-                api_version: K::api_version(&()).into(),
-                kind: K::kind(&()).into(),
+                api_version: K::api_version(dt).into(),
+                kind: K::kind(dt).into(),
             }),
             metadata: self,
             _phantom: PhantomData,
@@ -142,7 +164,7 @@ impl<K: Resource> Resource for PartialObjectMeta<K> {
 #[cfg(test)]
 mod test {
     use super::{ObjectMeta, PartialObjectMeta, PartialObjectMetaExt};
-    use crate::Resource;
+    use crate::{ApiResource, DynamicObject, GroupVersionKind, Resource};
     use k8s_openapi::api::core::v1::Pod;
 
     #[test]
@@ -171,4 +193,27 @@ mod test {
         assert_eq!(response_pom.types.as_ref().unwrap().api_version, "meta.k8s.io/v1");
         assert_eq!(response_pom.types.as_ref().unwrap().kind, "PartialObjectMetadata");
     }
+
+    #[test]
+    fn request_partials_serialize_as_apply_payloads() {
+        let meta = ObjectMeta {
+            name: Some("mypod".into()),
+            finalizers: Some(vec!["kube.rs/cleanup".into()]),
+            ..Default::default()
+        };
+        assert_eq!(
+            serde_json::to_value(meta.clone().into_request_partial::<Pod>()).unwrap(),
+            serde_json::json!({
+                "apiVersion": "v1",
+                "kind": "Pod",
+                "metadata": { "name": "mypod", "finalizers": ["kube.rs/cleanup"] },
+            })
+        );
+
+        let ar = ApiResource::from_gvk(&GroupVersionKind::gvk("clux.dev", "v1", "Foo"));
+        let dynamic_pom = meta.into_request_partial_with::<DynamicObject>(&ar);
+        assert_eq!(dynamic_pom.types.as_ref().unwrap().api_version, "clux.dev/v1");
+        assert_eq!(dynamic_pom.types.as_ref().unwrap().kind, "Foo");
+        assert_eq!(dynamic_pom.metadata.finalizers.unwrap(), vec!["kube.rs/cleanup"]);
+    }
 }
@@ -1,10 +1,11 @@
 //! Generic object and objectlist wrappers.
 use crate::{
     discovery::ApiResource,
+    dynamic::{DynamicObject, ParseDynamicObjectError},
     metadata::{ListMeta, ObjectMeta, TypeMeta},
     resource::{DynamicResourceScope, Resource},
 };
-use serde::{Deserialize, Deserializer, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize};
 use std::borrow::Cow;
 
 /// A generic Kubernetes object list
@@ -81,10 +82,27 @@ impl<T: Clone> ObjectList<T> {
     ///     *elem = 2;
     ///     println!("First element: {:?}", elem); // prints "First element: 2"
     /// }
-
     pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> {
         self.items.iter_mut()
     }
+
+    /// The token to continue the list from, when the list was limited and more items remain
+    ///
+    /// Pass it to [`ListParams::next_page`](crate::params::ListParams::next_page) to list the next page.
+    /// Continue tokens expire after a few minutes, and lists with expired tokens fail with a `410 Gone`.
+    pub fn continue_token(&self) -> Option<&str> {
+        self.metadata
+            .continue_
+            .as_deref()
+            .filter(|token| !token.is_empty())
+    }
+
+    /// How many items remain after this page, if the apiserver could count them
+    ///
+    /// The count is approximate and not set for lists with a label or field selector.
+    pub fn remaining_item_count(&self) -> Option<i64> {
+        self.metadata.remaining_item_count
+    }
 }
 
 impl<T: Clone> IntoIterator for ObjectList<T> {
@@ -220,6 +238,66 @@ where
         self.metadata.namespace = Some(ns.into());
         self
     }
+
+    /// Set the `TypeMeta` of an Object from an `ApiResource`
+    ///
+    /// Useful for objects that were deserialized without type information, such as list items.
+    #[must_use]
+    pub fn with_types(mut self, ar: &ApiResource) -> Self {
+        self.types = Some(TypeMeta {
+            api_version: ar.api_version.clone(),
+            kind: ar.kind.clone(),
+        });
+        self
+    }
+
+    /// Attach a status to an Object
+    #[must_use]
+    pub fn with_status(mut self, status: U) -> Self {
+        self.status = Some(status);
+        self
+    }
+
+    /// A patch for the status subresource containing only the type information and the status
+    ///
+    /// The status subresource ignores changes to anything but the status, so this
+    /// avoids sending the spec along with `Api::patch_status`.
+    /// It can be used with both `Patch::Apply` and `Patch::Merge`.
+    pub fn status_patch(&self) -> serde_json::Value
+    where
+        U: Serialize,
+    {
+        let mut patch = serde_json::json!({ "status": self.status });
+        if let Some(types) = &self.types {
+            patch["apiVersion"] = types.api_version.clone().into();
+            patch["kind"] = types.kind.clone().into();
+        }
+        patch
+    }
+}
+
+impl<P, U> TryFrom<DynamicObject> for Object<P, U>
+where
+    P: Clone + DeserializeOwned,
+    U: Clone + DeserializeOwned + Default,
+{
+    type Error = ParseDynamicObjectError;
+
+    fn try_from(obj: DynamicObject) -> Result<Self, Self::Error> {
+        Ok(serde_json::from_value(serde_json::to_value(obj)?)?)
+    }
+}
+
+impl<P, U> TryFrom<Object<P, U>> for DynamicObject
+where
+    P: Clone + Serialize,
+    U: Clone + Serialize,
+{
+    type Error = ParseDynamicObjectError;
+
+    fn try_from(obj: Object<P, U>) -> Result<Self, Self::Error> {
+        Ok(serde_json::from_value(serde_json::to_value(obj)?)?)
+    }
 }
 
 impl<P, U> Resource for Object<P, U>
@@ -347,4 +425,73 @@ mod test {
         assert_eq!(PodSimple::kind(&ar), "Pod");
         assert_eq!(PodSimple::group(&ar), "");
     }
+
+    #[test]
+    fn ad_hoc_object_builders_and_conversions() {
+        use crate::{DynamicObject, GroupVersionKind};
+        use serde::{Deserialize, Serialize};
+        #[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+        struct FooSpec {
+            replicas: i32,
+        }
+        #[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+        struct FooStatus {
+            ready: bool,
+        }
+        type Foo = Object<FooSpec, FooStatus>;
+        let ar = ApiResource::from_gvk(&GroupVersionKind::gvk("clux.dev", "v1", "Foo"));
+
+        let foo = Foo {
+            types: None,
+            metadata: Default::default(),
+            spec: FooSpec { replicas: 2 },
+            status: None,
+        }
+        .with_types(&ar)
+        .with_status(FooStatus { ready: true })
+        .within("dev");
+        assert_eq!(foo.types.as_ref().unwrap().api_version, "clux.dev/v1");
+        assert_eq!(
+            foo.status_patch(),
+            serde_json::json!({
+                "apiVersion": "clux.dev/v1",
+                "kind": "Foo",
+                "status": { "ready": true },
+            })
+        );
+
+        let dynamic = DynamicObject::try_from(foo.clone()).unwrap();
+        assert_eq!(dynamic.data["spec"]["replicas"], 2);
+        assert_eq!(dynamic.metadata.namespace.as_deref(), Some("dev"));
+        let parsed = Foo::try_from(dynamic).unwrap();
+        assert_eq!(parsed.spec, foo.spec);
+        assert_eq!(parsed.status, foo.status);
+
+        let unobserved =
+            DynamicObject::new("new", &ar).data(serde_json::json!({ "spec": { "replicas": 1 } }));
+        assert_eq!(Foo::try_from(unobserved).unwrap().status, None);
+        let invalid =
+            DynamicObject::new("bad", &ar).data(serde_json::json!({ "spec": { "replicas": "two" } }));
+        assert!(Foo::try_from(invalid).is_err());
+    }
+
+    #[test]
+    fn list_chunking_metadata() {
+        use super::ObjectList;
+        let list: ObjectList<NotUsed> = serde_json::from_value(serde_json::json!({
+            "metadata": {"resourceVersion": "10", "continue": "abc", "remainingItemCount": 40},
+            "items": []
+        }))
+        .unwrap();
+        assert_eq!(list.continue_token(), Some("abc"));
+        assert_eq!(list.remaining_item_count(), Some(40));
+
+        let last: ObjectList<NotUsed> = serde_json::from_value(serde_json::json!({
+            "metadata": {"resourceVersion": "10", "continue": ""},
+            "items": null
+        }))
+        .unwrap();
+        assert_eq!(last.continue_token(), None);
+        assert_eq!(last.remaining_item_count(), None);
+    }
 }
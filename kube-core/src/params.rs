@@ -1,5 +1,5 @@
 //! A port of request parameter *Optionals from apimachinery/types.go
-use crate::request::Error;
+use crate::{fields, labels::Selector, request::Error};
 use serde::Serialize;
 
 /// Controls how the resource version parameter is applied for list calls
@@ -76,6 +76,23 @@ pub struct ListParams {
 
 impl ListParams {
     pub(crate) fn validate(&self) -> Result<(), Error> {
+        if let Some(labels) = &self.label_selector {
+            labels
+                .parse::<Selector>()
+                .map_err(|err| Error::Validation(err.to_string()))?;
+        }
+        if let Some(fields) = &self.field_selector {
+            fields
+                .parse::<fields::Selector>()
+                .map_err(|err| Error::Validation(err.to_string()))?;
+        }
+        if self.continue_token.is_some() && (self.resource_version.is_some() || self.version_match.is_some())
+        {
+            // The continue token already determines the resource version of the list
+            return Err(Error::Validation(
+                "A resource_version or version_match cannot be used with a continue_token".into(),
+            ));
+        }
         if let Some(rv) = &self.resource_version {
             if self.version_match == Some(VersionMatch::Exact) && rv == "0" {
                 return Err(Error::Validation(
@@ -146,6 +163,7 @@ impl ListParams {
     /// Defaults to everything.
     /// Supports `=`, `==`, `!=`, and can be comma separated: `key1=value1,key2=value2`.
     /// The server only supports a limited number of field queries per type.
+    /// The syntax of the selector is checked when the request is built.
     #[must_use]
     pub fn fields(mut self, field_selector: &str) -> Self {
         self.field_selector = Some(field_selector.to_string());
@@ -156,12 +174,33 @@ impl ListParams {
     ///
     /// Defaults to everything.
     /// Supports `=`, `==`, `!=`, and can be comma separated: `key1=value1,key2=value2`.
+    /// The syntax of the selector is checked when the request is built, see [`Selector`] for a typed selector.
     #[must_use]
     pub fn labels(mut self, label_selector: &str) -> Self {
         self.label_selector = Some(label_selector.to_string());
         self
     }
 
+    /// Configure the selector to restrict the list of returned objects by their labels, from a typed [`Selector`]
+    ///
+    /// ```
+    /// use kube::{api::ListParams, core::labels::Expression};
+    /// let lp = ListParams::default().labels_from(&Expression::Exists("app".into()).into());
+    /// assert_eq!(lp.label_selector.as_deref(), Some("app"));
+    /// ```
+    #[must_use]
+    pub fn labels_from(mut self, selector: &Selector) -> Self {
+        self.label_selector = Some(selector.to_string());
+        self
+    }
+
+    /// Configure the selector to restrict the list of returned objects by their fields, from a typed [`fields::Selector`]
+    #[must_use]
+    pub fn fields_from(mut self, selector: &fields::Selector) -> Self {
+        self.field_selector = Some(selector.to_string());
+        self
+    }
+
     /// Sets a result limit.
     #[must_use]
     pub fn limit(mut self, limit: u32) -> Self {
@@ -176,6 +215,29 @@ impl ListParams {
         self
     }
 
+    /// Parameters for the page after the one listed with these parameters, from its continue `token`
+    ///
+    /// The following pages are served from the snapshot of the first page, so the resource version and
+    /// version match are cleared, as the apiserver rejects them together with a continue token.
+    /// The token of a page is available from [`ObjectList::continue_token`](crate::ObjectList::continue_token).
+    ///
+    /// ```
+    /// use kube::api::ListParams;
+    /// let lp = ListParams::default().limit(50).match_any();
+    /// let next = lp.next_page("eyJ2IjoibWV0YS5rOHMuaW8vdjEifQ");
+    /// assert_eq!(next.resource_version, None);
+    /// assert_eq!(next.limit, Some(50));
+    /// ```
+    #[must_use]
+    pub fn next_page(&self, token: &str) -> Self {
+        Self {
+            continue_token: Some(token.to_string()),
+            resource_version: None,
+            version_match: None,
+            ..self.clone()
+        }
+    }
+
     /// Sets the resource version
     #[must_use]
     pub fn at(mut self, resource_version: &str) -> Self {
@@ -217,6 +279,9 @@ pub struct GetParams {
     /// like providing a `VersionMatch::NotOlderThan`.
     /// See <https://kubernetes.io/docs/reference/using-api/api-concepts/#resource-versions> for details.
     pub resource_version: Option<String>,
+
+    /// Determines how resourceVersion is matched, see [`GetParams::matching`]
+    pub(crate) version_match: Option<VersionMatch>,
 }
 
 /// Helper interface to GetParams
@@ -232,14 +297,69 @@ impl GetParams {
     pub fn at(resource_version: &str) -> Self {
         Self {
             resource_version: Some(resource_version.into()),
+            version_match: None,
         }
     }
 
     /// Sets the resource version to "0"
+    ///
+    /// This lets the apiserver serve the object from its watch cache rather than from etcd,
+    /// which is cheaper, but may return an object that is older than one that was previously observed.
     #[must_use]
     pub fn any() -> Self {
         Self::at("0")
     }
+
+    /// Sets the resource version with an 'Exact' match
+    ///
+    /// The object is returned as it was at exactly this resource version, or the request fails with a `410 Gone`
+    /// once the version has been compacted away.
+    #[must_use]
+    pub fn exact(resource_version: &str) -> Self {
+        Self::at(resource_version).matching(VersionMatch::Exact)
+    }
+
+    /// Sets how the resource version is matched
+    ///
+    /// The apiserver only supports `NotOlderThan` for gets, which is also the default.
+    /// An `Exact` match is served by `Api::get_with` as a list of the single object,
+    /// and is rejected when building a plain get request.
+    #[must_use]
+    pub fn matching(mut self, version_match: VersionMatch) -> Self {
+        self.version_match = Some(version_match);
+        self
+    }
+
+    /// How the resource version is matched, if it was set with [`GetParams::matching`]
+    pub fn version_match(&self) -> Option<&VersionMatch> {
+        self.version_match.as_ref()
+    }
+
+    pub(crate) fn validate(&self) -> Result<(), Error> {
+        match (&self.version_match, self.resource_version.as_deref()) {
+            (Some(_), None) => Err(Error::Validation(
+                "A resource_version is required when using an explicit match".into(),
+            )),
+            (Some(VersionMatch::Exact), Some("0")) => Err(Error::Validation(
+                "A non-zero resource_version is required when using an Exact match".into(),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// The [`ListParams`] of a list of only the object `name` that matches the version like these params
+    ///
+    /// This is how gets with an `Exact` version match are made, see [`GetParams::exact`].
+    pub fn list_params(&self, name: &str) -> ListParams {
+        let name_selector =
+            fields::Selector::from(fields::Expression::Equal("metadata.name".into(), name.into()));
+        ListParams {
+            resource_version: self.resource_version.clone(),
+            version_match: self.version_match.clone(),
+            ..ListParams::default()
+        }
+        .fields_from(&name_selector)
+    }
 }
 
 /// The validation directive to use for `fieldValidation` when using server-side apply.
@@ -392,6 +512,20 @@ impl WatchParams {
         self
     }
 
+    /// Configure the selector to restrict the watched objects by their labels, from a typed [`Selector`]
+    #[must_use]
+    pub fn labels_from(mut self, selector: &Selector) -> Self {
+        self.label_selector = Some(selector.to_string());
+        self
+    }
+
+    /// Configure the selector to restrict the watched objects by their fields, from a typed [`fields::Selector`]
+    #[must_use]
+    pub fn fields_from(mut self, selector: &fields::Selector) -> Self {
+        self.field_selector = Some(selector.to_string());
+        self
+    }
+
     /// Disables watch bookmarks to simplify watch handling
     ///
     /// This is not recommended to use with production watchers as it can cause desyncs.
@@ -499,6 +633,8 @@ pub enum Patch<T: Serialize> {
     /// [JSON Merge patch](https://kubernetes.io/docs/tasks/run-application/update-api-object-kubectl-patch/#use-a-json-merge-patch-to-update-a-deployment)
     Merge(T),
     /// [Strategic JSON Merge patch](https://kubernetes.io/docs/tasks/run-application/update-api-object-kubectl-patch/#use-a-strategic-merge-patch-to-update-a-deployment)
+    ///
+    /// See [`StrategicPatchBuilder`](crate::strategic::StrategicPatchBuilder) to build one from a partial object.
     Strategic(T),
 }
 
@@ -709,7 +845,23 @@ impl DeleteParams {
         self
     }
 
+    /// Set whether or how garbage collection is performed on the dependents.
+    #[must_use]
+    pub fn propagation_policy(mut self, policy: PropagationPolicy) -> Self {
+        self.propagation_policy = Some(policy);
+        self
+    }
+
     /// Set the condtions that must be fulfilled before a deletion is carried out.
+    ///
+    /// When they do not hold, [`Api::delete`] returns a `409 Conflict` error.
+    ///
+    /// ```
+    /// use kube::api::{DeleteParams, Preconditions};
+    /// let dp = DeleteParams::default().preconditions(Preconditions::default().uid("1f0a"));
+    /// ```
+    ///
+    /// [`Api::delete`]: https://docs.rs/kube/*/kube/struct.Api.html#method.delete
     #[must_use]
     pub fn preconditions(mut self, preconditions: Preconditions) -> Self {
         self.preconditions = Some(preconditions);
@@ -776,6 +928,26 @@ mod test {
         assert_eq!(ser, serde_json::json!({"propagationPolicy": "Orphan"}));
     }
 
+    #[test]
+    fn delete_param_builder() {
+        use super::{Preconditions, PropagationPolicy};
+        let dp = DeleteParams::default()
+            .propagation_policy(PropagationPolicy::Foreground)
+            .grace_period(5)
+            .dry_run()
+            .preconditions(Preconditions::default().uid("1f0a").resource_version("10"));
+        let ser = serde_json::to_value(dp).unwrap();
+        assert_eq!(
+            ser,
+            serde_json::json!({
+                "dryRun": ["All"],
+                "gracePeriodSeconds": 5,
+                "propagationPolicy": "Foreground",
+                "preconditions": {"uid": "1f0a", "resourceVersion": "10"}
+            })
+        );
+    }
+
     #[test]
     fn patch_param_serializes_field_validation() {
         let pp = PatchParams::default().validation_ignore();
@@ -796,6 +968,51 @@ mod test {
         let urlstr = qp.finish();
         assert_eq!(String::from("some/resource?&fieldValidation=Strict"), urlstr);
     }
+
+    #[test]
+    fn list_params_validate_selectors() {
+        use super::ListParams;
+        for fields in [
+            "metadata.name=web,status.phase!=Running",
+            "spec.nodeName==",
+            "metadata.name=a\\,b\\=c",
+            "",
+        ] {
+            assert!(
+                ListParams::default().fields(fields).validate().is_ok(),
+                "{fields}"
+            );
+        }
+        for fields in [
+            "metadata.name",
+            "=web",
+            "metadata.name=pod=1",
+            "metadata.name=a\\b",
+        ] {
+            assert!(
+                ListParams::default().fields(fields).validate().is_err(),
+                "{fields}"
+            );
+        }
+        assert!(ListParams::default()
+            .labels("app in (a,b),!legacy")
+            .validate()
+            .is_ok());
+        assert!(ListParams::default().labels("app in a").validate().is_err());
+    }
+
+    #[test]
+    fn list_params_continue_excludes_resource_version() {
+        use super::{ListParams, VersionMatch};
+        let lp = ListParams::default().continue_token("abc");
+        assert!(lp.clone().validate().is_ok());
+        assert!(lp.clone().at("10").validate().is_err());
+        assert!(lp
+            .at("10")
+            .matching(VersionMatch::NotOlderThan)
+            .validate()
+            .is_err());
+    }
 }
 
 /// Preconditions must be fulfilled before an operation (update, delete, etc.) is carried out.
@@ -810,6 +1027,26 @@ pub struct Preconditions {
     pub uid: Option<String>,
 }
 
+impl Preconditions {
+    /// Require the object to have the given uid
+    ///
+    /// This prevents acting on a different object that was recreated with the same name.
+    #[must_use]
+    pub fn uid(mut self, uid: &str) -> Self {
+        self.uid = Some(uid.into());
+        self
+    }
+
+    /// Require the object to be at the given resourceVersion
+    ///
+    /// This prevents acting on an object that was modified since it was last read.
+    #[must_use]
+    pub fn resource_version(mut self, resource_version: &str) -> Self {
+        self.resource_version = Some(resource_version.into());
+        self
+    }
+}
+
 /// Propagation policy when deleting single objects
 #[derive(Clone, Debug, Serialize, PartialEq)]
 pub enum PropagationPolicy {
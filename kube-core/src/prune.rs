@@ -0,0 +1,201 @@
+//! Pruning of server-populated fields, to turn objects from the apiserver into intent
+//!
+//! Objects read from the apiserver carry fields that the user never set: bookkeeping in the metadata,
+//! the `status`, and `null`s for unset optional fields. Pruning them gives the object as it would be
+//! written in a manifest, which can be compared with desired objects or applied again.
+//!
+//! ```
+//! use k8s_openapi::api::core::v1::ConfigMap;
+//! use kube::core::{prune, ObjectMeta};
+//!
+//! let from_server = ConfigMap {
+//!     metadata: ObjectMeta {
+//!         name: Some("settings".into()),
+//!         resource_version: Some("4242".into()),
+//!         uid: Some("5cd4b7d0-07a5-4a34-8b2f-7a7c5e7a6f1b".into()),
+//!         ..ObjectMeta::default()
+//!     },
+//!     data: Some([("mode".to_string(), "fast".to_string())].into()),
+//!     ..ConfigMap::default()
+//! };
+//! let intent = prune::intent(&from_server).unwrap();
+//! assert_eq!(intent, serde_json::json!({
+//!     "apiVersion": "v1",
+//!     "kind": "ConfigMap",
+//!     "metadata": { "name": "settings" },
+//!     "data": { "mode": "fast" },
+//! }));
+//! ```
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::ObjectMeta;
+
+/// The metadata fields that are populated by the apiserver
+pub const SERVER_METADATA_FIELDS: &[&str] = &[
+    "creationTimestamp",
+    "deletionGracePeriodSeconds",
+    "deletionTimestamp",
+    "generation",
+    "managedFields",
+    "resourceVersion",
+    "selfLink",
+    "uid",
+];
+
+/// The annotation where `kubectl apply` stores the last applied object
+pub const LAST_APPLIED_ANNOTATION: &str = "kubectl.kubernetes.io/last-applied-configuration";
+
+/// Serializes `object` and [`prune`]s it
+pub fn intent<K: Serialize>(object: &K) -> Result<Value, serde_json::Error> {
+    let mut value = serde_json::to_value(object)?;
+    prune(&mut value);
+    Ok(value)
+}
+
+/// Removes server-populated fields from a serialized object
+///
+/// This removes:
+///
+/// - the `status`
+/// - the [`SERVER_METADATA_FIELDS`] and the [`LAST_APPLIED_ANNOTATION`] from the `metadata`
+/// - empty `labels`, `annotations`, `finalizers` and `ownerReferences`
+/// - `null` values at any depth
+///
+/// Empty objects and lists in the rest of the object are kept, as they can be meaningful, like `emptyDir: {}`.
+pub fn prune(object: &mut Value) {
+    remove_nulls(object);
+    if let Some(object) = object.as_object_mut() {
+        object.remove("status");
+        if let Some(metadata) = object.get_mut("metadata").and_then(Value::as_object_mut) {
+            for field in SERVER_METADATA_FIELDS {
+                metadata.remove(*field);
+            }
+            if let Some(annotations) = metadata.get_mut("annotations").and_then(Value::as_object_mut) {
+                annotations.remove(LAST_APPLIED_ANNOTATION);
+            }
+            metadata.retain(|key, value| match key.as_str() {
+                "labels" | "annotations" => value.as_object().map_or(true, |map| !map.is_empty()),
+                "finalizers" | "ownerReferences" => value.as_array().map_or(true, |list| !list.is_empty()),
+                _ => true,
+            });
+        }
+    }
+}
+
+/// Removes server-populated fields from typed metadata
+///
+/// This is the equivalent of [`prune`] for the `metadata` of typed objects.
+pub fn prune_metadata(metadata: &mut ObjectMeta) {
+    metadata.creation_timestamp = None;
+    metadata.deletion_grace_period_seconds = None;
+    metadata.deletion_timestamp = None;
+    metadata.generation = None;
+    metadata.managed_fields = None;
+    metadata.resource_version = None;
+    metadata.self_link = None;
+    metadata.uid = None;
+    if let Some(annotations) = &mut metadata.annotations {
+        annotations.remove(LAST_APPLIED_ANNOTATION);
+    }
+    if metadata.labels.as_ref().map_or(false, |m| m.is_empty()) {
+        metadata.labels = None;
+    }
+    if metadata.annotations.as_ref().map_or(false, |m| m.is_empty()) {
+        metadata.annotations = None;
+    }
+    if metadata.finalizers.as_ref().map_or(false, |f| f.is_empty()) {
+        metadata.finalizers = None;
+    }
+    if metadata.owner_references.as_ref().map_or(false, |o| o.is_empty()) {
+        metadata.owner_references = None;
+    }
+}
+
+fn remove_nulls(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            map.retain(|_, value| !value.is_null());
+            map.values_mut().for_each(remove_nulls);
+        }
+        Value::Array(list) => list.iter_mut().for_each(remove_nulls),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn prunes_server_fields() {
+        let mut deploy = json!({
+            "apiVersion": "apps/v1",
+            "kind": "Deployment",
+            "metadata": {
+                "name": "web",
+                "namespace": "default",
+                "uid": "5cd4b7d0-07a5-4a34-8b2f-7a7c5e7a6f1b",
+                "resourceVersion": "4242",
+                "generation": 3,
+                "creationTimestamp": "2023-06-01T12:00:00Z",
+                "managedFields": [{ "manager": "kubectl", "operation": "Update" }],
+                "labels": { "app": "web" },
+                "annotations": { LAST_APPLIED_ANNOTATION: "{}" },
+                "finalizers": [],
+            },
+            "spec": {
+                "replicas": 2,
+                "paused": null,
+                "template": {
+                    "spec": {
+                        "containers": [{ "name": "web", "image": "nginx", "resources": {}, "args": null }],
+                        "volumes": [{ "name": "cache", "emptyDir": {} }],
+                    }
+                }
+            },
+            "status": { "replicas": 2 },
+        });
+        prune(&mut deploy);
+        assert_eq!(
+            deploy,
+            json!({
+                "apiVersion": "apps/v1",
+                "kind": "Deployment",
+                "metadata": {
+                    "name": "web",
+                    "namespace": "default",
+                    "labels": { "app": "web" },
+                },
+                "spec": {
+                    "replicas": 2,
+                    "template": {
+                        "spec": {
+                            "containers": [{ "name": "web", "image": "nginx", "resources": {} }],
+                            "volumes": [{ "name": "cache", "emptyDir": {} }],
+                        }
+                    }
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn prunes_typed_metadata() {
+        let mut metadata: ObjectMeta = serde_json::from_value(json!({
+            "name": "web",
+            "uid": "5cd4b7d0-07a5-4a34-8b2f-7a7c5e7a6f1b",
+            "resourceVersion": "4242",
+            "managedFields": [{ "manager": "kubectl", "operation": "Update" }],
+            "annotations": { LAST_APPLIED_ANNOTATION: "{}" },
+            "labels": { "app": "web" },
+        }))
+        .unwrap();
+        prune_metadata(&mut metadata);
+        assert_eq!(
+            serde_json::to_value(&metadata).unwrap(),
+            json!({ "name": "web", "labels": { "app": "web" } })
+        );
+    }
+}
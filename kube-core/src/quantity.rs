@@ -0,0 +1,550 @@
+//! Kubernetes [`Quantity`] parsing and arithmetic.
+use k8s_openapi::apimachinery::pkg::api::resource::Quantity;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::{
+    cmp::Ordering,
+    fmt,
+    hash::{Hash, Hasher},
+    iter::Sum,
+    ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign},
+    str::FromStr,
+};
+
+/// Nanos per unit, the smallest precision of a quantity
+const NANOS: i128 = 1_000_000_000;
+
+/// A parsed Kubernetes [`Quantity`]
+///
+/// This is equivalent to the [`resource.Quantity`] type in the Go Kubernetes apimachinery package,
+/// and accepts the same formats:
+///
+/// - binary SI suffixes (`Ki`, `Mi`, `Gi`, `Ti`, `Pi`, `Ei`), e.g. `512Mi`
+/// - decimal SI suffixes (`n`, `u`, `m`, `k`, `M`, `G`, `T`, `P`, `E`), e.g. `250m` or `1.5G`
+/// - decimal exponents, e.g. `1e3` or `12E-3`
+///
+/// Quantities are stored with nano precision, and values with more precision are rounded away from zero.
+/// They can be compared and summed regardless of their format, and are formatted in the format
+/// they were parsed with when possible:
+///
+/// ```
+/// use kube::core::quantity::ParsedQuantity;
+/// let requests = ["250m", "1.5", "500m"].iter().map(|q| q.parse::<ParsedQuantity>().unwrap());
+/// let total: ParsedQuantity = requests.sum();
+/// assert_eq!(total.to_string(), "2250m");
+/// assert!(total > "2".parse().unwrap());
+/// assert_eq!(total.milli_value(), 2250);
+///
+/// let memory = "1Gi".parse::<ParsedQuantity>().unwrap() + "512Mi".parse().unwrap();
+/// assert_eq!(memory.to_string(), "1536Mi");
+/// assert_eq!(memory.value(), 1536 * 1024 * 1024);
+/// ```
+///
+/// [`resource.Quantity`]: https://pkg.go.dev/k8s.io/apimachinery/pkg/api/resource#Quantity
+#[derive(Copy, Clone, Debug)]
+pub struct ParsedQuantity {
+    nanos: i128,
+    format: Format,
+}
+
+/// The suffix family of a [`ParsedQuantity`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Format {
+    /// Binary SI suffixes like `Ki` and `Mi`
+    BinarySI,
+    /// Decimal SI suffixes like `m` and `k`
+    DecimalSI,
+    /// Decimal exponents like `e3`
+    DecimalExponent,
+}
+
+/// Errors returned by the [`FromStr`] implementation for [`ParsedQuantity`].
+#[derive(Debug, thiserror::Error, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum ParseQuantityError {
+    /// The quantity was empty
+    #[error("quantities must not be empty")]
+    Empty,
+
+    /// The number of the quantity was invalid
+    #[error("invalid number in quantity {0:?}")]
+    InvalidNumber(String),
+
+    /// The suffix of the quantity was not a known suffix
+    #[error("invalid suffix in quantity {0:?}")]
+    InvalidSuffix(String),
+
+    /// The quantity is too large to be represented
+    #[error("quantity {0:?} is too large")]
+    Overflow(String),
+}
+
+const BINARY_SUFFIXES: [(&str, u32); 6] = [("Ki", 1), ("Mi", 2), ("Gi", 3), ("Ti", 4), ("Pi", 5), ("Ei", 6)];
+const DECIMAL_SUFFIXES: [(&str, i32); 9] = [
+    ("n", -9),
+    ("u", -6),
+    ("m", -3),
+    ("k", 3),
+    ("M", 6),
+    ("G", 9),
+    ("T", 12),
+    ("P", 15),
+    ("E", 18),
+];
+
+impl ParsedQuantity {
+    /// The format the quantity is displayed with
+    #[must_use]
+    pub fn format(&self) -> Format {
+        self.format
+    }
+
+    /// Returns `true` if the quantity is zero
+    #[must_use]
+    pub fn is_zero(&self) -> bool {
+        self.nanos == 0
+    }
+
+    /// Returns `true` if the quantity is negative
+    #[must_use]
+    pub fn is_negative(&self) -> bool {
+        self.nanos < 0
+    }
+
+    /// The value of the quantity in whole units, rounded away from zero
+    ///
+    /// Saturates at the bounds of `i64`.
+    #[must_use]
+    pub fn value(&self) -> i64 {
+        saturate(div_away_from_zero(self.nanos, NANOS))
+    }
+
+    /// The value of the quantity in thousandths of a unit, rounded away from zero
+    ///
+    /// This is the precision of cpu quantities. Saturates at the bounds of `i64`.
+    #[must_use]
+    pub fn milli_value(&self) -> i64 {
+        saturate(div_away_from_zero(self.nanos, NANOS / 1000))
+    }
+
+    /// The (approximate) value of the quantity as a float
+    #[must_use]
+    pub fn as_f64(&self) -> f64 {
+        self.nanos as f64 / NANOS as f64
+    }
+
+    fn with_nanos(self, nanos: i128) -> Self {
+        Self {
+            nanos,
+            format: self.format,
+        }
+    }
+}
+
+fn div_away_from_zero(value: i128, divisor: i128) -> i128 {
+    let (quotient, remainder) = (value / divisor, value % divisor);
+    match remainder.cmp(&0) {
+        Ordering::Greater => quotient + 1,
+        Ordering::Less => quotient - 1,
+        Ordering::Equal => quotient,
+    }
+}
+
+fn saturate(value: i128) -> i64 {
+    value.clamp(i64::MIN.into(), i64::MAX.into()) as i64
+}
+
+impl FromStr for ParsedQuantity {
+    type Err = ParseQuantityError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // implements the same format as
+        // https://github.com/kubernetes/apimachinery/blob/master/pkg/api/resource/quantity.go
+        if s.is_empty() {
+            return Err(ParseQuantityError::Empty);
+        }
+        let invalid_number = || ParseQuantityError::InvalidNumber(s.to_string());
+        let overflow = || ParseQuantityError::Overflow(s.to_string());
+
+        let (is_negative, unsigned) = match s.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, s.strip_prefix('+').unwrap_or(s)),
+        };
+        let number_len = unsigned
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(unsigned.len());
+        let (number, suffix) = unsigned.split_at(number_len);
+        let (whole, fraction) = number.split_once('.').unwrap_or((number, ""));
+        if whole.is_empty() && fraction.is_empty() || fraction.contains('.') {
+            return Err(invalid_number());
+        }
+
+        let (format, exponent, binary_power) = parse_suffix(suffix)?;
+
+        // the digits as an integer, scaled by the exponent of the suffix and the fraction
+        let mut mantissa: i128 = 0;
+        for digit in whole.bytes().chain(fraction.bytes()) {
+            mantissa = mantissa
+                .checked_mul(10)
+                .and_then(|m| m.checked_add(i128::from(digit - b'0')))
+                .ok_or_else(overflow)?;
+        }
+        let mut exponent = exponent + 9 - fraction.len() as i32;
+        if exponent < 0 {
+            // digits below nano precision round the value up
+            mantissa = match 10i128.checked_pow(exponent.unsigned_abs()) {
+                Some(divisor) => div_away_from_zero(mantissa, divisor),
+                None => mantissa.signum(),
+            };
+            exponent = 0;
+        }
+        let scale = 10i128.checked_pow(exponent as u32).ok_or_else(overflow)?;
+        let binary_scale = 1024i128.pow(binary_power);
+        let nanos = mantissa
+            .checked_mul(scale)
+            .and_then(|n| n.checked_mul(binary_scale))
+            .ok_or_else(overflow)?;
+        Ok(Self {
+            nanos: if is_negative { -nanos } else { nanos },
+            format,
+        })
+    }
+}
+
+/// Parses a suffix into its format, decimal exponent and binary power
+fn parse_suffix(suffix: &str) -> Result<(Format, i32, u32), ParseQuantityError> {
+    if suffix.is_empty() {
+        return Ok((Format::DecimalSI, 0, 0));
+    }
+    if let Some(&(_, power)) = BINARY_SUFFIXES.iter().find(|(s, _)| *s == suffix) {
+        return Ok((Format::BinarySI, 0, power));
+    }
+    if let Some(&(_, exponent)) = DECIMAL_SUFFIXES.iter().find(|(s, _)| *s == suffix) {
+        return Ok((Format::DecimalSI, exponent, 0));
+    }
+    let exponent = suffix
+        .strip_prefix(['e', 'E'])
+        .filter(|e| !e.is_empty())
+        .and_then(|e| e.parse::<i32>().ok())
+        .filter(|e| e.abs() <= 100)
+        .ok_or_else(|| ParseQuantityError::InvalidSuffix(suffix.to_string()))?;
+    Ok((Format::DecimalExponent, exponent, 0))
+}
+
+impl fmt::Display for ParsedQuantity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.nanos == 0 {
+            return f.write_str("0");
+        }
+        // binary suffixes can only represent whole units, otherwise fall back to decimal SI like Go
+        if self.format == Format::BinarySI && self.nanos % NANOS == 0 {
+            let units = self.nanos / NANOS;
+            let (suffix, scale) = BINARY_SUFFIXES
+                .iter()
+                .rev()
+                .map(|&(suffix, power)| (suffix, 1024i128.pow(power)))
+                .find(|(_, scale)| units % scale == 0)
+                .unwrap_or(("", 1));
+            return write!(f, "{}{suffix}", units / scale);
+        }
+        // the largest exponent that is a multiple of 3 and divides the value exactly
+        let exponent = (-3..=9)
+            .rev()
+            .map(|e| e * 3)
+            .find(|e| self.nanos % 10i128.pow((e + 9) as u32) == 0)
+            .unwrap_or(-9);
+        let value = self.nanos / 10i128.pow((exponent + 9) as u32);
+        match (self.format, exponent) {
+            (_, 0) => write!(f, "{value}"),
+            (Format::DecimalExponent, e) => write!(f, "{value}e{e}"),
+            (_, e) => {
+                let suffix = DECIMAL_SUFFIXES
+                    .iter()
+                    .find(|(_, exp)| *exp == e)
+                    .map(|(s, _)| *s);
+                write!(f, "{value}{}", suffix.unwrap_or_default())
+            }
+        }
+    }
+}
+
+impl PartialEq for ParsedQuantity {
+    fn eq(&self, other: &Self) -> bool {
+        self.nanos == other.nanos
+    }
+}
+
+impl Eq for ParsedQuantity {}
+
+impl Hash for ParsedQuantity {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.nanos.hash(state);
+    }
+}
+
+impl PartialOrd for ParsedQuantity {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ParsedQuantity {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.nanos.cmp(&other.nanos)
+    }
+}
+
+/// Adds quantities, keeping the format of the left-hand side
+///
+/// # Panics
+/// Panics on overflow, which needs quantities far beyond the exa range.
+impl Add for ParsedQuantity {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        self.with_nanos(self.nanos.checked_add(rhs.nanos).expect("quantity overflow"))
+    }
+}
+
+impl AddAssign for ParsedQuantity {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+/// Subtracts quantities, keeping the format of the left-hand side
+///
+/// # Panics
+/// Panics on overflow, which needs quantities far beyond the exa range.
+impl Sub for ParsedQuantity {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        self.with_nanos(self.nanos.checked_sub(rhs.nanos).expect("quantity overflow"))
+    }
+}
+
+impl SubAssign for ParsedQuantity {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl Neg for ParsedQuantity {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        self.with_nanos(-self.nanos)
+    }
+}
+
+/// Scales a quantity, e.g. by the number of replicas
+///
+/// # Panics
+/// Panics on overflow, which needs quantities far beyond the exa range.
+impl Mul<i64> for ParsedQuantity {
+    type Output = Self;
+
+    fn mul(self, rhs: i64) -> Self {
+        self.with_nanos(self.nanos.checked_mul(rhs.into()).expect("quantity overflow"))
+    }
+}
+
+/// Sums quantities, keeping the format of the first quantity
+impl Sum for ParsedQuantity {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.reduce(Add::add).unwrap_or(Self {
+            nanos: 0,
+            format: Format::DecimalSI,
+        })
+    }
+}
+
+impl TryFrom<&Quantity> for ParsedQuantity {
+    type Error = ParseQuantityError;
+
+    fn try_from(quantity: &Quantity) -> Result<Self, Self::Error> {
+        quantity.0.parse()
+    }
+}
+
+impl TryFrom<Quantity> for ParsedQuantity {
+    type Error = ParseQuantityError;
+
+    fn try_from(quantity: Quantity) -> Result<Self, Self::Error> {
+        quantity.0.parse()
+    }
+}
+
+impl From<ParsedQuantity> for Quantity {
+    fn from(quantity: ParsedQuantity) -> Self {
+        Quantity(quantity.to_string())
+    }
+}
+
+impl Serialize for ParsedQuantity {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for ParsedQuantity {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct Visitor;
+        impl<'de> de::Visitor<'de> for Visitor {
+            type Value = ParsedQuantity;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a Kubernetes quantity string or number")
+            }
+
+            fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                value.parse::<ParsedQuantity>().map_err(de::Error::custom)
+            }
+
+            fn visit_i64<E>(self, value: i64) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                self.visit_str(&value.to_string())
+            }
+
+            fn visit_u64<E>(self, value: u64) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                self.visit_str(&value.to_string())
+            }
+        }
+        deserializer.deserialize_any(Visitor)
+    }
+}
+
+#[cfg(feature = "schema")]
+impl schemars::JsonSchema for ParsedQuantity {
+    // see
+    // https://github.com/kubernetes/apimachinery/blob/master/pkg/api/resource/quantity.go
+    fn schema_name() -> String {
+        "Quantity".to_owned()
+    }
+
+    fn is_referenceable() -> bool {
+        false
+    }
+
+    fn json_schema(_: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        let mut extensions = schemars::Map::new();
+        extensions.insert("x-kubernetes-int-or-string".into(), true.into());
+        schemars::schema::SchemaObject {
+            extensions,
+            ..Default::default()
+        }
+        .into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn q(s: &str) -> ParsedQuantity {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn parses_the_same_as_go() {
+        let cases: &[(&str, i64, &str)] = &[
+            // input, milli value, canonical form
+            ("0", 0, "0"),
+            ("1", 1000, "1"),
+            ("+1", 1000, "1"),
+            ("-1", -1000, "-1"),
+            ("100m", 100, "100m"),
+            ("0.1", 100, "100m"),
+            (".5", 500, "500m"),
+            ("1.", 1000, "1"),
+            ("1.5k", 1_500_000, "1500"),
+            ("2M", 2_000_000_000, "2M"),
+            ("1Ki", 1_024_000, "1Ki"),
+            ("1536Mi", 1536 * 1024 * 1024 * 1000, "1536Mi"),
+            ("0.5Gi", 512 * 1024 * 1024 * 1000, "512Mi"),
+            ("1e3", 1_000_000, "1e3"),
+            ("1E3", 1_000_000, "1e3"),
+            ("1.5e-3", 2, "1500e-6"),
+            ("100n", 1, "100n"),
+            ("1.0000000001", 1001, "1000000001n"),
+        ];
+        for &(input, milli, canonical) in cases {
+            let parsed = q(input);
+            assert_eq!(parsed.milli_value(), milli, "milli value of {input}");
+            assert_eq!(parsed.to_string(), canonical, "canonical form of {input}");
+            assert_eq!(q(canonical), parsed, "{canonical} round trips");
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_quantities() {
+        assert_eq!("".parse::<ParsedQuantity>(), Err(ParseQuantityError::Empty));
+        for invalid in [".", "-", "1..2", "m"] {
+            assert_eq!(
+                invalid.parse::<ParsedQuantity>(),
+                Err(ParseQuantityError::InvalidNumber(invalid.to_string()))
+            );
+        }
+        for invalid in ["1KB", "1ki", "1e", "1 Gi", "1e1.5"] {
+            assert!(
+                matches!(
+                    invalid.parse::<ParsedQuantity>(),
+                    Err(ParseQuantityError::InvalidSuffix(_))
+                ),
+                "{invalid} should have an invalid suffix"
+            );
+        }
+        assert!(matches!(
+            "1e100".parse::<ParsedQuantity>(),
+            Err(ParseQuantityError::Overflow(_))
+        ));
+    }
+
+    #[test]
+    fn arithmetic_and_comparisons() {
+        assert_eq!(q("1Gi") + q("1Gi"), q("2Gi"));
+        assert_eq!((q("1Gi") + q("1Gi")).to_string(), "2Gi");
+        assert_eq!((q("1") - q("250m")).to_string(), "750m");
+        assert_eq!((q("500m") * 3).to_string(), "1500m");
+        assert_eq!((-q("1Ki")).to_string(), "-1Ki");
+        assert!((q("1") - q("2")).is_negative());
+        assert_eq!(q("1000m"), q("1"));
+        assert_eq!(q("1k"), q("1e3"));
+        assert!(q("1Gi") > q("1G"));
+        assert!(q("999m") < q("1"));
+        assert_eq!(["1", "500m", "0.5"].iter().map(|s| q(s)).max(), Some(q("1")));
+        assert!(Vec::<ParsedQuantity>::new()
+            .into_iter()
+            .sum::<ParsedQuantity>()
+            .is_zero());
+        // sums that are not whole units are formatted with decimal suffixes
+        assert_eq!((q("1Ki") + q("1m")).to_string(), "1024001m");
+    }
+
+    #[test]
+    fn converts_and_serializes() {
+        let quantity = Quantity("250m".to_string());
+        let parsed = ParsedQuantity::try_from(&quantity).unwrap();
+        assert_eq!(Quantity::from(parsed), quantity);
+        assert_eq!(serde_json::to_value(parsed).unwrap(), serde_json::json!("250m"));
+        let limits: Vec<ParsedQuantity> = serde_json::from_value(serde_json::json!(["1Gi", 2])).unwrap();
+        assert_eq!(limits, [q("1Gi"), q("2")]);
+        assert_eq!(q("1.5").value(), 2);
+        assert_eq!(q("-1.5").value(), -2);
+        assert_eq!(q("2.5").as_f64(), 2.5);
+    }
+}
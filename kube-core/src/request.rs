@@ -1,7 +1,7 @@
 //! Request builder type for arbitrary api types
 use thiserror::Error;
 
-use crate::params::GetParams;
+use crate::params::{GetParams, VersionMatch};
 
 use super::params::{DeleteParams, ListParams, Patch, PatchParams, PostParams, WatchParams};
 
@@ -80,6 +80,7 @@ impl Request {
 
     /// Get a single instance
     pub fn get(&self, name: &str, gp: &GetParams) -> Result<http::Request<Vec<u8>>, Error> {
+        validate_get(gp)?;
         let urlstr = if let Some(rv) = &gp.resource_version {
             let target = format!("{}/{}?", self.url_path, name);
             form_urlencoded::Serializer::new(target)
@@ -122,11 +123,10 @@ impl Request {
     ) -> Result<http::Request<Vec<u8>>, Error> {
         let target = format!("{}?", self.url_path);
         let mut qp = form_urlencoded::Serializer::new(target);
-        if let Some(fields) = &lp.field_selector {
-            qp.append_pair("fieldSelector", fields);
-        }
-        if let Some(labels) = &lp.label_selector {
-            qp.append_pair("labelSelector", labels);
+        lp.validate()?;
+        lp.populate_qp(&mut qp);
+        if dp.dry_run {
+            qp.append_pair("dryRun", "All");
         }
         let urlstr = qp.finish();
 
@@ -249,6 +249,18 @@ impl Request {
     }
 }
 
+/// Validates the params of a get, which cannot match a version exactly
+fn validate_get(gp: &GetParams) -> Result<(), Error> {
+    gp.validate()?;
+    if gp.version_match == Some(VersionMatch::Exact) {
+        return Err(Error::Validation(
+            "An Exact match is not supported by gets, list the object with GetParams::list_params instead"
+                .into(),
+        ));
+    }
+    Ok(())
+}
+
 /// Metadata-only request implementations
 ///
 /// Requests set an extended Accept header compromised of JSON media type and
@@ -256,6 +268,7 @@ impl Request {
 impl Request {
     /// Get a single metadata instance for a named resource
     pub fn get_metadata(&self, name: &str, gp: &GetParams) -> Result<http::Request<Vec<u8>>, Error> {
+        validate_get(gp)?;
         let urlstr = if let Some(rv) = &gp.resource_version {
             let target = format!("{}/{}?", self.url_path, name);
             form_urlencoded::Serializer::new(target)
@@ -471,6 +484,21 @@ mod test {
         );
     }
 
+    #[test]
+    fn get_rejects_exact_match() {
+        let url = appsv1::Deployment::url_path(&(), Some("ns"));
+        let req = Request::new(url);
+        assert!(req.get("mydeploy", &GetParams::exact("665")).is_err());
+        assert!(req.get_metadata("mydeploy", &GetParams::exact("665")).is_err());
+
+        let lp = GetParams::exact("665").list_params("mydeploy");
+        let list = req.list(&lp).unwrap();
+        assert_eq!(
+            list.uri(),
+            "/apis/apps/v1/namespaces/ns/deployments?&fieldSelector=metadata.name%3Dmydeploy&resourceVersion=665&resourceVersionMatch=Exact"
+        );
+    }
+
     #[test]
     fn get_meta_path_with_rv() {
         let url = appsv1::Deployment::url_path(&(), Some("ns"));
@@ -573,6 +601,28 @@ mod test {
         assert_eq!(req.headers().get(header::CONTENT_TYPE).unwrap(), super::JSON_MIME);
     }
 
+    #[test]
+    fn delete_collection_params() {
+        let url = corev1::Pod::url_path(&(), Some("ns"));
+        let lp = ListParams::default()
+            .fields("status.phase=Succeeded")
+            .labels("app=myapp")
+            .limit(100);
+        let dp = DeleteParams::background().dry_run().grace_period(0);
+        let req = Request::new(url).delete_collection(&dp, &lp).unwrap();
+        assert_eq!(
+            req.uri(),
+            "/api/v1/namespaces/ns/pods?&fieldSelector=status.phase%3DSucceeded&labelSelector=app%3Dmyapp&limit=100&dryRun=All"
+        );
+        assert_eq!(
+            req.body(),
+            br#"{"dryRun":["All"],"gracePeriodSeconds":0,"propagationPolicy":"Background"}"#
+        );
+
+        let lp = ListParams::default().matching(VersionMatch::Exact);
+        assert!(Request::new("/api/v1/pods").delete_collection(&dp, &lp).is_err());
+    }
+
     #[test]
     fn namespace_path() {
         let url = corev1::Namespace::url_path(&(), None);
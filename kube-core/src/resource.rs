@@ -4,6 +4,11 @@ use k8s_openapi::{
     apimachinery::pkg::apis::meta::v1::{ManagedFieldsEntry, OwnerReference, Time},
 };
 
+use crate::{
+    dynamic::{DynamicObject, ParseDynamicObjectError},
+    gvk::GroupVersionKind,
+};
+use serde::Serialize;
 use std::{borrow::Cow, collections::BTreeMap};
 
 pub use k8s_openapi::{ClusterResourceScope, NamespaceResourceScope, ResourceScope, SubResourceScope};
@@ -205,10 +210,35 @@ pub trait ResourceExt: Resource {
     fn finalizers(&self) -> &[String];
     /// Provides mutable access to the finalizers
     fn finalizers_mut(&mut self) -> &mut Vec<String>;
+    /// Adds a finalizer unless it is already present
+    ///
+    /// Returns whether the finalizer was added.
+    fn add_finalizer(&mut self, finalizer: &str) -> bool;
+    /// Removes a finalizer
+    ///
+    /// Returns whether the finalizer was present.
+    fn remove_finalizer(&mut self, finalizer: &str) -> bool;
+    /// Adds an owner reference to `owner`, replacing any existing reference to it
+    ///
+    /// Returns `false` without changes if the owner has no name or uid, which is
+    /// only the case for objects that were not received from the apiserver.
+    /// Only one owner may be the `controller`, the apiserver rejects objects with more.
+    fn owned_by<O>(&mut self, owner: &O, controller: bool) -> bool
+    where
+        O: Resource,
+        O::DynamicType: Default;
+    /// Whether an owner reference points to the object with the `uid`
+    fn is_owned_by(&self, uid: &str) -> bool;
     /// Returns managed fields
     fn managed_fields(&self) -> &[ManagedFieldsEntry];
     /// Provides mutable access to managed fields
     fn managed_fields_mut(&mut self) -> &mut Vec<ManagedFieldsEntry>;
+    /// Converts the resource into a [`DynamicObject`]
+    ///
+    /// This is the inverse of [`DynamicObject::try_parse`].
+    fn into_dynamic(self) -> Result<DynamicObject, ParseDynamicObjectError>
+    where
+        Self: Serialize + Sized;
 }
 
 // TODO: replace with ordinary static when BTreeMap::new() is no longer
@@ -277,6 +307,44 @@ impl<K: Resource> ResourceExt for K {
         self.meta_mut().finalizers.get_or_insert_with(Vec::new)
     }
 
+    fn add_finalizer(&mut self, finalizer: &str) -> bool {
+        let finalizers = self.finalizers_mut();
+        if finalizers.iter().any(|f| f == finalizer) {
+            return false;
+        }
+        finalizers.push(finalizer.to_string());
+        true
+    }
+
+    fn remove_finalizer(&mut self, finalizer: &str) -> bool {
+        let finalizers = self.finalizers_mut();
+        let len = finalizers.len();
+        finalizers.retain(|f| f != finalizer);
+        finalizers.len() != len
+    }
+
+    fn owned_by<O>(&mut self, owner: &O, controller: bool) -> bool
+    where
+        O: Resource,
+        O::DynamicType: Default,
+    {
+        let owner_ref = match owner.controller_owner_ref(&Default::default()) {
+            Some(owner_ref) => OwnerReference {
+                controller: Some(controller),
+                ..owner_ref
+            },
+            None => return false,
+        };
+        let owner_refs = self.owner_references_mut();
+        owner_refs.retain(|r| r.uid != owner_ref.uid);
+        owner_refs.push(owner_ref);
+        true
+    }
+
+    fn is_owned_by(&self, uid: &str) -> bool {
+        self.owner_references().iter().any(|r| r.uid == uid)
+    }
+
     fn managed_fields(&self) -> &[ManagedFieldsEntry] {
         self.meta().managed_fields.as_deref().unwrap_or_default()
     }
@@ -284,4 +352,187 @@ impl<K: Resource> ResourceExt for K {
     fn managed_fields_mut(&mut self) -> &mut Vec<ManagedFieldsEntry> {
         self.meta_mut().managed_fields.get_or_insert_with(Vec::new)
     }
+
+    fn into_dynamic(self) -> Result<DynamicObject, ParseDynamicObjectError>
+    where
+        Self: Serialize + Sized,
+    {
+        Ok(serde_json::from_value(serde_json::to_value(self)?)?)
+    }
+}
+
+/// An object-safe view of any [`Resource`]
+///
+/// [`Resource`] cannot be made into a trait object because of its associated types,
+/// so this erases them to allow working with collections of different kinds:
+///
+/// ```
+/// use k8s_openapi::api::core::v1::{ConfigMap, Secret};
+/// use kube::core::{AnyResource, ObjectMeta};
+///
+/// let meta = ObjectMeta {
+///     name: Some("creds".to_string()),
+///     ..ObjectMeta::default()
+/// };
+/// let objects: Vec<Box<dyn AnyResource>> = vec![
+///     Box::new(ConfigMap { metadata: meta.clone(), ..ConfigMap::default() }),
+///     Box::new(Secret { metadata: meta, ..Secret::default() }),
+/// ];
+/// let kinds = objects.iter().map(|o| o.gvk().unwrap().kind).collect::<Vec<_>>();
+/// assert_eq!(kinds, ["ConfigMap", "Secret"]);
+/// assert!(objects.iter().all(|o| o.name_any() == "creds"));
+/// ```
+///
+/// It is implemented for every [`Resource`] with static type information, and for [`DynamicObject`].
+pub trait AnyResource: Send + Sync {
+    /// The type of the resource
+    ///
+    /// This is always set for static types, but is `None` for a [`DynamicObject`] without valid `TypeMeta`.
+    fn gvk(&self) -> Option<GroupVersionKind>;
+
+    /// Metadata of the resource
+    ///
+    /// This is named differently from [`Resource::meta`] to avoid ambiguity when both traits are in scope.
+    fn any_meta(&self) -> &ObjectMeta;
+}
+
+impl<K> AnyResource for K
+where
+    K: Resource<DynamicType = ()> + Send + Sync,
+{
+    fn gvk(&self) -> Option<GroupVersionKind> {
+        Some(GroupVersionKind::gvk(
+            &K::group(&()),
+            &K::version(&()),
+            &K::kind(&()),
+        ))
+    }
+
+    fn any_meta(&self) -> &ObjectMeta {
+        self.meta()
+    }
+}
+
+impl AnyResource for DynamicObject {
+    fn gvk(&self) -> Option<GroupVersionKind> {
+        self.types
+            .as_ref()
+            .and_then(|tm| GroupVersionKind::try_from(tm).ok())
+    }
+
+    fn any_meta(&self) -> &ObjectMeta {
+        &self.metadata
+    }
+}
+
+impl dyn AnyResource + '_ {
+    /// Returns the most useful name identifier available
+    ///
+    /// See [`ResourceExt::name_any`].
+    pub fn name_any(&self) -> String {
+        let meta = self.any_meta();
+        meta.name
+            .clone()
+            .or_else(|| meta.generate_name.clone())
+            .unwrap_or_default()
+    }
+
+    /// The namespace the resource is in
+    pub fn namespace(&self) -> Option<String> {
+        self.any_meta().namespace.clone()
+    }
+
+    /// Unique ID of the resource
+    pub fn uid(&self) -> Option<String> {
+        self.any_meta().uid.clone()
+    }
+
+    /// Returns resource labels
+    pub fn labels(&self) -> &BTreeMap<String, String> {
+        self.any_meta().labels.as_ref().unwrap_or(&*EMPTY_MAP)
+    }
+
+    /// Returns resource annotations
+    pub fn annotations(&self) -> &BTreeMap<String, String> {
+        self.any_meta().annotations.as_ref().unwrap_or(&*EMPTY_MAP)
+    }
+
+    /// Returns resource owner references
+    pub fn owner_references(&self) -> &[OwnerReference] {
+        self.any_meta().owner_references.as_deref().unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{AnyResource, ResourceExt};
+    use crate::{ApiResource, DynamicObject, GroupVersionKind, ObjectMeta};
+    use k8s_openapi::api::{
+        apps::v1::{Deployment, ReplicaSet},
+        core::v1::Pod,
+    };
+
+    #[test]
+    fn finalizers_and_owners() {
+        let mut pod = Pod::default();
+        assert!(pod.add_finalizer("kube.rs/cleanup"));
+        assert!(!pod.add_finalizer("kube.rs/cleanup"));
+        assert!(pod.add_finalizer("kube.rs/other"));
+        assert!(pod.remove_finalizer("kube.rs/cleanup"));
+        assert!(!pod.remove_finalizer("kube.rs/cleanup"));
+        assert_eq!(pod.finalizers(), ["kube.rs/other"]);
+
+        let mut rs = ReplicaSet::default();
+        assert!(!pod.owned_by(&rs, true), "owners need a name and uid");
+        rs.metadata.name = Some("web-abc".into());
+        rs.metadata.uid = Some("1234".into());
+        assert!(pod.owned_by(&rs, false));
+        assert!(pod.owned_by(&rs, true));
+        assert_eq!(pod.owner_references().len(), 1);
+        let owner = &pod.owner_references()[0];
+        assert_eq!(owner.kind, "ReplicaSet");
+        assert_eq!(owner.api_version, "apps/v1");
+        assert_eq!(owner.controller, Some(true));
+        assert!(pod.is_owned_by("1234"));
+        assert!(!pod.is_owned_by("5678"));
+    }
+
+    #[test]
+    fn any_resources_erase_types() {
+        let metadata = ObjectMeta {
+            name: Some("web".into()),
+            namespace: Some("default".into()),
+            labels: Some([("app".to_string(), "web".to_string())].into()),
+            ..ObjectMeta::default()
+        };
+        let foo = GroupVersionKind::gvk("clux.dev", "v1", "Foo");
+        let mut untyped = DynamicObject::new("web", &ApiResource::from_gvk(&foo));
+        untyped.types = None;
+        let objects: Vec<Box<dyn AnyResource>> = vec![
+            Box::new(Pod {
+                metadata: metadata.clone(),
+                ..Pod::default()
+            }),
+            Box::new(Deployment {
+                metadata,
+                ..Deployment::default()
+            }),
+            Box::new(DynamicObject::new("web", &ApiResource::from_gvk(&foo)).within("default")),
+            Box::new(untyped),
+        ];
+
+        let gvks = objects.iter().map(|o| o.gvk()).collect::<Vec<_>>();
+        let expected = [
+            Some(GroupVersionKind::gvk("", "v1", "Pod")),
+            Some(GroupVersionKind::gvk("apps", "v1", "Deployment")),
+            Some(foo),
+            None,
+        ];
+        assert_eq!(gvks, expected);
+        assert!(objects.iter().all(|o| o.name_any() == "web"));
+        assert_eq!(objects[1].namespace().as_deref(), Some("default"));
+        assert_eq!(objects[0].labels()["app"], "web");
+        assert!(objects[2].labels().is_empty());
+        assert!(objects[3].owner_references().is_empty());
+    }
 }
@@ -1,4 +1,10 @@
 //! Generic api response types
+use std::time::Duration;
+
+use http::{
+    header::{RETRY_AFTER, WARNING},
+    HeaderMap, StatusCode,
+};
 use serde::{Deserialize, Serialize};
 
 /// A Kubernetes status object
@@ -151,6 +157,156 @@ pub struct StatusCause {
     pub field: String,
 }
 
+/// The API Priority and Fairness metadata of a response, and the time that was spent waiting for it
+///
+/// The apiserver classifies every request by a FlowSchema into a PriorityLevel, and reports both in the
+/// `X-Kubernetes-PF-FlowSchema-UID` and `X-Kubernetes-PF-PriorityLevel-UID` headers. When the priority level
+/// is saturated, requests are rejected with `429 Too Many Requests` and a `Retry-After` delay.
+///
+/// The client sets this as an extension on responses, and on [`ErrorResponse`](crate::ErrorResponse)s.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct PriorityAndFairness {
+    /// The UID of the FlowSchema that matched the request
+    pub flow_schema_uid: Option<String>,
+    /// The UID of the PriorityLevelConfiguration that the request was assigned to
+    pub priority_level_uid: Option<String>,
+    /// The delay that the apiserver asked for before trying again, when it rejected the request with `429`
+    pub retry_after: Option<Duration>,
+    /// How long the request waited for the client-side rate limit before it was sent
+    pub rate_limit_wait: Option<Duration>,
+    /// How long the request waited between retries of rejected attempts
+    pub retry_wait: Option<Duration>,
+}
+
+impl PriorityAndFairness {
+    /// Reads the metadata that the apiserver sets in the headers of a response with the given `status`
+    ///
+    /// The wait durations are left unset, since they are measured by the client rather than reported.
+    pub fn from_headers(status: StatusCode, headers: &HeaderMap) -> Self {
+        let header = |name: &str| {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(|value| value.trim().to_owned())
+        };
+        let retry_after = match status {
+            StatusCode::TOO_MANY_REQUESTS => header(RETRY_AFTER.as_str())
+                .and_then(|value| value.parse().ok())
+                .map(Duration::from_secs),
+            _ => None,
+        };
+        Self {
+            flow_schema_uid: header("x-kubernetes-pf-flowschema-uid"),
+            priority_level_uid: header("x-kubernetes-pf-prioritylevel-uid"),
+            retry_after,
+            rate_limit_wait: None,
+            retry_wait: None,
+        }
+    }
+
+    /// Whether the request was delayed by the client, or rejected by the apiserver
+    pub fn throttled(&self) -> bool {
+        self.retry_after.is_some() || self.rate_limit_wait.is_some() || self.retry_wait.is_some()
+    }
+}
+
+/// Metadata of a response that the apiserver sets in its headers, besides [`PriorityAndFairness`]
+///
+/// The client sets this as an extension on responses.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ResponseMetadata {
+    /// The `Audit-Id` of the request, which identifies it in the audit logs of the apiserver
+    pub audit_id: Option<String>,
+    /// The warnings of the `Warning` headers, such as about the use of deprecated APIs
+    pub warnings: Vec<Warning>,
+}
+
+impl ResponseMetadata {
+    /// Reads the metadata from the headers of a response
+    ///
+    /// `Warning` headers that are not formatted as in [RFC 7234](https://www.rfc-editor.org/rfc/rfc7234#section-5.5)
+    /// are skipped.
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        let audit_id = headers
+            .get("audit-id")
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.trim().to_owned());
+        let warnings = headers
+            .get_all(WARNING)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(Warning::parse_all)
+            .collect();
+        Self { audit_id, warnings }
+    }
+}
+
+/// A warning from a `Warning` response header
+///
+/// The apiserver sends warnings with the code `299` and an agent of `-`, for example when a deprecated
+/// API version is used, or when an admission webhook warns about a request. This is how `kubectl` shows warnings.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Warning {
+    /// The warn-code, `299` for the warnings of the apiserver
+    pub code: u16,
+    /// The warn-agent, `-` when unknown
+    pub agent: String,
+    /// The message of the warning
+    pub text: String,
+}
+
+impl Warning {
+    /// Parses the comma-separated warnings of a `Warning` header value, up to the first malformed one
+    pub fn parse_all(value: &str) -> Vec<Self> {
+        let mut warnings = Vec::new();
+        let mut rest = value;
+        loop {
+            rest = rest.trim_start_matches(|c: char| c == ',' || c.is_ascii_whitespace());
+            if rest.is_empty() {
+                return warnings;
+            }
+            match Self::parse_one(rest) {
+                Some((warning, after)) => {
+                    warnings.push(warning);
+                    rest = after;
+                }
+                None => return warnings,
+            }
+        }
+    }
+
+    /// Parses `warn-code SP warn-agent SP warn-text [SP warn-date]`, and returns the rest of the header value
+    fn parse_one(value: &str) -> Option<(Self, &str)> {
+        let (code, rest) = value.split_once(' ')?;
+        let (agent, rest) = rest.trim_start().split_once(' ')?;
+        let (text, mut rest) = parse_quoted(rest.trim_start())?;
+        // Skip the warn-date, which is a quoted string as well
+        if let Some((_, after)) = parse_quoted(rest.trim_start()) {
+            rest = after;
+        }
+        let warning = Self {
+            code: code.parse().ok()?,
+            agent: agent.to_owned(),
+            text,
+        };
+        Some((warning, rest))
+    }
+}
+
+/// Parses a quoted-string at the start of `value`, and returns its unescaped content and the rest of `value`
+fn parse_quoted(value: &str) -> Option<(String, &str)> {
+    let mut chars = value.strip_prefix('"')?.char_indices();
+    let mut text = String::new();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' => text.push(chars.next()?.1),
+            '"' => return Some((text, &value[i + 2..])),
+            c => text.push(c),
+        }
+    }
+    None
+}
+
 fn is_u16_zero(&v: &u16) -> bool {
     v == 0
 }
@@ -161,7 +317,11 @@ fn is_u32_zero(&v: &u32) -> bool {
 
 #[cfg(test)]
 mod test {
-    use super::Status;
+    use super::{PriorityAndFairness, ResponseMetadata, Status, Warning};
+
+    use std::time::Duration;
+
+    use http::{HeaderMap, HeaderValue, StatusCode};
 
     // ensure our status schema is sensible
     #[test]
@@ -174,4 +334,61 @@ mod test {
         let s2: Status = serde_json::from_str::<Status>(statusnoname).unwrap();
         assert_eq!(s2.details.unwrap().name, ""); // optional probably better..
     }
+
+    #[test]
+    fn priority_and_fairness_from_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert("X-Kubernetes-PF-FlowSchema-UID", HeaderValue::from_static("flow"));
+        headers.insert(
+            "X-Kubernetes-PF-PriorityLevel-UID",
+            HeaderValue::from_static("level"),
+        );
+        headers.insert("Retry-After", HeaderValue::from_static("2"));
+
+        let apf = PriorityAndFairness::from_headers(StatusCode::TOO_MANY_REQUESTS, &headers);
+        assert_eq!(apf.flow_schema_uid.as_deref(), Some("flow"));
+        assert_eq!(apf.priority_level_uid.as_deref(), Some("level"));
+        assert_eq!(apf.retry_after, Some(Duration::from_secs(2)));
+        assert!(apf.throttled());
+
+        // Retry-After is only a rejection by API Priority and Fairness on 429 responses
+        let apf = PriorityAndFairness::from_headers(StatusCode::SERVICE_UNAVAILABLE, &headers);
+        assert_eq!(apf.retry_after, None);
+        assert!(!apf.throttled());
+    }
+
+    #[test]
+    fn parse_warnings() {
+        let warning = |text: &str| Warning {
+            code: 299,
+            agent: "-".into(),
+            text: text.into(),
+        };
+        assert_eq!(
+            Warning::parse_all(
+                r#"299 - "batch/v1beta1 CronJob is deprecated in v1.21+, unavailable in v1.25+""#
+            ),
+            [warning(
+                "batch/v1beta1 CronJob is deprecated in v1.21+, unavailable in v1.25+"
+            )]
+        );
+        assert_eq!(
+            Warning::parse_all(
+                r#"299 - "first, with \"quotes\"" "Sat, 25 Aug 2012 23:34:45 GMT", 299 - "second""#
+            ),
+            [warning(r#"first, with "quotes""#), warning("second")]
+        );
+        // Malformed warnings end the parsing
+        let warnings = Warning::parse_all(r#"299 - "first", unquoted"#);
+        assert_eq!(warnings, [warning("first")]);
+        assert_eq!(Warning::parse_all(r#"299 - "unterminated"#), []);
+
+        let mut headers = HeaderMap::new();
+        headers.append("Warning", HeaderValue::from_static(r#"299 - "first""#));
+        headers.append("Warning", HeaderValue::from_static(r#"299 - "second""#));
+        headers.insert("Audit-Id", HeaderValue::from_static("2b5c3f8a"));
+        let metadata = ResponseMetadata::from_headers(&headers);
+        assert_eq!(metadata.audit_id.as_deref(), Some("2b5c3f8a"));
+        assert_eq!(metadata.warnings, [warning("first"), warning("second")]);
+    }
 }
@@ -17,6 +17,8 @@ use schemars::{
 ///  * Rewrite enums from `oneOf` to `object`s with multiple variants ([schemars#84](https://github.com/GREsau/schemars/issues/84))
 ///  * Rewrite untagged enums from `anyOf` to `object`s with multiple variants ([kube#1028](https://github.com/kube-rs/kube/pull/1028))
 ///  * Rewrite `additionalProperties` from `#[serde(flatten)]` to `x-kubernetes-preserve-unknown-fields` ([kube#844](https://github.com/kube-rs/kube/issues/844))
+///  * Rewrite internally and adjacently tagged enums from `oneOf` to `object`s with the tag as a required `enum`,
+///    and `x-kubernetes-validations` rules requiring the fields of the variant that the tag selects, and only those
 ///
 /// This is used automatically by `kube::derive`'s `#[derive(CustomResource)]`,
 /// but it can also be used manually with [`SchemaSettings::with_visitor`].
@@ -34,6 +36,16 @@ impl Visitor for StructuralSchemaRewriter {
 
         if let Some(subschemas) = &mut schema.subschemas {
             if let Some(one_of) = subschemas.one_of.as_mut() {
+                // Internally and adjacently tagged enums are told apart by their tag, rather than by `one_of`
+                if hoist_discriminated_union(
+                    one_of,
+                    &mut schema.object,
+                    &mut schema.instance_type,
+                    &mut schema.extensions,
+                ) {
+                    one_of.clear();
+                }
+
                 // Tagged enums are serialized using `one_of`
                 hoist_subschema_properties(one_of, &mut schema.object, &mut schema.instance_type);
 
@@ -102,6 +114,148 @@ fn hoist_subschema_enum_values(
     })
 }
 
+/// Bring the variants of an internally or adjacently tagged enum up to the root schema.
+///
+/// The variants are objects that all require a tag property with a single string value. The root schema gets
+/// the union of their properties, with the tag as a required `enum` of all the values, and CEL rules per variant
+/// that require its fields and forbid the fields of the other variants when the tag selects it. Properties with a
+/// different schema per variant (like the content of adjacently tagged enums) are left open with
+/// `x-kubernetes-preserve-unknown-fields`.
+///
+/// Returns whether the variants were such an enum.
+fn hoist_discriminated_union(
+    subschemas: &[Schema],
+    common_obj: &mut Option<Box<ObjectValidation>>,
+    instance_type: &mut Option<SingleOrVec<InstanceType>>,
+    extensions: &mut schemars::Map<String, serde_json::Value>,
+) -> bool {
+    let variants = subschemas
+        .iter()
+        .map(|variant| match variant {
+            Schema::Object(SchemaObject {
+                object: Some(obj), ..
+            }) => Some(obj),
+            _ => None,
+        })
+        .collect::<Option<Vec<_>>>();
+    let variants = match variants {
+        Some(variants) if !variants.is_empty() => variants,
+        _ => return false,
+    };
+    let tag_value = |obj: &ObjectValidation, tag: &str| -> Option<String> {
+        if !obj.required.contains(tag) {
+            return None;
+        }
+        match obj.properties.get(tag) {
+            Some(Schema::Object(SchemaObject {
+                enum_values: Some(values),
+                ..
+            })) if values.len() == 1 => values[0].as_str().map(String::from),
+            _ => None,
+        }
+    };
+    let tag = variants[0]
+        .properties
+        .keys()
+        .find(|tag| variants.iter().all(|obj| tag_value(obj, tag).is_some()))
+        .cloned();
+    let tag = match tag {
+        Some(tag) => tag,
+        None => return false,
+    };
+
+    // The fields of all variants, in order, which are exclusive to the variants that have them
+    let mut fields: Vec<&String> = vec![];
+    for obj in &variants {
+        for name in obj.properties.keys() {
+            if *name != tag && !fields.contains(&name) {
+                fields.push(name);
+            }
+        }
+    }
+
+    let mut properties = schemars::Map::new();
+    let mut tag_values = vec![];
+    let mut rules = vec![];
+    for obj in &variants {
+        let value = tag_value(obj, &tag).unwrap_or_default();
+        let selected = format!("self.{tag} != '{}'", value.replace('\'', "\\'"));
+        let required = obj
+            .required
+            .iter()
+            .filter(|name| **name != tag)
+            .collect::<Vec<_>>();
+        if !required.is_empty() {
+            let has = required.iter().map(|name| format!("has(self.{name})"));
+            rules.push(serde_json::json!({
+                "rule": format!("{selected} || ({})", has.collect::<Vec<_>>().join(" && ")),
+                "message": format!("{tag} {value} requires {}", join(&required)),
+            }));
+        }
+        let forbidden = fields
+            .iter()
+            .copied()
+            .filter(|name| !obj.properties.contains_key(*name))
+            .collect::<Vec<_>>();
+        if !forbidden.is_empty() {
+            let has_not = forbidden.iter().map(|name| format!("!has(self.{name})"));
+            rules.push(serde_json::json!({
+                "rule": format!("{selected} || ({})", has_not.collect::<Vec<_>>().join(" && ")),
+                "message": format!("{tag} {value} does not allow {}", join(&forbidden)),
+            }));
+        }
+        tag_values.push(serde_json::Value::String(value));
+        for (name, property) in &obj.properties {
+            if *name == tag {
+                continue;
+            }
+            match properties.entry(name.clone()) {
+                MapEntry::Vacant(entry) => {
+                    entry.insert(property.clone());
+                }
+                MapEntry::Occupied(mut entry) => {
+                    if entry.get() != property {
+                        let mut open = SchemaObject::default();
+                        open.extensions
+                            .insert("x-kubernetes-preserve-unknown-fields".into(), true.into());
+                        entry.insert(Schema::Object(open));
+                    }
+                }
+            }
+        }
+    }
+    properties.insert(
+        tag.clone(),
+        Schema::Object(SchemaObject {
+            instance_type: Some(InstanceType::String.into()),
+            enum_values: Some(tag_values),
+            ..SchemaObject::default()
+        }),
+    );
+
+    let common_obj = common_obj.get_or_insert_with(Box::<ObjectValidation>::default);
+    common_obj.properties.extend(properties);
+    common_obj.required.insert(tag);
+    merge_metadata(instance_type, Some(InstanceType::Object.into()));
+    if !rules.is_empty() {
+        if let serde_json::Value::Array(existing) = extensions
+            .entry("x-kubernetes-validations".to_string())
+            .or_insert_with(|| serde_json::Value::Array(vec![]))
+        {
+            existing.extend(rules);
+        }
+    }
+    true
+}
+
+fn join(names: &[&String]) -> String {
+    names
+        .iter()
+        .map(|name| name.as_str())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
 /// Bring all property definitions from subschemas up to the root schema,
 /// since Kubernetes doesn't allow subschemas to define properties.
 fn hoist_subschema_properties(
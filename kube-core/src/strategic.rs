@@ -0,0 +1,528 @@
+//! Builders for [strategic merge patches](https://kubernetes.io/docs/tasks/manage-kubernetes-objects/update-api-object-kubectl-patch/#notes-on-the-strategic-merge-patch), as sent with [`Patch::Strategic`]
+use serde::Serialize;
+use serde_json::{Map, Value};
+use thiserror::Error;
+
+use crate::params::Patch;
+
+/// An invalid strategic merge patch
+#[derive(Debug, Error)]
+pub enum StrategicPatchError {
+    /// The partial object could not be serialized
+    #[error("failed to serialize the patch: {0}")]
+    Serialize(#[source] serde_json::Error),
+    /// An element of a list that is merged by key does not have the key
+    #[error("the elements of {0} need a {1}")]
+    MissingMergeKey(String, &'static str),
+    /// A path does not lead to a value that the directive applies to
+    #[error("invalid path {0}: {1}")]
+    InvalidPath(String, &'static str),
+}
+
+/// The `patchMergeKey` of a well-known list, by the name of the list and of the list that contains it
+///
+/// The apiserver merges the elements of these lists by key, and falls back to replacing
+/// the whole list for lists that are not listed here.
+fn merge_key(list: &str, parent: Option<&str>) -> Option<&'static str> {
+    Some(match (list, parent) {
+        ("ports", Some("containers" | "initContainers" | "ephemeralContainers")) => "containerPort",
+        ("ports", _) => "port",
+        (
+            "containers"
+            | "initContainers"
+            | "ephemeralContainers"
+            | "volumes"
+            | "env"
+            | "imagePullSecrets"
+            | "resourceClaims",
+            _,
+        ) => "name",
+        ("volumeMounts", _) => "mountPath",
+        ("volumeDevices", _) => "devicePath",
+        ("hostAliases", _) => "ip",
+        ("ownerReferences", _) => "uid",
+        ("conditions", _) => "type",
+        ("topologySpreadConstraints", _) => "topologyKey",
+        _ => return None,
+    })
+}
+
+fn has_merge_key(element: &Value, key: &str) -> bool {
+    match element.get(key) {
+        Some(Value::String(value)) => !value.is_empty(),
+        Some(Value::Number(value)) => value.as_i64() != Some(0),
+        _ => false,
+    }
+}
+
+fn matches_merge_key(element: &Value, key: &str, value: &str) -> bool {
+    match element.get(key) {
+        Some(Value::String(s)) => s == value,
+        Some(Value::Number(n)) => n.to_string() == value,
+        _ => false,
+    }
+}
+
+fn merge_key_value(value: &str) -> Value {
+    value.parse::<i64>().map_or_else(|_| value.into(), Value::from)
+}
+
+/// Drops the fields that were not set, and checks that the elements of merged lists have their merge key
+///
+/// Nulls are dropped because they delete fields in a strategic merge patch, and empty merged lists
+/// because they do not change anything.
+fn prepare(value: &mut Value, path: &str, parent: Option<&str>) -> Result<(), StrategicPatchError> {
+    match value {
+        Value::Object(map) => {
+            map.retain(|field, value| {
+                let empty_merged_list =
+                    merge_key(field, parent).is_some() && value.as_array().map_or(false, Vec::is_empty);
+                !(value.is_null() || empty_merged_list)
+            });
+            for (field, value) in map.iter_mut() {
+                let path = if path.is_empty() {
+                    field.clone()
+                } else {
+                    format!("{path}.{field}")
+                };
+                match (merge_key(field, parent), value) {
+                    (Some(key), Value::Array(elements)) => {
+                        for element in elements {
+                            if !has_merge_key(element, key) {
+                                return Err(StrategicPatchError::MissingMergeKey(path, key));
+                            }
+                            prepare(element, &path, Some(field))?;
+                        }
+                    }
+                    (_, value) => prepare(value, &path, parent)?,
+                }
+            }
+        }
+        Value::Array(elements) => {
+            for element in elements {
+                prepare(element, path, parent)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// The value at `path`, which is created when it is missing, along with the merge key if it is a merged list
+///
+/// The elements of merged lists are addressed by the value of their merge key, so the `env` of the container
+/// `app` is at `["spec", "containers", "app", "env"]`.
+fn navigate<'v>(
+    mut value: &'v mut Value,
+    path: &[&str],
+) -> Result<(&'v mut Value, Option<&'static str>), StrategicPatchError> {
+    // The field that `value` is, and the merged list of the element that contains it
+    let mut field: Option<&str> = None;
+    let mut parent: Option<&str> = None;
+    for segment in path {
+        value = match value {
+            Value::Array(elements) => {
+                let key = field
+                    .and_then(|field| merge_key(field, parent))
+                    .ok_or_else(|| navigate_error(path, "not a list that is merged by key"))?;
+                let position = match elements
+                    .iter()
+                    .position(|element| matches_merge_key(element, key, segment))
+                {
+                    Some(position) => position,
+                    None => {
+                        let mut element = Map::new();
+                        element.insert(key.to_string(), merge_key_value(segment));
+                        elements.push(element.into());
+                        elements.len() - 1
+                    }
+                };
+                parent = field;
+                field = None;
+                &mut elements[position]
+            }
+            Value::Object(map) => {
+                let merged = merge_key(segment, parent).is_some();
+                field = Some(segment);
+                map.entry(*segment).or_insert_with(|| {
+                    if merged {
+                        Value::Array(Vec::new())
+                    } else {
+                        Value::Object(Map::new())
+                    }
+                })
+            }
+            _ => return Err(navigate_error(path, "not an object")),
+        };
+    }
+    let key = match value {
+        Value::Array(_) => field.and_then(|field| merge_key(field, parent)),
+        _ => None,
+    };
+    Ok((value, key))
+}
+
+/// Builds a [`Patch::Strategic`] from a partial object
+///
+/// Only the fields that are set in the partial object are changed. The elements of lists such as
+/// `containers` are merged with the existing elements that have the same merge key, like their `name`,
+/// so every element needs its merge key, and the other elements of the list are kept.
+/// The directives remove or replace values that a partial object cannot express.
+///
+/// ```
+/// use k8s_openapi::api::{apps::v1::{Deployment, DeploymentSpec}, core::v1::{Container, PodSpec, PodTemplateSpec}};
+/// use kube::core::strategic::StrategicPatchBuilder;
+/// let partial = Deployment {
+///     spec: Some(DeploymentSpec {
+///         template: PodTemplateSpec {
+///             spec: Some(PodSpec {
+///                 containers: vec![Container {
+///                     name: "app".into(),
+///                     image: Some("app:2.0".into()),
+///                     ..Container::default()
+///                 }],
+///                 ..PodSpec::default()
+///             }),
+///             ..PodTemplateSpec::default()
+///         },
+///         ..DeploymentSpec::default()
+///     }),
+///     ..Deployment::default()
+/// };
+/// let patch = StrategicPatchBuilder::from_partial(&partial)
+///     .delete_element(&["spec", "template", "spec", "containers", "app", "env"], "DEBUG")
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug)]
+pub struct StrategicPatchBuilder {
+    value: Value,
+    error: Option<StrategicPatchError>,
+}
+
+impl Default for StrategicPatchBuilder {
+    fn default() -> Self {
+        Self {
+            value: Value::Object(Map::new()),
+            error: None,
+        }
+    }
+}
+
+impl StrategicPatchBuilder {
+    /// A patch that does not change anything
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A patch that sets the fields that are set in `partial`
+    ///
+    /// Fields that serialize to `null` are left out, use [`StrategicPatchBuilder::delete_field`] to delete a field.
+    pub fn from_partial<T: Serialize>(partial: &T) -> Self {
+        let mut value = match serde_json::to_value(partial) {
+            Ok(value) => value,
+            Err(err) => return Self::new().fail(StrategicPatchError::Serialize(err)),
+        };
+        if !value.is_object() {
+            return Self::new().fail(StrategicPatchError::InvalidPath(String::new(), "not an object"));
+        }
+        match prepare(&mut value, "", None) {
+            Ok(()) => Self { value, error: None },
+            Err(err) => Self::new().fail(err),
+        }
+    }
+
+    fn fail(mut self, error: StrategicPatchError) -> Self {
+        self.error.get_or_insert(error);
+        self
+    }
+
+    fn directive(
+        mut self,
+        path: &[&str],
+        f: impl FnOnce(&mut Value, Option<&'static str>) -> Result<(), &'static str>,
+    ) -> Self {
+        if self.error.is_some() {
+            return self;
+        }
+        let result = navigate(&mut self.value, path)
+            .and_then(|(value, key)| f(value, key).map_err(|reason| navigate_error(path, reason)));
+        match result {
+            Ok(()) => self,
+            Err(err) => self.fail(err),
+        }
+    }
+
+    /// Delete the field at `path`
+    #[must_use]
+    pub fn delete_field(self, path: &[&str]) -> Self {
+        let (field, parent) = match path.split_last() {
+            Some(split) => split,
+            None => return self.fail(navigate_error(path, "the whole object cannot be deleted")),
+        };
+        self.directive(parent, |value, _| match value {
+            Value::Object(map) => {
+                map.insert((*field).to_string(), Value::Null);
+                Ok(())
+            }
+            _ => Err("not an object"),
+        })
+    }
+
+    /// Delete the element with the merge key `key` from the list at `path`
+    #[must_use]
+    pub fn delete_element(self, path: &[&str], key: &str) -> Self {
+        self.directive(path, |value, merge_key| match (value, merge_key) {
+            (Value::Array(elements), Some(merge_key)) => {
+                elements.retain(|element| !matches_merge_key(element, merge_key, key));
+                let mut element = Map::new();
+                element.insert(merge_key.to_string(), merge_key_value(key));
+                element.insert("$patch".into(), "delete".into());
+                elements.push(element.into());
+                Ok(())
+            }
+            _ => Err("not a list that is merged by key"),
+        })
+    }
+
+    /// Replace the object or list at `path` with the value in the patch, instead of merging them
+    ///
+    /// A list that is not in the patch is replaced with an empty list.
+    #[must_use]
+    pub fn replace(self, path: &[&str]) -> Self {
+        self.directive(path, |value, merge_key| match (value, merge_key) {
+            (Value::Object(map), _) => {
+                map.insert("$patch".into(), "replace".into());
+                Ok(())
+            }
+            (Value::Array(elements), Some(_)) => {
+                let mut directive = Map::new();
+                directive.insert("$patch".into(), "replace".into());
+                elements.insert(0, directive.into());
+                Ok(())
+            }
+            _ => Err("lists that are not merged by key are always replaced"),
+        })
+    }
+
+    /// Remove the `values` from the list of strings at `path`, such as the `finalizers` of an object
+    #[must_use]
+    pub fn delete_from_primitive_list<I>(self, path: &[&str], values: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        let (field, parent) = match path.split_last() {
+            Some(split) => split,
+            None => return self.fail(navigate_error(path, "not a list")),
+        };
+        self.directive(parent, |value, _| {
+            let map = value.as_object_mut().ok_or("not an object")?;
+            let deleted = map
+                .entry(format!("$deleteFromPrimitiveList/{field}"))
+                .or_insert_with(|| Value::Array(Vec::new()));
+            if let Value::Array(deleted) = deleted {
+                deleted.extend(values.into_iter().map(|value| Value::String(value.into())));
+            }
+            Ok(())
+        })
+    }
+
+    /// The patch, or the first error that occurred while building it
+    pub fn build(self) -> Result<Patch<Value>, StrategicPatchError> {
+        match self.error {
+            Some(err) => Err(err),
+            None => Ok(Patch::Strategic(self.value)),
+        }
+    }
+}
+
+fn navigate_error(path: &[&str], reason: &'static str) -> StrategicPatchError {
+    StrategicPatchError::InvalidPath(path.join("."), reason)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use k8s_openapi::api::{
+        apps::v1::{Deployment, DeploymentSpec},
+        core::v1::{
+            Container, ContainerPort, EnvVar, PodSpec, PodTemplateSpec, Service, ServicePort, ServiceSpec,
+        },
+    };
+    use serde_json::json;
+
+    fn deployment(containers: Vec<Container>) -> Deployment {
+        Deployment {
+            spec: Some(DeploymentSpec {
+                template: PodTemplateSpec {
+                    spec: Some(PodSpec {
+                        containers,
+                        ..PodSpec::default()
+                    }),
+                    ..PodTemplateSpec::default()
+                },
+                ..DeploymentSpec::default()
+            }),
+            ..Deployment::default()
+        }
+    }
+
+    fn value(patch: Patch<Value>) -> Value {
+        match patch {
+            Patch::Strategic(value) => value,
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn serializes_set_fields() {
+        let partial = deployment(vec![Container {
+            name: "app".into(),
+            image: Some("app:2.0".into()),
+            ports: Some(vec![ContainerPort {
+                container_port: 8080,
+                ..ContainerPort::default()
+            }]),
+            ..Container::default()
+        }]);
+        let patch = StrategicPatchBuilder::from_partial(&partial).build().unwrap();
+        assert_eq!(
+            value(patch),
+            json!({
+                "apiVersion": "apps/v1",
+                "kind": "Deployment",
+                "metadata": {},
+                "spec": {
+                    "selector": {},
+                    "template": {"spec": {"containers": [{
+                        "name": "app",
+                        "image": "app:2.0",
+                        "ports": [{"containerPort": 8080}],
+                    }]}},
+                }
+            })
+        );
+
+        // Empty merged lists do not change anything, and are left out
+        let patch = StrategicPatchBuilder::from_partial(&deployment(vec![]))
+            .build()
+            .unwrap();
+        assert_eq!(
+            value(patch),
+            json!({
+                "apiVersion": "apps/v1",
+                "kind": "Deployment",
+                "metadata": {},
+                "spec": {"selector": {}, "template": {"spec": {}}},
+            })
+        );
+    }
+
+    #[test]
+    fn requires_merge_keys() {
+        let partial = deployment(vec![Container {
+            image: Some("app:2.0".into()),
+            ..Container::default()
+        }]);
+        let err = StrategicPatchBuilder::from_partial(&partial).build().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "the elements of spec.template.spec.containers need a name"
+        );
+
+        // Service ports are merged by port rather than containerPort
+        let service = Service {
+            spec: Some(ServiceSpec {
+                ports: Some(vec![ServicePort {
+                    port: 80,
+                    ..ServicePort::default()
+                }]),
+                ..ServiceSpec::default()
+            }),
+            ..Service::default()
+        };
+        assert!(StrategicPatchBuilder::from_partial(&service).build().is_ok());
+        let partial = deployment(vec![Container {
+            name: "app".into(),
+            ports: Some(vec![ContainerPort::default()]),
+            env: Some(vec![EnvVar {
+                name: "DEBUG".into(),
+                ..EnvVar::default()
+            }]),
+            ..Container::default()
+        }]);
+        let err = StrategicPatchBuilder::from_partial(&partial).build().unwrap_err();
+        assert!(matches!(
+            err,
+            StrategicPatchError::MissingMergeKey(_, "containerPort")
+        ));
+    }
+
+    #[test]
+    fn adds_directives() {
+        let partial = deployment(vec![Container {
+            name: "app".into(),
+            image: Some("app:2.0".into()),
+            ..Container::default()
+        }]);
+        let pod_spec = ["spec", "template", "spec"];
+        let patch = StrategicPatchBuilder::from_partial(&partial)
+            .delete_element(&[&pod_spec[..], &["containers", "app", "env"]].concat(), "DEBUG")
+            .delete_element(&[&pod_spec[..], &["containers"]].concat(), "sidecar")
+            .delete_field(&[&pod_spec[..], &["containers", "app", "command"]].concat())
+            .replace(&[&pod_spec[..], &["nodeSelector"]].concat())
+            .replace(&[&pod_spec[..], &["containers", "app", "ports", "8080"]].concat())
+            .replace(&[&pod_spec[..], &["volumes"]].concat())
+            .delete_from_primitive_list(&["metadata", "finalizers"], ["example.com/cleanup"])
+            .build()
+            .unwrap();
+        assert_eq!(
+            value(patch),
+            json!({
+                "apiVersion": "apps/v1",
+                "kind": "Deployment",
+                "metadata": {"$deleteFromPrimitiveList/finalizers": ["example.com/cleanup"]},
+                "spec": {
+                    "selector": {},
+                    "template": {"spec": {
+                        "containers": [
+                            {
+                                "name": "app",
+                                "image": "app:2.0",
+                                "env": [{"name": "DEBUG", "$patch": "delete"}],
+                                "command": null,
+                                "ports": [{"containerPort": 8080, "$patch": "replace"}],
+                            },
+                            {"name": "sidecar", "$patch": "delete"},
+                        ],
+                        "nodeSelector": {"$patch": "replace"},
+                        "volumes": [{"$patch": "replace"}],
+                    }},
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_paths() {
+        let partial = deployment(vec![Container {
+            name: "app".into(),
+            args: Some(vec!["--verbose".into()]),
+            ..Container::default()
+        }]);
+        let args = ["spec", "template", "spec", "containers", "app", "args"];
+        let err = StrategicPatchBuilder::from_partial(&partial)
+            .delete_element(&args, "--verbose")
+            .build()
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid path spec.template.spec.containers.app.args: not a list that is merged by key"
+        );
+        assert!(StrategicPatchBuilder::new().delete_field(&[]).build().is_err());
+        assert!(StrategicPatchBuilder::from_partial(&"not an object")
+            .build()
+            .is_err());
+    }
+}
@@ -1,6 +1,8 @@
 //! Request builder types and parameters for subresources
 use std::fmt::Debug;
 
+use chrono::{DateTime, Utc};
+
 use crate::{
     params::{DeleteParams, PostParams},
     request::{Error, Request, JSON_MIME},
@@ -37,6 +39,70 @@ pub struct LogParams {
     pub timestamps: bool,
 }
 
+impl LogParams {
+    /// Show the logs of the named container, rather than those of the only container of the pod
+    #[must_use]
+    pub fn container(mut self, container: impl Into<String>) -> Self {
+        self.container = Some(container.into());
+        self
+    }
+
+    /// Keep streaming new logs as they are written, until the container terminates
+    #[must_use]
+    pub fn follow(mut self) -> Self {
+        self.follow = true;
+        self
+    }
+
+    /// Show only the last `lines` lines of the logs
+    #[must_use]
+    pub fn tail_lines(mut self, lines: i64) -> Self {
+        self.tail_lines = Some(lines);
+        self
+    }
+
+    /// Show only the logs of the last `seconds` seconds
+    #[must_use]
+    pub fn since_seconds(mut self, seconds: i64) -> Self {
+        self.since_seconds = Some(seconds);
+        self
+    }
+
+    /// Show only the logs since `time`
+    ///
+    /// This sets [`LogParams::since_seconds`] to the whole seconds between `time` and now, rounded up so that
+    /// no logs written after `time` are missed. Logs from up to a second before `time` can be returned as well.
+    #[must_use]
+    pub fn since_time(mut self, time: DateTime<Utc>) -> Self {
+        let elapsed = Utc::now().signed_duration_since(time);
+        let seconds = elapsed.num_seconds() + i64::from(elapsed.num_milliseconds() % 1000 != 0);
+        // The apiserver rejects values below one second
+        self.since_seconds = Some(seconds.max(1));
+        self
+    }
+
+    /// Prefix every line with the RFC3339 timestamp that it was written at
+    #[must_use]
+    pub fn timestamps(mut self) -> Self {
+        self.timestamps = true;
+        self
+    }
+
+    /// Show the logs of the previous instance of the container, which has terminated
+    #[must_use]
+    pub fn previous(mut self) -> Self {
+        self.previous = true;
+        self
+    }
+
+    /// Stop after `bytes` bytes of logs
+    #[must_use]
+    pub fn limit_bytes(mut self, bytes: i64) -> Self {
+        self.limit_bytes = Some(bytes);
+        self
+    }
+}
+
 impl Request {
     /// Get a pod logs
     pub fn logs(&self, name: &str, lp: &LogParams) -> Result<http::Request<Vec<u8>>, Error> {
@@ -89,6 +155,8 @@ impl Request {
 #[derive(Default, Clone)]
 pub struct EvictParams {
     /// How the eviction should occur
+    ///
+    /// These are sent as the `deleteOptions` of the `policy/v1` `Eviction`, for example to set a grace period.
     pub delete_options: Option<DeleteParams>,
     /// How the http post should occur
     pub post_options: PostParams,
@@ -104,17 +172,55 @@ impl Request {
         let mut qp = form_urlencoded::Serializer::new(target);
         pp.populate_qp(&mut qp);
         let urlstr = qp.finish();
-        // eviction body parameters are awkward, need metadata with name
-        let data = serde_json::to_vec(&serde_json::json!({
-            "delete_options": ep.delete_options,
-            "metadata": { "name": name }
-        }))
-        .map_err(Error::SerializeBody)?;
+        // The body is a policy/v1 Eviction, which needs metadata with the name
+        let mut eviction = serde_json::json!({
+            "apiVersion": "policy/v1",
+            "kind": "Eviction",
+            "metadata": { "name": name },
+        });
+        if let Some(delete_options) = &ep.delete_options {
+            eviction["deleteOptions"] = serde_json::to_value(delete_options).map_err(Error::SerializeBody)?;
+        }
+        let data = serde_json::to_vec(&eviction).map_err(Error::SerializeBody)?;
         let req = http::Request::post(urlstr).header(http::header::CONTENT_TYPE, JSON_MIME);
         req.body(data).map_err(Error::BuildRequest)
     }
 }
 
+// ----------------------------------------------------------------------------
+// Proxy subresource
+// ----------------------------------------------------------------------------
+
+impl Request {
+    /// Proxy a request through the apiserver to an object, such as a pod, service or node
+    ///
+    /// The `request` is sent to the path and query of its uri, like `/metrics?format=text`, with its method,
+    /// headers and body. The `name` can pick a port and scheme, like `web:8080` or `https:web:8443` for a pod.
+    pub fn proxy(
+        &self,
+        name: &str,
+        request: http::Request<Vec<u8>>,
+    ) -> Result<http::Request<Vec<u8>>, Error> {
+        let (mut parts, body) = request.into_parts();
+        if parts.uri.scheme().is_some() || parts.uri.authority().is_some() {
+            return Err(Error::Validation(format!(
+                "proxied requests need a path rather than the absolute uri {}",
+                parts.uri
+            )));
+        }
+        let path = parts.uri.path();
+        let path = if path.starts_with('/') { path } else { "/" };
+        let target = match parts.uri.query() {
+            Some(query) => format!("{}/{}/proxy{}?{}", self.url_path, name, path, query),
+            None => format!("{}/{}/proxy{}", self.url_path, name, path),
+        };
+        parts.uri = target
+            .parse()
+            .map_err(|err: http::uri::InvalidUri| Error::BuildRequest(err.into()))?;
+        Ok(http::Request::from_parts(parts, body))
+    }
+}
+
 // ----------------------------------------------------------------------------
 // Attach subresource
 // ----------------------------------------------------------------------------
@@ -143,7 +249,7 @@ pub struct AttachParams {
     pub stderr: bool,
     /// Allocate TTY. Defaults to `false`.
     ///
-    /// NOTE: Terminal resizing is not implemented yet.
+    /// Call [`AttachedProcess::terminal_size`](https://docs.rs/kube/*/kube/api/struct.AttachedProcess.html#method.terminal_size) to resize the terminal.
     pub tty: bool,
 
     /// The maximum amount of bytes that can be written to the internal `stdin`
@@ -339,37 +445,6 @@ impl Request {
     }
 }
 
-// ----------------------------------------------------------------------------
-// tests
-// ----------------------------------------------------------------------------
-
-/// Cheap sanity check to ensure type maps work as expected
-#[cfg(test)]
-mod test {
-    use crate::{request::Request, resource::Resource};
-    use k8s::core::v1 as corev1;
-    use k8s_openapi::api as k8s;
-
-    use crate::subresource::LogParams;
-
-    #[test]
-    fn logs_all_params() {
-        let url = corev1::Pod::url_path(&(), Some("ns"));
-        let lp = LogParams {
-            container: Some("nginx".into()),
-            follow: true,
-            limit_bytes: Some(10 * 1024 * 1024),
-            pretty: true,
-            previous: true,
-            since_seconds: Some(3600),
-            tail_lines: Some(4096),
-            timestamps: true,
-        };
-        let req = Request::new(url).logs("mypod", &lp).unwrap();
-        assert_eq!(req.uri(), "/api/v1/namespaces/ns/pods/mypod/log?&container=nginx&follow=true&limitBytes=10485760&pretty=true&previous=true&sinceSeconds=3600&tailLines=4096&timestamps=true");
-    }
-}
-
 // ----------------------------------------------------------------------------
 // Portforward subresource
 // ----------------------------------------------------------------------------
@@ -410,3 +485,116 @@ impl Request {
         req.body(vec![]).map_err(Error::BuildRequest)
     }
 }
+
+// ----------------------------------------------------------------------------
+// tests
+// ----------------------------------------------------------------------------
+
+/// Cheap sanity check to ensure type maps work as expected
+#[cfg(test)]
+mod test {
+    use crate::{request::Request, resource::Resource};
+    use chrono::Utc;
+    use k8s::core::v1 as corev1;
+    use k8s_openapi::api as k8s;
+
+    use crate::{
+        params::DeleteParams,
+        subresource::{EvictParams, LogParams},
+    };
+
+    #[test]
+    fn logs_all_params() {
+        let url = corev1::Pod::url_path(&(), Some("ns"));
+        let lp = LogParams {
+            container: Some("nginx".into()),
+            follow: true,
+            limit_bytes: Some(10 * 1024 * 1024),
+            pretty: true,
+            previous: true,
+            since_seconds: Some(3600),
+            tail_lines: Some(4096),
+            timestamps: true,
+        };
+        let req = Request::new(url).logs("mypod", &lp).unwrap();
+        assert_eq!(req.uri(), "/api/v1/namespaces/ns/pods/mypod/log?&container=nginx&follow=true&limitBytes=10485760&pretty=true&previous=true&sinceSeconds=3600&tailLines=4096&timestamps=true");
+    }
+
+    #[test]
+    fn logs_builder() {
+        let url = corev1::Pod::url_path(&(), Some("ns"));
+        let lp = LogParams::default()
+            .container("nginx")
+            .follow()
+            .tail_lines(10)
+            .timestamps()
+            .previous()
+            .limit_bytes(1024);
+        let req = Request::new(url).logs("mypod", &lp).unwrap();
+        assert_eq!(
+            req.uri(),
+            "/api/v1/namespaces/ns/pods/mypod/log?&container=nginx&follow=true&limitBytes=1024&previous=true&tailLines=10&timestamps=true"
+        );
+    }
+
+    #[test]
+    fn logs_since_time() {
+        let since = Utc::now() - chrono::Duration::milliseconds(90_500);
+        let lp = LogParams::default().since_time(since);
+        assert!(
+            matches!(lp.since_seconds, Some(91 | 92)),
+            "{:?}",
+            lp.since_seconds
+        );
+        // Times in the future are clamped to the minimum that is accepted
+        let lp = LogParams::default().since_time(Utc::now() + chrono::Duration::seconds(10));
+        assert_eq!(lp.since_seconds, Some(1));
+    }
+
+    #[test]
+    fn proxy_uri() {
+        let url = corev1::Pod::url_path(&(), Some("ns"));
+        let request = http::Request::post("/admin/reload?force=true")
+            .header("x-token", "secret")
+            .body(b"{}".to_vec())
+            .unwrap();
+        let req = Request::new(url.clone()).proxy("mypod:9090", request).unwrap();
+        assert_eq!(
+            req.uri(),
+            "/api/v1/namespaces/ns/pods/mypod:9090/proxy/admin/reload?force=true"
+        );
+        assert_eq!(req.method(), "POST");
+        assert_eq!(req.headers()["x-token"], "secret");
+        assert_eq!(req.body(), b"{}");
+
+        let request = http::Request::get("*").body(vec![]).unwrap();
+        let req = Request::new(url.clone()).proxy("mypod", request).unwrap();
+        assert_eq!(req.uri(), "/api/v1/namespaces/ns/pods/mypod/proxy/");
+        let request = http::Request::get("http://example.com/").body(vec![]).unwrap();
+        assert!(Request::new(url).proxy("mypod", request).is_err());
+    }
+
+    #[test]
+    fn evict_body() {
+        let url = corev1::Pod::url_path(&(), Some("ns"));
+        let ep = EvictParams {
+            delete_options: Some(DeleteParams {
+                grace_period_seconds: Some(10),
+                ..DeleteParams::default()
+            }),
+            ..EvictParams::default()
+        };
+        let req = Request::new(url).evict("mypod", &ep).unwrap();
+        assert_eq!(req.uri(), "/api/v1/namespaces/ns/pods/mypod/eviction?");
+        let body: serde_json::Value = serde_json::from_slice(req.body()).unwrap();
+        assert_eq!(
+            body,
+            serde_json::json!({
+                "apiVersion": "policy/v1",
+                "kind": "Eviction",
+                "metadata": { "name": "mypod" },
+                "deleteOptions": { "gracePeriodSeconds": 10 },
+            })
+        );
+    }
+}
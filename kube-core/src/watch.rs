@@ -4,7 +4,7 @@
 
 use crate::{error::ErrorResponse, metadata::TypeMeta};
 use serde::{Deserialize, Serialize};
-use std::fmt::Debug;
+use std::{collections::BTreeMap, fmt::Debug};
 /// A raw event returned from a watch query
 ///
 /// Note that a watch query returns many of these as newline separated JSON.
@@ -33,7 +33,7 @@ impl<K> Debug for WatchEvent<K> {
             WatchEvent::Added(_) => write!(f, "Added event"),
             WatchEvent::Modified(_) => write!(f, "Modified event"),
             WatchEvent::Deleted(_) => write!(f, "Deleted event"),
-            WatchEvent::Bookmark(_) => write!(f, "Bookmark event"),
+            WatchEvent::Bookmark(b) => write!(f, "Bookmark event: {}", b.metadata.resource_version),
             WatchEvent::Error(e) => write!(f, "Error event: {e:?}"),
         }
     }
@@ -43,7 +43,7 @@ impl<K> Debug for WatchEvent<K> {
 ///
 /// Can only be relied upon to have metadata with resource version.
 /// Bookmarks contain apiVersion + kind + basically empty metadata.
-#[derive(Serialize, Deserialize, Clone)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
 pub struct Bookmark {
     /// apiVersion + kind
     #[serde(flatten)]
@@ -53,10 +53,57 @@ pub struct Bookmark {
     pub metadata: BookmarkMeta,
 }
 
+impl Bookmark {
+    /// The resource version to resume the watch from
+    pub fn resource_version(&self) -> &str {
+        &self.metadata.resource_version
+    }
+
+    /// Whether this bookmark marks the end of the initial events of a watch that streams the list first
+    ///
+    /// This is the `k8s.io/initial-events-end` annotation of watches with `sendInitialEvents`.
+    pub fn is_initial_events_end(&self) -> bool {
+        self.metadata
+            .annotations
+            .get("k8s.io/initial-events-end")
+            .map_or(false, |end| end == "true")
+    }
+}
+
 /// Slimed down Metadata for WatchEvent::Bookmark
-#[derive(Serialize, Deserialize, Clone)]
+///
+/// This is `#[non_exhaustive]`, so that more of the metadata of bookmarks can be decoded when the apiserver adds it.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct BookmarkMeta {
     /// The only field we need from a Bookmark event.
     pub resource_version: String,
+
+    /// Annotations that the apiserver sets on some bookmarks, such as `k8s.io/initial-events-end`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub annotations: BTreeMap<String, String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use k8s_openapi::api::core::v1::Pod;
+
+    #[test]
+    fn decodes_bookmarks() {
+        let line = r#"{"type":"BOOKMARK","object":{"kind":"Pod","apiVersion":"v1","metadata":{"resourceVersion":"3845","creationTimestamp":null}}}"#;
+        let bookmark = match serde_json::from_str::<WatchEvent<Pod>>(line).unwrap() {
+            WatchEvent::Bookmark(bookmark) => bookmark,
+            event => panic!("expected a bookmark, got {event:?}"),
+        };
+        assert_eq!(bookmark.resource_version(), "3845");
+        assert_eq!(bookmark.types.kind, "Pod");
+        assert!(!bookmark.is_initial_events_end());
+
+        let line = r#"{"type":"BOOKMARK","object":{"kind":"Pod","apiVersion":"v1","metadata":{"resourceVersion":"3846","annotations":{"k8s.io/initial-events-end":"true"}}}}"#;
+        let event = serde_json::from_str::<WatchEvent<Pod>>(line).unwrap();
+        assert_eq!(format!("{event:?}"), "Bookmark event: 3846");
+        assert!(matches!(event, WatchEvent::Bookmark(bookmark) if bookmark.is_initial_events_end()));
+    }
 }
@@ -1,4 +1,4 @@
-use darling::{FromDeriveInput, FromMeta};
+use darling::{util::Override, FromDeriveInput, FromField, FromMeta};
 use proc_macro2::{Ident, Literal, Span, TokenStream};
 use quote::ToTokens;
 use syn::{parse_quote, Data, DeriveInput, Path, Visibility};
@@ -16,6 +16,8 @@ struct KubeAttrs {
     plural: Option<String>,
     /// singular defaults to lowercased kind
     singular: Option<String>,
+    /// listKind defaults to kind with a List suffix
+    list_kind: Option<String>,
     #[darling(default)]
     namespaced: bool,
     #[darling(multiple, rename = "derive")]
@@ -26,17 +28,333 @@ struct KubeAttrs {
     categories: Vec<String>,
     #[darling(multiple, rename = "shortname")]
     shortnames: Vec<String>,
+    /// Only parsed to reject it with a helpful error
+    #[darling(multiple, rename = "selectable")]
+    _selectable: Vec<SelectableField>,
     #[darling(multiple, rename = "printcolumn")]
-    printcolums: Vec<String>,
-    scale: Option<String>,
+    printcolums: Vec<PrintColumn>,
+    scale: Option<Scale>,
+    #[darling(multiple, rename = "rule")]
+    rules: Vec<RuleAttr>,
+    conversion: Option<Conversion>,
+    #[darling(default)]
+    apply: bool,
+    #[darling(default)]
+    validating_admission_policy: bool,
+    #[darling(default)]
+    api: bool,
+    doc: Option<String>,
+    example: Option<Example>,
+    #[darling(default)]
+    conditions: bool,
+    served: Option<bool>,
+    storage: Option<bool>,
+    deprecated: Option<Deprecated>,
     #[darling(default)]
     crates: Crates,
 }
 
+/// Values we can parse from #[kube(attrs)] on the fields of the spec
+#[derive(Debug, FromField)]
+#[darling(attributes(kube), forward_attrs(serde))]
+struct KubeFieldAttrs {
+    ident: Option<Ident>,
+    attrs: Vec<syn::Attribute>,
+    #[darling(multiple, rename = "rule")]
+    rules: Vec<RuleAttr>,
+    /// JSON of the default value of the field, overriding any default from serde
+    default: Option<String>,
+    /// JSON of an example value of the field
+    example: Option<String>,
+    #[darling(default)]
+    preserve_unknown_fields: bool,
+    #[darling(default)]
+    embedded_resource: bool,
+    #[darling(default)]
+    int_or_string: bool,
+    /// Use the apply type of the field type from `#[derive(Apply)]` in apply types
+    #[darling(default)]
+    apply: bool,
+}
+
+impl KubeFieldAttrs {
+    /// Whether the field changes its schema, and therefore needs a name to find it
+    fn has_schema_attrs(&self) -> bool {
+        !self.rules.is_empty()
+            || self.default.is_some()
+            || self.example.is_some()
+            || self.preserve_unknown_fields
+            || self.embedded_resource
+            || self.int_or_string
+    }
+}
+
+/// A CEL validation rule, from `rule = "expr"` or `rule(rule = "expr", message = "..")`
+#[derive(Debug, FromMeta)]
+struct Rule {
+    rule: String,
+    message: Option<String>,
+}
+
+#[derive(Debug)]
+struct RuleAttr(Rule);
+
+impl FromMeta for RuleAttr {
+    fn from_string(value: &str) -> darling::Result<Self> {
+        Ok(Self(Rule {
+            rule: value.to_string(),
+            message: None,
+        }))
+    }
+
+    fn from_list(items: &[syn::NestedMeta]) -> darling::Result<Self> {
+        // The ValidationRule of k8s-openapi 0.18 has no fields for these, so they would be dropped from the crd
+        for item in items {
+            if let syn::NestedMeta::Meta(meta) = item {
+                if meta.path().is_ident("reason") || meta.path().is_ident("field_path") {
+                    return Err(darling::Error::custom(
+                        "the `reason` and `field_path` of rules need Kubernetes 1.28, which k8s-openapi 0.18 does not support",
+                    )
+                    .with_span(meta));
+                }
+            }
+        }
+        Rule::from_list(items).map(Self)
+    }
+}
+
+impl Rule {
+    /// The validation of a ValidatingAdmissionPolicy with the `expression` of this rule
+    fn to_policy_json(&self, expression: &str) -> serde_json::Value {
+        let mut validation = serde_json::json!({ "expression": expression });
+        if let Some(message) = &self.message {
+            validation["message"] = message.as_str().into();
+        }
+        validation
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        let mut rule = serde_json::json!({ "rule": self.rule });
+        if let Some(message) = &self.message {
+            rule["message"] = message.as_str().into();
+        }
+        rule
+    }
+}
+
+/// A printer column, from `printcolumn = r#"json"#` or `printcolumn(name = "..", jsonpath = "..", type = "..")`
+#[derive(Debug)]
+struct PrintColumn(serde_json::Value);
+
+#[derive(Debug, FromMeta)]
+struct PrintColumnFields {
+    name: String,
+    jsonpath: String,
+    #[darling(rename = "type")]
+    type_: ColumnType,
+    description: Option<String>,
+    format: Option<String>,
+    priority: Option<i32>,
+}
+
+impl FromMeta for PrintColumn {
+    fn from_string(value: &str) -> darling::Result<Self> {
+        let column: serde_json::Value = serde_json::from_str(value)
+            .map_err(|err| darling::Error::custom(format!("printcolumn is not valid json: {err}")))?;
+        for key in ["name", "jsonPath", "type"] {
+            if !column.get(key).map_or(false, serde_json::Value::is_string) {
+                return Err(darling::Error::custom(format!(
+                    "printcolumn needs a string `{key}`"
+                )));
+            }
+        }
+        ColumnType::from_string(column["type"].as_str().unwrap_or_default())?;
+        Ok(Self(column))
+    }
+
+    fn from_list(items: &[syn::NestedMeta]) -> darling::Result<Self> {
+        let fields = PrintColumnFields::from_list(items)?;
+        let mut column = serde_json::json!({
+            "name": fields.name,
+            "jsonPath": fields.jsonpath,
+            "type": fields.type_.0,
+        });
+        if let Some(description) = fields.description {
+            column["description"] = description.into();
+        }
+        if let Some(format) = fields.format {
+            column["format"] = format.into();
+        }
+        if let Some(priority) = fields.priority {
+            column["priority"] = priority.into();
+        }
+        Ok(Self(column))
+    }
+}
+
+/// The type of a printer column, one of the OpenAPI types that kubectl can print
+#[derive(Debug)]
+struct ColumnType(String);
+
+impl FromMeta for ColumnType {
+    fn from_string(value: &str) -> darling::Result<Self> {
+        match value {
+            "integer" | "number" | "string" | "boolean" | "date" => Ok(Self(value.to_string())),
+            x => Err(darling::Error::unknown_value(x)),
+        }
+    }
+}
+
+/// The scale subresource, from `scale = r#"json"#` or `scale(spec_replicas_path = "..", status_replicas_path = "..")`
+#[derive(Debug)]
+struct Scale(serde_json::Value);
+
+#[derive(Debug, FromMeta)]
+struct ScaleFields {
+    spec_replicas_path: String,
+    status_replicas_path: String,
+    label_selector_path: Option<String>,
+}
+
+impl FromMeta for Scale {
+    fn from_string(value: &str) -> darling::Result<Self> {
+        let scale: serde_json::Value = serde_json::from_str(value)
+            .map_err(|err| darling::Error::custom(format!("scale is not valid json: {err}")))?;
+        for key in ["specReplicasPath", "statusReplicasPath"] {
+            if !scale.get(key).map_or(false, serde_json::Value::is_string) {
+                return Err(darling::Error::custom(format!("scale needs a string `{key}`")));
+            }
+        }
+        Ok(Self(scale))
+    }
+
+    fn from_list(items: &[syn::NestedMeta]) -> darling::Result<Self> {
+        let fields = ScaleFields::from_list(items)?;
+        let mut scale = serde_json::json!({
+            "specReplicasPath": fields.spec_replicas_path,
+            "statusReplicasPath": fields.status_replicas_path,
+        });
+        if let Some(label_selector_path) = fields.label_selector_path {
+            scale["labelSelectorPath"] = label_selector_path.into();
+        }
+        Ok(Self(scale))
+    }
+}
+
+/// A selectable field, from `selectable = ".spec.foo"`, which is always rejected
+///
+/// The `CustomResourceDefinitionVersion` of k8s-openapi 0.18 has no `selectableFields`, so they would be dropped from the crd.
+#[derive(Debug)]
+enum SelectableField {}
+
+impl FromMeta for SelectableField {
+    fn from_string(value: &str) -> darling::Result<Self> {
+        Err(darling::Error::custom(format!(
+            "selectable field `{value}` needs Kubernetes 1.30, which k8s-openapi 0.18 does not support"
+        )))
+    }
+}
+
+/// Example generation, from `example` or `example = r#"json"#` with an example of the spec
+#[derive(Debug)]
+struct Example(Option<serde_json::Value>);
+
+impl FromMeta for Example {
+    fn from_word() -> darling::Result<Self> {
+        Ok(Self(None))
+    }
+
+    fn from_string(value: &str) -> darling::Result<Self> {
+        serde_json::from_str(value)
+            .map(|example| Self(Some(example)))
+            .map_err(|err| darling::Error::custom(format!("example is not valid json: {err}")))
+    }
+}
+
+/// A deprecated version, from `deprecated` or `deprecated = "warning"`
+#[derive(Debug)]
+struct Deprecated(Option<String>);
+
+impl FromMeta for Deprecated {
+    fn from_word() -> darling::Result<Self> {
+        Ok(Self(None))
+    }
+
+    fn from_string(value: &str) -> darling::Result<Self> {
+        Ok(Self(Some(value.to_string())))
+    }
+}
+
+/// Webhook conversion, from `conversion(service(name = "..", namespace = ".."), review_version = "v1")`
+#[derive(Debug, FromMeta)]
+#[darling(and_then = "Self::validate")]
+struct Conversion {
+    service: Option<ConversionService>,
+    url: Option<String>,
+    ca_bundle: Option<String>,
+    #[darling(multiple, rename = "review_version")]
+    review_versions: Vec<String>,
+    /// Either `hub` for the hub version, or `hub = "path::to::Hub"` for the other versions
+    hub: Option<Override<Path>>,
+}
+
+#[derive(Debug, FromMeta)]
+struct ConversionService {
+    name: String,
+    namespace: String,
+    path: Option<String>,
+    port: Option<i32>,
+}
+
+impl Conversion {
+    fn validate(self) -> darling::Result<Self> {
+        match (&self.service, &self.url) {
+            (Some(_), None) | (None, Some(_)) => Ok(self),
+            _ => Err(darling::Error::custom(
+                "conversion needs exactly one of `service(..)` or `url`",
+            )),
+        }
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        let mut client_config = serde_json::json!({});
+        if let Some(service) = &self.service {
+            let mut svc = serde_json::json!({ "name": service.name, "namespace": service.namespace });
+            if let Some(path) = &service.path {
+                svc["path"] = path.as_str().into();
+            }
+            if let Some(port) = service.port {
+                svc["port"] = port.into();
+            }
+            client_config["service"] = svc;
+        }
+        if let Some(url) = &self.url {
+            client_config["url"] = url.as_str().into();
+        }
+        if let Some(ca_bundle) = &self.ca_bundle {
+            client_config["caBundle"] = ca_bundle.as_str().into();
+        }
+        let review_versions = if self.review_versions.is_empty() {
+            vec!["v1".to_string()]
+        } else {
+            self.review_versions.clone()
+        };
+        serde_json::json!({
+            "strategy": "Webhook",
+            "webhook": {
+                "clientConfig": client_config,
+                "conversionReviewVersions": review_versions,
+            }
+        })
+    }
+}
+
 #[derive(Debug, FromMeta)]
 struct Crates {
     #[darling(default = "Self::default_kube_core")]
     kube_core: Path,
+    #[darling(default = "Self::default_kube_client")]
+    kube_client: Path,
     #[darling(default = "Self::default_k8s_openapi")]
     k8s_openapi: Path,
     #[darling(default = "Self::default_schemars")]
@@ -62,6 +380,10 @@ impl Crates {
         parse_quote! { ::kube::core } // by default must work well with people using facade crate
     }
 
+    fn default_kube_client() -> Path {
+        parse_quote! { ::kube }
+    }
+
     fn default_k8s_openapi() -> Path {
         parse_quote! { ::k8s_openapi }
     }
@@ -151,13 +473,27 @@ pub(crate) fn derive(input: proc_macro2::TokenStream) -> proc_macro2::TokenStrea
         status,
         plural,
         singular,
+        list_kind,
         categories,
         shortnames,
+        _selectable: _,
         printcolums,
         scale,
+        rules,
+        conversion,
+        apply,
+        validating_admission_policy,
+        api,
+        doc,
+        example,
+        conditions,
+        served,
+        storage,
+        deprecated,
         crates:
             Crates {
                 kube_core,
+                kube_client,
                 k8s_openapi,
                 schemars,
                 serde,
@@ -166,6 +502,94 @@ pub(crate) fn derive(input: proc_macro2::TokenStream) -> proc_macro2::TokenStrea
             },
     } = kube_attrs;
 
+    // CEL validation rules as pairs of the JSON pointer of the schema they apply to and the rule
+    let mut validations: Vec<(String, serde_json::Value)> = rules
+        .iter()
+        .map(|RuleAttr(rule)| ("/properties/spec".to_string(), rule.to_json()))
+        .collect();
+    // The same rules as validations of a ValidatingAdmissionPolicy, where they apply to the whole `object`
+    let mut policy_validations: Vec<serde_json::Value> = rules
+        .iter()
+        .map(|RuleAttr(rule)| rule.to_policy_json(&policy_expression(&rule.rule, "object.spec", false)))
+        .collect();
+    // Properties of the schemas of fields, like their default, as the JSON pointer of the schema, the key and the value
+    let mut field_props: Vec<(String, &str, serde_json::Value)> = vec![];
+    if let Some(Example(Some(example))) = &example {
+        field_props.push(("/properties/spec".to_string(), "example", example.clone()));
+    }
+    if let Data::Struct(data) = &derive_input.data {
+        let rename_all = serde_rename(&derive_input.attrs, "rename_all");
+        for field in &data.fields {
+            let field = match KubeFieldAttrs::from_field(field) {
+                Err(err) => return err.write_errors(),
+                Ok(field) => field,
+            };
+            let ident = match (&field.ident, !field.has_schema_attrs()) {
+                (_, true) => continue,
+                (Some(ident), false) => ident,
+                (None, false) => {
+                    return syn::Error::new_spanned(
+                        &derive_input.ident,
+                        "#[kube(..)] attributes are only supported on named fields",
+                    )
+                    .to_compile_error()
+                }
+            };
+            let name = serde_rename(&field.attrs, "rename")
+                .unwrap_or_else(|| rename_field(&ident.to_string(), rename_all.as_deref()));
+            let pointer = format!(
+                "/properties/spec/properties/{}",
+                name.replace('~', "~0").replace('/', "~1")
+            );
+            for (key, json) in [("default", &field.default), ("example", &field.example)] {
+                if let Some(json) = json {
+                    match serde_json::from_str(json) {
+                        Ok(value) => field_props.push((pointer.clone(), key, value)),
+                        Err(err) => {
+                            return syn::Error::new_spanned(
+                                ident,
+                                format!("#[kube({key})] is not valid json: {err}"),
+                            )
+                            .to_compile_error()
+                        }
+                    }
+                }
+            }
+            if field.preserve_unknown_fields {
+                field_props.push((
+                    pointer.clone(),
+                    "x-kubernetes-preserve-unknown-fields",
+                    true.into(),
+                ));
+            }
+            if field.embedded_resource {
+                field_props.push((pointer.clone(), "x-kubernetes-embedded-resource", true.into()));
+                field_props.push((pointer.clone(), "type", "object".into()));
+            }
+            if field.int_or_string {
+                field_props.push((pointer.clone(), "x-kubernetes-int-or-string", true.into()));
+                field_props.push((
+                    pointer.clone(),
+                    "anyOf",
+                    serde_json::json!([{ "type": "integer" }, { "type": "string" }]),
+                ));
+            }
+            validations.extend(
+                field
+                    .rules
+                    .iter()
+                    .map(|RuleAttr(rule)| (pointer.clone(), rule.to_json())),
+            );
+            let target = format!("object.spec.{name}");
+            policy_validations.extend(
+                field
+                    .rules
+                    .iter()
+                    .map(|RuleAttr(rule)| rule.to_policy_json(&policy_expression(&rule.rule, &target, true))),
+            );
+        }
+    }
+
     let struct_name = kind_struct.unwrap_or_else(|| kind.clone());
     if derive_input.ident == struct_name {
         return syn::Error::new_spanned(
@@ -174,6 +598,14 @@ pub(crate) fn derive(input: proc_macro2::TokenStream) -> proc_macro2::TokenStrea
         )
         .to_compile_error();
     }
+    let apply_fields = if apply {
+        match apply_fields(&derive_input) {
+            Err(err) => return err.write_errors(),
+            Ok(fields) => Some(fields),
+        }
+    } else {
+        None
+    };
     let visibility = derive_input.vis;
     let ident = derive_input.ident;
 
@@ -191,7 +623,10 @@ pub(crate) fn derive(input: proc_macro2::TokenStream) -> proc_macro2::TokenStrea
         field: status_field,
         default: status_default,
         impl_hasstatus,
-    } = process_status(&rootident, &status, &visibility, &kube_core);
+    } = match process_status(&rootident, &status, &visibility, &kube_core) {
+        Err(err) => return err.to_compile_error(),
+        Ok(info) => info,
+    };
     let has_status = status.is_some();
     let serialize_status = if has_status {
         quote! {
@@ -235,11 +670,19 @@ pub(crate) fn derive(input: proc_macro2::TokenStream) -> proc_macro2::TokenStrea
     } else {
         quote! {}
     };
+    let quoted_schemars = Literal::string(&schemars.to_token_stream().to_string());
+    let schemars_crate = if schema_mode.derive() {
+        quote! { #[schemars(crate = #quoted_schemars)] }
+    } else {
+        quote! {}
+    };
     if schema_mode.derive() {
         derive_paths.push(syn::parse_quote! { #schemars::JsonSchema });
     }
 
-    let docstr = format!(" Auto-generated derived type for {ident} via `CustomResource`");
+    // The doc comment of the root type is also the description of the schema of the whole resource
+    let docstr =
+        doc.unwrap_or_else(|| format!(" Auto-generated derived type for {ident} via `CustomResource`"));
     let quoted_serde = Literal::string(&serde.to_token_stream().to_string());
     let root_obj = quote! {
         #[doc = #docstr]
@@ -248,6 +691,7 @@ pub(crate) fn derive(input: proc_macro2::TokenStream) -> proc_macro2::TokenStrea
         #[derive(#(#derive_paths),*)]
         #[serde(rename_all = "camelCase")]
         #[serde(crate = #quoted_serde)]
+        #schemars_crate
         #visibility struct #rootident {
             #schemars_skip
             #visibility metadata: #k8s_openapi::apimachinery::pkg::apis::meta::v1::ObjectMeta,
@@ -284,6 +728,7 @@ pub(crate) fn derive(input: proc_macro2::TokenStream) -> proc_macro2::TokenStrea
     // 2. Implement Resource trait
     let name = singular.unwrap_or_else(|| kind.to_ascii_lowercase());
     let plural = plural.unwrap_or_else(|| to_plural(&name));
+    let list_kind = list_kind.unwrap_or_else(|| format!("{kind}List"));
     let (scope, scope_quote) = if namespaced {
         ("Namespaced", quote! { #kube_core::NamespaceResourceScope })
     } else {
@@ -296,23 +741,23 @@ pub(crate) fn derive(input: proc_macro2::TokenStream) -> proc_macro2::TokenStrea
             type DynamicType = ();
             type Scope = #scope_quote;
 
-            fn group(_: &()) -> std::borrow::Cow<'_, str> {
+            fn group(_: &()) -> #std::borrow::Cow<'_, str> {
                #group.into()
             }
 
-            fn kind(_: &()) -> std::borrow::Cow<'_, str> {
+            fn kind(_: &()) -> #std::borrow::Cow<'_, str> {
                 #kind.into()
             }
 
-            fn version(_: &()) -> std::borrow::Cow<'_, str> {
+            fn version(_: &()) -> #std::borrow::Cow<'_, str> {
                 #version.into()
             }
 
-            fn api_version(_: &()) -> std::borrow::Cow<'_, str> {
+            fn api_version(_: &()) -> #std::borrow::Cow<'_, str> {
                 #api_ver.into()
             }
 
-            fn plural(_: &()) -> std::borrow::Cow<'_, str> {
+            fn plural(_: &()) -> #std::borrow::Cow<'_, str> {
                 #plural.into()
             }
 
@@ -346,8 +791,12 @@ pub(crate) fn derive(input: proc_macro2::TokenStream) -> proc_macro2::TokenStrea
     // 4. Implement CustomResource
 
     // Compute a bunch of crd props
-    let printers = format!("[ {} ]", printcolums.join(",")); // hacksss
-    let scale_code = if let Some(s) = scale { s } else { "".to_string() };
+    let printers = serde_json::Value::from_iter(printcolums.into_iter().map(|PrintColumn(c)| c)).to_string();
+    let scale_code = if let Some(Scale(s)) = &scale {
+        s.to_string()
+    } else {
+        "".to_string()
+    };
 
     // Ensure it generates for the correct CRD version (only v1 supported now)
     let apiext = quote! {
@@ -380,7 +829,7 @@ pub(crate) fn derive(input: proc_macro2::TokenStream) -> proc_macro2::TokenStrea
                 })
                 .with_visitor(#kube_core::schema::StructuralSchemaRewriter)
                 .into_generator();
-            let schema = gen.into_root_schema_for::<Self>();
+            let schema = Some(gen.into_root_schema_for::<Self>());
         }
     } else {
         // we could issue a compile time warning for this, but it would hit EVERY compile, which would be noisy
@@ -391,8 +840,69 @@ pub(crate) fn derive(input: proc_macro2::TokenStream) -> proc_macro2::TokenStrea
         }
     };
 
+    // Add the validation rules and field properties to the generated schema, when the derive has any
+    let validationgen = if (validations.is_empty() && field_props.is_empty()) || !schema_mode.use_in_crd() {
+        quote! {}
+    } else {
+        let validations_json = serde_json::to_string(&validations).unwrap();
+        let field_props_json = serde_json::to_string(&field_props).unwrap();
+        quote! {
+            let validations: Vec<(String, #serde_json::Value)> = #serde_json::from_str(#validations_json).expect("valid validation rules");
+            let field_props: Vec<(String, String, #serde_json::Value)> = #serde_json::from_str(#field_props_json).expect("valid field properties");
+            let schema = schema.map(|schema| {
+                let mut schema = #serde_json::to_value(schema).expect("valid schema");
+                for (pointer, rule) in validations {
+                    if let Some(#serde_json::Value::Object(props)) = schema.pointer_mut(&pointer) {
+                        if let #serde_json::Value::Array(rules) = props
+                            .entry("x-kubernetes-validations")
+                            .or_insert_with(|| #serde_json::Value::Array(vec![]))
+                        {
+                            rules.push(rule);
+                        }
+                    }
+                }
+                for (pointer, key, value) in field_props {
+                    if let Some(#serde_json::Value::Object(props)) = schema.pointer_mut(&pointer) {
+                        // int-or-string schemas can not have a type of their own
+                        if key == "x-kubernetes-int-or-string" {
+                            props.remove("type");
+                            props.remove("format");
+                        }
+                        props.insert(key, value);
+                    }
+                }
+                schema
+            });
+        }
+    };
+
+    let conversion_json = conversion.as_ref().map(|c| c.to_json().to_string());
+    let conversiongen = if let Some(conversion_json) = &conversion_json {
+        quote! {
+            let conversion: Option<#serde_json::Value> = Some(#serde_json::from_str(#conversion_json).expect("valid conversion"));
+        }
+    } else {
+        quote! {
+            let conversion: Option<#serde_json::Value> = None;
+        }
+    };
+
+    let served = served.unwrap_or(true);
+    let storage = storage.unwrap_or(true);
+    let is_deprecated = if deprecated.is_some() {
+        quote! { Some(true) }
+    } else {
+        quote! { None::<bool> }
+    };
+    let deprecation_warning = match deprecated.and_then(|Deprecated(warning)| warning) {
+        Some(warning) => quote! { Some(#warning) },
+        None => quote! { None::<String> },
+    };
+
     let jsondata = quote! {
         #schemagen
+        #conversiongen
+        #validationgen
 
         let jsondata = #serde_json::json!({
             "metadata": #crd_meta,
@@ -404,15 +914,17 @@ pub(crate) fn derive(input: proc_macro2::TokenStream) -> proc_macro2::TokenStrea
                     "plural": #plural,
                     "singular": #name,
                     "kind": #kind,
+                    "listKind": #list_kind,
                     "shortNames": shorts
                 },
+                "conversion": conversion,
                 "versions": [{
                     "name": #version,
-                    "served": true,
-                    "storage": true,
-                    "schema": {
-                        "openAPIV3Schema": schema,
-                    },
+                    "served": #served,
+                    "storage": #storage,
+                    "deprecated": #is_deprecated,
+                    "deprecationWarning": #deprecation_warning,
+                    "schema": schema.map(|schema| #serde_json::json!({ "openAPIV3Schema": schema })),
                     "additionalPrinterColumns": columns,
                     "subresources": subres,
                 }],
@@ -465,6 +977,254 @@ pub(crate) fn derive(input: proc_macro2::TokenStream) -> proc_macro2::TokenStrea
         }
     };
 
+    // Implement HasConversion when the versions of the kind are converted by a webhook
+    let impl_conversion = if let Some(conversion_json) = &conversion_json {
+        // Link the version to its hub with the marker traits, so that versions of other kinds fail to compile
+        let impl_hub = match conversion.as_ref().and_then(|c| c.hub.as_ref()) {
+            Some(Override::Inherit) => quote! {
+                impl #extver::Hub for #rootident {}
+                impl #extver::Convertible<#rootident> for #rootident {}
+            },
+            Some(Override::Explicit(hub)) => quote! {
+                impl #extver::Convertible<#hub> for #rootident {}
+            },
+            None => quote! {},
+        };
+        quote! {
+            impl #extver::HasConversion for #rootident {
+                fn conversion() -> #apiext::CustomResourceConversion {
+                    #serde_json::from_str(#conversion_json).expect("valid conversion")
+                }
+            }
+            #impl_hub
+        }
+    } else {
+        quote! {}
+    };
+
+    // Generate a ValidatingAdmissionPolicy with the CEL rules if requested
+    let impl_policy = if validating_admission_policy {
+        let admission = quote! { #k8s_openapi::api::admissionregistration::v1alpha1 };
+        let policy_json = serde_json::json!({
+            "apiVersion": "admissionregistration.k8s.io/v1alpha1",
+            "kind": "ValidatingAdmissionPolicy",
+            "metadata": { "name": crd_meta_name },
+            "spec": {
+                "failurePolicy": "Fail",
+                "matchConstraints": {
+                    "resourceRules": [{
+                        "apiGroups": [group],
+                        "apiVersions": [version],
+                        "operations": ["CREATE", "UPDATE"],
+                        "resources": [plural],
+                    }]
+                },
+                "validations": policy_validations,
+            }
+        })
+        .to_string();
+        let binding_json = serde_json::json!({
+            "apiVersion": "admissionregistration.k8s.io/v1alpha1",
+            "kind": "ValidatingAdmissionPolicyBinding",
+            "metadata": { "name": crd_meta_name },
+            "spec": { "policyName": crd_meta_name }
+        })
+        .to_string();
+        quote! {
+            impl #rootident {
+                /// A `ValidatingAdmissionPolicy` with the CEL rules of this custom resource, and a binding that enforces it
+                pub fn validating_admission_policy() -> (#admission::ValidatingAdmissionPolicy, #admission::ValidatingAdmissionPolicyBinding) {
+                    let policy = #serde_json::from_str(#policy_json).expect("valid admission policy");
+                    let binding = #serde_json::from_str(#binding_json).expect("valid admission policy binding");
+                    (policy, binding)
+                }
+            }
+        }
+    } else {
+        quote! {}
+    };
+
+    // Generate condition helpers on the status if requested
+    let impl_conditions = match (conditions, &status) {
+        (false, _) => quote! {},
+        (true, None) => {
+            return syn::Error::new_spanned(
+                &rootident,
+                "#[kube(conditions)] needs a #[kube(status)] with a `conditions: Vec<Condition>` field",
+            )
+            .to_compile_error()
+        }
+        (true, Some(status)) => {
+            let status_ty: syn::Type = match syn::parse_str(status) {
+                Err(err) => return err.to_compile_error(),
+                Ok(ty) => ty,
+            };
+            let condition = quote! { #k8s_openapi::apimachinery::pkg::apis::meta::v1::Condition };
+            quote! {
+                impl #status_ty {
+                    /// Set a condition, replacing the one of the same type and only moving its `lastTransitionTime` when its status changes
+                    pub fn set_condition(&mut self, condition: #condition) {
+                        #kube_core::conditions::set_condition(&mut self.conditions, condition)
+                    }
+
+                    /// Get the condition of a type
+                    pub fn get_condition(&self, type_: &str) -> Option<&#condition> {
+                        #kube_core::conditions::get_condition(&self.conditions, type_)
+                    }
+                }
+
+                impl #kube_core::conditions::HasConditions for #rootident {
+                    type Condition = #condition;
+
+                    fn conditions(&self) -> &[#condition] {
+                        self.status.as_ref().map_or(&[], |s| &s.conditions)
+                    }
+
+                    fn conditions_mut(&mut self) -> &mut Vec<#condition> {
+                        &mut self.status.get_or_insert_with(Default::default).conditions
+                    }
+                }
+            }
+        }
+    };
+
+    // Generate an example object if requested
+    let impl_example = if example.is_some() {
+        quote! {
+            impl #rootident {
+                /// An example of this custom resource from the examples and defaults in its schema, for sample manifests
+                pub fn example() -> #serde_json::Value {
+                    #extver::crd_example(&<Self as #extver::CustomResourceExt>::crd())
+                }
+            }
+        }
+    } else {
+        quote! {}
+    };
+
+    // Generate Api constructors for the declared scope if requested
+    let impl_api = if api {
+        let namespaced_api = if namespaced {
+            quote! {
+                /// An `Api` for this custom resource in the given namespace
+                pub fn namespaced_api(client: #kube_client::Client, ns: &str) -> #kube_client::Api<Self> {
+                    #kube_client::Api::namespaced(client, ns)
+                }
+            }
+        } else {
+            quote! {}
+        };
+        let api_doc = if namespaced {
+            " An `Api` for this custom resource in the default namespace of the client"
+        } else {
+            " An `Api` for this cluster scoped custom resource"
+        };
+        let api_fn = if namespaced {
+            quote! { #kube_client::Api::default_namespaced(client) }
+        } else {
+            quote! { #kube_client::Api::all(client) }
+        };
+        quote! {
+            impl #rootident {
+                #[doc = #api_doc]
+                pub fn api(client: #kube_client::Client) -> #kube_client::Api<Self> {
+                    #api_fn
+                }
+
+                #namespaced_api
+
+                /// An `Api` for this custom resource across all namespaces
+                pub fn all(client: #kube_client::Client) -> #kube_client::Api<Self> {
+                    #kube_client::Api::all(client)
+                }
+            }
+        }
+    } else {
+        quote! {}
+    };
+
+    // Implement HasScale with the replica paths of the scale subresource
+    let impl_scale = if let Some(Scale(scale)) = &scale {
+        let spec_path = scale["specReplicasPath"].as_str().unwrap_or_default();
+        let status_path = scale["statusReplicasPath"].as_str().unwrap_or_default();
+        let label_selector_path = match scale.get("labelSelectorPath").and_then(|p| p.as_str()) {
+            Some(path) => quote! { Some(#path) },
+            None => quote! { None },
+        };
+        quote! {
+            impl #extver::HasScale for #rootident {
+                fn scale_spec_path() -> &'static str {
+                    #spec_path
+                }
+
+                fn scale_status_path() -> &'static str {
+                    #status_path
+                }
+
+                fn scale_label_selector_path() -> Option<&'static str> {
+                    #label_selector_path
+                }
+            }
+        }
+    } else {
+        quote! {}
+    };
+
+    // 5. Generate the server-side apply type if requested
+    let apply_obj = if let Some(fields) = apply_fields {
+        let apply_ident = format_ident!("{}Apply", rootident);
+        let spec_apply_ident = format_ident!("{}Apply", ident);
+        let apply_str = apply_ident.to_string();
+        let apply_doc = format!(" Server-side apply patch for {rootident} with the spec fields that are set");
+        let spec_apply_doc = format!(" The fields of {ident} that are set in a {apply_ident}");
+        quote! {
+            #[doc = #spec_apply_doc]
+            #[automatically_derived]
+            #[allow(missing_docs)]
+            #[derive(#serde::Serialize, Default)]
+            #[serde(crate = #quoted_serde)]
+            #visibility struct #spec_apply_ident {
+                #fields
+            }
+
+            #[doc = #apply_doc]
+            #[automatically_derived]
+            #[allow(missing_docs)]
+            #[derive(Default)]
+            #visibility struct #apply_ident {
+                #visibility metadata: #k8s_openapi::apimachinery::pkg::apis::meta::v1::ObjectMeta,
+                #visibility spec: #spec_apply_ident,
+            }
+
+            impl #serde::Serialize for #apply_ident {
+                fn serialize<S: #serde::Serializer>(&self, ser: S) -> #std::result::Result<S::Ok, S::Error> {
+                    use #serde::ser::SerializeStruct;
+                    let mut obj = ser.serialize_struct(#apply_str, 4)?;
+                    obj.serialize_field("apiVersion", &<#rootident as #kube_core::Resource>::api_version(&()))?;
+                    obj.serialize_field("kind", &<#rootident as #kube_core::Resource>::kind(&()))?;
+                    obj.serialize_field("metadata", &self.metadata)?;
+                    obj.serialize_field("spec", &self.spec)?;
+                    obj.end()
+                }
+            }
+
+            impl #rootident {
+                /// Named constructor for a server-side apply patch of this custom resource, with no spec fields set
+                pub fn apply_patch(name: &str) -> #apply_ident {
+                    #apply_ident {
+                        metadata: #k8s_openapi::apimachinery::pkg::apis::meta::v1::ObjectMeta {
+                            name: Some(name.to_string()),
+                            ..Default::default()
+                        },
+                        spec: Default::default(),
+                    }
+                }
+            }
+        }
+    } else {
+        quote! {}
+    };
+
     let impl_hasspec = generate_hasspec(&ident, &rootident, &kube_core);
 
     // Concat output
@@ -473,6 +1233,13 @@ pub(crate) fn derive(input: proc_macro2::TokenStream) -> proc_macro2::TokenStrea
         #impl_resource
         #impl_default
         #impl_crd
+        #impl_conversion
+        #impl_scale
+        #apply_obj
+        #impl_policy
+        #impl_api
+        #impl_example
+        #impl_conditions
         #impl_hasspec
         #impl_hasstatus
     }
@@ -523,16 +1290,17 @@ struct StatusInformation {
 /// * `visibility`: Desired visibility of the generated field
 /// * `kube_core`: The path stream for the analagous kube::core import location from users POV
 ///
-/// returns: A `StatusInformation` struct
+/// returns: A `StatusInformation` struct, or an error when `status` is not a type
 fn process_status(
     root_ident: &Ident,
     status: &Option<String>,
     visibility: &Visibility,
     kube_core: &Path,
-) -> StatusInformation {
+) -> Result<StatusInformation, syn::Error> {
     if let Some(status_name) = &status {
-        let ident = format_ident!("{}", status_name);
-        StatusInformation {
+        // A type rather than an ident, so that the status can live in another module
+        let ident: syn::Type = syn::parse_str(status_name)?;
+        Ok(StatusInformation {
             field: quote! {
                 #[serde(skip_serializing_if = "Option::is_none")]
                 #visibility status: Option<#ident>,
@@ -552,17 +1320,266 @@ fn process_status(
                     }
                 }
             },
-        }
+        })
     } else {
         let empty_quote = quote! {};
-        StatusInformation {
+        Ok(StatusInformation {
             field: empty_quote.clone(),
             default: empty_quote.clone(),
             impl_hasstatus: empty_quote,
+        })
+    }
+}
+
+/// The value of `#[serde(key = "value")]` in `attrs`, like the `rename` of a field
+fn serde_rename(attrs: &[syn::Attribute], key: &str) -> Option<String> {
+    attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("serde"))
+        .filter_map(|attr| match attr.parse_meta() {
+            Ok(syn::Meta::List(list)) => Some(list.nested),
+            _ => None,
+        })
+        .flatten()
+        .find_map(|nested| match nested {
+            syn::NestedMeta::Meta(syn::Meta::NameValue(nv)) if nv.path.is_ident(key) => match nv.lit {
+                syn::Lit::Str(value) => Some(value.value()),
+                _ => None,
+            },
+            _ => None,
+        })
+}
+
+/// Whether any `#[serde(..)]` attribute has the flag `key`, such as `skip`
+fn serde_flag(attrs: &[syn::Attribute], key: &str) -> bool {
+    attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("serde"))
+        .filter_map(|attr| match attr.parse_meta() {
+            Ok(syn::Meta::List(list)) => Some(list.nested),
+            _ => None,
+        })
+        .flatten()
+        .any(|nested| matches!(nested, syn::NestedMeta::Meta(syn::Meta::Path(path)) if path.is_ident(key)))
+}
+
+/// The fields of an apply type, as optional versions of the serialized fields of `derive_input`
+///
+/// Fields with `#[kube(apply)]` use the apply type of their own type, so that nested structs are only sent in part.
+fn apply_fields(derive_input: &DeriveInput) -> darling::Result<TokenStream> {
+    let fields = match &derive_input.data {
+        Data::Struct(syn::DataStruct {
+            fields: syn::Fields::Named(fields),
+            ..
+        }) => &fields.named,
+        _ => {
+            return Err(
+                darling::Error::custom("apply types are only supported on structs with named fields")
+                    .with_span(&derive_input.ident),
+            )
+        }
+    };
+    let rename_all = serde_rename(&derive_input.attrs, "rename_all");
+    let mut errors = darling::Error::accumulator();
+    let fields = fields
+        .iter()
+        .filter(|field| !serde_flag(&field.attrs, "skip") && !serde_flag(&field.attrs, "skip_serializing"))
+        .filter_map(|field| {
+            let attrs = errors.handle(KubeFieldAttrs::from_field(field))?;
+            let syn::Field { ident, ty, vis, .. } = field;
+            let ty = if attrs.apply {
+                errors.handle(apply_type(ty))?
+            } else {
+                ty.clone()
+            };
+            let ident = ident.as_ref().expect("named field");
+            let name = serde_rename(&field.attrs, "rename")
+                .unwrap_or_else(|| rename_field(&ident.to_string(), rename_all.as_deref()));
+            Some(if serde_flag(&field.attrs, "flatten") {
+                quote! {
+                    #[serde(flatten)]
+                    #vis #ident: Option<#ty>,
+                }
+            } else {
+                quote! {
+                    #[serde(rename = #name, skip_serializing_if = "Option::is_none")]
+                    #vis #ident: Option<#ty>,
+                }
+            })
+        })
+        .collect();
+    errors.finish_with(fields)
+}
+
+/// The apply type from `#[derive(Apply)]` of the type of a field, looking through an `Option`
+fn apply_type(ty: &syn::Type) -> darling::Result<syn::Type> {
+    let mut ty = ty;
+    if let syn::Type::Path(path) = ty {
+        let last = path.path.segments.last().expect("non-empty path");
+        if let (true, syn::PathArguments::AngleBracketed(args)) = (last.ident == "Option", &last.arguments) {
+            if let Some(syn::GenericArgument::Type(inner)) = args.args.first() {
+                ty = inner;
+            }
+        }
+    }
+    match ty {
+        syn::Type::Path(path) if path.qself.is_none() => {
+            let mut path = path.clone();
+            let last = path.path.segments.last_mut().expect("non-empty path");
+            if last.arguments.is_empty() {
+                last.ident = format_ident!("{}Apply", last.ident);
+                return Ok(syn::Type::Path(path));
+            }
+        }
+        _ => {}
+    }
+    Err(darling::Error::custom(
+        "#[kube(apply)] fields need a struct type with #[derive(Apply)], or an Option of one",
+    )
+    .with_span(ty))
+}
+
+/// Derive the apply type of a struct that is nested in the spec of a custom resource
+pub(crate) fn derive_apply(input: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+    let derive_input: DeriveInput = match syn::parse2(input) {
+        Err(err) => return err.to_compile_error(),
+        Ok(di) => di,
+    };
+    if !derive_input.generics.params.is_empty() {
+        return syn::Error::new_spanned(
+            &derive_input.generics,
+            "#[derive(Apply)] does not support generics",
+        )
+        .to_compile_error();
+    }
+    let fields = match apply_fields(&derive_input) {
+        Err(err) => return err.write_errors(),
+        Ok(fields) => fields,
+    };
+    let visibility = &derive_input.vis;
+    let ident = &derive_input.ident;
+    let apply_ident = format_ident!("{}Apply", ident);
+    let apply_doc = format!(" The fields of {ident} that are set in a server-side apply patch");
+    quote! {
+        #[doc = #apply_doc]
+        #[automatically_derived]
+        #[allow(missing_docs)]
+        #[derive(::serde::Serialize, Default)]
+        #visibility struct #apply_ident {
+            #fields
         }
     }
 }
 
+/// The expression of a ValidatingAdmissionPolicy for a CEL rule of the CRD schema at `target` of the `object`
+///
+/// Like in the CRD schema, rules of a `field` only run when it is set, and transition rules that refer to `oldSelf`
+/// only on updates where the old object had the value too.
+fn policy_expression(rule: &str, target: &str, field: bool) -> String {
+    let old_target = format!("oldObject{}", target.trim_start_matches("object"));
+    let (expression, uses_old) = rebind_self(rule, target, &old_target);
+    let mut guards = vec![];
+    if field {
+        guards.push(format!("!has({target})"));
+    }
+    if uses_old {
+        guards.push("oldObject == null".to_string());
+        if field {
+            guards.push(format!("!has({old_target})"));
+        }
+    }
+    if guards.is_empty() {
+        expression
+    } else {
+        format!("{} || ({expression})", guards.join(" || "))
+    }
+}
+
+/// Replace the `self` and `oldSelf` of a CEL rule of the CRD schema with `target` and `old_target`,
+/// leaving string literals and members alone, and return whether it referred to `oldSelf`
+fn rebind_self(rule: &str, target: &str, old_target: &str) -> (String, bool) {
+    let mut out = String::with_capacity(rule.len());
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+    let mut word = String::new();
+    let mut after_dot = false;
+    let mut uses_old = false;
+    let mut flush = |word: &mut String, out: &mut String, after_dot: bool| {
+        match word.as_str() {
+            "self" if !after_dot => out.push_str(target),
+            "oldSelf" if !after_dot => {
+                uses_old = true;
+                out.push_str(old_target);
+            }
+            _ => out.push_str(word),
+        }
+        word.clear();
+    };
+    for c in rule.chars() {
+        if let Some(q) = quote {
+            out.push(c);
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == q {
+                quote = None;
+            }
+            continue;
+        }
+        if c.is_alphanumeric() || c == '_' {
+            word.push(c);
+            continue;
+        }
+        if !word.is_empty() {
+            flush(&mut word, &mut out, after_dot);
+        }
+        if !c.is_whitespace() {
+            after_dot = c == '.';
+        }
+        if c == '"' || c == '\'' {
+            quote = Some(c);
+        }
+        out.push(c);
+    }
+    flush(&mut word, &mut out, after_dot);
+    (out, uses_old)
+}
+
+/// The serialized name of a snake_case field under a serde `rename_all` rule
+fn rename_field(field: &str, rename_all: Option<&str>) -> String {
+    let field = field.trim_start_matches("r#");
+    let pascal = || {
+        field
+            .split('_')
+            .map(|word| {
+                let mut chars = word.chars();
+                chars
+                    .next()
+                    .map(|first| first.to_uppercase().chain(chars).collect::<String>())
+                    .unwrap_or_default()
+            })
+            .collect::<String>()
+    };
+    match rename_all {
+        Some("lowercase") => field.to_lowercase(),
+        Some("UPPERCASE") => field.to_uppercase(),
+        Some("PascalCase") => pascal(),
+        Some("camelCase") => {
+            let pascal = pascal();
+            let mut chars = pascal.chars();
+            chars
+                .next()
+                .map(|first| first.to_lowercase().chain(chars).collect())
+                .unwrap_or_default()
+        }
+        Some("SCREAMING_SNAKE_CASE") => field.to_uppercase(),
+        Some("kebab-case") => field.replace('_', "-"),
+        Some("SCREAMING-KEBAB-CASE") => field.to_uppercase().replace('_', "-"),
+        _ => field.to_string(),
+    }
+}
+
 // Simple pluralizer.
 // Duplicating the code from kube (without special casing) because it's simple enough.
 // Irregular plurals must be explicitly specified.
@@ -599,6 +1616,70 @@ mod tests {
     use super::*;
     // TODO Unit test `derive`
 
+    #[test]
+    fn test_rename_field() {
+        assert_eq!(rename_field("min_replicas", Some("camelCase")), "minReplicas");
+        assert_eq!(rename_field("min_replicas", Some("PascalCase")), "MinReplicas");
+        assert_eq!(rename_field("min_replicas", Some("kebab-case")), "min-replicas");
+        assert_eq!(rename_field("r#type", Some("camelCase")), "type");
+        assert_eq!(rename_field("min_replicas", None), "min_replicas");
+    }
+
+    #[test]
+    fn test_to_plural() {
+        assert_eq!(to_plural("gateway"), "gateways");
+        assert_eq!(to_plural("policy"), "policies");
+        assert_eq!(to_plural("ingress"), "ingresses");
+        assert_eq!(to_plural("patch"), "patches");
+        assert_eq!(to_plural("foo"), "foos");
+    }
+
+    #[test]
+    fn test_rebind_self() {
+        let rebind = |rule| rebind_self(rule, "o", "old");
+        assert_eq!(
+            rebind_self("self > 0", "object.spec", "oldObject.spec"),
+            ("object.spec > 0".to_string(), false)
+        );
+        assert_eq!(
+            rebind("self.min <= self.max"),
+            ("o.min <= o.max".to_string(), false)
+        );
+        assert_eq!(
+            rebind("self.startsWith('self') && x.self"),
+            ("o.startsWith('self') && x.self".to_string(), false)
+        );
+        assert_eq!(
+            rebind("myself == selfish"),
+            ("myself == selfish".to_string(), false)
+        );
+        assert_eq!(rebind("self == oldSelf"), ("o == old".to_string(), true));
+        assert_eq!(
+            rebind("x.oldSelf == 'oldSelf'"),
+            ("x.oldSelf == 'oldSelf'".to_string(), false)
+        );
+    }
+
+    #[test]
+    fn test_policy_expression() {
+        assert_eq!(
+            policy_expression("self.a <= self.b", "object.spec", false),
+            "object.spec.a <= object.spec.b"
+        );
+        assert_eq!(
+            policy_expression("self > 0", "object.spec.a", true),
+            "!has(object.spec.a) || (object.spec.a > 0)"
+        );
+        assert_eq!(
+            policy_expression("self.a >= oldSelf.a", "object.spec", false),
+            "oldObject == null || (object.spec.a >= oldObject.spec.a)"
+        );
+        assert_eq!(
+            policy_expression("self == oldSelf", "object.spec.a", true),
+            "!has(object.spec.a) || oldObject == null || !has(oldObject.spec.a) || (object.spec.a == oldObject.spec.a)"
+        );
+    }
+
     #[test]
     fn test_parse_default() {
         let input = quote! {
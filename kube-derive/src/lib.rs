@@ -81,7 +81,11 @@ mod custom_resource;
 /// To specify the singular name. Defaults to lowercased `kind`.
 ///
 /// ## `#[kube(plural = "nonstandard-plural")]`
-/// To specify the plural name. Defaults to inferring from singular.
+/// To specify the plural name. Defaults to inferring from singular with the regular English rules, like `gateways`
+/// or `policies`, so irregular plurals must be specified.
+///
+/// ## `#[kube(list_kind = "NonstandardList")]`
+/// To specify the `listKind` of the crd names. Defaults to `kind` with a `List` suffix.
 ///
 /// ## `#[kube(namespaced)]`
 /// To specify that this is a namespaced resource rather than cluster level.
@@ -89,10 +93,20 @@ mod custom_resource;
 /// ## `#[kube(struct = "StructName")]`
 /// Customize the name of the generated root struct (defaults to `kind`).
 ///
+/// ## `#[kube(doc = "description")]`
+/// Set the doc comment of the generated root struct, which is also the `description` at the top of its schema,
+/// shown by `kubectl explain foo`. Defaults to a note that the type was generated.
+///
+/// Doc comments on the spec and status structs, and on their fields, become the `description`s of their schemas.
+///
 /// ## `#[kube(crates(kube_core = "::kube::core"))]`
 /// Customize the crate name the generated code will reach into (defaults to `::kube::core`).
 /// Should be one of `kube::core`, `kube_client::core` or `kube_core`.
 ///
+/// ## `#[kube(crates(kube_client = "::kube"))]`
+/// Customize the crate name the generated code will use for `Api` and `Client` with `#[kube(api)]` (defaults to `::kube`).
+/// Should be one of `kube` or `kube_client`.
+///
 /// ## `#[kube(crates(k8s_openapi = "::k8s_openapi"))]`
 /// Customize the crate name the generated code will use for [`k8s_openapi`](https://docs.rs/k8s-openapi/) (defaults to `::k8s_openapi`).
 ///
@@ -107,7 +121,11 @@ mod custom_resource;
 ///
 /// ## `#[kube(status = "StatusStructName")]`
 /// Adds a status struct to the top level generated type and enables the status
-/// subresource in your crd.
+/// subresource in your crd. The struct can also be given by path, like `status = "status::FooStatus"`.
+///
+/// The generated type implements [`HasSpec`](https://docs.rs/kube/latest/kube/core/object/trait.HasSpec.html),
+/// and [`HasStatus`](https://docs.rs/kube/latest/kube/core/object/trait.HasStatus.html) when it has a status,
+/// so that helpers can be written generically over custom resources.
 ///
 /// ## `#[kube(derive = "Trait")]`
 /// Adding `#[kube(derive = "PartialEq")]` is required if you want your generated
@@ -119,7 +137,7 @@ mod custom_resource;
 /// Legal values:
 /// - `"derived"`: A `JsonSchema` implementation is automatically derived
 /// - `"manual"`: `JsonSchema` is not derived, but used when creating the `CustomResourceDefinition` object
-/// - `"disabled"`: No `JsonSchema` is used
+/// - `"disabled"`: No `JsonSchema` is used, and the `schema` of the version is left unset
 ///
 /// This can be used to provide a completely custom schema, or to interact with third-party custom resources
 /// where you are not responsible for installing the `CustomResourceDefinition`.
@@ -127,13 +145,19 @@ mod custom_resource;
 /// Defaults to `"derived"`.
 ///
 /// NOTE: `CustomResourceDefinition`s require a schema. If `schema = "disabled"` then
-/// `Self::crd()` will not be installable into the cluster as-is.
+/// `Self::crd()` will not be installable into the cluster as-is, until a schema is set in `crd.spec.versions[0].schema`.
 ///
-/// ## `#[kube(scale = r#"json"#)]`
+/// ## `#[kube(scale(spec_replicas_path = ".spec.replicas", status_replicas_path = ".status.replicas"))]`
 /// Allow customizing the scale struct for the [scale subresource](https://kubernetes.io/docs/tasks/extend-kubernetes/custom-resources/custom-resource-definitions/#subresources).
+/// An optional `label_selector_path` can also be set. The older `#[kube(scale = r#"json"#)]` form takes the json of the scale struct.
+/// This also implements [`HasScale`](https://docs.rs/kube/latest/kube/core/crd/trait.HasScale.html) with these paths,
+/// and the replicas can be changed with `Api::scale_replicas`.
 ///
-/// ## `#[kube(printcolumn = r#"json"#)]`
-/// Allows adding straight json to [printcolumns](https://kubernetes.io/docs/tasks/extend-kubernetes/custom-resources/custom-resource-definitions/#additional-printer-columns).
+/// ## `#[kube(printcolumn(name = "Spec", jsonpath = ".spec.name", type = "string"))]`
+/// Add a [printcolumn](https://kubernetes.io/docs/tasks/extend-kubernetes/custom-resources/custom-resource-definitions/#additional-printer-columns).
+/// The `type` must be one of `integer`, `number`, `string`, `boolean` or `date`, and `description`, `format` and `priority`
+/// can also be set. The older `#[kube(printcolumn = r#"json"#)]` form takes the json of the column.
+/// Both forms are checked at compile time.
 ///
 /// ## `#[kube(shortname = "sn")]`
 /// Add a single shortname to the generated crd.
@@ -141,6 +165,84 @@ mod custom_resource;
 /// ## `#[kube(category = "apps")]`
 /// Add a single category to `crd.spec.names.categories`.
 ///
+/// ## `#[kube(preserve_unknown_fields)]`, `#[kube(embedded_resource)]` and `#[kube(int_or_string)]`
+/// Mark a field of the spec in the schema with `x-kubernetes-preserve-unknown-fields`, so the apiserver keeps fields
+/// it does not know, `x-kubernetes-embedded-resource` (as an `object`), for fields holding a whole Kubernetes object, or
+/// `x-kubernetes-int-or-string`, replacing its type with either an integer or a string.
+/// An embedded resource with an open schema, like a `serde_json::Value`, usually needs both of the first two.
+///
+/// ## `#[kube(selectable = ".spec.nodeName")]`
+/// Add a [selectable field](https://kubernetes.io/docs/tasks/extend-kubernetes/custom-resources/custom-resource-definitions/#crd-selectable-fields)
+/// to the crd version, so that it can be used in field selectors. This needs Kubernetes 1.30, which is newer than the
+/// supported k8s-openapi, whose `CustomResourceDefinition` would drop the field, so it is rejected for now.
+///
+/// ## `#[kube(apply)]`
+/// Generate a `FooApply` type for [server-side apply](https://kubernetes.io/docs/reference/using-api/server-side-apply/)
+/// patches, with a `FooSpecApply` spec where every field is an `Option` that is left out when `None`, and a
+/// `Foo::apply_patch(name)` constructor. Only the fields that are set are sent, so the field manager only owns those.
+/// The fields keep their types, so nested structs are sent whole, unless the field has `#[kube(apply)]` and its
+/// struct derives [`Apply`](macro@Apply), in which case the field is the `Option` of its apply type instead.
+///
+/// ## `#[kube(conditions)]`
+/// Generate `set_condition` and `get_condition` methods on the status struct, which must have a
+/// `conditions: Vec<Condition>` field of [`Condition`](https://docs.rs/k8s-openapi/latest/k8s_openapi/apimachinery/pkg/apis/meta/v1/struct.Condition.html)s.
+/// Setting a condition replaces the one of the same type, and sets its `lastTransitionTime` to now only when its status changes.
+///
+/// This also implements [`HasConditions`](https://docs.rs/kube/latest/kube/core/conditions/trait.HasConditions.html) for the kind, which needs a status that implements `Default`,
+/// and sets the `observedGeneration` of conditions to the generation of the object.
+///
+/// ## `#[kube(example)]`
+/// Generate a `Foo::example()` with an example object of the kind as json, to ship a sample manifest that stays in
+/// sync with the crd. There is no generated `sample_yaml()`, as that would make `kube-core` depend on a yaml library
+/// for every user of the derive; `serde_yaml::to_string(&Foo::example())` gives the yaml. The spec is built from the schema:
+/// the `default`s of fields (including those from `#[serde(default)]`), the `#[kube(example = r#"json"#)]` of fields,
+/// and empty values for other required fields. `#[kube(example = r#"{"replicas": 3}"#)]` on the spec overrides fields of the spec.
+/// The examples are also the `example`s of the schema.
+///
+/// ## `#[kube(api)]`
+/// Generate `Api` constructors that follow the declared scope: `Foo::api(client)` for the default namespace of
+/// the client on namespaced resources (or the cluster on cluster scoped ones), `Foo::namespaced_api(client, ns)`
+/// on namespaced resources only, and `Foo::all(client)`. This needs the `client` feature of `kube`.
+///
+/// ## `#[kube(validating_admission_policy)]`
+/// Generate a `Foo::validating_admission_policy()` that returns a `v1alpha1`
+/// [`ValidatingAdmissionPolicy`](https://kubernetes.io/docs/reference/access-authn-authz/validating-admission-policy/)
+/// for creates and updates of the kind, with the `#[kube(rule)]`s of the type as its validations, and a binding for it.
+/// The `self` and `oldSelf` of the rules are rewritten to the spec or field of the `object` and `oldObject`, and rules
+/// with `oldSelf` only run on updates. Only `v1alpha1` is generated, as it is the only version in the supported
+/// k8s-openapi; the apiserver needs to serve `admissionregistration.k8s.io/v1alpha1` for it.
+/// These are a starting point; review them before applying.
+///
+/// ## `#[kube(served = false, storage = false)]`
+/// Set whether the version of the generated crd is served by the apiserver, and whether it is the storage version.
+/// Both default to `true`. When versions are combined with `merge_crds`, its `stored_apiversion` decides the storage version.
+///
+/// ## `#[kube(deprecated = "v1alpha1 is deprecated, use v1")]`
+/// Mark the version of the generated crd as deprecated, with an optional `deprecationWarning` for clients using it.
+/// A plain `#[kube(deprecated)]` uses the default warning of the apiserver.
+///
+/// ## `#[kube(rule = "self.minReplicas <= self.maxReplicas")]`
+/// Add a [CEL validation rule](https://kubernetes.io/docs/tasks/extend-kubernetes/custom-resources/custom-resource-definitions/#validation-rules)
+/// to the `x-kubernetes-validations` of the spec in the schema. It can be repeated, and also put on fields of the spec to validate
+/// only that field, where `self` is the value of the field. The longer form
+/// `#[kube(rule(rule = "self > 0", message = "must be positive"))]` also sets the message of the rule.
+/// The `reason` and `fieldPath` of rules need Kubernetes 1.28, which is newer than the supported k8s-openapi, and are rejected.
+///
+/// ## `#[kube(default = r#"json"#)]`
+/// Set the `default` of a field of the spec in the schema to the given json value, for defaults the apiserver
+/// should apply that are not expressed by `#[serde(default)]`. Fields with `#[serde(default)]` or
+/// `#[serde(default = "path")]` get their default in the schema from `schemars`, which this overrides.
+///
+/// ## `#[kube(conversion(service(name = "webhook", namespace = "default", path = "/convert"), review_version = "v1"))]`
+/// Set `spec.conversion` of the generated crd to the `Webhook` strategy, calling either the given `service(..)`
+/// (with optional `path` and `port`) or a `url = "https://.."`. The optional `ca_bundle` is the base64 encoded
+/// PEM bundle of the webhook, and `review_version` can be repeated, defaulting to `v1`.
+/// This also implements [`HasConversion`](https://docs.rs/kube/latest/kube/core/crd/trait.HasConversion.html),
+/// and should be set identically on every version of the kind so that `merge_crds` accepts them.
+/// Add `hub` on the version that the others are converted to and from, and `hub = "path::to::Hub"` on the others,
+/// to implement the [`Hub`](https://docs.rs/kube/latest/kube/core/crd/trait.Hub.html) and
+/// [`Convertible`](https://docs.rs/kube/latest/kube/core/crd/trait.Convertible.html) marker traits that link the versions.
+///
 /// ## Example with all properties
 ///
 /// ```rust
@@ -160,13 +262,14 @@ mod custom_resource;
 ///     singular = "foot",
 ///     plural = "feetz",
 ///     shortname = "f",
-///     scale = r#"{"specReplicasPath":".spec.replicas", "statusReplicasPath":".status.replicas"}"#,
-///     printcolumn = r#"{"name":"Spec", "type":"string", "description":"name of foo", "jsonPath":".spec.name"}"#
+///     scale(spec_replicas_path = ".spec.replicas", status_replicas_path = ".status.replicas"),
+///     printcolumn(name = "Spec", type = "string", description = "name of foo", jsonpath = ".spec.name")
 /// )]
 /// #[serde(rename_all = "camelCase")]
 /// struct FooSpec {
 ///     #[schemars(length(min = 3))]
 ///     data: String,
+///     #[kube(rule = "self >= 0")]
 ///     replicas_count: i32
 /// }
 ///
@@ -213,7 +316,7 @@ mod custom_resource;
 /// - [`#[schemars(schema_with = "func")]`](https://graham.cool/schemars/examples/7-custom_serialization/) (e.g. like in the [`crd_derive` example](https://github.com/kube-rs/kube/blob/main/examples/crd_derive.rs))
 /// - `impl JsonSchema` on a type / newtype around external type. See [#129](https://github.com/kube-rs/kube/issues/129#issuecomment-750852916)
 /// - [`#[garde(...)]` field attributes for client-side validation](https://github.com/jprochazk/garde) (see [`crd_api`
-/// example](https://github.com/kube-rs/kube/blob/main/examples/crd_api.rs))
+///   example](https://github.com/kube-rs/kube/blob/main/examples/crd_api.rs))
 ///
 /// You might need to override parts of the schemas (for fields in question) when you are:
 /// - **using complex enums**: enums do not currently generate [structural schemas](https://kubernetes.io/docs/tasks/extend-kubernetes/custom-resources/custom-resource-definitions/#specifying-a-structural-schema), so kubernetes won't support them by default
@@ -288,13 +391,16 @@ mod custom_resource;
 /// ```
 ///
 /// ## Runtime dependencies
-/// Due to [rust-lang/rust#54363](https://github.com/rust-lang/rust/issues/54363), we cannot be resilient against crate renames within our generated code.
-/// It's therefore **required** that you have the following crates in scope, not renamed:
+/// Due to [rust-lang/rust#54363](https://github.com/rust-lang/rust/issues/54363), we cannot detect crate renames within our generated code.
+/// By default, the generated code expects the following crates in scope, not renamed:
 ///
 /// - `serde_json`
 /// - `k8s_openapi`
 /// - `schemars` (by default, unless `schema` feature disabled)
 ///
+/// When they are renamed, or re-exported through a facade crate, point the generated code at them with
+/// `#[kube(crates(..))]`. The derives of your own types need the same, through `#[serde(crate = "..")]` and `#[schemars(crate = "..")]`.
+///
 /// You are ultimately responsible for maintaining the versions and feature flags of these libraries.
 ///
 /// [`kube`]: https://docs.rs/kube
@@ -306,3 +412,38 @@ mod custom_resource;
 pub fn derive_custom_resource(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     custom_resource::derive(proc_macro2::TokenStream::from(input)).into()
 }
+
+/// A custom derive for the server-side apply types of structs nested in the spec of a custom resource
+///
+/// This generates a `BarApply` struct for a `Bar`, where every serialized field is an `Option` that is left out
+/// when `None`, like the spec of a `#[kube(apply)]` custom resource. Fields with `#[kube(apply)]` use the apply type
+/// of their own struct in turn, which must also derive `Apply`.
+///
+/// ```rust
+/// use kube_derive::{Apply, CustomResource};
+/// use schemars::JsonSchema;
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(CustomResource, Serialize, Deserialize, Debug, Clone, JsonSchema)]
+/// #[kube(group = "clux.dev", version = "v1", kind = "Foo", apply)]
+/// struct FooSpec {
+///     #[kube(apply)]
+///     container: Container,
+/// }
+///
+/// #[derive(Apply, Serialize, Deserialize, Debug, Clone, JsonSchema)]
+/// struct Container {
+///     image: String,
+///     replicas: i32,
+/// }
+///
+/// let mut patch = Foo::apply_patch("foo");
+/// patch.spec.container = Some(ContainerApply {
+///     image: Some("nginx".into()),
+///     ..Default::default()
+/// });
+/// ```
+#[proc_macro_derive(Apply, attributes(kube))]
+pub fn derive_apply(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    custom_resource::derive_apply(proc_macro2::TokenStream::from(input)).into()
+}
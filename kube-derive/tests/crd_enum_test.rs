@@ -15,6 +15,28 @@ enum FooEnumSpec {
     VariantThree {},
 }
 
+#[derive(CustomResource, Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+#[kube(group = "clux.dev", version = "v1", kind = "Storage")]
+#[serde(tag = "type", rename_all = "camelCase")]
+enum StorageSpec {
+    Ephemeral,
+    Disk { size: i32 },
+    Bucket { name: String, region: Option<String> },
+}
+
+#[derive(CustomResource, Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+#[kube(group = "clux.dev", version = "v1", kind = "Action")]
+struct ActionSpec {
+    action: AdjacentAction,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+#[serde(tag = "kind", content = "args", rename_all = "camelCase")]
+enum AdjacentAction {
+    Sleep(i32),
+    Echo { text: String },
+}
+
 #[test]
 fn test_crd_name() {
     use kube::core::CustomResourceExt;
@@ -70,6 +92,7 @@ fn test_crd_schema_matches_expected() {
             "names": {
               "categories": [],
               "kind": "FooEnum",
+              "listKind": "FooEnumList",
               "plural": "fooenums",
               "shortNames": [],
               "singular": "fooenum"
@@ -153,3 +176,75 @@ fn test_crd_schema_matches_expected() {
         .unwrap()
     );
 }
+
+#[test]
+fn internally_tagged_enum_schema() {
+    use kube::core::CustomResourceExt;
+    let crd = serde_json::to_value(Storage::crd()).unwrap();
+    assert_json_diff::assert_json_eq!(
+        crd["spec"]["versions"][0]["schema"]["openAPIV3Schema"]["properties"]["spec"],
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "type": {"type": "string", "enum": ["ephemeral", "disk", "bucket"]},
+                "size": {"type": "integer", "format": "int32"},
+                "name": {"type": "string"},
+                "region": {"type": "string", "nullable": true}
+            },
+            "required": ["type"],
+            "x-kubernetes-validations": [
+                {
+                    "rule": "self.type != 'ephemeral' || (!has(self.size) && !has(self.name) && !has(self.region))",
+                    "message": "type ephemeral does not allow size, name, region"
+                },
+                {"rule": "self.type != 'disk' || (has(self.size))", "message": "type disk requires size"},
+                {
+                    "rule": "self.type != 'disk' || (!has(self.name) && !has(self.region))",
+                    "message": "type disk does not allow name, region"
+                },
+                {"rule": "self.type != 'bucket' || (has(self.name))", "message": "type bucket requires name"},
+                {
+                    "rule": "self.type != 'bucket' || (!has(self.size))",
+                    "message": "type bucket does not allow size"
+                }
+            ]
+        })
+    );
+
+    let spec = StorageSpec::Bucket {
+        name: "backups".into(),
+        region: None,
+    };
+    let json = serde_json::to_value(&spec).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({"type": "bucket", "name": "backups", "region": null})
+    );
+    assert_eq!(serde_json::from_value::<StorageSpec>(json).unwrap(), spec);
+}
+
+#[test]
+fn adjacently_tagged_enum_schema() {
+    use kube::core::CustomResourceExt;
+    let crd = serde_json::to_value(Action::crd()).unwrap();
+    assert_json_diff::assert_json_eq!(
+        crd["spec"]["versions"][0]["schema"]["openAPIV3Schema"]["properties"]["spec"]["properties"]["action"],
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "kind": {"type": "string", "enum": ["sleep", "echo"]},
+                "args": {"x-kubernetes-preserve-unknown-fields": true}
+            },
+            "required": ["kind"],
+            "x-kubernetes-validations": [
+                {"rule": "self.kind != 'sleep' || (has(self.args))", "message": "kind sleep requires args"},
+                {"rule": "self.kind != 'echo' || (has(self.args))", "message": "kind echo requires args"}
+            ]
+        })
+    );
+
+    let action = AdjacentAction::Echo { text: "hi".into() };
+    let json = serde_json::to_value(&action).unwrap();
+    assert_eq!(json, serde_json::json!({"kind": "echo", "args": {"text": "hi"}}));
+    assert_eq!(serde_json::from_value::<AdjacentAction>(json).unwrap(), action);
+}
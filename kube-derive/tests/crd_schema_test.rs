@@ -1,8 +1,8 @@
 #![recursion_limit = "256"]
 
 use assert_json_diff::assert_json_eq;
-use chrono::{DateTime, NaiveDateTime, Utc};
-use kube_derive::CustomResource;
+use chrono::{DateTime, TimeZone, Utc};
+use kube_derive::{Apply, CustomResource};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -15,6 +15,7 @@ use std::collections::HashMap;
     kind = "Foo",
     category = "clux",
     namespaced,
+    api,
     derive = "PartialEq",
     shortname = "fo",
     shortname = "f"
@@ -63,6 +64,272 @@ pub struct FlatteningSpec {
     arbitrary: HashMap<String, serde_json::Value>,
 }
 
+#[derive(CustomResource, Deserialize, Serialize, Clone, Debug, JsonSchema)]
+#[kube(
+    group = "clux.dev",
+    version = "v1",
+    kind = "Autoscaler",
+    validating_admission_policy,
+    rule = "self.minReplicas <= self.maxReplicas",
+    rule(
+        rule = "!has(self.paused) || !self.paused || has(self.pauseReason)",
+        message = "paused autoscalers need a reason"
+    )
+)]
+#[serde(rename_all = "camelCase")]
+pub struct AutoscalerSpec {
+    min_replicas: i32,
+    #[kube(rule = "self <= 100")]
+    max_replicas: i32,
+    paused: Option<bool>,
+    #[serde(rename = "pauseReason")]
+    #[kube(rule(rule = "size(self) > 0", message = "must not be empty"))]
+    reason: Option<String>,
+}
+
+#[derive(CustomResource, Deserialize, Serialize, Clone, Debug, JsonSchema)]
+#[kube(
+    group = "clux.dev",
+    version = "v1",
+    kind = "Defaulted",
+    example = r#"{"replicas": 2}"#
+)]
+#[serde(rename_all = "camelCase")]
+pub struct DefaultedSpec {
+    #[serde(default)]
+    replicas: i32,
+    #[serde(default = "default_image")]
+    image: String,
+    #[serde(default = "default_image")]
+    #[kube(default = r#""nginx:latest""#)]
+    sidecar_image: String,
+    #[kube(default = r#"{"enabled": true}"#)]
+    probe: Option<serde_json::Value>,
+    #[kube(example = r#""web""#)]
+    name: String,
+    tier: Option<String>,
+}
+
+fn default_image() -> String {
+    "nginx".into()
+}
+
+#[derive(CustomResource, Deserialize, Serialize, Clone, Debug, JsonSchema)]
+#[kube(
+    group = "clux.dev",
+    version = "v1",
+    kind = "Scaled",
+    status = "ScaledStatus",
+    scale(
+        spec_replicas_path = ".spec.replicas",
+        status_replicas_path = ".status.replicas"
+    ),
+    printcolumn(
+        name = "Replicas",
+        jsonpath = ".spec.replicas",
+        type = "integer",
+        priority = 1
+    ),
+    printcolumn = r#"{"name":"Age", "jsonPath":".metadata.creationTimestamp", "type":"date"}"#
+)]
+pub struct ScaledSpec {
+    replicas: i32,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, JsonSchema)]
+pub struct ScaledStatus {
+    replicas: i32,
+}
+
+#[derive(CustomResource, Deserialize, Serialize, Clone, Debug, JsonSchema)]
+#[kube(group = "clux.dev", version = "v1", kind = "Applied", apply, api)]
+#[serde(rename_all = "camelCase")]
+pub struct AppliedSpec {
+    min_replicas: i32,
+    #[serde(rename = "image")]
+    container_image: String,
+    #[serde(skip)]
+    pub cache: Option<String>,
+    #[kube(apply)]
+    sidecar: Option<AppliedContainer>,
+}
+
+#[derive(Apply, Deserialize, Serialize, Clone, Debug, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AppliedContainer {
+    image_pull_policy: String,
+    #[kube(apply)]
+    resources: AppliedResources,
+}
+
+#[derive(Apply, Deserialize, Serialize, Clone, Debug, JsonSchema)]
+pub struct AppliedResources {
+    cpu: String,
+    memory: String,
+}
+
+mod facade {
+    pub mod kube {
+        pub use ::kube::core;
+    }
+    pub use ::k8s_openapi;
+    pub use ::schemars;
+    pub use ::serde;
+    pub use ::serde_json;
+    pub use ::std;
+}
+
+#[derive(
+    CustomResource,
+    facade::serde::Serialize,
+    facade::serde::Deserialize,
+    Clone,
+    Debug,
+    facade::schemars::JsonSchema,
+)]
+#[serde(crate = "facade::serde")]
+#[schemars(crate = "facade::schemars")]
+#[kube(
+    group = "clux.dev",
+    version = "v1",
+    kind = "Facaded",
+    crates(
+        kube_core = "facade::kube::core",
+        k8s_openapi = "facade::k8s_openapi",
+        schemars = "facade::schemars",
+        serde = "facade::serde",
+        serde_json = "facade::serde_json",
+        std = "facade::std"
+    )
+)]
+pub struct FacadedSpec {
+    replicas: i32,
+}
+
+#[derive(CustomResource, Deserialize, Serialize, Clone, Debug, JsonSchema)]
+#[kube(
+    group = "clux.dev",
+    version = "v1",
+    kind = "Observed",
+    status = "observed::ObservedStatus",
+    conditions,
+    doc = "Observed is a thing that is observed"
+)]
+/// Desired state of an observed thing
+pub struct ObservedSpec {
+    /// The generation that should be observed
+    generation: i64,
+}
+
+mod observed {
+    use schemars::JsonSchema;
+    use serde::{Deserialize, Serialize};
+
+    /// Observed state of an observed thing
+    #[derive(Deserialize, Serialize, Clone, Debug, Default, JsonSchema)]
+    pub struct ObservedStatus {
+        pub observed_generation: Option<i64>,
+        #[serde(default)]
+        pub conditions: Vec<k8s_openapi::apimachinery::pkg::apis::meta::v1::Condition>,
+    }
+}
+
+#[derive(CustomResource, Deserialize, Serialize, Clone, Debug, JsonSchema)]
+#[kube(
+    group = "clux.dev",
+    version = "v1",
+    kind = "Embedding",
+    list_kind = "EmbeddingCollection"
+)]
+pub struct EmbeddingSpec {
+    #[kube(preserve_unknown_fields, embedded_resource)]
+    template: serde_json::Value,
+    #[kube(preserve_unknown_fields)]
+    values: Option<serde_json::Value>,
+    #[kube(int_or_string)]
+    port: String,
+}
+
+mod converted {
+    pub mod v1 {
+        use kube_derive::CustomResource;
+        use schemars::JsonSchema;
+        use serde::{Deserialize, Serialize};
+
+        #[derive(CustomResource, Deserialize, Serialize, Clone, Debug, JsonSchema)]
+        #[kube(
+            group = "clux.dev",
+            version = "v1",
+            kind = "Converted",
+            conversion(
+                service(name = "webhook", namespace = "kube-system", path = "/convert"),
+                hub = "super::v2::Converted"
+            ),
+            storage = false,
+            deprecated = "clux.dev/v1 Converted is deprecated, use clux.dev/v2"
+        )]
+        pub struct ConvertedSpec {
+            replicas: i32,
+        }
+    }
+
+    pub mod v2 {
+        use kube_derive::CustomResource;
+        use schemars::JsonSchema;
+        use serde::{Deserialize, Serialize};
+
+        #[derive(CustomResource, Deserialize, Serialize, Clone, Debug, JsonSchema)]
+        #[kube(
+            group = "clux.dev",
+            version = "v2",
+            kind = "Converted",
+            conversion(service(name = "webhook", namespace = "kube-system", path = "/convert"), hub)
+        )]
+        pub struct ConvertedSpec {
+            replicas: i64,
+        }
+    }
+}
+
+#[derive(CustomResource, Deserialize, Serialize, Clone, Debug)]
+#[kube(
+    group = "clux.dev",
+    version = "v1",
+    kind = "Unschematized",
+    schema = "disabled"
+)]
+pub struct UnschematizedSpec {
+    foo: String,
+}
+
+#[derive(CustomResource, Deserialize, Serialize, Clone, Debug)]
+#[kube(group = "clux.dev", version = "v1", kind = "HandWritten", schema = "manual")]
+pub struct HandWrittenSpec {
+    foo: String,
+}
+
+// The schema of the whole object, which the derive only generates itself for `schema = "derived"`
+impl JsonSchema for HandWritten {
+    fn schema_name() -> String {
+        "HandWritten".into()
+    }
+
+    fn json_schema(_: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        serde_json::from_value(serde_json::json!({
+            "type": "object",
+            "properties": {
+                "spec": {
+                    "type": "object",
+                    "properties": {"foo": {"type": "string", "pattern": "^[a-z]+$"}},
+                    "required": ["foo"]
+                }
+            },
+            "required": ["spec"]
+        }))
+        .unwrap()
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 #[allow(clippy::enum_variant_names)]
@@ -132,7 +399,7 @@ fn test_serialized_matches_expected() {
             nullable: None,
             nullable_skipped_with_default: None,
             nullable_with_default: None,
-            timestamp: DateTime::from_utc(NaiveDateTime::from_timestamp_opt(0, 0).unwrap(), Utc),
+            timestamp: Utc.timestamp_opt(0, 0).unwrap(),
             complex_enum: ComplexEnum::VariantOne { int: 23 },
             untagged_enum_person: UntaggedEnumPerson::GenderAndAge(GenderAndAge {
                 age: 42,
@@ -182,6 +449,7 @@ fn test_crd_schema_matches_expected() {
                 "names": {
                     "categories": ["clux"],
                     "kind": "Foo",
+                    "listKind": "FooList",
                     "plural": "foos",
                     "shortNames": ["fo", "f"],
                     "singular": "foo"
@@ -338,3 +606,375 @@ fn flattening() {
     assert_eq!(spec.x_kubernetes_preserve_unknown_fields, Some(true));
     assert_eq!(spec.additional_properties, None);
 }
+
+#[test]
+fn schema_modes() {
+    use kube::core::CustomResourceExt;
+    assert_eq!(Unschematized::crd().spec.versions[0].schema, None);
+
+    let schema = HandWritten::crd().spec.versions[0]
+        .schema
+        .clone()
+        .unwrap()
+        .open_api_v3_schema
+        .unwrap();
+    let foo = &schema.properties.unwrap()["spec"].properties.clone().unwrap()["foo"];
+    assert_eq!(foo.pattern.as_deref(), Some("^[a-z]+$"));
+}
+
+#[test]
+fn validation_rules() {
+    use kube::core::CustomResourceExt;
+    let schema = serde_json::to_value(&Autoscaler::crd().spec.versions[0].schema).unwrap();
+    let spec = &schema["openAPIV3Schema"]["properties"]["spec"];
+    assert_json_eq!(
+        spec["x-kubernetes-validations"],
+        serde_json::json!([
+            {"rule": "self.minReplicas <= self.maxReplicas"},
+            {
+                "rule": "!has(self.paused) || !self.paused || has(self.pauseReason)",
+                "message": "paused autoscalers need a reason"
+            }
+        ])
+    );
+    assert_json_eq!(
+        spec["properties"]["maxReplicas"]["x-kubernetes-validations"],
+        serde_json::json!([{"rule": "self <= 100"}])
+    );
+    assert_json_eq!(
+        spec["properties"]["pauseReason"]["x-kubernetes-validations"],
+        serde_json::json!([{"rule": "size(self) > 0", "message": "must not be empty"}])
+    );
+    assert_eq!(
+        spec["properties"]["minReplicas"].get("x-kubernetes-validations"),
+        None
+    );
+}
+
+#[test]
+fn schema_defaults() {
+    use kube::core::CustomResourceExt;
+    let schema = serde_json::to_value(&Defaulted::crd().spec.versions[0].schema).unwrap();
+    let props = &schema["openAPIV3Schema"]["properties"]["spec"]["properties"];
+    assert_json_eq!(props["replicas"]["default"], serde_json::json!(0));
+    assert_json_eq!(props["image"]["default"], serde_json::json!("nginx"));
+    assert_json_eq!(
+        props["sidecarImage"]["default"],
+        serde_json::json!("nginx:latest")
+    );
+    assert_json_eq!(props["probe"]["default"], serde_json::json!({"enabled": true}));
+}
+
+#[test]
+fn conversion_webhook() {
+    use k8s_openapi::apiextensions_apiserver::pkg::apis::apiextensions::v1::CustomResourceConversion;
+    use kube::core::{
+        crd::{merge_crds, Convertible, HasConversion, Hub},
+        CustomResourceExt,
+    };
+    let crd = converted::v1::Converted::crd();
+    assert_json_eq!(
+        crd.spec.conversion,
+        serde_json::json!({
+            "strategy": "Webhook",
+            "webhook": {
+                "clientConfig": {
+                    "service": {"name": "webhook", "namespace": "kube-system", "path": "/convert"}
+                },
+                "conversionReviewVersions": ["v1"]
+            }
+        })
+    );
+    assert_eq!(crd.spec.conversion, Some(converted::v2::Converted::conversion()));
+    assert_eq!(Foo::crd().spec.conversion, None);
+
+    let merged = merge_crds(
+        vec![converted::v1::Converted::crd(), converted::v2::Converted::crd()],
+        "v2",
+    )
+    .unwrap();
+    assert_eq!(merged.spec.versions.len(), 2);
+    assert_eq!(merged.spec.conversion, crd.spec.conversion);
+
+    // Both versions are linked to the hub by the marker traits, and converted with its webhook
+    fn conversions<K: Convertible<H>, H: Hub>() -> (CustomResourceConversion, CustomResourceConversion) {
+        (K::conversion(), H::conversion())
+    }
+    let (v1, hub) = conversions::<converted::v1::Converted, converted::v2::Converted>();
+    assert_eq!(v1, hub);
+    let (v2, hub) = conversions::<converted::v2::Converted, converted::v2::Converted>();
+    assert_eq!(v2, hub);
+}
+
+#[test]
+fn printcolumns_and_scale() {
+    use kube::core::CustomResourceExt;
+    let version = &Scaled::crd().spec.versions[0];
+    assert_json_eq!(
+        version.additional_printer_columns,
+        serde_json::json!([
+            {"name": "Replicas", "jsonPath": ".spec.replicas", "type": "integer", "priority": 1},
+            {"name": "Age", "jsonPath": ".metadata.creationTimestamp", "type": "date"}
+        ])
+    );
+    assert_json_eq!(
+        version.subresources.as_ref().unwrap().scale,
+        serde_json::json!({"specReplicasPath": ".spec.replicas", "statusReplicasPath": ".status.replicas"})
+    );
+}
+
+#[test]
+fn scale_paths() {
+    use kube::core::crd::HasScale;
+    assert_eq!(Scaled::scale_spec_path(), ".spec.replicas");
+    assert_eq!(Scaled::scale_status_path(), ".status.replicas");
+    assert_eq!(Scaled::scale_label_selector_path(), None);
+}
+
+#[test]
+fn apply_patch_only_sends_set_fields() {
+    let mut patch = Applied::apply_patch("web");
+    assert_json_eq!(
+        serde_json::to_value(&patch).unwrap(),
+        serde_json::json!({
+            "apiVersion": "clux.dev/v1",
+            "kind": "Applied",
+            "metadata": {"name": "web"},
+            "spec": {}
+        })
+    );
+    patch.spec.min_replicas = Some(2);
+    assert_json_eq!(
+        serde_json::to_value(&patch).unwrap()["spec"],
+        serde_json::json!({"minReplicas": 2})
+    );
+    patch.spec.container_image = Some("nginx".into());
+    assert_json_eq!(
+        serde_json::to_value(&patch).unwrap()["spec"],
+        serde_json::json!({"minReplicas": 2, "image": "nginx"})
+    );
+    // nested structs are sent in part too
+    patch.spec.sidecar = Some(AppliedContainerApply {
+        resources: Some(AppliedResourcesApply {
+            cpu: Some("100m".into()),
+            ..Default::default()
+        }),
+        ..Default::default()
+    });
+    assert_json_eq!(
+        serde_json::to_value(&patch).unwrap()["spec"]["sidecar"],
+        serde_json::json!({"resources": {"cpu": "100m"}})
+    );
+}
+
+#[test]
+fn crate_paths_through_a_facade() {
+    use kube::{core::CustomResourceExt, Resource};
+    assert_eq!(Facaded::api_version(&()), "clux.dev/v1");
+    assert_eq!(Facaded::crd_name(), "facadeds.clux.dev");
+    let crd = serde_json::to_value(Facaded::crd()).unwrap();
+    assert_json_eq!(
+        crd["spec"]["versions"][0]["schema"]["openAPIV3Schema"]["properties"]["spec"]["properties"],
+        serde_json::json!({"replicas": {"type": "integer", "format": "int32"}})
+    );
+}
+
+#[test]
+fn version_flags() {
+    use kube::core::CustomResourceExt;
+    let v1 = &converted::v1::Converted::crd().spec.versions[0];
+    assert!(v1.served);
+    assert!(!v1.storage);
+    assert_eq!(v1.deprecated, Some(true));
+    assert_eq!(
+        v1.deprecation_warning.as_deref(),
+        Some("clux.dev/v1 Converted is deprecated, use clux.dev/v2")
+    );
+    let v2 = &converted::v2::Converted::crd().spec.versions[0];
+    assert!(v2.served);
+    assert!(v2.storage);
+    assert_eq!(v2.deprecated, None);
+    assert_eq!(v2.deprecation_warning, None);
+}
+
+#[test]
+fn validating_admission_policy() {
+    let (policy, binding) = Autoscaler::validating_admission_policy();
+    assert_eq!(policy.metadata.name.as_deref(), Some("autoscalers.clux.dev"));
+    assert_json_eq!(
+        policy.spec.unwrap(),
+        serde_json::json!({
+            "failurePolicy": "Fail",
+            "matchConstraints": {
+                "resourceRules": [{
+                    "apiGroups": ["clux.dev"],
+                    "apiVersions": ["v1"],
+                    "operations": ["CREATE", "UPDATE"],
+                    "resources": ["autoscalers"]
+                }]
+            },
+            "validations": [
+                {"expression": "object.spec.minReplicas <= object.spec.maxReplicas"},
+                {
+                    "expression": "!has(object.spec.paused) || !object.spec.paused || has(object.spec.pauseReason)",
+                    "message": "paused autoscalers need a reason"
+                },
+                {"expression": "!has(object.spec.maxReplicas) || (object.spec.maxReplicas <= 100)"},
+                {
+                    "expression": "!has(object.spec.pauseReason) || (size(object.spec.pauseReason) > 0)",
+                    "message": "must not be empty"
+                }
+            ]
+        })
+    );
+    assert_eq!(
+        binding.spec.unwrap().policy_name.as_deref(),
+        Some("autoscalers.clux.dev")
+    );
+}
+
+#[test]
+fn api_constructors_follow_the_scope() {
+    use kube::{Api, Client};
+    // Namespaced resources get Apis for the default namespace, a given namespace, and all namespaces
+    let _: fn(Client) -> Api<Foo> = Foo::api;
+    let _: fn(Client, &str) -> Api<Foo> = Foo::namespaced_api;
+    let _: fn(Client) -> Api<Foo> = Foo::all;
+    // Cluster scoped resources only get cluster Apis
+    let _: fn(Client) -> Api<Applied> = Applied::api;
+    let _: fn(Client) -> Api<Applied> = Applied::all;
+}
+
+#[test]
+fn spec_and_status_traits() {
+    use kube::core::{HasSpec, HasStatus};
+
+    // A helper that works for any custom resource with this status
+    fn mark_observed<K>(obj: &mut K, generation: i64)
+    where
+        K: HasStatus<Status = observed::ObservedStatus>,
+    {
+        obj.status_mut()
+            .get_or_insert_with(Default::default)
+            .observed_generation = Some(generation);
+    }
+
+    let mut obj = Observed::new("web", ObservedSpec { generation: 2 });
+    assert!(obj.status().is_none());
+    let generation = obj.spec().generation;
+    mark_observed(&mut obj, generation);
+    assert_eq!(obj.status().unwrap().observed_generation, Some(2));
+}
+
+#[test]
+fn schema_extensions() {
+    use kube::core::CustomResourceExt;
+    let schema = serde_json::to_value(&Embedding::crd().spec.versions[0].schema).unwrap();
+    let props = &schema["openAPIV3Schema"]["properties"]["spec"]["properties"];
+    assert_json_eq!(
+        props["template"],
+        serde_json::json!({
+            "type": "object",
+            "x-kubernetes-embedded-resource": true,
+            "x-kubernetes-preserve-unknown-fields": true
+        })
+    );
+    assert_eq!(props["values"]["x-kubernetes-preserve-unknown-fields"], true);
+    assert_json_eq!(
+        props["port"],
+        serde_json::json!({
+            "anyOf": [{"type": "integer"}, {"type": "string"}],
+            "x-kubernetes-int-or-string": true
+        })
+    );
+}
+
+#[test]
+fn doc_comments_are_descriptions() {
+    use kube::core::CustomResourceExt;
+    let schema = serde_json::to_value(&Observed::crd().spec.versions[0].schema).unwrap();
+    let root = &schema["openAPIV3Schema"];
+    assert_eq!(root["description"], "Observed is a thing that is observed");
+    assert_eq!(
+        root["properties"]["spec"]["description"],
+        "Desired state of an observed thing"
+    );
+    assert_eq!(
+        root["properties"]["spec"]["properties"]["generation"]["description"],
+        "The generation that should be observed"
+    );
+    assert_eq!(
+        root["properties"]["status"]["description"],
+        "Observed state of an observed thing"
+    );
+}
+
+#[test]
+fn crd_names() {
+    use kube::core::CustomResourceExt;
+    let names = Embedding::crd().spec.names;
+    assert_eq!(names.list_kind.as_deref(), Some("EmbeddingCollection"));
+    assert_eq!(names.plural, "embeddings");
+    assert_eq!(Foo::crd().spec.names.list_kind.as_deref(), Some("FooList"));
+}
+
+#[test]
+fn example_manifest() {
+    assert_json_eq!(
+        Defaulted::example(),
+        serde_json::json!({
+            "apiVersion": "clux.dev/v1",
+            "kind": "Defaulted",
+            "metadata": {"name": "defaulted-example"},
+            "spec": {
+                "replicas": 2,
+                "image": "nginx",
+                "sidecarImage": "nginx:latest",
+                "probe": {"enabled": true},
+                "name": "web"
+            }
+        })
+    );
+}
+
+#[test]
+fn status_conditions() {
+    use k8s_openapi::apimachinery::pkg::apis::meta::v1::{Condition, Time};
+    let ready = |status: &str| Condition {
+        type_: "Ready".into(),
+        status: status.into(),
+        reason: "Reconciled".into(),
+        message: String::new(),
+        observed_generation: Some(1),
+        last_transition_time: Time(Utc.timestamp_opt(0, 0).unwrap()),
+    };
+    let mut status = observed::ObservedStatus::default();
+    status.set_condition(ready("False"));
+    let since = status
+        .get_condition("Ready")
+        .unwrap()
+        .last_transition_time
+        .clone();
+    status.set_condition(ready("False"));
+    assert_eq!(status.conditions.len(), 1);
+    assert_eq!(status.get_condition("Ready").unwrap().last_transition_time, since);
+    status.set_condition(ready("True"));
+    assert_eq!(status.get_condition("Ready").unwrap().status, "True");
+    assert!(status.get_condition("Ready").unwrap().last_transition_time.0 >= since.0);
+
+    use kube::core::conditions::HasConditions;
+    let mut observed = Observed::new("o", ObservedSpec { generation: 1 });
+    assert!(observed.conditions().is_empty());
+    observed.metadata.generation = Some(1);
+    observed.set_condition(Condition {
+        observed_generation: None,
+        ..ready("True")
+    });
+    assert_eq!(
+        observed.get_condition("Ready").unwrap().observed_generation,
+        Some(1)
+    );
+    assert!(observed.is_condition_true("Ready"));
+    observed.metadata.generation = Some(2);
+    assert!(!observed.is_condition_true("Ready"));
+}
@@ -0,0 +1,27 @@
+use kube_derive::CustomResource;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+#[derive(CustomResource, Serialize, Deserialize, Debug, Clone, JsonSchema)]
+#[kube(
+    group = "clux.dev",
+    version = "v1",
+    kind = "Foo",
+    printcolumn(name = "Spec", jsonpath = ".spec.foo", type = "strnig")
+)]
+struct FooSpec {
+    foo: String,
+}
+
+#[derive(CustomResource, Serialize, Deserialize, Debug, Clone, JsonSchema)]
+#[kube(
+    group = "clux.dev",
+    version = "v1",
+    kind = "Bar",
+    printcolumn = r#"{"name":"Spec", "jsonpath":".spec.bar", "type":"string"}"#
+)]
+struct BarSpec {
+    bar: String,
+}
+
+fn main() {}
@@ -0,0 +1,20 @@
+use kube_derive::CustomResource;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+#[derive(CustomResource, Serialize, Deserialize, Debug, Clone, JsonSchema)]
+#[kube(
+    group = "clux.dev",
+    version = "v1",
+    kind = "Foo",
+    rule(
+        rule = "self.foo != ''",
+        message = "foo must be set",
+        reason = "FieldValueRequired"
+    )
+)]
+struct FooSpec {
+    foo: String,
+}
+
+fn main() {}
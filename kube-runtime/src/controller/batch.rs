@@ -0,0 +1,265 @@
+//! Collects triggered reconciliations into batches, see [`Controller::run_batched`](super::Controller::run_batched)
+use super::{runner, Action, Error, Priority, ReconcileReason, ReconcileRequest};
+use crate::{
+    reflector::{ObjectRef, Store},
+    scheduler::{scheduler, ScheduleRequest},
+    utils::{trystream_try_via, KubeRuntimeStreamExt},
+};
+use futures::{
+    channel, future, stream, Future, FutureExt, Stream, StreamExt, TryFuture, TryFutureExt, TryStream,
+    TryStreamExt,
+};
+use kube_client::Resource;
+use pin_project::pin_project;
+use std::{
+    fmt::Debug,
+    hash::Hash,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+    time::Duration,
+};
+use stream::Fuse;
+use tokio::time::{sleep, Instant, Sleep};
+use tracing::{info_span, Instrument};
+
+/// Groups the items of a stream into batches of up to `size` distinct items
+///
+/// A batch is emitted once it is full, or `window` after its first item was received, whichever comes first.
+#[pin_project]
+pub(super) struct Batched<St: Stream> {
+    #[pin]
+    stream: Fuse<St>,
+    size: usize,
+    window: Duration,
+    batch: Vec<St::Item>,
+    deadline: Option<Pin<Box<Sleep>>>,
+}
+
+impl<St: Stream> Batched<St>
+where
+    St::Item: PartialEq,
+{
+    pub(super) fn new(stream: St, size: usize, window: Duration) -> Self {
+        Self {
+            stream: stream.fuse(),
+            size: size.max(1),
+            window,
+            batch: Vec::new(),
+            deadline: None,
+        }
+    }
+}
+
+impl<St: Stream> Stream for Batched<St>
+where
+    St::Item: PartialEq,
+{
+    type Item = Vec<St::Item>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+        loop {
+            match this.stream.as_mut().poll_next(cx) {
+                Poll::Ready(Some(item)) => {
+                    if this.batch.is_empty() {
+                        *this.deadline = Some(Box::pin(sleep(*this.window)));
+                    }
+                    if !this.batch.contains(&item) {
+                        this.batch.push(item);
+                    }
+                    if this.batch.len() >= *this.size {
+                        break;
+                    }
+                }
+                Poll::Ready(None) if this.batch.is_empty() => return Poll::Ready(None),
+                Poll::Ready(None) => break,
+                Poll::Pending => {
+                    let expired = this
+                        .deadline
+                        .as_mut()
+                        .map_or(false, |deadline| deadline.as_mut().poll(cx).is_ready());
+                    if !expired {
+                        return Poll::Pending;
+                    }
+                    break;
+                }
+            }
+        }
+        *this.deadline = None;
+        Poll::Ready(Some(std::mem::take(this.batch)))
+    }
+}
+
+/// The batched equivalent of [`applier`](super::applier)
+///
+/// Batches are reconciled one at a time. Requeues requested by the [`Action`] (or the `error_policy`)
+/// apply to every object in the batch.
+#[allow(clippy::type_complexity)]
+pub(super) fn batch_applier<K, QueueStream, ReconcilerFut, Ctx>(
+    mut reconciler: impl FnMut(Vec<ObjectRef<K>>, Arc<Ctx>) -> ReconcilerFut,
+    error_policy: impl Fn(&[ObjectRef<K>], &ReconcilerFut::Error, Arc<Ctx>) -> Action,
+    context: Arc<Ctx>,
+    store: Store<K>,
+    queue: QueueStream,
+    batch_size: usize,
+    window: Duration,
+) -> impl Stream<Item = Result<(Vec<ObjectRef<K>>, Action), Error<ReconcilerFut::Error, QueueStream::Error>>>
+where
+    K: Clone + Resource + 'static,
+    K::DynamicType: Debug + Eq + Hash + Clone + Unpin,
+    ReconcilerFut: TryFuture<Ok = Action>,
+    ReconcilerFut::Error: std::error::Error + 'static,
+    QueueStream: TryStream,
+    QueueStream::Ok: Into<ReconcileRequest<K>>,
+    QueueStream::Error: std::error::Error + 'static,
+{
+    let (scheduler_shutdown_tx, scheduler_shutdown_rx) = channel::oneshot::channel();
+    let (scheduler_tx, scheduler_rx) = channel::mpsc::unbounded::<ScheduleRequest<ReconcileRequest<K>>>();
+    let context_reconciler = context.clone();
+    trystream_try_via(
+        Box::pin(stream::select(
+            queue
+                .map_err(Error::QueueError)
+                .map_ok(|request| ScheduleRequest {
+                    message: request.into(),
+                    run_at: Instant::now() + Duration::from_millis(1),
+                })
+                .on_complete(async move {
+                    let _ = scheduler_shutdown_tx.send(());
+                    tracing::debug!("batch applier queue terminated, starting graceful shutdown")
+                }),
+            scheduler_rx.map(Ok).take_until(scheduler_shutdown_rx),
+        )),
+        move |s| {
+            let batches = Batched::new(scheduler(s).map(|request| request.obj_ref), batch_size, window).then(
+                move |batch| {
+                    let reconciler_span = info_span!("reconciling batch", batch.size = batch.len());
+                    reconciler_span
+                        .in_scope(|| reconciler(batch.clone(), context_reconciler.clone()))
+                        .into_future()
+                        .map(|res| Ok((batch, res)))
+                        .instrument(reconciler_span)
+                },
+            );
+            // Hold off on reconciling until the store is ready, like the applier's runner
+            async move { store.wait_until_ready().await }
+                .map(|ready| {
+                    ready
+                        .map(|()| batches)
+                        .map_err(|err| Error::RunnerError(runner::Error::Readiness(err)))
+                })
+                .into_stream()
+                .try_flatten()
+                .and_then(move |(batch, res)| {
+                    let reconciler_finished_at = Instant::now();
+                    let (action, reason) = match &res {
+                        Ok(action) => (action.clone(), ReconcileReason::ReconcilerRequestedRetry),
+                        Err(err) => (
+                            error_policy(&batch, err, context.clone()),
+                            ReconcileReason::ErrorPolicyRequestedRetry,
+                        ),
+                    };
+                    if let Some(requeue_after) = action.requeue_after {
+                        for obj_ref in &batch {
+                            // Failure to schedule item = in graceful shutdown mode, ignore
+                            let _ = scheduler_tx.unbounded_send(ScheduleRequest {
+                                message: ReconcileRequest {
+                                    obj_ref: obj_ref.clone(),
+                                    reason: reason.clone(),
+                                    priority: Priority::Low,
+                                },
+                                run_at: reconciler_finished_at + requeue_after,
+                            });
+                        }
+                    }
+                    future::ready(match res {
+                        Ok(_) => Ok((batch, action)),
+                        Err(err) => Err(Error::BatchReconcilerFailed(
+                            err,
+                            batch.into_iter().map(ObjectRef::erase).collect(),
+                        )),
+                    })
+                })
+        },
+    )
+    .on_complete(async { tracing::debug!("batch applier terminated") })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{batch_applier, Batched};
+    use crate::{
+        controller::Action,
+        reflector::{self, ObjectRef},
+        watcher,
+    };
+    use futures::{channel::mpsc, pin_mut, poll, stream, SinkExt, StreamExt, TryStreamExt};
+    use k8s_openapi::api::core::v1::ConfigMap;
+    use kube_client::core::ObjectMeta;
+    use std::{convert::Infallible, sync::Arc, task::Poll, time::Duration};
+
+    #[tokio::test]
+    async fn batched_should_emit_full_batches() {
+        let batches = Batched::new(stream::iter([1, 2, 3, 1, 4, 5]), 2, Duration::from_secs(10));
+        let expected = vec![vec![1, 2], vec![3, 1], vec![4, 5]];
+        assert_eq!(batches.collect::<Vec<_>>().await, expected);
+    }
+
+    #[tokio::test]
+    async fn batched_should_deduplicate_within_batch() {
+        let batches = Batched::new(stream::iter([1, 1, 2, 1]), 10, Duration::from_secs(10));
+        assert_eq!(batches.collect::<Vec<_>>().await, vec![vec![1, 2]]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn batched_should_emit_after_window() {
+        let (mut tx, rx) = mpsc::unbounded();
+        let batches = Batched::new(rx, 10, Duration::from_secs(1));
+        pin_mut!(batches);
+        assert!(poll!(batches.next()).is_pending());
+        tx.send(1).await.unwrap();
+        assert!(poll!(batches.next()).is_pending());
+        tokio::time::advance(Duration::from_millis(500)).await;
+        tx.send(2).await.unwrap();
+        assert!(poll!(batches.next()).is_pending());
+        tokio::time::advance(Duration::from_millis(501)).await;
+        assert_eq!(poll!(batches.next()), Poll::Ready(Some(vec![1, 2])));
+        assert!(poll!(batches.next()).is_pending());
+        drop(tx);
+        assert_eq!(poll!(batches.next()), Poll::Ready(None));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn batch_applier_should_reconcile_batches() {
+        let (reader, mut writer) = reflector::store();
+        let objs = (0..3)
+            .map(|i| ConfigMap {
+                metadata: ObjectMeta {
+                    name: Some(format!("cm-{i}")),
+                    namespace: Some("default".to_string()),
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .collect::<Vec<_>>();
+        writer.apply_watcher_event(&watcher::Event::Restarted(objs.clone()));
+        let applier = batch_applier(
+            |batch, _| async move {
+                assert_eq!(batch.len(), 3);
+                Ok::<_, Infallible>(Action::await_change())
+            },
+            |_, _, _| unreachable!(),
+            Arc::new(()),
+            reader,
+            stream::iter(objs.iter().map(ObjectRef::from_obj).map(Ok::<_, Infallible>))
+                .chain(stream::pending()),
+            10,
+            Duration::from_secs(1),
+        );
+        let batches = applier.take(1).try_collect::<Vec<_>>().await.unwrap();
+        let mut batch = batches[0].0.clone();
+        batch.sort_by(|a, b| a.name.cmp(&b.name));
+        assert_eq!(batch, objs.iter().map(ObjectRef::from_obj).collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,230 @@
+//! Middleware for reconcilers, in the style of [`tower::Layer`](https://docs.rs/tower/*/tower/trait.Layer.html)
+use super::Action;
+use futures::{future::IntoFuture, Future, TryFuture, TryFutureExt};
+use pin_project::pin_project;
+use std::{
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+    time::Duration,
+};
+use thiserror::Error;
+
+/// A reconciler for objects of type `K`, with a context of type `Ctx`
+///
+/// This is implemented for all `FnMut(Arc<K>, Arc<Ctx>) -> impl TryFuture<Ok = Action>`,
+/// which is what [`Controller::run`](crate::Controller::run) accepts. Implement it directly
+/// for the types produced by your [`Layer`]s.
+pub trait Reconciler<K, Ctx> {
+    /// The error returned when reconciliation fails, which is passed to the `error_policy`
+    type Error;
+    /// The [`Future`] returned by [`reconcile`](Self::reconcile)
+    type Future: Future<Output = Result<Action, Self::Error>>;
+
+    /// Reconcile `obj`
+    fn reconcile(&mut self, obj: Arc<K>, ctx: Arc<Ctx>) -> Self::Future;
+}
+
+impl<K, Ctx, F, Fut> Reconciler<K, Ctx> for F
+where
+    F: FnMut(Arc<K>, Arc<Ctx>) -> Fut,
+    Fut: TryFuture<Ok = Action>,
+{
+    type Error = Fut::Error;
+    type Future = IntoFuture<Fut>;
+
+    fn reconcile(&mut self, obj: Arc<K>, ctx: Arc<Ctx>) -> Self::Future {
+        self(obj, ctx).into_future()
+    }
+}
+
+/// Decorates a [`Reconciler`] `R`, producing a new [`Reconciler`]
+///
+/// Layers are added to a [`Controller`](crate::Controller) with [`wrap`](crate::Controller::wrap).
+pub trait Layer<R> {
+    /// The wrapped reconciler
+    type Reconciler;
+
+    /// Wrap `inner` in this layer
+    fn layer(&self, inner: R) -> Self::Reconciler;
+}
+
+/// A [`Layer`] that leaves the reconciler as it is
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Identity;
+
+impl<R> Layer<R> for Identity {
+    type Reconciler = R;
+
+    fn layer(&self, inner: R) -> Self::Reconciler {
+        inner
+    }
+}
+
+/// Two [`Layer`]s chained together, `Outer` wraps the result of `Inner`
+#[derive(Debug, Clone)]
+pub struct Stack<Inner, Outer> {
+    inner: Inner,
+    outer: Outer,
+}
+
+impl<Inner, Outer> Stack<Inner, Outer> {
+    /// Chain `outer` on top of `inner`
+    pub fn new(inner: Inner, outer: Outer) -> Self {
+        Self { inner, outer }
+    }
+}
+
+impl<R, Inner, Outer> Layer<R> for Stack<Inner, Outer>
+where
+    Inner: Layer<R>,
+    Outer: Layer<Inner::Reconciler>,
+{
+    type Reconciler = Outer::Reconciler;
+
+    fn layer(&self, inner: R) -> Self::Reconciler {
+        self.outer.layer(self.inner.layer(inner))
+    }
+}
+
+/// A [`Layer`] that cancels reconciliations that take longer than a given [`Duration`]
+///
+/// Usually added with [`Controller::reconcile_timeout`](crate::Controller::reconcile_timeout).
+#[derive(Debug, Clone, Copy)]
+pub struct TimeoutLayer {
+    timeout: Duration,
+}
+
+impl TimeoutLayer {
+    /// Cancel reconciliations after `timeout`
+    #[must_use]
+    pub fn new(timeout: Duration) -> Self {
+        Self { timeout }
+    }
+}
+
+impl<R> Layer<R> for TimeoutLayer {
+    type Reconciler = Timeout<R>;
+
+    fn layer(&self, inner: R) -> Self::Reconciler {
+        Timeout {
+            inner,
+            timeout: self.timeout,
+        }
+    }
+}
+
+/// A [`Reconciler`] that is cancelled if it takes longer than a given [`Duration`], see [`TimeoutLayer`]
+#[derive(Debug, Clone)]
+pub struct Timeout<R> {
+    inner: R,
+    timeout: Duration,
+}
+
+/// The error returned by a [`Timeout`] reconciler
+#[derive(Debug, Error)]
+pub enum TimeoutError<E> {
+    /// The reconciler did not finish in time, and was cancelled
+    #[error("reconciler timed out after {0:?}")]
+    Elapsed(Duration),
+    /// The reconciler failed
+    #[error(transparent)]
+    Reconciler(E),
+}
+
+impl<K, Ctx, R> Reconciler<K, Ctx> for Timeout<R>
+where
+    R: Reconciler<K, Ctx>,
+{
+    type Error = TimeoutError<R::Error>;
+    type Future = TimeoutFuture<R::Future>;
+
+    fn reconcile(&mut self, obj: Arc<K>, ctx: Arc<Ctx>) -> Self::Future {
+        TimeoutFuture {
+            inner: tokio::time::timeout(self.timeout, self.inner.reconcile(obj, ctx)),
+            timeout: self.timeout,
+        }
+    }
+}
+
+/// The [`Future`] returned by [`Timeout`]
+#[pin_project]
+#[must_use = "futures do nothing unless polled"]
+pub struct TimeoutFuture<F> {
+    #[pin]
+    inner: tokio::time::Timeout<F>,
+    timeout: Duration,
+}
+
+impl<F, E> Future for TimeoutFuture<F>
+where
+    F: Future<Output = Result<Action, E>>,
+{
+    type Output = Result<Action, TimeoutError<E>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        this.inner.poll(cx).map(|res| {
+            if let Ok(res) = res {
+                res.map_err(TimeoutError::Reconciler)
+            } else {
+                tracing::warn!(timeout = ?this.timeout, "reconciler timed out");
+                Err(TimeoutError::Elapsed(*this.timeout))
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Identity, Layer, Reconciler, Stack, TimeoutError, TimeoutLayer};
+    use crate::controller::Action;
+    use futures::future::{self, Ready};
+    use std::{sync::Arc, time::Duration};
+
+    /// Appends its name to the error of the inner reconciler
+    struct Tag(&'static str);
+
+    struct Tagged<R>(&'static str, R);
+
+    impl<R> Layer<R> for Tag {
+        type Reconciler = Tagged<R>;
+
+        fn layer(&self, inner: R) -> Self::Reconciler {
+            Tagged(self.0, inner)
+        }
+    }
+
+    impl<R: Reconciler<(), (), Error = String>> Reconciler<(), ()> for Tagged<R> {
+        type Error = String;
+        type Future = Ready<Result<Action, String>>;
+
+        fn reconcile(&mut self, obj: Arc<()>, ctx: Arc<()>) -> Self::Future {
+            let res = futures::executor::block_on(self.1.reconcile(obj, ctx));
+            future::ready(res.map_err(|err| format!("{err}{}", self.0)))
+        }
+    }
+
+    #[test]
+    fn stack_should_apply_first_layer_outermost() {
+        let layer = Stack::new(Tag("inner"), Stack::new(Tag("outer"), Identity));
+        let mut reconciler =
+            layer.layer(|_: Arc<()>, _: Arc<()>| future::err::<Action, _>(String::from(">")));
+        let res = futures::executor::block_on(reconciler.reconcile(Arc::new(()), Arc::new(())));
+        assert_eq!(res.unwrap_err(), ">innerouter");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn timeout_should_cancel_slow_reconcilers() {
+        let mut reconciler =
+            TimeoutLayer::new(Duration::from_secs(10)).layer(|wait: Arc<u64>, _: Arc<()>| async move {
+                tokio::time::sleep(Duration::from_secs(*wait)).await;
+                Ok::<_, std::io::Error>(Action::await_change())
+            });
+        assert!(reconciler.reconcile(Arc::new(5), Arc::new(())).await.is_ok());
+        assert!(matches!(
+            reconciler.reconcile(Arc::new(15), Arc::new(())).await,
+            Err(TimeoutError::Elapsed(timeout)) if timeout == Duration::from_secs(10)
+        ));
+    }
+}
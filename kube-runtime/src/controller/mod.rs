@@ -2,6 +2,7 @@
 
 use self::runner::Runner;
 use crate::{
+    health::HealthChecker,
     reflector::{
         self, reflector,
         store::{Store, Writer},
@@ -9,7 +10,7 @@ use crate::{
     },
     scheduler::{scheduler, ScheduleRequest},
     utils::{trystream_try_via, CancelableJoinHandle, KubeRuntimeStreamExt, StreamBackoff, WatchStreamExt},
-    watcher::{self, metadata_watcher, watcher, Config, DefaultBackoff},
+    watcher::{self, metadata_watcher, watcher, DefaultBackoff},
 };
 use backoff::backoff::Backoff;
 use derivative::Derivative;
@@ -18,7 +19,11 @@ use futures::{
     future::{self, BoxFuture},
     ready, stream, Future, FutureExt, Stream, StreamExt, TryFuture, TryFutureExt, TryStream, TryStreamExt,
 };
-use kube_client::api::{Api, DynamicObject, Resource};
+use kube_client::{
+    api::{Api, DynamicObject, Resource},
+    core::GroupVersionKind,
+    discovery, Client,
+};
 use pin_project::pin_project;
 use serde::de::DeserializeOwned;
 use std::{
@@ -33,9 +38,13 @@ use thiserror::Error;
 use tokio::{runtime::Handle, time::Instant};
 use tracing::{info_span, Instrument};
 
+mod batch;
 mod future_hash_map;
+mod layer;
 mod runner;
 
+pub use layer::{Identity, Layer, Reconciler, Stack, Timeout, TimeoutError, TimeoutFuture, TimeoutLayer};
+
 pub type RunnerError = runner::Error<reflector::store::WriterDropped>;
 
 #[derive(Debug, Error)]
@@ -44,6 +53,8 @@ pub enum Error<ReconcilerErr: 'static, QueueErr: 'static> {
     ObjectNotFound(ObjectRef<DynamicObject>),
     #[error("reconciler for object {1} failed")]
     ReconcilerFailed(#[source] ReconcilerErr, ObjectRef<DynamicObject>),
+    #[error("reconciler for a batch of {} objects failed", .1.len())]
+    BatchReconcilerFailed(#[source] ReconcilerErr, Vec<ObjectRef<DynamicObject>>),
     #[error("event queue error")]
     QueueError(#[source] QueueErr),
     #[error("runner error")]
@@ -118,6 +129,11 @@ where
         Some(ReconcileRequest {
             obj_ref: ObjectRef::from_obj_with(&obj, dyntype.clone()),
             reason: ReconcileReason::ObjectUpdated,
+            priority: if obj.meta().deletion_timestamp.is_some() {
+                Priority::High
+            } else {
+                Priority::Normal
+            },
         })
     })
 }
@@ -149,6 +165,7 @@ where
                 reason: ReconcileReason::RelatedObjectUpdated {
                     obj_ref: Box::new(watch_ref.clone()),
                 },
+                priority: Priority::Normal,
             })
     })
 }
@@ -180,9 +197,9 @@ where
 
 /// A request to reconcile an object, annotated with why that request was made.
 ///
-/// NOTE: The reason is ignored for comparison purposes. This means that, for example,
+/// NOTE: The reason and priority are ignored for comparison purposes. This means that, for example,
 /// an object can only occupy one scheduler slot, even if it has been scheduled for multiple reasons.
-/// In this case, only *the first* reason is stored.
+/// In this case, only *the first* reason and priority are stored.
 #[derive(Derivative)]
 #[derivative(
     Debug(bound = "K::DynamicType: Debug"),
@@ -191,10 +208,31 @@ where
     Eq(bound = "K::DynamicType: Eq"),
     Hash(bound = "K::DynamicType: Hash")
 )]
+#[non_exhaustive]
 pub struct ReconcileRequest<K: Resource> {
     pub obj_ref: ObjectRef<K>,
     #[derivative(PartialEq = "ignore", Hash = "ignore")]
     pub reason: ReconcileReason,
+    #[derivative(PartialEq = "ignore", Hash = "ignore")]
+    pub priority: Priority,
+}
+
+impl<K: Resource> ReconcileRequest<K> {
+    /// Creates a request to reconcile `obj_ref` with [`Priority::Normal`]
+    pub fn new(obj_ref: ObjectRef<K>, reason: ReconcileReason) -> Self {
+        ReconcileRequest {
+            obj_ref,
+            reason,
+            priority: Priority::Normal,
+        }
+    }
+
+    /// Sets the [`Priority`] of the request
+    #[must_use]
+    pub fn with_priority(mut self, priority: Priority) -> Self {
+        self.priority = priority;
+        self
+    }
 }
 
 impl<K: Resource> From<ObjectRef<K>> for ReconcileRequest<K> {
@@ -202,10 +240,26 @@ impl<K: Resource> From<ObjectRef<K>> for ReconcileRequest<K> {
         ReconcileRequest {
             obj_ref,
             reason: ReconcileReason::Unknown,
+            priority: Priority::Normal,
         }
     }
 }
 
+/// How urgently a [`ReconcileRequest`] should be handled
+///
+/// When the [`Controller`] is congested (see [`Config::concurrency`]), ready requests with a higher
+/// priority are dispatched before those with a lower priority.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum Priority {
+    /// Periodic resyncs, such as requeues requested by the reconciler or bulk reconciles
+    Low,
+    /// Changes to the root object or related objects
+    #[default]
+    Normal,
+    /// Root objects that are being deleted
+    High,
+}
+
 #[derive(Debug, Clone)]
 pub enum ReconcileReason {
     Unknown,
@@ -235,6 +289,57 @@ impl Display for ReconcileReason {
 
 const APPLIER_REQUEUE_BUF_SIZE: usize = 100;
 
+/// Accumulates all options that can be used on a [`Controller`] or [`applier`] invocation
+#[derive(Clone, Debug, Default)]
+pub struct Config {
+    concurrency: u16,
+    dry_run: bool,
+}
+
+impl Config {
+    /// The number of concurrent reconciliations that are allowed to run at any given moment
+    ///
+    /// This can be adjusted to the controller's needs to increase performance and/or make
+    /// performance predictable. When all reconciliations are busy, the ready requests are
+    /// dispatched by their [`Priority`]. By default, it is 0, meaning that the controller
+    /// runs with unbounded concurrency.
+    ///
+    /// Note that despite concurrency, a controller never schedules concurrent reconciles
+    /// on the same object.
+    #[must_use]
+    pub fn concurrency(mut self, concurrency: u16) -> Self {
+        self.concurrency = concurrency;
+        self
+    }
+
+    /// Run the controller in dry-run mode
+    ///
+    /// This is exposed to reconcilers through [`is_dry_run`], which they should check before
+    /// making changes. It is not enforced by the controller itself — to make the apiserver
+    /// discard all writes, also build the controller's [`Client`] with a
+    /// [`DryRunLayer`](kube_client::client::middleware::DryRunLayer).
+    ///
+    /// By default, it is disabled.
+    #[must_use]
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+}
+
+tokio::task_local! {
+    static DRY_RUN: bool;
+}
+
+/// Whether the current reconciliation is running in dry-run mode
+///
+/// This must be called from within the reconciler's future, rather than from the function that creates it.
+/// This is only ever `true` inside reconcilers run by a [`Controller`] with [`Config::dry_run`] enabled.
+#[must_use]
+pub fn is_dry_run() -> bool {
+    DRY_RUN.try_with(|dry_run| *dry_run).unwrap_or(false)
+}
+
 /// Apply a reconciler to an input stream, with a given retry policy
 ///
 /// Takes a `store` parameter for the core objects, which should usually be updated by a [`reflector`].
@@ -246,12 +351,35 @@ const APPLIER_REQUEUE_BUF_SIZE: usize = 100;
 ///
 /// This is the "hard-mode" version of [`Controller`], which allows you some more customization
 /// (such as triggering from arbitrary [`Stream`]s), at the cost of being a bit more verbose.
+#[allow(clippy::type_complexity)]
 pub fn applier<K, QueueStream, ReconcilerFut, Ctx>(
+    reconciler: impl FnMut(Arc<K>, Arc<Ctx>) -> ReconcilerFut,
+    error_policy: impl Fn(Arc<K>, &ReconcilerFut::Error, Arc<Ctx>) -> Action,
+    context: Arc<Ctx>,
+    store: Store<K>,
+    queue: QueueStream,
+) -> impl Stream<Item = Result<(ObjectRef<K>, Action), Error<ReconcilerFut::Error, QueueStream::Error>>>
+where
+    K: Clone + Resource + 'static,
+    K::DynamicType: Debug + Eq + Hash + Clone + Unpin,
+    ReconcilerFut: TryFuture<Ok = Action> + Unpin,
+    ReconcilerFut::Error: std::error::Error + 'static,
+    QueueStream: TryStream,
+    QueueStream::Ok: Into<ReconcileRequest<K>>,
+    QueueStream::Error: std::error::Error + 'static,
+{
+    applier_with_config(reconciler, error_policy, context, store, queue, Config::default())
+}
+
+/// Variant of [`applier`] that takes a [`Config`], such as to limit the concurrency of reconciliations
+#[allow(clippy::type_complexity, clippy::needless_pass_by_value)]
+pub fn applier_with_config<K, QueueStream, ReconcilerFut, Ctx>(
     mut reconciler: impl FnMut(Arc<K>, Arc<Ctx>) -> ReconcilerFut,
     error_policy: impl Fn(Arc<K>, &ReconcilerFut::Error, Arc<Ctx>) -> Action,
     context: Arc<Ctx>,
     store: Store<K>,
     queue: QueueStream,
+    config: Config,
 ) -> impl Stream<Item = Result<(ObjectRef<K>, Action), Error<ReconcilerFut::Error, QueueStream::Error>>>
 where
     K: Clone + Resource + 'static,
@@ -291,7 +419,12 @@ where
         )),
         // all the Oks from the select gets passed through the scheduler stream, and are then executed
         move |s| {
-            Runner::new(scheduler(s), move |request| {
+            let scheduler = if config.concurrency > 0 {
+                scheduler(s).prioritize_by(|request: &ReconcileRequest<K>| request.priority as u8)
+            } else {
+                scheduler(s)
+            };
+            Runner::new(scheduler, move |request| {
                 let request = request.clone();
                 match store.get(&request.obj_ref) {
                     Some(obj) => {
@@ -324,6 +457,7 @@ where
                     None => future::err(Error::ObjectNotFound(request.obj_ref.erase())).right_future(),
                 }
             })
+            .max_concurrent_executions(config.concurrency)
             .delay_tasks_until(async move {
                 tracing::debug!("applier runner held until store is ready");
                 let res = delay_store.wait_until_ready().await;
@@ -380,6 +514,7 @@ where
                 message: ReconcileRequest {
                     obj_ref,
                     reason: reschedule_reason,
+                    priority: Priority::Low,
                 },
                 run_at: reconciler_finished_at + requeue_after,
             }),
@@ -487,13 +622,13 @@ where
 ///     Ok(())
 /// }
 /// ```
-pub struct Controller<K>
+pub struct Controller<K, L = Identity>
 where
     K: Clone + Resource + Debug + 'static,
     K::DynamicType: Eq + Hash,
 {
     // NB: Need to Unpin for stream::select_all
-    trigger_selector: stream::SelectAll<BoxStream<'static, Result<ReconcileRequest<K>, watcher::Error>>>,
+    trigger_selector: Vec<BoxStream<'static, Result<ReconcileRequest<K>, watcher::Error>>>,
     trigger_backoff: Box<dyn Backoff + Send>,
     /// [`run`](crate::Controller::run) starts a graceful shutdown when any of these [`Future`]s complete,
     /// refusing to start any new reconciliations but letting any existing ones finish.
@@ -505,6 +640,8 @@ where
     forceful_shutdown_selector: Vec<BoxFuture<'static, ()>>,
     dyntype: K::DynamicType,
     reader: Store<K>,
+    config: Config,
+    layer: L,
 }
 
 impl<K> Controller<K>
@@ -516,11 +653,11 @@ where
     ///
     /// Takes an [`Api`] object that determines how the `Controller` listens for changes to the `K`.
     ///
-    /// The [`Config`] controls to the possible subset of objects of `K` that you want to manage
+    /// The [`watcher::Config`] controls to the possible subset of objects of `K` that you want to manage
     /// and receive reconcile events for.
-    /// For the full set of objects `K` in the given `Api` scope, you can use [`Config::default`].
+    /// For the full set of objects `K` in the given `Api` scope, you can use [`watcher::Config::default`].
     #[must_use]
-    pub fn new(main_api: Api<K>, wc: Config) -> Self
+    pub fn new(main_api: Api<K>, wc: watcher::Config) -> Self
     where
         K::DynamicType: Default,
     {
@@ -531,20 +668,18 @@ where
     ///
     /// Takes an [`Api`] object that determines how the `Controller` listens for changes to the `K`.
     ///
-    /// The [`Config`] lets you define a possible subset of objects of `K` that you want the [`Api`]
+    /// The [`watcher::Config`] lets you define a possible subset of objects of `K` that you want the [`Api`]
     /// to watch - in the Api's  configured scope - and receive reconcile events for.
-    /// For the full set of objects `K` in the given `Api` scope, you can use [`Config::default`].
+    /// For the full set of objects `K` in the given `Api` scope, you can use [`watcher::Config::default`].
     ///
     /// This variant constructor is for [`dynamic`] types found through discovery. Prefer [`Controller::new`] for static types.
     ///
-    /// [`Config`]: crate::watcher::Config
     /// [`Api`]: kube_client::Api
     /// [`dynamic`]: kube_client::core::dynamic
-    /// [`Config::default`]: crate::watcher::Config::default
-    pub fn new_with(main_api: Api<K>, wc: Config, dyntype: K::DynamicType) -> Self {
+    pub fn new_with(main_api: Api<K>, wc: watcher::Config, dyntype: K::DynamicType) -> Self {
         let writer = Writer::<K>::new(dyntype.clone());
         let reader = writer.as_reader();
-        let mut trigger_selector = stream::SelectAll::new();
+        let mut trigger_selector = Vec::new();
         let self_watcher = trigger_self(
             reflector(writer, watcher(main_api, wc)).applied_objects(),
             dyntype.clone(),
@@ -564,6 +699,8 @@ where
             ],
             dyntype,
             reader,
+            config: Default::default(),
+            layer: Identity,
         }
     }
 
@@ -631,7 +768,7 @@ where
         reader: Store<K>,
         dyntype: K::DynamicType,
     ) -> Self {
-        let mut trigger_selector = stream::SelectAll::new();
+        let mut trigger_selector = Vec::new();
         let self_watcher = trigger_self(trigger, dyntype.clone()).boxed();
         trigger_selector.push(self_watcher);
         Self {
@@ -647,9 +784,17 @@ where
             ],
             dyntype,
             reader,
+            config: Default::default(),
+            layer: Identity,
         }
     }
+}
 
+impl<K, L> Controller<K, L>
+where
+    K: Clone + Resource + DeserializeOwned + Debug + Send + Sync + 'static,
+    K::DynamicType: Eq + Hash + Clone,
+{
     /// Specify the backoff policy for "trigger" watches
     ///
     /// This includes the core watch, as well as auxilary watches introduced by [`Self::owns`] and [`Self::watches`].
@@ -662,6 +807,85 @@ where
         self
     }
 
+    /// Specify the configuration for the controller's behavior
+    #[must_use]
+    pub fn with_config(mut self, config: Config) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Report the health of the controller's main watch to a [`HealthChecker`]
+    ///
+    /// The controller is registered under the kind of `K`. It becomes ready once the reflector's
+    /// [`Store`] has been populated, and is healthy for as long as the main watch is not returning errors.
+    ///
+    /// ```no_run
+    /// # use k8s_openapi::api::core::v1::ConfigMap;
+    /// # use kube::{runtime::{health::HealthChecker, watcher, Controller}, Api};
+    /// # async fn wrapper(client: kube::Client) {
+    /// let health = HealthChecker::default();
+    /// Controller::new(Api::<ConfigMap>::all(client), watcher::Config::default())
+    ///     .report_health(&health)
+    /// # ;
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn report_health(mut self, checker: &HealthChecker) -> Self {
+        let reporter = Arc::new(checker.register(K::kind(&self.dyntype)));
+        let ready_reporter = reporter.clone();
+        let reader = self.reader.clone();
+        let ready = async move {
+            if reader.wait_until_ready().await.is_ok() {
+                ready_reporter.set_ready(true);
+            }
+            None
+        }
+        .into_stream()
+        .filter_map(future::ready);
+        let main = self
+            .trigger_selector
+            .remove(0)
+            .inspect(move |res| reporter.set_healthy(res.is_ok()));
+        self.trigger_selector
+            .insert(0, stream::select(main, ready).boxed());
+        self
+    }
+
+    /// Override the [`Priority`] of reconciliations triggered by the most recently added relation
+    ///
+    /// This applies to the relation added by the last call to [`Self::owns`], [`Self::watches`],
+    /// [`Self::reconcile_all_on`] (and their variants), or to the watch of the root objects when
+    /// called directly after creating the `Controller`.
+    ///
+    /// Priorities only matter when the controller is congested, see [`Config::concurrency`].
+    ///
+    /// ```no_run
+    /// # async fn doc(client: kube::Client) {
+    /// # use futures::StreamExt;
+    /// # use k8s_openapi::api::{apps::v1::Deployment, core::v1::ConfigMap};
+    /// # use kube::runtime::{controller::{Action, Config, Controller, Priority}, watcher};
+    /// # use kube::api::Api;
+    /// # use std::{convert::Infallible, sync::Arc};
+    /// Controller::new(Api::<Deployment>::all(client.clone()), watcher::Config::default())
+    ///     .owns(Api::<ConfigMap>::all(client), watcher::Config::default())
+    ///     // changes to the ConfigMaps are less important than changes to the Deployments
+    ///     .trigger_priority(Priority::Low)
+    ///     .with_config(Config::default().concurrency(2))
+    /// # ;
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn trigger_priority(mut self, priority: Priority) -> Self {
+        if let Some(trigger) = self.trigger_selector.pop() {
+            self.trigger_selector.push(
+                trigger
+                    .map_ok(move |request| ReconcileRequest { priority, ..request })
+                    .boxed(),
+            );
+        }
+        self
+    }
+
     /// Retrieve a copy of the reader before starting the controller
     pub fn store(&self) -> Store<K> {
         self.reader.clone()
@@ -681,7 +905,7 @@ where
     pub fn owns<Child: Clone + Resource<DynamicType = ()> + DeserializeOwned + Debug + Send + 'static>(
         self,
         api: Api<Child>,
-        wc: Config,
+        wc: watcher::Config,
     ) -> Self {
         self.owns_with(api, (), wc)
     }
@@ -694,7 +918,7 @@ where
         mut self,
         api: Api<Child>,
         dyntype: Child::DynamicType,
-        wc: Config,
+        wc: watcher::Config,
     ) -> Self
     where
         Child::DynamicType: Debug + Eq + Hash + Clone,
@@ -845,7 +1069,7 @@ where
     pub fn watches<Other, I>(
         self,
         api: Api<Other>,
-        wc: Config,
+        wc: watcher::Config,
         mapper: impl Fn(Other) -> I + Sync + Send + 'static,
     ) -> Self
     where
@@ -865,7 +1089,7 @@ where
         mut self,
         api: Api<Other>,
         dyntype: Other::DynamicType,
-        wc: Config,
+        wc: watcher::Config,
         mapper: impl Fn(Other) -> I + Sync + Send + 'static,
     ) -> Self
     where
@@ -1019,6 +1243,7 @@ where
                         Ok(ReconcileRequest {
                             obj_ref: ObjectRef::from_obj_with(&*obj, dyntype.clone()),
                             reason: ReconcileReason::BulkReconcile,
+                            priority: Priority::Low,
                         })
                     }))
                 })
@@ -1080,6 +1305,7 @@ where
                 .map_ok(move |obj| ReconcileRequest {
                     obj_ref: obj,
                     reason: ReconcileReason::Unknown,
+                    priority: Priority::Normal,
                 })
                 .boxed(),
         );
@@ -1185,37 +1411,246 @@ where
         self
     }
 
+    /// Wrap the reconciler in a [`Layer`]
+    ///
+    /// Layers are useful for concerns that apply to every reconciliation, such as metrics,
+    /// audit logging, or enforcing timeouts, and are applied when the controller is [`run`](Self::run).
+    /// The first layer added is the outermost one, so it sees each reconciliation first.
+    ///
+    /// A layer may change the error type of the reconciler, in which case the `error_policy`
+    /// receives the error type of the outermost layer.
+    ///
+    /// ```no_run
+    /// # use futures::{future::BoxFuture, FutureExt};
+    /// # use k8s_openapi::api::core::v1::ConfigMap;
+    /// # use kube::{runtime::{controller::{Action, Controller, Layer, Reconciler}, watcher}, Api, ResourceExt};
+    /// # use std::sync::Arc;
+    /// /// Logs the outcome of every reconciliation
+    /// struct Audit;
+    ///
+    /// struct AuditReconciler<R>(R);
+    ///
+    /// impl<R> Layer<R> for Audit {
+    ///     type Reconciler = AuditReconciler<R>;
+    ///
+    ///     fn layer(&self, inner: R) -> Self::Reconciler {
+    ///         AuditReconciler(inner)
+    ///     }
+    /// }
+    ///
+    /// impl<R, Ctx> Reconciler<ConfigMap, Ctx> for AuditReconciler<R>
+    /// where
+    ///     R: Reconciler<ConfigMap, Ctx>,
+    ///     R::Future: Send + 'static,
+    ///     R::Error: std::fmt::Debug,
+    /// {
+    ///     type Error = R::Error;
+    ///     type Future = BoxFuture<'static, Result<Action, R::Error>>;
+    ///
+    ///     fn reconcile(&mut self, obj: Arc<ConfigMap>, ctx: Arc<Ctx>) -> Self::Future {
+    ///         let name = obj.name_any();
+    ///         self.0
+    ///             .reconcile(obj, ctx)
+    ///             .inspect(move |res| println!("reconciled {name}: {res:?}"))
+    ///             .boxed()
+    ///     }
+    /// }
+    ///
+    /// # async fn doc(client: kube::Client) {
+    /// Controller::new(Api::<ConfigMap>::all(client), watcher::Config::default())
+    ///     .wrap(Audit)
+    /// # ;
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn wrap<Outer>(self, layer: Outer) -> Controller<K, Stack<Outer, L>> {
+        Controller {
+            trigger_selector: self.trigger_selector,
+            trigger_backoff: self.trigger_backoff,
+            graceful_shutdown_selector: self.graceful_shutdown_selector,
+            forceful_shutdown_selector: self.forceful_shutdown_selector,
+            dyntype: self.dyntype,
+            reader: self.reader,
+            config: self.config,
+            layer: Stack::new(layer, self.layer),
+        }
+    }
+
+    /// Cancel reconciliations that take longer than `timeout`
+    ///
+    /// Cancelled reconciliations are passed to the `error_policy` as [`TimeoutError::Elapsed`], while errors
+    /// returned by the reconciler itself are wrapped in [`TimeoutError::Reconciler`]. This keeps a single hung
+    /// API call from occupying a reconciliation slot forever. Each timeout is also logged as a `tracing` warning
+    /// within the span of the reconciliation.
+    ///
+    /// This is a shorthand for [`wrap`](Self::wrap)ping the reconciler in a [`TimeoutLayer`]. Since the first
+    /// layer added is the outermost one, the timeout only covers the reconciler and the layers added after it,
+    /// while layers added before it see the [`TimeoutError`]s.
+    ///
+    /// ```no_run
+    /// # use k8s_openapi::api::core::v1::ConfigMap;
+    /// # use kube::{runtime::{controller::{Action, Controller, TimeoutError}, watcher}, Api};
+    /// # use std::{sync::Arc, time::Duration};
+    /// # async fn reconcile(_: Arc<ConfigMap>, _: Arc<()>) -> Result<Action, kube::Error> { todo!() }
+    /// # async fn doc(client: kube::Client) {
+    /// Controller::new(Api::<ConfigMap>::all(client), watcher::Config::default())
+    ///     .reconcile_timeout(Duration::from_secs(30))
+    ///     .run(
+    ///         reconcile,
+    ///         |_, err: &TimeoutError<kube::Error>, _| match err {
+    ///             TimeoutError::Elapsed(_) => Action::requeue(Duration::from_secs(1)),
+    ///             TimeoutError::Reconciler(_) => Action::requeue(Duration::from_secs(60)),
+    ///         },
+    ///         Arc::new(()),
+    ///     )
+    /// # ;
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn reconcile_timeout(self, timeout: Duration) -> Controller<K, Stack<TimeoutLayer, L>> {
+        self.wrap(TimeoutLayer::new(timeout))
+    }
+
     /// Consume all the parameters of the Controller and start the applier stream
     ///
     /// This creates a stream from all builder calls and starts an applier with
     /// a specified `reconciler` and `error_policy` callbacks. Each of these will be called
     /// with a configurable `context`.
-    pub fn run<ReconcilerFut, Ctx>(
+    ///
+    /// The `reconciler` is wrapped in any [`Layer`]s added by [`wrap`](Self::wrap).
+    #[allow(clippy::type_complexity)]
+    pub fn run<R, ReconcilerFut, Ctx>(
         self,
-        mut reconciler: impl FnMut(Arc<K>, Arc<Ctx>) -> ReconcilerFut,
-        error_policy: impl Fn(Arc<K>, &ReconcilerFut::Error, Arc<Ctx>) -> Action,
+        reconciler: R,
+        error_policy: impl Fn(Arc<K>, &<L::Reconciler as Reconciler<K, Ctx>>::Error, Arc<Ctx>) -> Action,
         context: Arc<Ctx>,
-    ) -> impl Stream<Item = Result<(ObjectRef<K>, Action), Error<ReconcilerFut::Error, watcher::Error>>>
+    ) -> impl Stream<
+        Item = Result<
+            (ObjectRef<K>, Action),
+            Error<<L::Reconciler as Reconciler<K, Ctx>>::Error, watcher::Error>,
+        >,
+    >
     where
         K::DynamicType: Debug + Unpin,
-        ReconcilerFut: TryFuture<Ok = Action> + Send + 'static,
-        ReconcilerFut::Error: std::error::Error + Send + 'static,
+        R: FnMut(Arc<K>, Arc<Ctx>) -> ReconcilerFut,
+        ReconcilerFut: TryFuture<Ok = Action>,
+        L: Layer<R>,
+        L::Reconciler: Reconciler<K, Ctx>,
+        <L::Reconciler as Reconciler<K, Ctx>>::Future: Send + 'static,
+        <L::Reconciler as Reconciler<K, Ctx>>::Error: std::error::Error + Send + 'static,
     {
-        applier(
+        let mut reconciler = self.layer.layer(reconciler);
+        let dry_run = self.config.dry_run;
+        applier_with_config(
             move |obj, ctx| {
                 CancelableJoinHandle::spawn(
-                    reconciler(obj, ctx).into_future().in_current_span(),
+                    DRY_RUN
+                        .scope(dry_run, reconciler.reconcile(obj, ctx))
+                        .in_current_span(),
                     &Handle::current(),
                 )
             },
             error_policy,
             context,
             self.reader,
-            StreamBackoff::new(self.trigger_selector, self.trigger_backoff)
+            StreamBackoff::new(stream::select_all(self.trigger_selector), self.trigger_backoff)
                 .take_until(future::select_all(self.graceful_shutdown_selector)),
+            self.config,
         )
         .take_until(futures::future::select_all(self.forceful_shutdown_selector))
     }
+
+    /// Consume all the parameters of the Controller and start a batched applier stream
+    ///
+    /// This is an alternative to [`run`](Self::run) for reconcilers that are more efficient when
+    /// handling many objects at once. Triggered objects are collected for up to `window`
+    /// (or until `batch_size` distinct objects are pending), and then passed to the `reconciler` together.
+    /// Objects can be looked up in the [`store`](Self::store) from within the reconciler, but may
+    /// have been removed from it by the time the batch is reconciled.
+    ///
+    /// Batches are reconciled one at a time, the [`Config`] concurrency does not apply.
+    /// The returned [`Action`] (or the one chosen by the `error_policy`) applies to every object in the batch.
+    /// [`Layer`]s added by [`wrap`](Self::wrap) are also not applied, since they operate on individual objects.
+    ///
+    /// ```no_run
+    /// # use k8s_openapi::api::core::v1::ConfigMap;
+    /// # use kube::{runtime::{controller::Action, watcher, Controller}, Api};
+    /// # use std::{convert::Infallible, sync::Arc, time::Duration};
+    /// # use futures::StreamExt;
+    /// # async fn wrapper(client: kube::Client) {
+    /// Controller::new(Api::<ConfigMap>::all(client), watcher::Config::default())
+    ///     .run_batched(
+    ///         100,
+    ///         Duration::from_secs(1),
+    ///         |batch, _ctx| async move {
+    ///             println!("reconciling {} config maps", batch.len());
+    ///             Ok::<_, Infallible>(Action::await_change())
+    ///         },
+    ///         |_batch, _err, _ctx| Action::requeue(Duration::from_secs(5)),
+    ///         Arc::new(()),
+    ///     )
+    ///     .for_each(|_| futures::future::ready(()))
+    ///     .await;
+    /// # }
+    /// ```
+    #[allow(clippy::type_complexity)]
+    pub fn run_batched<ReconcilerFut, Ctx>(
+        self,
+        batch_size: usize,
+        window: Duration,
+        reconciler: impl FnMut(Vec<ObjectRef<K>>, Arc<Ctx>) -> ReconcilerFut,
+        error_policy: impl Fn(&[ObjectRef<K>], &ReconcilerFut::Error, Arc<Ctx>) -> Action,
+        context: Arc<Ctx>,
+    ) -> impl Stream<Item = Result<(Vec<ObjectRef<K>>, Action), Error<ReconcilerFut::Error, watcher::Error>>>
+    where
+        K::DynamicType: Debug + Unpin,
+        ReconcilerFut: TryFuture<Ok = Action>,
+        ReconcilerFut::Error: std::error::Error + 'static,
+    {
+        batch::batch_applier(
+            reconciler,
+            error_policy,
+            context,
+            self.reader,
+            StreamBackoff::new(stream::select_all(self.trigger_selector), self.trigger_backoff)
+                .take_until(future::select_all(self.graceful_shutdown_selector)),
+            batch_size,
+            window,
+        )
+        .take_until(futures::future::select_all(self.forceful_shutdown_selector))
+    }
+}
+
+impl Controller<DynamicObject> {
+    /// Create a Controller for a kind that is only known at runtime
+    ///
+    /// The [`ApiResource`](kube_client::discovery::ApiResource) for `gvk` is resolved through
+    /// [`discovery::pinned_kind`], and the resulting `Controller` watches the kind across the whole
+    /// cluster, which covers both cluster-scoped kinds and namespaced kinds in every namespace.
+    ///
+    /// Use [`Controller::new_with`] with a namespaced [`Api`] if you only want to watch a single namespace.
+    ///
+    /// ```no_run
+    /// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+    /// use kube::{core::GroupVersionKind, runtime::{watcher, Controller}, Client};
+    /// let client = Client::try_default().await?;
+    /// let gvk = GroupVersionKind::gvk("apps", "v1", "Deployment");
+    /// let controller = Controller::new_dynamic(client, &gvk, watcher::Config::default()).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns the discovery error if `gvk` could not be resolved.
+    pub async fn new_dynamic(
+        client: Client,
+        gvk: &GroupVersionKind,
+        wc: watcher::Config,
+    ) -> Result<Self, kube_client::Error> {
+        let (ar, _caps) = discovery::pinned_kind(&client, gvk).await?;
+        Ok(Self::new_with(Api::all_with(client, &ar), wc, ar))
+    }
 }
 
 #[cfg(test)]
@@ -1342,4 +1777,44 @@ mod tests {
         .expect("applier cleanup timeout expired, individual reconciler likely deadlocked?")
         .unwrap();
     }
+
+    #[cfg(feature = "unstable-runtime-stream-control")]
+    #[tokio::test]
+    async fn controller_should_expose_dry_run_to_reconcilers() {
+        use super::is_dry_run;
+        use futures::stream;
+        use std::sync::atomic::{AtomicBool, Ordering};
+
+        let obj = ConfigMap {
+            metadata: ObjectMeta {
+                name: Some("cm".to_string()),
+                namespace: Some("default".to_string()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let (reader, mut writer) = reflector::store();
+        writer.apply_watcher_event(&watcher::Event::Applied(obj.clone()));
+        let saw_dry_run = Arc::new(AtomicBool::new(false));
+        let reconciler_saw_dry_run = saw_dry_run.clone();
+        Controller::for_stream(stream::iter([Ok(obj)]).chain(stream::pending()), reader)
+            .with_config(super::Config::default().dry_run(true))
+            .run(
+                move |_, _| {
+                    let saw_dry_run = reconciler_saw_dry_run.clone();
+                    async move {
+                        saw_dry_run.store(is_dry_run(), Ordering::SeqCst);
+                        Ok::<_, Infallible>(Action::await_change())
+                    }
+                },
+                |_, _, _| unreachable!(),
+                Arc::new(()),
+            )
+            .take(1)
+            .try_for_each(|_| async { Ok(()) })
+            .await
+            .unwrap();
+        assert!(saw_dry_run.load(Ordering::SeqCst));
+        assert!(!is_dry_run());
+    }
 }
@@ -32,6 +32,7 @@ pub struct Runner<T, R, F, MkF, Ready = future::Ready<Result<(), Infallible>>> {
     ready_to_execute_after: future::Fuse<Ready>,
     is_ready_to_execute: bool,
     stopped: bool,
+    max_concurrent_executions: u16,
 }
 
 impl<T, R, F, MkF> Runner<T, R, F, MkF>
@@ -47,9 +48,20 @@ where
             ready_to_execute_after: future::ready(Ok(())).fuse(),
             is_ready_to_execute: false,
             stopped: false,
+            max_concurrent_executions: 0,
         }
     }
 
+    /// Restricts the runner to only run `max_concurrent_executions` at any given time.
+    ///
+    /// Messages that are ready while all slots are busy are held pending in the [`Scheduler`],
+    /// until a slot becomes available. The default of `0` means no limit.
+    #[must_use]
+    pub fn max_concurrent_executions(mut self, max_concurrent_executions: u16) -> Self {
+        self.max_concurrent_executions = max_concurrent_executions;
+        self
+    }
+
     /// Wait for `ready_to_execute_after` to complete before starting to run any scheduled tasks.
     ///
     /// `scheduler` will still be polled in the meantime.
@@ -67,6 +79,7 @@ where
             ready_to_execute_after: ready_to_execute_after.fuse(),
             is_ready_to_execute: false,
             stopped: false,
+            max_concurrent_executions: self.max_concurrent_executions,
         }
     }
 }
@@ -101,13 +114,15 @@ where
             }
             Poll::Pending => {}
         }
+        let max_concurrent_executions = usize::from(*this.max_concurrent_executions);
         loop {
             // Try to take take a new message that isn't already being processed
             // leave the already-processing ones in the queue, so that we can take them once
             // we're free again.
+            let has_free_slot = max_concurrent_executions == 0 || slots.len() < max_concurrent_executions;
             let next_msg_poll = scheduler
                 .as_mut()
-                .hold_unless(|msg| *this.is_ready_to_execute && !slots.contains_key(msg))
+                .hold_unless(|msg| *this.is_ready_to_execute && has_free_slot && !slots.contains_key(msg))
                 .poll_next_unpin(cx);
             match next_msg_poll {
                 Poll::Ready(Some(msg)) => {
@@ -142,18 +157,21 @@ mod tests {
     };
     use futures::{
         channel::{mpsc, oneshot},
-        future, poll, stream, FutureExt, SinkExt, StreamExt, TryStreamExt,
+        future, poll, stream, SinkExt, StreamExt, TryStreamExt,
     };
     use std::{
         cell::RefCell,
-        collections::{HashMap, HashSet},
-        sync::Mutex,
+        collections::HashMap,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc, Mutex,
+        },
         time::Duration,
     };
     use tokio::{
         runtime::Handle,
         task::yield_now,
-        time::{error::Elapsed, pause, sleep, timeout, Instant},
+        time::{pause, sleep, timeout, Instant},
     };
 
     #[tokio::test]
@@ -163,7 +181,7 @@ mod tests {
         let mut count = 0;
         let (mut sched_tx, sched_rx) = mpsc::unbounded();
         let mut runner = Box::pin(
-            Runner::new(scheduler(sched_rx), |_| {
+            Runner::new(scheduler(sched_rx), |()| {
                 count += 1;
                 // Panic if this ref is already held, to simulate some unsafe action..
                 let mutex_ref = rc.borrow_mut();
@@ -201,6 +219,38 @@ mod tests {
         assert_eq!(count, 2);
     }
 
+    #[tokio::test]
+    async fn runner_should_respect_max_concurrent_executions() {
+        pause();
+        let running = Arc::new(AtomicUsize::new(0));
+        let mut runner = Box::pin(
+            Runner::new(
+                scheduler(
+                    stream::iter((0..5_u8).map(|msg| ScheduleRequest {
+                        message: msg,
+                        run_at: Instant::now(),
+                    }))
+                    .chain(stream::pending()),
+                ),
+                |_| {
+                    let running = running.clone();
+                    assert!(
+                        running.fetch_add(1, Ordering::SeqCst) < 2,
+                        "ran too many messages at once"
+                    );
+                    Box::pin(async move {
+                        sleep(Duration::from_secs(1)).await;
+                        running.fetch_sub(1, Ordering::SeqCst);
+                    })
+                },
+            )
+            .max_concurrent_executions(2),
+        );
+        for _ in 0..5 {
+            runner.next().await.unwrap().unwrap();
+        }
+    }
+
     // Test MUST be single-threaded to be consistent, since it concerns a relatively messy
     // interplay between multiple tasks
     #[tokio::test(flavor = "current_thread")]
@@ -20,7 +20,7 @@ pub struct Event {
 
     /// The short reason explaining why the `action` was taken.
     ///
-    /// This must be at most 128 characters, and is often PascalCased. Shows up in `kubectl describe` as `Reason`.
+    /// This must be at most 128 characters, and is often `PascalCased`. Shows up in `kubectl describe` as `Reason`.
     /// Usually denoted
     pub reason: String,
 
@@ -221,7 +221,7 @@ impl Recorder {
                 deprecated_source: None,
                 event_time: Some(MicroTime(Utc::now())),
                 regarding: Some(self.reference.clone()),
-                note: ev.note.map(Into::into),
+                note: ev.note,
                 metadata: ObjectMeta {
                     namespace: self.reference.namespace.clone(),
                     generate_name: Some(format!("{}-", self.reporter.controller)),
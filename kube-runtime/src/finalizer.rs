@@ -1,13 +1,14 @@
 //! Finalizer helper for [`Controller`](crate::Controller) reconcilers
 use crate::controller::Action;
-use futures::{TryFuture, TryFutureExt};
+use futures::{future::BoxFuture, FutureExt, TryFuture, TryFutureExt};
 use json_patch::{AddOperation, PatchOperation, RemoveOperation, TestOperation};
 use kube_client::{
-    api::{Patch, PatchParams},
+    api::{DeleteParams, ListParams, Patch, PatchParams},
+    core::ErrorResponse,
     Api, Resource, ResourceExt,
 };
 use serde::{de::DeserializeOwned, Serialize};
-use std::{error::Error as StdError, fmt::Debug, sync::Arc};
+use std::{error::Error as StdError, fmt::Debug, sync::Arc, time::Duration};
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -23,6 +24,8 @@ where
     AddFinalizer(#[source] kube_client::Error),
     #[error("failed to remove finalizer: {0}")]
     RemoveFinalizer(#[source] kube_client::Error),
+    #[error("failed to delete dependent objects: {0}")]
+    DeleteDependents(#[source] kube_client::Error),
     #[error("object has no name")]
     UnnamedObject,
 }
@@ -104,6 +107,34 @@ pub async fn finalizer<K, ReconcileFut>(
     obj: Arc<K>,
     reconcile: impl FnOnce(Event<K>) -> ReconcileFut,
 ) -> Result<Action, Error<ReconcileFut::Error>>
+where
+    K: Resource + Clone + DeserializeOwned + Serialize + Debug,
+    ReconcileFut: TryFuture<Ok = Action>,
+    ReconcileFut::Error: StdError + 'static,
+{
+    finalizer_with_policy(api, finalizer_name, obj, &CleanupPolicy::default(), reconcile).await
+}
+
+/// Variant of [`finalizer`] that deletes dependent objects before running [`Event::Cleanup`]
+///
+/// When the object is being deleted, each stage of the [`CleanupPolicy`] is processed in order:
+/// the dependents of a stage are deleted, and the next stage is only started once they have all been
+/// observed as gone. Until then, the finalizer is kept and the object is requeued after
+/// [`CleanupPolicy::recheck_after`]. [`Event::Cleanup`] only runs once every stage has completed.
+///
+/// Apart from that, this behaves exactly like [`finalizer`].
+///
+/// # Errors
+///
+/// In addition to the errors returned by [`finalizer`], failing to list or delete the dependents
+/// is returned as [`Error::DeleteDependents`].
+pub async fn finalizer_with_policy<K, ReconcileFut>(
+    api: &Api<K>,
+    finalizer_name: &str,
+    obj: Arc<K>,
+    policy: &CleanupPolicy,
+    reconcile: impl FnOnce(Event<K>) -> ReconcileFut,
+) -> Result<Action, Error<ReconcileFut::Error>>
 where
     K: Resource + Clone + DeserializeOwned + Serialize + Debug,
     ReconcileFut: TryFuture<Ok = Action>,
@@ -123,6 +154,16 @@ where
         } => {
             // Cleanup reconciliation must succeed before it's safe to remove the finalizer
             let name = obj.meta().name.clone().ok_or(Error::UnnamedObject)?;
+            // Dependents must be gone before cleanup can run, so keep the finalizer and check again later
+            if let Some(uid) = obj.uid() {
+                if !policy
+                    .delete_dependents(&uid)
+                    .await
+                    .map_err(Error::DeleteDependents)?
+                {
+                    return Ok(Action::requeue(policy.recheck_after));
+                }
+            }
             let action = reconcile(Event::Cleanup(obj))
                 .into_future()
                 .await
@@ -199,6 +240,112 @@ where
     }
 }
 
+type DependentsStage =
+    Box<dyn Fn(String) -> BoxFuture<'static, Result<bool, kube_client::Error>> + Send + Sync>;
+
+/// Dependent objects that must be deleted before [`finalizer_with_policy`] runs [`Event::Cleanup`]
+///
+/// Dependents are grouped into stages, which are deleted in the order that they were added.
+///
+/// ```no_run
+/// use kube::{Api, Client, runtime::finalizer::CleanupPolicy};
+/// use k8s_openapi::api::{apps::v1::Deployment, core::v1::PersistentVolumeClaim};
+/// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+/// # let client: kube::Client = todo!();
+/// // Tear down the workloads before the volumes they are using
+/// let policy = CleanupPolicy::default()
+///     .delete_owned(Api::<Deployment>::namespaced(client.clone(), "apps"))
+///     .delete_owned(Api::<PersistentVolumeClaim>::namespaced(client, "apps"));
+/// # Ok(())
+/// # }
+/// ```
+pub struct CleanupPolicy {
+    stages: Vec<DependentsStage>,
+    recheck_after: Duration,
+}
+
+impl Default for CleanupPolicy {
+    fn default() -> Self {
+        Self {
+            stages: Vec::new(),
+            recheck_after: Duration::from_secs(5),
+        }
+    }
+}
+
+impl CleanupPolicy {
+    /// Add a stage deleting every object in `api` that has an owner reference to the object being finalized
+    #[must_use]
+    pub fn delete_owned<Child>(self, api: Api<Child>) -> Self
+    where
+        Child: Resource + Clone + DeserializeOwned + Debug + Send + Sync + 'static,
+    {
+        self.delete_owned_matching(api, ListParams::default())
+    }
+
+    /// Add a stage deleting the owned objects in `api` that match `lp`
+    ///
+    /// Use this to narrow down the list call using label or field selectors.
+    #[must_use]
+    pub fn delete_owned_matching<Child>(mut self, api: Api<Child>, lp: ListParams) -> Self
+    where
+        Child: Resource + Clone + DeserializeOwned + Debug + Send + Sync + 'static,
+    {
+        self.stages.push(Box::new(move |owner_uid| {
+            let api = api.clone();
+            let lp = lp.clone();
+            async move { delete_owned(&api, &lp, &owner_uid).await }.boxed()
+        }));
+        self
+    }
+
+    /// How long to wait before checking whether the dependents are gone (default: 5s)
+    #[must_use]
+    pub fn recheck_after(mut self, duration: Duration) -> Self {
+        self.recheck_after = duration;
+        self
+    }
+
+    /// Returns whether all dependents are gone
+    async fn delete_dependents(&self, owner_uid: &str) -> Result<bool, kube_client::Error> {
+        for stage in &self.stages {
+            if !stage(owner_uid.to_string()).await? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+}
+
+/// Starts deleting the children of `owner_uid`, returning whether none were left
+async fn delete_owned<Child>(
+    api: &Api<Child>,
+    lp: &ListParams,
+    owner_uid: &str,
+) -> Result<bool, kube_client::Error>
+where
+    Child: Resource + Clone + DeserializeOwned + Debug,
+{
+    let children = api.list_metadata(lp).await?;
+    let mut gone = true;
+    for child in children.iter().filter(|child| {
+        child
+            .owner_references()
+            .iter()
+            .any(|owner| owner.uid == owner_uid)
+    }) {
+        gone = false;
+        if child.meta().deletion_timestamp.is_some() {
+            continue;
+        }
+        match api.delete(&child.name_any(), &DeleteParams::background()).await {
+            Ok(_) | Err(kube_client::Error::Api(ErrorResponse { code: 404, .. })) => {}
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(gone)
+}
+
 /// A representation of an action that should be taken by a reconciler.
 pub enum Event<K> {
     /// The reconciler should ensure that the actual state matches the state desired in the object.
@@ -220,3 +367,138 @@ pub enum Event<K> {
     /// - The grinch's heart grows a size or two
     Cleanup(Arc<K>),
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{finalizer_with_policy, CleanupPolicy, Event};
+    use crate::controller::Action;
+    use futures::pin_mut;
+    use http::{Method, Request, Response};
+    use hyper::Body;
+    use k8s_openapi::api::core::v1::{ConfigMap, Pod};
+    use kube_client::{Api, Client};
+    use serde_json::{json, Value};
+    use std::{
+        convert::Infallible,
+        sync::{Arc, Mutex},
+        time::Duration,
+    };
+    use tower_test::mock;
+
+    /// A client whose apiserver lists `children` as pods, recording every request as `METHOD path`
+    fn mock_client(children: Arc<Mutex<Vec<Value>>>) -> (Client, Arc<Mutex<Vec<String>>>) {
+        let (mock_service, handle) = mock::pair::<Request<Body>, Response<Body>>();
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let log = requests.clone();
+        tokio::spawn(async move {
+            pin_mut!(handle);
+            while let Some((request, send)) = handle.next_request().await {
+                let path = request.uri().path().to_string();
+                log.lock().unwrap().push(format!("{} {}", request.method(), path));
+                let body = match (request.method(), path.as_str()) {
+                    (&Method::GET, "/api/v1/namespaces/ns/pods") => json!({
+                        "apiVersion": "meta.k8s.io/v1",
+                        "kind": "PartialObjectMetadataList",
+                        "metadata": {},
+                        "items": children.lock().unwrap().clone(),
+                    }),
+                    (&Method::DELETE, _) => json!({ "apiVersion": "v1", "kind": "Pod", "metadata": {} }),
+                    (&Method::PATCH, _) => json!({ "apiVersion": "v1", "kind": "ConfigMap", "metadata": {} }),
+                    (method, path) => panic!("unexpected request {method} {path}"),
+                };
+                send.send_response(Response::new(Body::from(serde_json::to_vec(&body).unwrap())));
+            }
+        });
+        (Client::new(mock_service, "ns"), requests)
+    }
+
+    fn child(name: &str, owner_uid: &str, deleting: bool) -> Value {
+        let mut meta = json!({
+            "name": name,
+            "ownerReferences": [{ "apiVersion": "v1", "kind": "ConfigMap", "name": "parent", "uid": owner_uid }],
+        });
+        if deleting {
+            meta["deletionTimestamp"] = json!("2023-01-01T00:00:00Z");
+        }
+        json!({ "apiVersion": "meta.k8s.io/v1", "kind": "PartialObjectMetadata", "metadata": meta })
+    }
+
+    fn deleting_parent() -> Arc<ConfigMap> {
+        Arc::new(
+            serde_json::from_value(json!({
+                "apiVersion": "v1",
+                "kind": "ConfigMap",
+                "metadata": {
+                    "name": "parent",
+                    "namespace": "ns",
+                    "uid": "parent-uid",
+                    "finalizers": ["kube.rs/test"],
+                    "deletionTimestamp": "2023-01-01T00:00:00Z",
+                },
+            }))
+            .unwrap(),
+        )
+    }
+
+    #[tokio::test]
+    async fn default_policy_has_no_dependents() {
+        assert!(CleanupPolicy::default().delete_dependents("uid").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn delete_dependents_only_deletes_owned_children() {
+        let children = Arc::new(Mutex::new(vec![
+            child("owned", "parent-uid", false),
+            child("terminating", "parent-uid", true),
+            child("unrelated", "other-uid", false),
+        ]));
+        let (client, requests) = mock_client(children.clone());
+        let policy = CleanupPolicy::default().delete_owned(Api::<Pod>::namespaced(client, "ns"));
+
+        assert!(!policy.delete_dependents("parent-uid").await.unwrap());
+        let expected = [
+            "GET /api/v1/namespaces/ns/pods",
+            "DELETE /api/v1/namespaces/ns/pods/owned",
+        ];
+        assert_eq!(*requests.lock().unwrap(), expected);
+
+        // Children that are still terminating are not gone yet
+        children.lock().unwrap().remove(0);
+        assert!(!policy.delete_dependents("parent-uid").await.unwrap());
+        children.lock().unwrap().remove(0);
+        assert!(policy.delete_dependents("parent-uid").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn finalizer_with_policy_waits_for_dependents_before_cleanup() {
+        let children = Arc::new(Mutex::new(vec![child("owned", "parent-uid", true)]));
+        let (client, requests) = mock_client(children.clone());
+        let api = Api::<ConfigMap>::namespaced(client.clone(), "ns");
+        let policy = CleanupPolicy::default()
+            .delete_owned(Api::<Pod>::namespaced(client, "ns"))
+            .recheck_after(Duration::from_secs(1));
+        let reconcile = |event| async move {
+            assert!(matches!(event, Event::Cleanup(_)));
+            Ok::<_, Infallible>(Action::await_change())
+        };
+
+        let action = finalizer_with_policy(&api, "kube.rs/test", deleting_parent(), &policy, reconcile)
+            .await
+            .unwrap();
+        assert_eq!(action, Action::requeue(Duration::from_secs(1)));
+        let expected = ["GET /api/v1/namespaces/ns/pods"];
+        assert_eq!(*requests.lock().unwrap(), expected);
+
+        children.lock().unwrap().clear();
+        let action = finalizer_with_policy(&api, "kube.rs/test", deleting_parent(), &policy, reconcile)
+            .await
+            .unwrap();
+        assert_eq!(action, Action::await_change());
+        let expected = [
+            "GET /api/v1/namespaces/ns/pods",
+            "GET /api/v1/namespaces/ns/pods",
+            "PATCH /api/v1/namespaces/ns/configmaps/parent",
+        ];
+        assert_eq!(*requests.lock().unwrap(), expected);
+    }
+}
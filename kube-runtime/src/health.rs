@@ -0,0 +1,233 @@
+//! Aggregated health and readiness reporting for watchers, reflectors, and controllers
+//!
+//! Components register with a shared [`HealthChecker`] and receive a [`HealthReporter`] that they
+//! update as they run. The checker can then be queried from a liveness or readiness probe handler:
+//!
+//! ```no_run
+//! # use futures::StreamExt;
+//! # use k8s_openapi::api::core::v1::Pod;
+//! # use kube::{runtime::{health::HealthChecker, reflector, watcher, WatchStreamExt}, Api};
+//! # async fn wrapper(client: kube::Client) {
+//! let health = HealthChecker::default();
+//! let (reader, writer) = reflector::store::<Pod>();
+//! let pods = reflector(writer, watcher(Api::<Pod>::all(client), watcher::Config::default()))
+//!     .report_health(health.register("pods"));
+//!
+//! // in the probe handlers
+//! let ready = health.is_ready();
+//! let healthy = health.is_healthy();
+//! # }
+//! ```
+use crate::watcher;
+use futures::{Stream, TryStream};
+use pin_project::pin_project;
+use std::{
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, PoisonError,
+    },
+    task::{Context, Poll},
+};
+
+#[derive(Debug, Default)]
+struct ComponentState {
+    ready: AtomicBool,
+    healthy: AtomicBool,
+}
+
+type Components = Vec<(String, Arc<ComponentState>)>;
+
+/// Aggregates the health of every registered component
+///
+/// Cloning will produce a new reference to the same set of components.
+#[derive(Debug, Clone, Default)]
+pub struct HealthChecker {
+    components: Arc<Mutex<Components>>,
+}
+
+/// A snapshot of the health of a single component, as returned by [`HealthChecker::components`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ComponentHealth {
+    /// The name that the component was registered with
+    pub name: String,
+    /// Whether the component has finished its initial sync
+    pub ready: bool,
+    /// Whether the component is currently live
+    pub healthy: bool,
+}
+
+impl HealthChecker {
+    /// Register a new component, which starts out neither ready nor healthy
+    #[must_use]
+    pub fn register(&self, name: impl Into<String>) -> HealthReporter {
+        let state = Arc::<ComponentState>::default();
+        self.components
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push((name.into(), state.clone()));
+        HealthReporter { state }
+    }
+
+    /// Whether all registered components are ready
+    ///
+    /// For watchers and reflectors this means that the initial list has completed, so that stores are warm.
+    #[must_use]
+    pub fn is_ready(&self) -> bool {
+        self.components().iter().all(|c| c.ready)
+    }
+
+    /// Whether all registered components are healthy
+    ///
+    /// For watchers and reflectors this means that their last event was not an error.
+    #[must_use]
+    pub fn is_healthy(&self) -> bool {
+        self.components().iter().all(|c| c.healthy)
+    }
+
+    /// The current state of every registered component, in registration order
+    #[must_use]
+    pub fn components(&self) -> Vec<ComponentHealth> {
+        self.components
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .map(|(name, state)| ComponentHealth {
+                name: name.clone(),
+                ready: state.ready.load(Ordering::Relaxed),
+                healthy: state.healthy.load(Ordering::Relaxed),
+            })
+            .collect()
+    }
+}
+
+/// Handle for a single component registered with a [`HealthChecker`]
+///
+/// The component is marked as unhealthy when the reporter is dropped, since it can no longer report on itself.
+#[derive(Debug)]
+pub struct HealthReporter {
+    state: Arc<ComponentState>,
+}
+
+impl HealthReporter {
+    /// Mark the component as (not) ready
+    pub fn set_ready(&self, ready: bool) {
+        self.state.ready.store(ready, Ordering::Relaxed);
+    }
+
+    /// Mark the component as (not) healthy
+    pub fn set_healthy(&self, healthy: bool) {
+        self.state.healthy.store(healthy, Ordering::Relaxed);
+    }
+}
+
+impl Drop for HealthReporter {
+    fn drop(&mut self) {
+        self.set_healthy(false);
+    }
+}
+
+/// Stream returned by the [`report_health`](crate::WatchStreamExt::report_health) method.
+///
+/// Marks the component as ready once the first [`Event::Restarted`](watcher::Event::Restarted) is seen,
+/// and as healthy for as long as the stream keeps returning events rather than errors.
+#[pin_project]
+pub struct HealthReport<St> {
+    #[pin]
+    stream: St,
+    reporter: HealthReporter,
+}
+
+impl<St: TryStream> HealthReport<St> {
+    pub(crate) fn new(stream: St, reporter: HealthReporter) -> Self {
+        Self { stream, reporter }
+    }
+}
+
+impl<St, K> Stream for HealthReport<St>
+where
+    St: Stream<Item = Result<watcher::Event<K>, watcher::Error>>,
+{
+    type Item = St::Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let me = self.project();
+        let item = me.stream.poll_next(cx);
+        match &item {
+            Poll::Ready(Some(Ok(event))) => {
+                if let watcher::Event::Restarted(_) = event {
+                    me.reporter.set_ready(true);
+                }
+                me.reporter.set_healthy(true);
+            }
+            Poll::Ready(Some(Err(_)) | None) => me.reporter.set_healthy(false),
+            Poll::Pending => {}
+        }
+        item
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ComponentHealth, HealthChecker, HealthReport};
+    use crate::watcher::{Error, Event};
+    use futures::{pin_mut, stream, StreamExt};
+
+    #[test]
+    fn checker_should_require_all_components() {
+        let checker = HealthChecker::default();
+        assert!(checker.is_ready());
+        let a = checker.register("a");
+        let b = checker.register("b");
+        a.set_ready(true);
+        a.set_healthy(true);
+        assert!(!checker.is_ready());
+        assert!(!checker.is_healthy());
+        b.set_ready(true);
+        b.set_healthy(true);
+        assert!(checker.is_ready());
+        assert!(checker.is_healthy());
+        drop(b);
+        assert!(!checker.is_healthy());
+        let expected = vec![
+            ComponentHealth {
+                name: "a".to_string(),
+                ready: true,
+                healthy: true,
+            },
+            ComponentHealth {
+                name: "b".to_string(),
+                ready: true,
+                healthy: false,
+            },
+        ];
+        assert_eq!(checker.components(), expected);
+    }
+
+    #[tokio::test]
+    async fn report_should_track_watch_events() {
+        let checker = HealthChecker::default();
+        let st = HealthReport::new(
+            stream::iter([
+                Ok(Event::Applied(0)),
+                Ok(Event::Restarted(vec![1])),
+                Err(Error::TooManyObjects),
+                Ok(Event::Deleted(1)),
+            ]),
+            checker.register("watcher"),
+        );
+        pin_mut!(st);
+        st.next().await;
+        assert!(!checker.is_ready());
+        assert!(checker.is_healthy());
+        st.next().await;
+        assert!(checker.is_ready());
+        st.next().await;
+        assert!(checker.is_ready());
+        assert!(!checker.is_healthy());
+        st.next().await;
+        assert!(checker.is_healthy());
+        assert!(st.next().await.is_none());
+        assert!(!checker.is_healthy());
+    }
+}
@@ -18,18 +18,21 @@
 #![allow(clippy::semicolon_if_nothing_returned)]
 // Triggered by nightly clippy on idiomatic code
 #![allow(clippy::let_underscore_untyped)]
+// Triggered by newer clippy on the boxed kube_client::Error
+#![allow(clippy::result_large_err)]
 
 pub mod controller;
 pub mod events;
 
 pub mod finalizer;
+pub mod health;
 pub mod reflector;
 pub mod scheduler;
 pub mod utils;
 pub mod wait;
 pub mod watcher;
 
-pub use controller::{applier, Controller};
+pub use controller::{applier, applier_with_config, Controller};
 pub use finalizer::finalizer;
 pub use reflector::reflector;
 pub use scheduler::scheduler;
@@ -8,7 +8,9 @@ use kube_client::{
 use std::{
     fmt::{Debug, Display},
     hash::Hash,
+    str::FromStr,
 };
+use thiserror::Error;
 
 #[derive(Derivative)]
 #[derivative(
@@ -32,6 +34,17 @@ use std::{
 ///     ObjectRef::<Secret>::new("a").erase(),
 /// );
 /// ```
+///
+/// [`ObjectRef::path`] formats a reference as `kind.group/namespace/name` (or `kind.group/name` when
+/// cluster-scoped), which can be parsed back when the type is known statically:
+///
+/// ```
+/// use kube_runtime::reflector::ObjectRef;
+/// use k8s_openapi::api::apps::v1::Deployment;
+/// let deploy = ObjectRef::<Deployment>::new("web").within("default");
+/// assert_eq!(deploy.path(), "Deployment.apps/default/web");
+/// assert_eq!("Deployment.apps/default/web".parse::<ObjectRef<Deployment>>().unwrap(), deploy);
+/// ```
 #[non_exhaustive]
 pub struct ObjectRef<K: Resource> {
     pub dyntype: K::DynamicType,
@@ -143,6 +156,35 @@ impl<K: Resource> ObjectRef<K> {
         }
     }
 
+    /// Create an `OwnerReference` pointing to the referenced object
+    ///
+    /// Returns `None` if the uid of the object is not known, such as for an `ObjectRef` that was not
+    /// created from an object or an `OwnerReference`.
+    #[must_use]
+    pub fn to_owner_ref(&self, controller: bool) -> Option<OwnerReference> {
+        Some(OwnerReference {
+            api_version: K::api_version(&self.dyntype).into_owned(),
+            kind: K::kind(&self.dyntype).into_owned(),
+            name: self.name.clone(),
+            uid: self.extra.uid.clone()?,
+            controller: controller.then_some(true),
+            ..OwnerReference::default()
+        })
+    }
+
+    /// Format the reference as `kind.group/namespace/name` (or `kind.group/name` when cluster-scoped)
+    ///
+    /// Unlike the [`Display`] form, this can be parsed back into an `ObjectRef` with [`FromStr`].
+    #[must_use]
+    pub fn path(&self) -> String {
+        let kind = K::kind(&self.dyntype);
+        let group = K::group(&self.dyntype);
+        match &self.namespace {
+            Some(namespace) => format!("{kind}.{group}/{namespace}/{}", self.name),
+            None => format!("{kind}.{group}/{}", self.name),
+        }
+    }
+
     /// Convert into a reference to `K2`
     ///
     /// Note that no checking is done on whether this conversion makes sense. For example, every `Service`
@@ -207,6 +249,54 @@ impl<K: Resource> Display for ObjectRef<K> {
     }
 }
 
+/// Failed to parse an [`ObjectRef`] from its [`ObjectRef::path`] form
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum ParseObjectRefError {
+    /// The reference was not of the form `kind.group/namespace/name` or `kind.group/name`
+    #[error("malformed object reference {0:?}, expected kind.group/namespace/name or kind.group/name")]
+    Malformed(String),
+    /// The reference points to a different type than the one requested
+    #[error("object reference is for {found}, expected {expected}")]
+    TypeMismatch {
+        /// The `kind.group` of the requested type
+        expected: String,
+        /// The `kind.group` found in the reference
+        found: String,
+    },
+}
+
+impl<K: Resource> FromStr for ObjectRef<K>
+where
+    K::DynamicType: Default,
+{
+    type Err = ParseObjectRefError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let malformed = || ParseObjectRefError::Malformed(s.to_string());
+        let (kind_group, path) = s.split_once('/').ok_or_else(malformed)?;
+        let (namespace, name) = match path.split_once('/') {
+            Some((namespace, name)) => (Some(namespace), name),
+            None => (None, path),
+        };
+        if name.is_empty() || name.contains('/') || namespace.map_or(false, str::is_empty) {
+            return Err(malformed());
+        }
+        let dyntype = K::DynamicType::default();
+        let expected = format!("{}.{}", K::kind(&dyntype), K::group(&dyntype));
+        if kind_group != expected {
+            return Err(ParseObjectRefError::TypeMismatch {
+                expected,
+                found: kind_group.to_string(),
+            });
+        }
+        let obj_ref = Self::new_with(name, dyntype);
+        Ok(match namespace {
+            Some(namespace) => obj_ref.within(namespace),
+            None => obj_ref,
+        })
+    }
+}
+
 impl Extra {
     fn from_obj_meta(obj_meta: &ObjectMeta) -> Self {
         Self {
@@ -223,10 +313,13 @@ mod tests {
         hash::{Hash, Hasher},
     };
 
-    use super::{Extra, ObjectRef};
-    use k8s_openapi::api::{
-        apps::v1::Deployment,
-        core::v1::{Node, Pod},
+    use super::{Extra, ObjectRef, ParseObjectRefError};
+    use k8s_openapi::{
+        api::{
+            apps::v1::Deployment,
+            core::v1::{Node, Pod},
+        },
+        apimachinery::pkg::apis::meta::v1::OwnerReference,
     };
 
     #[test]
@@ -258,6 +351,53 @@ mod tests {
         assert_eq!(format!("{node_ref}"), format!("{}", node_ref.erase()));
     }
 
+    #[test]
+    fn path_should_round_trip_through_from_str() {
+        let pod_ref = ObjectRef::<Pod>::new("my-pod").within("my-namespace");
+        assert_eq!(pod_ref.path(), "Pod./my-namespace/my-pod");
+        assert_eq!(pod_ref.path().parse::<ObjectRef<Pod>>(), Ok(pod_ref));
+        let node_ref = ObjectRef::<Node>::new("my-node");
+        assert_eq!(node_ref.path(), "Node./my-node");
+        let parsed = node_ref.path().parse::<ObjectRef<Node>>().unwrap();
+        assert_eq!(parsed, node_ref);
+        assert_eq!(parsed.namespace, None);
+
+        assert_eq!(
+            "Pod./my-pod".parse::<ObjectRef<Deployment>>(),
+            Err(ParseObjectRefError::TypeMismatch {
+                expected: "Deployment.apps".to_string(),
+                found: "Pod.".to_string(),
+            })
+        );
+        for malformed in ["my-pod", "Pod./", "Pod.//my-pod", "Pod./ns/my-pod/extra"] {
+            assert_eq!(
+                malformed.parse::<ObjectRef<Pod>>(),
+                Err(ParseObjectRefError::Malformed(malformed.to_string()))
+            );
+        }
+    }
+
+    #[test]
+    fn owner_ref_should_round_trip() {
+        let owner = OwnerReference {
+            api_version: "apps/v1".to_string(),
+            kind: "Deployment".to_string(),
+            name: "my-deploy".to_string(),
+            uid: "638ffacd-f666-4402-ba10-7848c66ef576".to_string(),
+            controller: Some(true),
+            ..OwnerReference::default()
+        };
+        let deploy_ref = ObjectRef::<Deployment>::from_owner_ref(Some("my-namespace"), &owner, ()).unwrap();
+        assert_eq!(deploy_ref, ObjectRef::new("my-deploy").within("my-namespace"));
+        assert_eq!(deploy_ref.to_owner_ref(true), Some(owner.clone()));
+        assert!(ObjectRef::<Pod>::from_owner_ref(Some("my-namespace"), &owner, ()).is_none());
+        // The uid is required for an OwnerReference
+        assert_eq!(
+            ObjectRef::<Deployment>::new("my-deploy").to_owner_ref(false),
+            None
+        );
+    }
+
     #[test]
     fn comparison_should_ignore_extra() {
         let minimal = ObjectRef::<Pod>::new("my-pod").within("my-namespace");
@@ -174,8 +174,7 @@ where
     {
         self.store
             .read()
-            .iter()
-            .map(|(_, k)| k)
+            .values()
             .find(|k| predicate(k.as_ref()))
             .cloned()
     }
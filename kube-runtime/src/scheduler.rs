@@ -38,6 +38,8 @@ pub struct Scheduler<T, R> {
     /// Incoming queue of scheduling requests.
     #[pin]
     requests: Fuse<R>,
+    /// Orders messages that are ready at the same time, see `Scheduler::prioritize_by`.
+    priority: Option<fn(&T) -> u8>,
 }
 
 impl<T, R: Stream> Scheduler<T, R> {
@@ -47,19 +49,51 @@ impl<T, R: Stream> Scheduler<T, R> {
             scheduled: HashMap::new(),
             pending: HashSet::new(),
             requests: requests.fuse(),
+            priority: None,
         }
     }
+
+    /// Emit ready messages in order of descending `priority`, rather than in the order that they expired.
+    ///
+    /// This only makes a difference when the consumer cannot keep up, such as when messages are held
+    /// using [`hold_unless`](Self::hold_unless) because all workers are busy.
+    /// Messages with equal priority are emitted in an arbitrary order.
+    #[must_use]
+    pub fn prioritize_by(mut self, priority: fn(&T) -> u8) -> Self {
+        self.priority = Some(priority);
+        self
+    }
 }
 
-impl<'a, T: Hash + Eq + Clone, R> SchedulerProj<'a, T, R> {
+impl<T: Hash + Eq + Clone, R> SchedulerProj<'_, T, R> {
     /// Attempt to schedule a message into the queue.
     ///
     /// If the message is already in the queue then the earlier `request.run_at` takes precedence.
+    /// When prioritizing, the message with the higher priority is kept.
     fn schedule_message(&mut self, request: ScheduleRequest<T>) {
-        if self.pending.contains(&request.message) {
-            // Message is already pending, so we can't even expedite it
+        let priority = *self.priority;
+        let outranks = |new: &T, old: &T| priority.map_or(false, |priority| priority(new) > priority(old));
+        if let Some(old_message) = self.pending.get(&request.message) {
+            // Message is already pending, so we can't even expedite it, but it may have become more urgent
+            if outranks(&request.message, old_message) {
+                self.pending.replace(request.message);
+            }
             return;
         }
+        if let Some((old_message, _)) = self.scheduled.get_key_value(&request.message) {
+            if outranks(&request.message, old_message) {
+                // The queued copy of the message determines its priority once expired, so swap it out
+                let (_, old_entry) = self.scheduled.remove_entry(&request.message).unwrap();
+                self.queue.remove(&old_entry.queue_key);
+                let run_at = old_entry.run_at.min(request.run_at);
+                let message = request.message.clone();
+                self.scheduled.insert(request.message, ScheduledEntry {
+                    run_at,
+                    queue_key: self.queue.insert_at(message, run_at),
+                });
+                return;
+            }
+        }
         match self.scheduled.entry(request.message) {
             Entry::Occupied(mut old_entry) if old_entry.get().run_at >= request.run_at => {
                 // Old entry will run after the new request, so replace it..
@@ -88,6 +122,10 @@ impl<'a, T: Hash + Eq + Clone, R> SchedulerProj<'a, T, R> {
         cx: &mut Context<'_>,
         can_take_message: impl Fn(&T) -> bool,
     ) -> Poll<T> {
+        if let Some(priority) = *self.priority {
+            return self.poll_pop_prioritized_message(cx, can_take_message, priority);
+        }
+
         if let Some(msg) = self.pending.iter().find(|msg| can_take_message(*msg)).cloned() {
             return Poll::Ready(self.pending.take(&msg).unwrap());
         }
@@ -108,6 +146,34 @@ impl<'a, T: Hash + Eq + Clone, R> SchedulerProj<'a, T, R> {
             }
         }
     }
+
+    /// Variant of `poll_pop_queue_message` that considers all expired messages at once,
+    /// and picks the one with the highest priority.
+    fn poll_pop_prioritized_message(
+        &mut self,
+        cx: &mut Context<'_>,
+        can_take_message: impl Fn(&T) -> bool,
+        priority: fn(&T) -> u8,
+    ) -> Poll<T> {
+        while let Poll::Ready(Some(msg)) = self.queue.poll_expired(cx) {
+            let msg = msg.into_inner();
+            self.scheduled.remove(&msg).expect(
+                "Expired message was popped from the Scheduler queue, but was not in the metadata map",
+            );
+            self.pending.insert(msg);
+        }
+
+        match self
+            .pending
+            .iter()
+            .filter(|msg| can_take_message(*msg))
+            .max_by_key(|msg| priority(*msg))
+            .cloned()
+        {
+            Some(msg) => Poll::Ready(self.pending.take(&msg).unwrap()),
+            None => Poll::Pending,
+        }
+    }
 }
 
 /// See [`Scheduler::hold_unless`]
@@ -116,7 +182,7 @@ pub struct HoldUnless<'a, T, R, C> {
     can_take_message: C,
 }
 
-impl<'a, T, R, C> Stream for HoldUnless<'a, T, R, C>
+impl<T, R, C> Stream for HoldUnless<'_, T, R, C>
 where
     T: Eq + Hash + Clone,
     R: Stream<Item = ScheduleRequest<T>>,
@@ -159,7 +225,10 @@ where
     ///
     /// NOTE: `can_take_message` should be considered to be fairly performance-sensitive, since
     /// it will generally be executed for each pending message, for each [`poll_next`](Self::poll_next).
-    pub fn hold_unless<C: Fn(&T) -> bool>(self: Pin<&mut Self>, can_take_message: C) -> HoldUnless<T, R, C> {
+    pub fn hold_unless<C: Fn(&T) -> bool>(
+        self: Pin<&mut Self>,
+        can_take_message: C,
+    ) -> HoldUnless<'_, T, R, C> {
         HoldUnless {
             scheduler: self,
             can_take_message,
@@ -205,7 +274,10 @@ mod tests {
 
     use super::{scheduler, ScheduleRequest};
     use futures::{channel::mpsc, future, pin_mut, poll, stream, FutureExt, SinkExt, StreamExt};
-    use std::task::Poll;
+    use std::{
+        hash::{Hash, Hasher},
+        task::Poll,
+    };
     use tokio::time::{advance, pause, sleep, Duration, Instant};
 
     fn unwrap_poll<T>(poll: Poll<T>) -> T {
@@ -316,6 +388,122 @@ mod tests {
         assert!(scheduler.next().await.is_none());
     }
 
+    #[tokio::test]
+    async fn scheduler_should_emit_ready_items_by_priority() {
+        pause();
+        let scheduler = scheduler(
+            stream::iter(vec![
+                ScheduleRequest {
+                    message: 1_u8,
+                    run_at: Instant::now() + Duration::from_secs(1),
+                },
+                ScheduleRequest {
+                    message: 3,
+                    run_at: Instant::now() + Duration::from_secs(2),
+                },
+                ScheduleRequest {
+                    message: 2,
+                    run_at: Instant::now() + Duration::from_secs(3),
+                },
+            ])
+            .on_complete(sleep(Duration::from_secs(5))),
+        )
+        .prioritize_by(|msg| *msg);
+        pin_mut!(scheduler);
+        assert!(poll!(scheduler.next()).is_pending());
+        advance(Duration::from_secs(4)).await;
+        assert_eq!(scheduler.next().now_or_never().unwrap().unwrap(), 3);
+        assert_eq!(scheduler.next().now_or_never().unwrap().unwrap(), 2);
+        assert_eq!(scheduler.next().now_or_never().unwrap().unwrap(), 1);
+        // Stream has terminated
+        assert!(scheduler.next().await.is_none());
+    }
+
+    /// A message whose priority is ignored for deduplication, like a `ReconcileRequest`
+    #[derive(Debug, Clone, Copy)]
+    struct Prioritized {
+        id: u8,
+        priority: u8,
+    }
+
+    impl PartialEq for Prioritized {
+        fn eq(&self, other: &Self) -> bool {
+            self.id == other.id
+        }
+    }
+
+    impl Eq for Prioritized {}
+
+    impl Hash for Prioritized {
+        fn hash<H: Hasher>(&self, state: &mut H) {
+            self.id.hash(state);
+        }
+    }
+
+    #[tokio::test]
+    async fn scheduler_dedupe_should_keep_higher_priority() {
+        pause();
+        let now = Instant::now();
+        let scheduler = scheduler(
+            stream::iter(vec![
+                ScheduleRequest {
+                    message: Prioritized { id: 1, priority: 0 },
+                    run_at: now + Duration::from_secs(1),
+                },
+                ScheduleRequest {
+                    message: Prioritized { id: 2, priority: 1 },
+                    run_at: now + Duration::from_secs(2),
+                },
+                ScheduleRequest {
+                    message: Prioritized { id: 1, priority: 2 },
+                    run_at: now + Duration::from_secs(3),
+                },
+            ])
+            .on_complete(sleep(Duration::from_secs(5))),
+        )
+        .prioritize_by(|msg| msg.priority);
+        pin_mut!(scheduler);
+        assert!(poll!(scheduler.next()).is_pending());
+        // The earlier run_at is kept along with the higher priority
+        advance(Duration::from_millis(1500)).await;
+        let first = scheduler.next().now_or_never().unwrap().unwrap();
+        assert_eq!((first.id, first.priority), (1, 2));
+        assert!(poll!(scheduler.next()).is_pending());
+        advance(Duration::from_secs(1)).await;
+        let second = scheduler.next().now_or_never().unwrap().unwrap();
+        assert_eq!((second.id, second.priority), (2, 1));
+        assert!(scheduler.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn scheduler_should_raise_priority_of_pending_items() {
+        pause();
+        let (mut tx, rx) = mpsc::unbounded::<ScheduleRequest<Prioritized>>();
+        let mut scheduler = Box::pin(scheduler(rx).prioritize_by(|msg| msg.priority));
+        for message in [Prioritized { id: 1, priority: 0 }, Prioritized {
+            id: 2,
+            priority: 1,
+        }] {
+            tx.send(ScheduleRequest {
+                message,
+                run_at: Instant::now(),
+            })
+            .await
+            .unwrap();
+        }
+        assert!(poll!(scheduler.as_mut().hold_unless(|_| false).next()).is_pending());
+        tx.send(ScheduleRequest {
+            message: Prioritized { id: 1, priority: 2 },
+            run_at: Instant::now(),
+        })
+        .await
+        .unwrap();
+        let first = unwrap_poll(poll!(scheduler.as_mut().hold_unless(|_| true).next())).unwrap();
+        assert_eq!((first.id, first.priority), (1, 2));
+        let second = unwrap_poll(poll!(scheduler.as_mut().hold_unless(|_| true).next())).unwrap();
+        assert_eq!((second.id, second.priority), (2, 1));
+    }
+
     #[tokio::test]
     async fn scheduler_dedupe_should_keep_earlier_item() {
         pause();
@@ -63,7 +63,7 @@ impl<T: Clone + Send + Sync> DelayedInit<T> {
 // Using a manually implemented future because we don't want to hold the lock across poll calls
 // since that would mean that an unpolled writer would stall all other tasks from being able to poll it
 struct Get<'a, T>(&'a DelayedInit<T>);
-impl<'a, T> Future for Get<'a, T>
+impl<T> Future for Get<'_, T>
 where
     T: Clone,
 {
@@ -102,17 +102,12 @@ pub struct InitDropped;
 
 #[cfg(test)]
 mod tests {
-    use std::{
-        sync::{Arc, Mutex},
-        task::Poll,
-    };
+    use std::task::Poll;
 
     use futures::{pin_mut, poll};
     use tracing::Level;
     use tracing_subscriber::util::SubscriberInitExt;
 
-    use crate::utils::delayed_init::ReceiverState;
-
     use super::DelayedInit;
 
     fn setup_tracing() -> tracing::dispatcher::DefaultGuard {
@@ -158,7 +158,7 @@ mod tests {
         pin_mut!(rx_s1);
 
         for item in events.clone() {
-            assert_eq!(poll!(rx.next()), Poll::Ready(Some(Arc::new(item))), "rx",);
+            assert_eq!(poll!(rx.next()), Poll::Ready(Some(Arc::new(item))), "rx");
         }
 
         for item in events {
@@ -227,6 +227,10 @@ mod tests {
                 Poll::Ready(Some(Ok(Arc::new(event)))),
                 "rx_s1"
             );
+
+            // Reset tokio's cooperative budget, which would otherwise force `Pending` on
+            // the broadcast channel after enough polls within a single task poll.
+            tokio::task::yield_now().await;
         }
 
         assert_eq!(
@@ -3,6 +3,7 @@ use crate::utils::predicate::{Predicate, PredicateFilter};
 #[cfg(feature = "unstable-runtime-subscribe")]
 use crate::utils::stream_subscribe::StreamSubscribe;
 use crate::{
+    health::{HealthReport, HealthReporter},
     utils::{event_flatten::EventFlatten, event_modify::EventModify, stream_backoff::StreamBackoff},
     watcher,
 };
@@ -88,6 +89,17 @@ pub trait WatchStreamExt: Stream {
         EventModify::new(self, f)
     }
 
+    /// Report the progress of a [`watcher()`] or [`reflector`](crate::reflector::reflector) stream to a [`HealthChecker`](crate::health::HealthChecker)
+    ///
+    /// The component becomes ready after the initial list, and is healthy for as long as the watch is not returning errors.
+    /// See the [`health`](crate::health) module for an example.
+    fn report_health<K>(self, reporter: HealthReporter) -> HealthReport<Self>
+    where
+        Self: Stream<Item = Result<watcher::Event<K>, watcher::Error>> + Sized,
+    {
+        HealthReport::new(self, reporter)
+    }
+
     /// Filter out a flattened stream on [`predicates`](crate::predicates).
     ///
     /// This will filter out repeat calls where the predicate returns the same result.
@@ -162,7 +162,7 @@ impl<K, F: Fn(Option<&K>) -> bool> Condition<K> for F {
 pub mod conditions {
     pub use super::Condition;
     use k8s_openapi::{
-        api::{batch::v1::Job, core::v1::Pod},
+        api::{batch::v1::Job, certificates::v1::CertificateSigningRequest, core::v1::Pod},
         apiextensions_apiserver::pkg::apis::apiextensions::v1::CustomResourceDefinition,
     };
     use kube_client::Resource;
@@ -236,6 +236,41 @@ pub mod conditions {
         }
     }
 
+    /// An await condition for `CertificateSigningRequest` that returns `true` once its certificate has been issued
+    ///
+    /// The certificate is then available in `status.certificate`. Requests that are denied or fail are never issued,
+    /// so wait for this together with [`is_certificate_refused`] to not wait for them forever.
+    #[must_use]
+    pub fn is_certificate_issued() -> impl Condition<CertificateSigningRequest> {
+        |obj: Option<&CertificateSigningRequest>| {
+            if let Some(csr) = &obj {
+                if let Some(status) = &csr.status {
+                    if let Some(cert) = &status.certificate {
+                        return !cert.0.is_empty();
+                    }
+                }
+            }
+            false
+        }
+    }
+
+    /// An await condition for `CertificateSigningRequest` that returns `true` once it has been denied, or its signer failed
+    #[must_use]
+    pub fn is_certificate_refused() -> impl Condition<CertificateSigningRequest> {
+        |obj: Option<&CertificateSigningRequest>| {
+            if let Some(csr) = &obj {
+                if let Some(s) = &csr.status {
+                    if let Some(conds) = &s.conditions {
+                        return conds
+                            .iter()
+                            .any(|c| (c.type_ == "Denied" || c.type_ == "Failed") && c.status == "True");
+                    }
+                }
+            }
+            false
+        }
+    }
+
     /// See [`Condition::not`]
     #[derive(Copy, Clone, Debug, PartialEq, Eq)]
     pub struct Not<A>(pub(super) A);
@@ -316,3 +351,45 @@ pub mod delete {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{conditions, Condition};
+    use k8s_openapi::api::certificates::v1::CertificateSigningRequest;
+    use serde_json::json;
+
+    fn csr(status: &serde_json::Value) -> CertificateSigningRequest {
+        serde_json::from_value(json!({
+            "metadata": { "name": "webhook" },
+            "spec": { "request": "", "signerName": "kubernetes.io/kubelet-serving" },
+            "status": status,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn certificate_issued() {
+        let issued = conditions::is_certificate_issued();
+        assert!(!issued.matches_object(None));
+        assert!(!issued.matches_object(Some(&csr(&json!({})))));
+        assert!(!issued.matches_object(Some(&csr(&json!({ "certificate": "" })))));
+        let approved = json!({ "conditions": [{ "type": "Approved", "status": "True" }] });
+        assert!(!issued.matches_object(Some(&csr(&approved))));
+        assert!(issued.matches_object(Some(&csr(&json!({ "certificate": "Y2VydA==" })))));
+    }
+
+    #[test]
+    fn certificate_refused() {
+        let refused = conditions::is_certificate_refused();
+        assert!(!refused.matches_object(None));
+        assert!(!refused.matches_object(Some(&csr(&json!({})))));
+        let approved = json!({ "conditions": [{ "type": "Approved", "status": "True" }] });
+        assert!(!refused.matches_object(Some(&csr(&approved))));
+        let not_denied = json!({ "conditions": [{ "type": "Denied", "status": "False" }] });
+        assert!(!refused.matches_object(Some(&csr(&not_denied))));
+        let denied = json!({ "conditions": [{ "type": "Denied", "status": "True" }] });
+        assert!(refused.matches_object(Some(&csr(&denied))));
+        let failed = json!({ "conditions": [{ "type": "Failed", "status": "True" }] });
+        assert!(refused.matches_object(Some(&csr(&failed))));
+    }
+}
@@ -9,7 +9,7 @@ use derivative::Derivative;
 use futures::{stream::BoxStream, Stream, StreamExt};
 use kube_client::{
     api::{ListParams, Resource, ResourceExt, VersionMatch, WatchEvent, WatchParams},
-    core::{metadata::PartialObjectMeta, ObjectList},
+    core::{fields, labels, metadata::PartialObjectMeta, ObjectList},
     error::ErrorResponse,
     Api, Error as ClientErr,
 };
@@ -17,7 +17,7 @@ use serde::de::DeserializeOwned;
 use smallvec::SmallVec;
 use std::{clone::Clone, fmt::Debug, time::Duration};
 use thiserror::Error;
-use tracing::{debug, error, warn};
+use tracing::{debug, warn};
 
 #[derive(Debug, Error)]
 pub enum Error {
@@ -265,6 +265,25 @@ impl Config {
         self
     }
 
+    /// Configure the selector to restrict the list of returned objects by their fields, from a typed [`fields::Selector`]
+    ///
+    /// ```
+    /// use kube::{core::fields::field, runtime::watcher::Config};
+    /// let wc = Config::default().fields_from(&field("spec.nodeName").eq("node-1").into());
+    /// ```
+    #[must_use]
+    pub fn fields_from(mut self, selector: &fields::Selector) -> Self {
+        self.field_selector = Some(selector.to_string());
+        self
+    }
+
+    /// Configure the selector to restrict the list of returned objects by their labels, from a typed [`labels::Selector`]
+    #[must_use]
+    pub fn labels_from(mut self, selector: &labels::Selector) -> Self {
+        self.label_selector = Some(selector.to_string());
+        self
+    }
+
     /// Sets list semantic to configure re-list performance and consistency
     #[must_use]
     pub fn list_semantic(mut self, semantic: ListSemantic) -> Self {
@@ -326,7 +345,8 @@ where
     type Value = K;
 
     async fn list(&self, lp: &ListParams) -> kube_client::Result<ObjectList<Self::Value>> {
-        self.api.list(lp).await
+        // Deserialize the items as they are received, rather than buffering the whole response of large lists
+        self.api.list_stream(lp).await?.try_collect_list().await
     }
 
     async fn watch(
@@ -394,7 +414,7 @@ where
                 } else {
                     debug!("watch list error: {err:?}");
                 }
-                (Some(Err(err).map_err(Error::InitialListFailed)), State::Empty)
+                (Some(Err(Error::InitialListFailed(err))), State::Empty)
             }
         },
         State::InitListed { resource_version } => {
@@ -409,10 +429,9 @@ where
                     } else {
                         debug!("watch initlist error: {err:?}");
                     }
-                    (
-                        Some(Err(err).map_err(Error::WatchStartFailed)),
-                        State::InitListed { resource_version },
-                    )
+                    (Some(Err(Error::WatchStartFailed(err))), State::InitListed {
+                        resource_version,
+                    })
                 }
             }
         }
@@ -453,7 +472,7 @@ where
                 } else {
                     debug!("error watchevent error: {err:?}");
                 }
-                (Some(Err(err).map_err(Error::WatchError)), new_state)
+                (Some(Err(Error::WatchError(err))), new_state)
             }
             Some(Err(err)) => {
                 if std::matches!(err, ClientErr::Api(ErrorResponse { code: 403, .. })) {
@@ -461,7 +480,7 @@ where
                 } else {
                     debug!("watcher error: {err:?}");
                 }
-                (Some(Err(err).map_err(Error::WatchFailed)), State::Watching {
+                (Some(Err(Error::WatchFailed(err))), State::Watching {
                     resource_version,
                     stream,
                 })
@@ -165,7 +165,7 @@ cfg_error! {
 /// Re-exports from [`kube-derive`](kube_derive)
 #[cfg(feature = "derive")]
 #[cfg_attr(docsrs, doc(cfg(feature = "derive")))]
-pub use kube_derive::CustomResource;
+pub use kube_derive::{Apply, CustomResource};
 
 /// Re-exports from `kube-runtime`
 #[cfg(feature = "runtime")]
@@ -294,6 +294,16 @@ mod test {
             assert_eq!(o.status.unwrap().replicas, 1, "scale replicas got patched");
             let linked_replicas = o.spec.unwrap().replicas.unwrap();
             assert_eq!(linked_replicas, 3, "patch_scale updates linked spec.replicas");
+
+            let mut scale = foos.get_scale("baz").await?;
+            scale.spec = Some(crate::api::ScaleSpec { replicas: Some(4) });
+            let o = foos.replace_scale("baz", &Default::default(), &scale).await?;
+            assert_eq!(
+                o.spec.unwrap().replicas,
+                Some(4),
+                "replace_scale updates replicas"
+            );
+            assert_eq!(foos.get("baz").await?.spec.replicas, 4);
         }
 
         // cleanup